@@ -12,3 +12,4 @@
 
 mod macros;
 pub mod mock;
+pub mod retry;
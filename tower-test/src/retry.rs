@@ -0,0 +1,189 @@
+//! A scripted `Service` for testing retry policies.
+//!
+//! A retry policy's behavior depends on the sequence of results it sees and on
+//! how long it waits between attempts. [`Script`] builds a service that
+//! returns a fixed sequence of results, one per call, and an [`Attempts`]
+//! handle that records when each call was made, so that a policy's
+//! interaction with backoffs and budgets can be tested deterministically.
+//!
+//! Attempts are timestamped with Tokio's clock, so tests should pause time
+//! with `tokio::time::pause` to make the recorded delays deterministic.
+//!
+//! # Examples
+//!
+//! ```
+//! use tower_service::Service;
+//! use tower_test::retry::Script;
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let (mut service, attempts) = Script::new()
+//!     .fail_times(2, "unavailable")
+//!     .respond("hello")
+//!     .build();
+//!
+//! assert_eq!(service.call(()).await, Err("unavailable"));
+//! assert_eq!(service.call(()).await, Err("unavailable"));
+//! assert_eq!(service.call(()).await, Ok("hello"));
+//! assert_eq!(attempts.count(), 3);
+//! # }
+//! ```
+
+use std::{
+    collections::VecDeque,
+    future::{self, Ready},
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::time::Instant;
+use tower_service::Service;
+
+/// Builds a [`Scripted`] service from the sequence of results it returns.
+#[derive(Debug)]
+pub struct Script<T, E> {
+    steps: VecDeque<Result<T, E>>,
+}
+
+/// A service that returns the results of its [`Script`], in order, one per
+/// call.
+///
+/// Clones of a [`Scripted`] service share the same script, so the service may
+/// be wrapped in middleware that clones it, such as `Retry`.
+///
+/// # Panics
+///
+/// Calling the service after every scripted result has been returned panics,
+/// so that unexpected attempts fail the test.
+#[derive(Debug)]
+pub struct Scripted<T, E> {
+    steps: Arc<Mutex<VecDeque<Result<T, E>>>>,
+    attempts: Attempts,
+}
+
+/// Records the calls made to a [`Scripted`] service.
+#[derive(Clone, Debug, Default)]
+pub struct Attempts {
+    times: Arc<Mutex<Vec<Instant>>>,
+}
+
+// ===== impl Script =====
+
+impl<T, E> Script<T, E> {
+    /// Creates an empty script.
+    pub fn new() -> Self {
+        Script {
+            steps: VecDeque::new(),
+        }
+    }
+
+    /// Appends a successful `response` to the script.
+    pub fn respond(mut self, response: T) -> Self {
+        self.steps.push_back(Ok(response));
+        self
+    }
+
+    /// Appends a failure with `error` to the script.
+    pub fn fail(mut self, error: E) -> Self {
+        self.steps.push_back(Err(error));
+        self
+    }
+
+    /// Appends `times` failures with `error` to the script.
+    pub fn fail_times(mut self, times: usize, error: E) -> Self
+    where
+        E: Clone,
+    {
+        for _ in 0..times {
+            self.steps.push_back(Err(error.clone()));
+        }
+        self
+    }
+
+    /// Returns the scripted service, and a handle that records the calls made
+    /// to it.
+    pub fn build(self) -> (Scripted<T, E>, Attempts) {
+        let attempts = Attempts::default();
+        let service = Scripted {
+            steps: Arc::new(Mutex::new(self.steps)),
+            attempts: attempts.clone(),
+        };
+        (service, attempts)
+    }
+}
+
+impl<T, E> Default for Script<T, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ===== impl Scripted =====
+
+impl<T, E> Scripted<T, E> {
+    /// Returns the number of scripted results that have not yet been returned.
+    pub fn remaining(&self) -> usize {
+        self.steps.lock().unwrap().len()
+    }
+}
+
+impl<T, E> Clone for Scripted<T, E> {
+    fn clone(&self) -> Self {
+        Scripted {
+            steps: self.steps.clone(),
+            attempts: self.attempts.clone(),
+        }
+    }
+}
+
+impl<T, E, Request> Service<Request> for Scripted<T, E> {
+    type Response = T;
+    type Error = E;
+    type Future = Ready<Result<T, E>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), E>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _: Request) -> Self::Future {
+        let attempt = self.attempts.record();
+        let result = self
+            .steps
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or_else(|| panic!("unexpected attempt {}; the script has ended", attempt));
+        future::ready(result)
+    }
+}
+
+// ===== impl Attempts =====
+
+impl Attempts {
+    /// Returns the number of calls made to the service.
+    pub fn count(&self) -> usize {
+        self.times.lock().unwrap().len()
+    }
+
+    /// Returns the time at which each call was made.
+    pub fn times(&self) -> Vec<Instant> {
+        self.times.lock().unwrap().clone()
+    }
+
+    /// Returns the time elapsed between each call and the next.
+    pub fn delays(&self) -> Vec<Duration> {
+        self.times
+            .lock()
+            .unwrap()
+            .windows(2)
+            .map(|w| w[1] - w[0])
+            .collect()
+    }
+
+    /// Records a call, returning its number, starting from 1.
+    fn record(&self) -> usize {
+        let mut times = self.times.lock().unwrap();
+        times.push(Instant::now());
+        times.len()
+    }
+}
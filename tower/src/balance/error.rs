@@ -19,3 +19,26 @@ impl std::error::Error for Discover {
         Some(&*self.0)
     }
 }
+
+/// An endpoint was evicted because one of its responses failed with an error
+/// that was [classified] as an endpoint failure.
+///
+/// [classified]: crate::classify::ErrorClass::evicts
+#[derive(Debug)]
+pub struct Evicted {
+    _p: (),
+}
+
+impl Evicted {
+    pub(crate) fn new() -> Self {
+        Evicted { _p: () }
+    }
+}
+
+impl fmt::Display for Evicted {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("endpoint evicted after a failed response")
+    }
+}
+
+impl std::error::Error for Evicted {}
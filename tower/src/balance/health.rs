@@ -0,0 +1,395 @@
+//! Out-of-band health checking for balanced endpoints.
+//!
+//! A load balancer only evicts an endpoint when its [`poll_ready`] fails. An
+//! endpoint may, however, remain "ready" while failing every request it
+//! receives. [`HealthChecked`] wraps an endpoint so that it is periodically
+//! probed by a [`HealthCheck`] on a task of its own; after a configurable
+//! number of consecutive failed probes, the endpoint reports that it is not
+//! ready, which causes a balancer to stop selecting it until a probe succeeds
+//! again.
+//!
+//! Endpoints may also be evicted passively: [`EvictOnError`] wraps an endpoint
+//! so that it fails once one of its responses fails with an error that a
+//! [`Classify`] implementation classifies as an endpoint failure.
+//!
+//! [`poll_ready`]: crate::Service::poll_ready
+
+use super::error::Evicted;
+use crate::classify::Classify;
+use crate::load::{InFlight, Load};
+use futures_core::ready;
+use futures_util::task::AtomicWaker;
+use pin_project::pin_project;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{task::JoinHandle, time::Sleep};
+use tower_service::Service;
+use tracing::{debug, trace};
+
+/// Probes the health of an endpoint.
+///
+/// This trait is implemented for closures returning a [`Future`] that resolves
+/// to `true` if the endpoint is healthy.
+pub trait HealthCheck {
+    /// The future that resolves to the result of a probe.
+    type Future: Future<Output = bool>;
+
+    /// Starts a probe of the endpoint.
+    fn check(&mut self) -> Self::Future;
+}
+
+/// Wraps a service so that it becomes unready while periodic health checks
+/// fail.
+///
+/// Probes run on their own task, which is spawned onto the Tokio runtime when
+/// the endpoint is created and aborted when it is dropped, so an endpoint is
+/// probed whether or not it receives traffic. While the endpoint is unhealthy,
+/// [`poll_ready`] returns [`Poll::Pending`], and the task that polled it is
+/// woken as soon as a probe succeeds, so an idle, unhealthy endpoint recovers
+/// without being polled in the meantime. An endpoint that is ready is only
+/// polled when a balancer considers dispatching a request to it, so it
+/// becomes unready at that time.
+///
+/// [`poll_ready`]: crate::Service::poll_ready
+pub struct HealthChecked<S> {
+    inner: S,
+    health: Arc<Health>,
+    _probes: Probes,
+}
+
+/// The result of an endpoint's recent probes, shared with its probe task.
+struct Health {
+    failure_threshold: usize,
+    /// The number of consecutive failed probes.
+    failures: AtomicUsize,
+    /// Woken when an unhealthy endpoint recovers.
+    recovered: AtomicWaker,
+}
+
+/// Aborts an endpoint's probe task when the endpoint is dropped.
+struct Probes(JoinHandle<()>);
+
+/// Wraps a service so that it fails once one of its responses fails with an
+/// error that should evict it.
+///
+/// Each error returned by the inner service's responses is classified by a
+/// [`Classify`] implementation. Once an error is classified as one that
+/// [evicts] the endpoint, the next call to [`poll_ready`] fails with an
+/// [`Evicted`] error, which causes a balancer to drop the endpoint (or a
+/// [`Backoff`] that made it to make a new one). The failed response's error
+/// is returned to the caller as usual.
+///
+/// A balancer only polls a ready endpoint when it considers dispatching a
+/// request to it, so an endpoint is evicted once it is next selected.
+///
+/// [evicts]: crate::classify::ErrorClass::evicts
+/// [`poll_ready`]: crate::Service::poll_ready
+/// [`Backoff`]: super::p2c::Backoff
+#[derive(Clone)]
+pub struct EvictOnError<S, C> {
+    inner: S,
+    classify: C,
+    failed: Arc<AtomicBool>,
+}
+
+/// The [`Future`] returned by an [`EvictOnError`] service.
+#[pin_project]
+pub struct EvictOnErrorFuture<F, C> {
+    #[pin]
+    inner: F,
+    classify: C,
+    failed: Arc<AtomicBool>,
+}
+
+// ===== impl HealthCheck =====
+
+impl<F, Fut> HealthCheck for F
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = bool>,
+{
+    type Future = Fut;
+
+    fn check(&mut self) -> Self::Future {
+        (self)()
+    }
+}
+
+// ===== impl HealthChecked =====
+
+impl<S> HealthChecked<S> {
+    /// Wraps `inner` so that it is probed by `check` every `interval`.
+    ///
+    /// Once `failure_threshold` consecutive probes have failed, the service
+    /// becomes unready until a probe succeeds. The first probe is made
+    /// `interval` after the endpoint is created.
+    ///
+    /// # Panics
+    ///
+    /// If `failure_threshold` is zero, or if called outside of a Tokio
+    /// runtime.
+    pub fn new<H>(inner: S, check: H, interval: Duration, failure_threshold: usize) -> Self
+    where
+        H: HealthCheck + Send + 'static,
+        H::Future: Send,
+    {
+        assert!(failure_threshold > 0, "failure threshold must be positive");
+        let health = Arc::new(Health {
+            failure_threshold,
+            failures: AtomicUsize::new(0),
+            recovered: AtomicWaker::new(),
+        });
+        // The first probe is scheduled now, rather than when the task first
+        // runs.
+        let first = tokio::time::sleep(interval);
+        let probes = Probes(tokio::spawn(probe(check, first, interval, health.clone())));
+        Self {
+            inner,
+            health,
+            _probes: probes,
+        }
+    }
+
+    /// Returns `true` if fewer than `failure_threshold` consecutive probes have
+    /// failed.
+    pub fn is_healthy(&self) -> bool {
+        self.health.is_healthy()
+    }
+
+    /// Get a reference to the inner service.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner service.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Consume `self`, returning the inner service.
+    ///
+    /// The endpoint is no longer probed.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+/// Probes an endpoint once `first` elapses, and then `interval` after each
+/// probe completes, until the task is aborted.
+async fn probe<H: HealthCheck>(
+    mut check: H,
+    first: Sleep,
+    interval: Duration,
+    health: Arc<Health>,
+) {
+    first.await;
+    loop {
+        trace!("probing endpoint");
+        let healthy = check.check().await;
+        health.record(healthy);
+        tokio::time::sleep(interval).await;
+    }
+}
+
+impl<S, Request> Service<Request> for HealthChecked<S>
+where
+    S: Service<Request>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        if !self.health.is_healthy() {
+            self.health.recovered.register(cx.waker());
+            // A probe may have succeeded before the waker was registered.
+            if !self.health.is_healthy() {
+                return Poll::Pending;
+            }
+        }
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        self.inner.call(req)
+    }
+}
+
+impl<S: Load> Load for HealthChecked<S> {
+    type Metric = S::Metric;
+
+    fn load(&self) -> Self::Metric {
+        self.inner.load()
+    }
+}
+
+impl<S: InFlight> InFlight for HealthChecked<S> {
+    fn in_flight(&self) -> usize {
+        self.inner.in_flight()
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for HealthChecked<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HealthChecked")
+            .field("inner", &self.inner)
+            .field("failure_threshold", &self.health.failure_threshold)
+            .field("failures", &self.health.failures.load(Ordering::Acquire))
+            .finish()
+    }
+}
+
+// ===== impl Probes =====
+
+impl Drop for Probes {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+// ===== impl Health =====
+
+impl Health {
+    fn is_healthy(&self) -> bool {
+        self.failures.load(Ordering::Acquire) < self.failure_threshold
+    }
+
+    /// Records the result of a probe, waking the endpoint if it recovered.
+    fn record(&self, healthy: bool) {
+        if healthy {
+            if self.failures.swap(0, Ordering::AcqRel) >= self.failure_threshold {
+                debug!("endpoint recovered");
+                self.recovered.wake();
+            }
+        } else {
+            let failures = self.failures.fetch_add(1, Ordering::AcqRel) + 1;
+            if failures == self.failure_threshold {
+                debug!(failures, "endpoint is unhealthy");
+            }
+        }
+    }
+}
+
+// ===== impl EvictOnError =====
+
+impl<S, C> EvictOnError<S, C> {
+    /// Wraps `inner` so that it fails once one of its responses fails with an
+    /// error that `classify` classifies as an endpoint failure.
+    pub fn new(inner: S, classify: C) -> Self {
+        Self {
+            inner,
+            classify,
+            failed: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Returns `true` if a response has failed with an error that evicts the
+    /// endpoint.
+    pub fn is_failed(&self) -> bool {
+        self.failed.load(Ordering::Acquire)
+    }
+
+    /// Get a reference to the inner service.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner service.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Consume `self`, returning the inner service.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S, C, Request> Service<Request> for EvictOnError<S, C>
+where
+    S: Service<Request>,
+    S::Error: Into<crate::BoxError>,
+    C: Classify<S::Error> + Clone,
+{
+    type Response = S::Response;
+    type Error = crate::BoxError;
+    type Future = EvictOnErrorFuture<S::Future, C>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        if self.is_failed() {
+            return Poll::Ready(Err(Evicted::new().into()));
+        }
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        EvictOnErrorFuture {
+            inner: self.inner.call(req),
+            classify: self.classify.clone(),
+            failed: self.failed.clone(),
+        }
+    }
+}
+
+impl<S: Load, C> Load for EvictOnError<S, C> {
+    type Metric = S::Metric;
+
+    fn load(&self) -> Self::Metric {
+        self.inner.load()
+    }
+}
+
+impl<S: InFlight, C> InFlight for EvictOnError<S, C> {
+    fn in_flight(&self) -> usize {
+        self.inner.in_flight()
+    }
+}
+
+impl<S: fmt::Debug, C> fmt::Debug for EvictOnError<S, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EvictOnError")
+            .field("inner", &self.inner)
+            .field("failed", &self.is_failed())
+            .finish()
+    }
+}
+
+// ===== impl EvictOnErrorFuture =====
+
+impl<F, C, T, E> Future for EvictOnErrorFuture<F, C>
+where
+    F: Future<Output = Result<T, E>>,
+    E: Into<crate::BoxError>,
+    C: Classify<E>,
+{
+    type Output = Result<T, crate::BoxError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let error = match ready!(this.inner.poll(cx)) {
+            Ok(rsp) => return Poll::Ready(Ok(rsp)),
+            Err(error) => error,
+        };
+        if this.classify.classify(&error).evicts() && !this.failed.swap(true, Ordering::AcqRel) {
+            debug!("evicting endpoint after failed response");
+        }
+        Poll::Ready(Err(error.into()))
+    }
+}
+
+impl<F: fmt::Debug, C> fmt::Debug for EvictOnErrorFuture<F, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EvictOnErrorFuture")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
@@ -20,6 +20,14 @@
 //! services in response to increases or decreases in load. Use this if you are able to
 //! dynamically add more service endpoints to the system to handle added load.
 //!
+//! Requests that fail on an endpoint are not retried by the balancer itself. To retry such
+//! requests on another endpoint, wrap the balancer in [`Retry`] middleware; each retry selects an
+//! endpoint anew. [`BudgetedRetry`] limits the fraction of requests that may be retried, so that
+//! an outage of several endpoints does not turn into a retry storm.
+//!
+//! [`Retry`]: crate::retry::Retry
+//! [`BudgetedRetry`]: crate::retry::budget::BudgetedRetry
+//!
 //! # Examples
 //!
 //! ```rust
@@ -57,5 +65,6 @@
 //! [`poll_ready`]: crate::Service::poll_ready
 
 pub mod error;
+pub mod health;
 pub mod p2c;
 pub mod pool;
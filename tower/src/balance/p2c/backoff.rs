@@ -0,0 +1,121 @@
+use super::remake::Remake;
+use futures_util::future::{self, TryFutureExt};
+use std::{
+    fmt,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tower_service::Service;
+
+/// An endpoint that makes a new service for its target when its service
+/// fails, after an exponentially increasing backoff.
+///
+/// By default, a balancer drops an endpoint whose [`poll_ready`] fails, and
+/// does not use it again until discovery re-inserts it. A [`Backoff`] instead
+/// makes its services with a [`MakeService`], much like a [`Reconnect`].
+/// When its service fails, the failed service is dropped, and the endpoint is
+/// not ready for `base`, doubling with each consecutive failure up to `max`.
+/// A new service is then made for the target, so the balancer re-admits the
+/// endpoint once it becomes ready. An endpoint's failures are forgotten once
+/// it is called. If the [`MakeService`] itself fails to become ready, the
+/// endpoint fails.
+///
+/// A [`Backoff`] does not measure load. It is typically wrapped in a load
+/// measurement, such as [`PendingRequests`], when it is discovered, for
+/// instance with a [`DiscoverMap`] over a discovered set of targets.
+///
+/// [`poll_ready`]: crate::Service::poll_ready
+/// [`MakeService`]: crate::MakeService
+/// [`Reconnect`]: crate::reconnect::Reconnect
+/// [`PendingRequests`]: crate::load::PendingRequests
+/// [`DiscoverMap`]: crate::discover::DiscoverMap
+pub struct Backoff<M, Target>
+where
+    M: Service<Target>,
+{
+    inner: Remake<M, Target>,
+    base: Duration,
+    max: Duration,
+    /// The number of consecutive failures.
+    failures: u32,
+}
+
+// ===== impl Backoff =====
+
+impl<M, Target> Backoff<M, Target>
+where
+    M: Service<Target>,
+{
+    /// Creates an endpoint that makes services for `target` with `make`, and
+    /// backs off for between `base` and `max` when they fail.
+    ///
+    /// The first service is made when the endpoint is first polled.
+    ///
+    /// # Panics
+    ///
+    /// If `base` is zero or `max` is less than `base`.
+    pub fn new(make: M, target: Target, base: Duration, max: Duration) -> Self {
+        assert!(base > Duration::from_secs(0), "backoff must be positive");
+        assert!(
+            max >= base,
+            "maximum backoff must not be less than the base"
+        );
+        Self {
+            inner: Remake::new(make, target),
+            base,
+            max,
+            failures: 0,
+        }
+    }
+
+    /// Returns `true` if the endpoint is backing off after a failure.
+    pub fn is_backing_off(&self) -> bool {
+        self.inner.is_waiting()
+    }
+}
+
+impl<M, Target, S, Request> Service<Request> for Backoff<M, Target>
+where
+    M: Service<Target, Response = S>,
+    M::Error: Into<crate::BoxError>,
+    S: Service<Request>,
+    S::Error: Into<crate::BoxError>,
+    Target: Clone,
+{
+    type Response = S::Response;
+    type Error = crate::BoxError;
+    type Future = future::MapErr<S::Future, fn(S::Error) -> crate::BoxError>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let (base, max) = (self.base, self.max);
+        let failures = &mut self.failures;
+        self.inner.poll_ready(cx, || {
+            *failures = failures.saturating_add(1);
+            let delay = 2u32
+                .checked_pow(*failures - 1)
+                .and_then(|factor| base.checked_mul(factor))
+                .map_or(max, |delay| delay.min(max));
+            Some(delay)
+        })
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        self.failures = 0;
+        self.inner.call(request).map_err(Into::into)
+    }
+}
+
+impl<M, Target> fmt::Debug for Backoff<M, Target>
+where
+    M: Service<Target>,
+    Target: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Backoff")
+            .field("inner", &self.inner)
+            .field("base", &self.base)
+            .field("max", &self.max)
+            .field("failures", &self.failures)
+            .finish()
+    }
+}
@@ -0,0 +1,241 @@
+use super::Tier;
+use crate::discover::{Change, Discover};
+use crate::load::{InFlight, Inherit, Load};
+use futures_core::{ready, Stream};
+use pin_project::pin_project;
+use std::{
+    collections::VecDeque,
+    fmt,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::time::Instant;
+use tower_service::Service;
+use tracing::debug;
+
+/// Wraps a `D`-typed stream of discovered services with [`Damped`], so that
+/// new endpoints are disfavored while the set of endpoints is unstable.
+///
+/// The endpoints are considered unstable while more than `max_failures` of
+/// them have failed within the last `window`. This is determined when an
+/// endpoint fails and when discovery changes. While they are unstable, the
+/// loads of endpoints that were discovered or that failed within the last
+/// `window` are multiplied by `penalty`, so that a balancer favors endpoints
+/// that have proven healthy. Once failures subside, endpoints report their
+/// loads unchanged.
+///
+/// An endpoint fails when its [`poll_ready`] fails. A [`Backoff`] endpoint
+/// replaces its failed services rather than failing, so its failures are not
+/// counted.
+///
+/// [`poll_ready`]: crate::Service::poll_ready
+/// [`Backoff`]: super::Backoff
+#[pin_project]
+pub struct DampedDiscover<D> {
+    #[pin]
+    discover: D,
+    damping: Arc<Damping>,
+}
+
+/// An endpoint whose load is penalized while it is new and the set of
+/// endpoints is unstable. See [`DampedDiscover`].
+pub struct Damped<S> {
+    inner: S,
+    /// When the endpoint was discovered or last failed.
+    since: Instant,
+    damping: Arc<Damping>,
+}
+
+struct Damping {
+    window: Duration,
+    max_failures: usize,
+    penalty: f64,
+    /// When endpoints failed within the last `window`.
+    failures: Mutex<VecDeque<Instant>>,
+    /// Whether too many endpoints had failed when last checked.
+    unstable: AtomicBool,
+}
+
+// ===== impl DampedDiscover =====
+
+impl<D> DampedDiscover<D> {
+    /// Wraps a [`Discover`], wrapping all of its services with [`Damped`].
+    ///
+    /// # Panics
+    ///
+    /// If `window` is zero or `penalty` is less than 1.0.
+    pub fn new(discover: D, window: Duration, max_failures: usize, penalty: f64) -> Self
+    where
+        D: Discover,
+    {
+        assert!(window > Duration::from_secs(0), "window must be positive");
+        assert!(penalty >= 1.0, "penalty must be at least 1.0");
+        let damping = Arc::new(Damping {
+            window,
+            max_failures,
+            penalty,
+            failures: Mutex::new(VecDeque::new()),
+            unstable: AtomicBool::new(false),
+        });
+        Self { discover, damping }
+    }
+}
+
+impl<D> Stream for DampedDiscover<D>
+where
+    D: Discover,
+{
+    type Item = Result<Change<D::Key, Damped<D::Service>>, D::Error>;
+
+    /// Yields the next discovery change set.
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        use self::Change::*;
+
+        let this = self.project();
+        let change = ready!(this.discover.poll_discover(cx)).transpose()?;
+        this.damping.update(Instant::now());
+        let change = match change {
+            None => return Poll::Ready(None),
+            Some(Insert(k, svc)) => Insert(k, Damped::new(svc, this.damping.clone())),
+            Some(Remove(k)) => Remove(k),
+        };
+
+        Poll::Ready(Some(Ok(change)))
+    }
+}
+
+impl<D: fmt::Debug> fmt::Debug for DampedDiscover<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DampedDiscover")
+            .field("discover", &self.discover)
+            .field("window", &self.damping.window)
+            .field("max_failures", &self.damping.max_failures)
+            .field("penalty", &self.damping.penalty)
+            .finish()
+    }
+}
+
+// ===== impl Damped =====
+
+impl<S> Damped<S> {
+    fn new(inner: S, damping: Arc<Damping>) -> Self {
+        Self {
+            inner,
+            since: Instant::now(),
+            damping,
+        }
+    }
+
+    /// Get a reference to the inner service
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner service
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Consume `self`, returning the inner service
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S> Load for Damped<S>
+where
+    S: Load,
+    S::Metric: Into<f64>,
+{
+    type Metric = f64;
+
+    fn load(&self) -> Self::Metric {
+        let load = self.inner.load().into();
+        if self.damping.unstable.load(Ordering::Acquire)
+            && self.since.elapsed() < self.damping.window
+        {
+            return load * self.damping.penalty;
+        }
+        load
+    }
+}
+
+impl<S: InFlight> InFlight for Damped<S> {
+    fn in_flight(&self) -> usize {
+        self.inner.in_flight()
+    }
+}
+
+impl<S: Inherit> Inherit for Damped<S> {
+    fn inherit(&mut self, prior: &Self) {
+        self.inner.inherit(&prior.inner);
+    }
+}
+
+impl<S: Tier> Tier for Damped<S> {
+    fn tier(&self) -> u32 {
+        self.inner.tier()
+    }
+}
+
+impl<S, Request> Service<Request> for Damped<S>
+where
+    S: Service<Request>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let poll = self.inner.poll_ready(cx);
+        if let Poll::Ready(Err(_)) = poll {
+            self.since = Instant::now();
+            self.damping.failed(self.since);
+        }
+        poll
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        self.inner.call(req)
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for Damped<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Damped")
+            .field("inner", &self.inner)
+            .field("since", &self.since)
+            .finish()
+    }
+}
+
+// ===== impl Damping =====
+
+impl Damping {
+    /// Records that an endpoint failed at `now`.
+    fn failed(&self, now: Instant) {
+        self.failures
+            .lock()
+            .expect("damping failures poisoned")
+            .push_back(now);
+        self.update(now);
+    }
+
+    /// Forgets failures older than the window and records whether too many
+    /// endpoints have failed recently.
+    fn update(&self, now: Instant) {
+        let mut failures = self.failures.lock().expect("damping failures poisoned");
+        while matches!(failures.front(), Some(&t) if now - t >= self.window) {
+            failures.pop_front();
+        }
+        let unstable = failures.len() > self.max_failures;
+        if self.unstable.swap(unstable, Ordering::AcqRel) != unstable {
+            debug!(unstable, "endpoint stability changed");
+        }
+    }
+}
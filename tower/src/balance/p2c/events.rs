@@ -0,0 +1,267 @@
+use super::Tier;
+use crate::discover::{Change, Discover};
+use crate::load::{InFlight, Inherit, Load};
+use futures_core::{ready, Stream};
+use pin_project::pin_project;
+use std::{
+    fmt,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower_service::Service;
+
+/// A decision concerning a balanced endpoint, as reported to the sink of an
+/// [`EventsDiscover`].
+///
+/// Events identify endpoints by their [`Discover::Key`], so that log
+/// pipelines and metrics systems can correlate a balancer's decisions with
+/// the endpoints they concern.
+///
+/// [`Discover::Key`]: crate::discover::Discover::Key
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Event<'a, K> {
+    /// An endpoint was selected to serve a request.
+    Selected {
+        /// The endpoint's key.
+        key: &'a K,
+        /// The endpoint's load when it was selected.
+        load: f64,
+    },
+    /// An endpoint was removed from the balancer.
+    Evicted {
+        /// The endpoint's key.
+        key: &'a K,
+        /// Why the endpoint was removed.
+        reason: Eviction,
+    },
+}
+
+/// Why an endpoint was removed from a balancer. See [`Event::Evicted`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Eviction {
+    /// The endpoint was removed by discovery.
+    Removed,
+    /// The endpoint failed while it was being driven to readiness.
+    Failed,
+    /// The endpoint failed after it had become ready, when it was selected to
+    /// serve a request.
+    FailedWhenSelected,
+}
+
+/// Wraps a `D`-typed stream of discovered services with [`Evented`], so that
+/// the balancer's decisions about each endpoint are reported to a sink as
+/// structured [`Event`]s.
+///
+/// The sink is called with an [`Event::Selected`] each time an endpoint is
+/// called, and with an [`Event::Evicted`] each time an endpoint fails or is
+/// removed by discovery. The sink is called inline while the balancer is
+/// polled, so it should not block.
+///
+/// Endpoints should be wrapped after their [`Load`] is measured, so that the
+/// reported loads are those the balancer compares.
+///
+/// [`Load`]: crate::load::Load
+#[pin_project]
+pub struct EventsDiscover<D, F> {
+    #[pin]
+    discover: D,
+    sink: Arc<F>,
+}
+
+/// Reports the selection and failure of a discovered endpoint to a sink. See
+/// [`EventsDiscover`].
+pub struct Evented<K, S, F> {
+    key: K,
+    inner: S,
+    sink: Arc<F>,
+    /// Whether the endpoint has become ready and has not been called since.
+    ready: bool,
+}
+
+// ===== impl EventsDiscover =====
+
+impl<D, F> EventsDiscover<D, F> {
+    /// Wraps a [`Discover`], wrapping all of its services with [`Evented`],
+    /// reporting to `sink`.
+    pub fn new(discover: D, sink: F) -> Self
+    where
+        D: Discover,
+        F: Fn(&Event<'_, D::Key>),
+    {
+        Self {
+            discover,
+            sink: Arc::new(sink),
+        }
+    }
+
+    /// Get a reference to the inner discovery.
+    pub fn get_ref(&self) -> &D {
+        &self.discover
+    }
+
+    /// Get a mutable reference to the inner discovery.
+    pub fn get_mut(&mut self) -> &mut D {
+        &mut self.discover
+    }
+
+    /// Consume `self`, returning the inner discovery.
+    pub fn into_inner(self) -> D {
+        self.discover
+    }
+}
+
+impl<D, F> Stream for EventsDiscover<D, F>
+where
+    D: Discover,
+    D::Key: Clone,
+    F: Fn(&Event<'_, D::Key>),
+{
+    type Item = Result<Change<D::Key, Evented<D::Key, D::Service, F>>, D::Error>;
+
+    /// Yields the next discovery change set.
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        use self::Change::*;
+
+        let this = self.project();
+        let change = match ready!(this.discover.poll_discover(cx)).transpose()? {
+            None => return Poll::Ready(None),
+            Some(Insert(k, svc)) => Insert(k.clone(), Evented::new(k, svc, this.sink.clone())),
+            Some(Remove(k)) => {
+                (this.sink)(&Event::Evicted {
+                    key: &k,
+                    reason: Eviction::Removed,
+                });
+                Remove(k)
+            }
+        };
+
+        Poll::Ready(Some(Ok(change)))
+    }
+}
+
+impl<D: fmt::Debug, F> fmt::Debug for EventsDiscover<D, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventsDiscover")
+            .field("discover", &self.discover)
+            .finish()
+    }
+}
+
+// ===== impl Evented =====
+
+impl<K, S, F> Evented<K, S, F> {
+    fn new(key: K, inner: S, sink: Arc<F>) -> Self {
+        Self {
+            key,
+            inner,
+            sink,
+            ready: false,
+        }
+    }
+
+    /// Returns the endpoint's key.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Get a reference to the inner service
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner service
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Consume `self`, returning the inner service
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<K, S: Load, F> Load for Evented<K, S, F> {
+    type Metric = S::Metric;
+
+    fn load(&self) -> Self::Metric {
+        self.inner.load()
+    }
+}
+
+impl<K, S: InFlight, F> InFlight for Evented<K, S, F> {
+    fn in_flight(&self) -> usize {
+        self.inner.in_flight()
+    }
+}
+
+impl<K, S: Inherit, F> Inherit for Evented<K, S, F> {
+    fn inherit(&mut self, prior: &Self) {
+        self.inner.inherit(&prior.inner);
+    }
+}
+
+impl<K, S: Tier, F> Tier for Evented<K, S, F> {
+    fn tier(&self) -> u32 {
+        self.inner.tier()
+    }
+}
+
+impl<K, S, F, Request> Service<Request> for Evented<K, S, F>
+where
+    S: Service<Request> + Load,
+    S::Metric: Into<f64>,
+    F: Fn(&Event<'_, K>),
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        match self.inner.poll_ready(cx) {
+            Poll::Ready(Ok(())) => {
+                self.ready = true;
+                Poll::Ready(Ok(()))
+            }
+            Poll::Pending => {
+                self.ready = false;
+                Poll::Pending
+            }
+            Poll::Ready(Err(e)) => {
+                // A balancer only polls a ready endpoint again once it has
+                // been selected.
+                let reason = if self.ready {
+                    Eviction::FailedWhenSelected
+                } else {
+                    Eviction::Failed
+                };
+                self.ready = false;
+                (self.sink)(&Event::Evicted {
+                    key: &self.key,
+                    reason,
+                });
+                Poll::Ready(Err(e))
+            }
+        }
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        self.ready = false;
+        (self.sink)(&Event::Selected {
+            key: &self.key,
+            load: self.inner.load().into(),
+        });
+        self.inner.call(req)
+    }
+}
+
+impl<K: fmt::Debug, S: fmt::Debug, F> fmt::Debug for Evented<K, S, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Evented")
+            .field("key", &self.key)
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
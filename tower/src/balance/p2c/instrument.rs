@@ -0,0 +1,113 @@
+use crate::discover::Discover;
+use crate::load::{CompleteOnResponse, PeakEwmaConfig, PeakEwmaDiscover, PendingRequestsDiscover};
+use std::time::Duration;
+
+/// Wraps the [`Discover`] produced for each balancer built by a
+/// [`MakeBalance`], typically to attach a [`Load`] metric to each discovered
+/// endpoint.
+///
+/// This is implemented for closures taking the [`Discover`], so any
+/// instrumentation may be applied with, for example,
+/// `|discover| PendingRequestsDiscover::new(discover, CompleteOnResponse)`.
+/// [`PeakEwmaInstrument`] and [`PendingRequestsInstrument`] cover the common
+/// cases.
+///
+/// [`MakeBalance`]: super::MakeBalance
+/// [`Load`]: crate::load::Load
+pub trait Instrument<D> {
+    /// The instrumented [`Discover`].
+    type Discover;
+
+    /// Wraps `discover`.
+    fn instrument(&self, discover: D) -> Self::Discover;
+}
+
+/// An [`Instrument`] that leaves discovered endpoints unchanged.
+///
+/// This is the default for [`MakeBalance`], which then expects the discovered
+/// endpoints to implement [`Load`] already.
+///
+/// [`MakeBalance`]: super::MakeBalance
+/// [`Load`]: crate::load::Load
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoInstrument;
+
+/// An [`Instrument`] that wraps each discovered endpoint with a
+/// [`PeakEwma`] load metric.
+///
+/// Every balancer instrumented with this shares its [`PeakEwmaConfig`].
+///
+/// [`PeakEwma`]: crate::load::PeakEwma
+#[derive(Clone, Debug)]
+pub struct PeakEwmaInstrument {
+    config: PeakEwmaConfig,
+}
+
+/// An [`Instrument`] that wraps each discovered endpoint with a
+/// [`PendingRequests`] load metric.
+///
+/// [`PendingRequests`]: crate::load::PendingRequests
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PendingRequestsInstrument;
+
+impl<D> Instrument<D> for NoInstrument {
+    type Discover = D;
+
+    fn instrument(&self, discover: D) -> D {
+        discover
+    }
+}
+
+impl<F, D, O> Instrument<D> for F
+where
+    F: Fn(D) -> O,
+{
+    type Discover = O;
+
+    fn instrument(&self, discover: D) -> O {
+        self(discover)
+    }
+}
+
+// ===== impl PeakEwmaInstrument =====
+
+impl PeakEwmaInstrument {
+    /// Creates an [`Instrument`] that wraps endpoints with [`PeakEwma`] load
+    /// metrics using `default_rtt` and `decay`. See [`PeakEwmaConfig::new`].
+    ///
+    /// [`PeakEwma`]: crate::load::PeakEwma
+    pub fn new(default_rtt: Duration, decay: Duration) -> Self {
+        Self::with_config(PeakEwmaConfig::new(default_rtt, decay))
+    }
+
+    /// Creates an [`Instrument`] that wraps endpoints with [`PeakEwma`] load
+    /// metrics whose parameters are read from `config`.
+    ///
+    /// [`PeakEwma`]: crate::load::PeakEwma
+    pub fn with_config(config: PeakEwmaConfig) -> Self {
+        Self { config }
+    }
+
+    /// Returns the configuration shared by instrumented endpoints.
+    pub fn config(&self) -> &PeakEwmaConfig {
+        &self.config
+    }
+}
+
+impl<D: Discover> Instrument<D> for PeakEwmaInstrument {
+    type Discover = PeakEwmaDiscover<D>;
+
+    fn instrument(&self, discover: D) -> Self::Discover {
+        PeakEwmaDiscover::with_config(discover, self.config.clone(), CompleteOnResponse)
+    }
+}
+
+// ===== impl PendingRequestsInstrument =====
+
+impl<D: Discover> Instrument<D> for PendingRequestsInstrument {
+    type Discover = PendingRequestsDiscover<D>;
+
+    fn instrument(&self, discover: D) -> Self::Discover {
+        PendingRequestsDiscover::wrap(discover, CompleteOnResponse)
+    }
+}
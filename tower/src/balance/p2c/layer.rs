@@ -1,5 +1,6 @@
+use super::instrument::{NoInstrument, PeakEwmaInstrument, PendingRequestsInstrument};
 use super::MakeBalance;
-use std::{fmt, marker::PhantomData};
+use std::{fmt, marker::PhantomData, time::Duration};
 use tower_layer::Layer;
 
 /// Construct load balancers ([`Balance`]) over dynamic service sets ([`Discover`]) produced by the
@@ -19,7 +20,8 @@ use tower_layer::Layer;
 /// [`MakeService`]: crate::MakeService
 /// [`Service`]: crate::Service
 #[derive(Clone)]
-pub struct MakeBalanceLayer<D, Req> {
+pub struct MakeBalanceLayer<D, Req, I = NoInstrument> {
+    instrument: I,
     _marker: PhantomData<fn(D, Req)>,
 }
 
@@ -27,27 +29,54 @@ impl<D, Req> MakeBalanceLayer<D, Req> {
     /// Build balancers using operating system entropy.
     pub fn new() -> Self {
         Self {
+            instrument: NoInstrument,
             _marker: PhantomData,
         }
     }
 }
 
+impl<D, Req, I> MakeBalanceLayer<D, Req, I> {
+    /// See [`MakeBalance::with_instrument`].
+    pub fn with_instrument<I2>(self, instrument: I2) -> MakeBalanceLayer<D, Req, I2> {
+        MakeBalanceLayer {
+            instrument,
+            _marker: PhantomData,
+        }
+    }
+
+    /// See [`MakeBalance::with_peak_ewma`].
+    pub fn with_peak_ewma(
+        self,
+        default_rtt: Duration,
+        decay: Duration,
+    ) -> MakeBalanceLayer<D, Req, PeakEwmaInstrument> {
+        self.with_instrument(PeakEwmaInstrument::new(default_rtt, decay))
+    }
+
+    /// See [`MakeBalance::with_pending_requests`].
+    pub fn with_pending_requests(self) -> MakeBalanceLayer<D, Req, PendingRequestsInstrument> {
+        self.with_instrument(PendingRequestsInstrument)
+    }
+}
+
 impl<D, Req> Default for MakeBalanceLayer<D, Req> {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl<S, Req> Layer<S> for MakeBalanceLayer<S, Req> {
-    type Service = MakeBalance<S, Req>;
+impl<S, Req, I: Clone> Layer<S> for MakeBalanceLayer<S, Req, I> {
+    type Service = MakeBalance<S, Req, I>;
 
     fn layer(&self, make_discover: S) -> Self::Service {
-        MakeBalance::new(make_discover)
+        MakeBalance::new(make_discover).with_instrument(self.instrument.clone())
     }
 }
 
-impl<D, Req> fmt::Debug for MakeBalanceLayer<D, Req> {
+impl<D, Req, I: fmt::Debug> fmt::Debug for MakeBalanceLayer<D, Req, I> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.debug_struct("MakeBalanceLayer").finish()
+        f.debug_struct("MakeBalanceLayer")
+            .field("instrument", &self.instrument)
+            .finish()
     }
 }
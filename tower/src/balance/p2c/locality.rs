@@ -0,0 +1,190 @@
+use super::Tier;
+use crate::discover::{Change, Discover};
+use crate::load::{InFlight, Inherit, Load};
+use futures_core::{ready, Stream};
+use pin_project::pin_project;
+use std::{
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tower_service::Service;
+
+/// Identifies the locality of discovered endpoints, such as the availability
+/// zone in which they run.
+///
+/// This is implemented by the [`Discover::Key`] of a [`LocalityDiscover`],
+/// which prefers endpoints in the balancer's own locality, so that traffic
+/// stays local in multi-zone deployments.
+///
+/// [`Discover::Key`]: crate::discover::Discover::Key
+pub trait Locality {
+    /// Returns the locality of the endpoint with this key.
+    fn locality(&self) -> &str;
+}
+
+/// Wraps a `D`-typed stream of discovered services with [`Localized`], so that
+/// endpoints outside of the `local` [`Locality`] only receive requests when no
+/// local endpoint is available.
+///
+/// A local endpoint is available if it is ready and its load is at most
+/// `max_load`. Available local endpoints are in [`Tier`] 0, and all others
+/// are in tier 1, so a balancer configured with [`Balance::with_tiers`]
+/// selects among the available local endpoints as usual, and spills over to
+/// the other endpoints once no local endpoint is available.
+///
+/// [`Balance::with_tiers`]: super::Balance::with_tiers
+#[pin_project]
+pub struct LocalityDiscover<D> {
+    #[pin]
+    discover: D,
+    local: String,
+    max_load: f64,
+}
+
+/// An endpoint that is either in the balancer's own locality or is only used
+/// when no local endpoint is available. See [`LocalityDiscover`].
+pub struct Localized<S> {
+    inner: S,
+    local: bool,
+    max_load: f64,
+}
+
+// ===== impl LocalityDiscover =====
+
+impl<D> LocalityDiscover<D> {
+    /// Wraps a [`Discover`], wrapping all of its services with [`Localized`].
+    pub fn new(discover: D, local: impl Into<String>, max_load: f64) -> Self
+    where
+        D: Discover,
+        D::Key: Locality,
+    {
+        Self {
+            discover,
+            local: local.into(),
+            max_load,
+        }
+    }
+}
+
+impl<D> Stream for LocalityDiscover<D>
+where
+    D: Discover,
+    D::Key: Locality,
+{
+    type Item = Result<Change<D::Key, Localized<D::Service>>, D::Error>;
+
+    /// Yields the next discovery change set.
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        use self::Change::*;
+
+        let this = self.project();
+        let change = match ready!(this.discover.poll_discover(cx)).transpose()? {
+            None => return Poll::Ready(None),
+            Some(Insert(k, svc)) => {
+                let local = k.locality() == this.local.as_str();
+                Insert(k, Localized::new(svc, local, *this.max_load))
+            }
+            Some(Remove(k)) => Remove(k),
+        };
+
+        Poll::Ready(Some(Ok(change)))
+    }
+}
+
+impl<D: fmt::Debug> fmt::Debug for LocalityDiscover<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalityDiscover")
+            .field("discover", &self.discover)
+            .field("local", &self.local)
+            .finish()
+    }
+}
+
+// ===== impl Localized =====
+
+impl<S> Localized<S> {
+    fn new(inner: S, local: bool, max_load: f64) -> Self {
+        Self {
+            inner,
+            local,
+            max_load,
+        }
+    }
+
+    /// Returns `true` if the endpoint is in the balancer's own locality.
+    pub fn is_local(&self) -> bool {
+        self.local
+    }
+
+    /// Get a reference to the inner service
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner service
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+}
+
+impl<S: Load> Load for Localized<S> {
+    type Metric = S::Metric;
+
+    fn load(&self) -> Self::Metric {
+        self.inner.load()
+    }
+}
+
+impl<S: InFlight> InFlight for Localized<S> {
+    fn in_flight(&self) -> usize {
+        self.inner.in_flight()
+    }
+}
+
+impl<S: Inherit> Inherit for Localized<S> {
+    fn inherit(&mut self, prior: &Self) {
+        self.inner.inherit(&prior.inner);
+    }
+}
+
+impl<S> Tier for Localized<S>
+where
+    S: Load,
+    S::Metric: Into<f64>,
+{
+    fn tier(&self) -> u32 {
+        if self.local && self.inner.load().into() <= self.max_load {
+            0
+        } else {
+            1
+        }
+    }
+}
+
+impl<S, Request> Service<Request> for Localized<S>
+where
+    S: Service<Request>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        self.inner.call(req)
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for Localized<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Localized")
+            .field("inner", &self.inner)
+            .field("local", &self.local)
+            .field("max_load", &self.max_load)
+            .finish()
+    }
+}
@@ -1,3 +1,4 @@
+use super::instrument::{Instrument, NoInstrument, PeakEwmaInstrument, PendingRequestsInstrument};
 use super::Balance;
 use crate::discover::Discover;
 use futures_core::ready;
@@ -8,6 +9,7 @@ use std::{
     future::Future,
     pin::Pin,
     task::{Context, Poll},
+    time::Duration,
 };
 use tower_service::Service;
 
@@ -18,14 +20,19 @@ use tower_service::Service;
 /// service set in the form of a [`Discover`]. It then wraps the service set in a [`Balance`]
 /// before returning it as the "made" service.
 ///
+/// The discovered services must implement [`Load`]. Services that do not may be instrumented as
+/// they are discovered with, for example, [`MakeBalance::with_peak_ewma`].
+///
 /// See the [module-level documentation](crate::balance) for details on load balancing.
 ///
 /// [`MakeService`]: crate::MakeService
 /// [`Discover`]: crate::discover::Discover
 /// [`Balance`]: crate::balance::p2c::Balance
+/// [`Load`]: crate::load::Load
 #[derive(Clone, Debug)]
-pub struct MakeBalance<S, Req> {
+pub struct MakeBalance<S, Req, I = NoInstrument> {
     inner: S,
+    instrument: I,
     _marker: PhantomData<fn(Req)>,
 }
 
@@ -34,9 +41,10 @@ pub struct MakeBalance<S, Req> {
 /// [`Balance`]: crate::balance::p2c::Balance
 #[pin_project]
 #[derive(Debug)]
-pub struct MakeFuture<F, Req> {
+pub struct MakeFuture<F, Req, I = NoInstrument> {
     #[pin]
     inner: F,
+    instrument: I,
     _marker: PhantomData<fn(Req)>,
 }
 
@@ -45,22 +53,57 @@ impl<S, Req> MakeBalance<S, Req> {
     pub fn new(make_discover: S) -> Self {
         Self {
             inner: make_discover,
+            instrument: NoInstrument,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<S, Req, I> MakeBalance<S, Req, I> {
+    /// Wraps each [`Discover`] produced by the inner service with `instrument` before it is
+    /// balanced.
+    ///
+    /// [`Discover`]: crate::discover::Discover
+    pub fn with_instrument<I2>(self, instrument: I2) -> MakeBalance<S, Req, I2> {
+        MakeBalance {
+            inner: self.inner,
+            instrument,
             _marker: PhantomData,
         }
     }
+
+    /// Wraps each discovered service with a [`PeakEwma`] load metric. See
+    /// [`PeakEwmaInstrument`].
+    ///
+    /// [`PeakEwma`]: crate::load::PeakEwma
+    pub fn with_peak_ewma(
+        self,
+        default_rtt: Duration,
+        decay: Duration,
+    ) -> MakeBalance<S, Req, PeakEwmaInstrument> {
+        self.with_instrument(PeakEwmaInstrument::new(default_rtt, decay))
+    }
+
+    /// Wraps each discovered service with a [`PendingRequests`] load metric.
+    ///
+    /// [`PendingRequests`]: crate::load::PendingRequests
+    pub fn with_pending_requests(self) -> MakeBalance<S, Req, PendingRequestsInstrument> {
+        self.with_instrument(PendingRequestsInstrument)
+    }
 }
 
-impl<S, Target, Req> Service<Target> for MakeBalance<S, Req>
+impl<S, Target, Req, I> Service<Target> for MakeBalance<S, Req, I>
 where
     S: Service<Target>,
-    S::Response: Discover,
-    <S::Response as Discover>::Key: Hash,
-    <S::Response as Discover>::Service: Service<Req>,
-    <<S::Response as Discover>::Service as Service<Req>>::Error: Into<crate::BoxError>,
+    I: Instrument<S::Response> + Clone,
+    I::Discover: Discover,
+    <I::Discover as Discover>::Key: Hash,
+    <I::Discover as Discover>::Service: Service<Req>,
+    <<I::Discover as Discover>::Service as Service<Req>>::Error: Into<crate::BoxError>,
 {
-    type Response = Balance<S::Response, Req>;
+    type Response = Balance<I::Discover, Req>;
     type Error = S::Error;
-    type Future = MakeFuture<S::Future, Req>;
+    type Future = MakeFuture<S::Future, Req, I>;
 
     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
         self.inner.poll_ready(cx)
@@ -69,25 +112,27 @@ where
     fn call(&mut self, target: Target) -> Self::Future {
         MakeFuture {
             inner: self.inner.call(target),
+            instrument: self.instrument.clone(),
             _marker: PhantomData,
         }
     }
 }
 
-impl<F, T, E, Req> Future for MakeFuture<F, Req>
+impl<F, T, E, Req, I> Future for MakeFuture<F, Req, I>
 where
     F: Future<Output = Result<T, E>>,
-    T: Discover,
-    <T as Discover>::Key: Hash,
-    <T as Discover>::Service: Service<Req>,
-    <<T as Discover>::Service as Service<Req>>::Error: Into<crate::BoxError>,
+    I: Instrument<T>,
+    I::Discover: Discover,
+    <I::Discover as Discover>::Key: Hash,
+    <I::Discover as Discover>::Service: Service<Req>,
+    <<I::Discover as Discover>::Service as Service<Req>>::Error: Into<crate::BoxError>,
 {
-    type Output = Result<Balance<T, Req>, E>;
+    type Output = Result<Balance<I::Discover, Req>, E>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.project();
         let inner = ready!(this.inner.poll(cx))?;
-        let svc = Balance::new(inner);
+        let svc = Balance::new(this.instrument.instrument(inner));
         Poll::Ready(Ok(svc))
     }
 }
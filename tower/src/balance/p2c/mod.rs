@@ -25,17 +25,42 @@
 //! that lets you specify the random seed to use. Usually the former is what you'll want, though
 //! the latter may come in handy for reproducability or to reduce reliance on the operating system.
 //!
+//! For environments without an entropy source, such as some WebAssembly targets or deterministic
+//! simulations, the balancer is also available through the `balance-no-rand` feature, which does
+//! not depend on `rand`. Unless `rand` is enabled by some other means, endpoints are then sampled
+//! from a counter rather than at random, and [`Balance::new`] behaves like [`Balance::from_seed`].
+//!
 //! [Power of Two Random Choices]: http://www.eecs.harvard.edu/~michaelm/postscripts/handbook2001.pdf
 //! [finagle]: https://twitter.github.io/finagle/guide/Clients.html#power-of-two-choices-p2c-least-loaded
 //! [`Stream`]: https://docs.rs/futures/0.3/futures/stream/trait.Stream.html
 
+mod backoff;
+mod damping;
+mod events;
+mod instrument;
 mod layer;
+mod locality;
 mod make;
+mod priority;
+mod quarantine;
+mod remake;
+mod rng;
 mod service;
+mod shadow;
+mod stats;
 
 #[cfg(test)]
 mod test;
 
+pub use backoff::Backoff;
+pub use damping::{Damped, DampedDiscover};
+pub use events::{Event, Evented, EventsDiscover, Eviction};
+pub use instrument::{Instrument, NoInstrument, PeakEwmaInstrument, PendingRequestsInstrument};
 pub use layer::MakeBalanceLayer;
+pub use locality::{Locality, LocalityDiscover, Localized};
 pub use make::{MakeBalance, MakeFuture};
-pub use service::Balance;
+pub use priority::{Prioritized, Priority, PriorityDiscover};
+pub use quarantine::{QuarantineDiscover, Quarantined};
+pub use service::{Balance, Readiness, Scan, Tier};
+pub use shadow::{Mirror, Shadow, SplitShadows};
+pub use stats::{LoadSnapshot, LoadSnapshots, SelectionCount, SelectionCounts};
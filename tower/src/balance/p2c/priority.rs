@@ -0,0 +1,275 @@
+use super::Tier;
+use crate::discover::{Change, Discover};
+use crate::load::{InFlight, Inherit, Load};
+use futures_core::{ready, Stream};
+use pin_project::pin_project;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+};
+use tower_service::Service;
+use tracing::trace;
+
+/// Assigns discovered endpoints to priority groups.
+///
+/// This is implemented by the [`Discover::Key`] of a [`PriorityDiscover`].
+/// Lower values indicate higher priority: endpoints in group 0 are preferred
+/// over those in group 1, and so on. Lower priority groups only receive
+/// requests when every higher priority group lacks sufficient ready capacity,
+/// which allows, for instance, a backup cluster to take over when the primary
+/// cluster fails.
+///
+/// [`Discover::Key`]: crate::discover::Discover::Key
+pub trait Priority {
+    /// Returns the priority group of the endpoint with this key.
+    fn priority(&self) -> u32;
+}
+
+/// Wraps a `D`-typed stream of discovered services with [`Prioritized`], so
+/// that endpoints in lower [`Priority`] groups only receive requests when
+/// higher priority groups lack ready capacity.
+///
+/// Groups are considered in order of priority, and the first group in which
+/// at least `min_ready` (a fraction between 0.0 and 1.0) of the endpoints are
+/// ready is active. If no group has enough ready endpoints, the highest
+/// priority group with any ready endpoint is active. Endpoints in the active
+/// group are in [`Tier`] 0, and all others are in tier 1, so a balancer
+/// configured with [`Balance::with_tiers`] selects among the endpoints of the
+/// active group as usual.
+///
+/// [`Balance::with_tiers`]: super::Balance::with_tiers
+#[pin_project]
+pub struct PriorityDiscover<D> {
+    #[pin]
+    discover: D,
+    groups: Arc<Groups>,
+}
+
+/// An endpoint in a priority group. See [`PriorityDiscover`].
+pub struct Prioritized<S> {
+    inner: S,
+    priority: u32,
+    ready: bool,
+    groups: Arc<Groups>,
+}
+
+struct Groups {
+    min_ready: f64,
+    /// The number of ready and total endpoints in each group.
+    counts: Mutex<BTreeMap<u32, (usize, usize)>>,
+    /// The active group, or `NONE_ACTIVE` if no endpoint is ready.
+    active: AtomicU64,
+}
+
+const NONE_ACTIVE: u64 = u64::MAX;
+
+// ===== impl PriorityDiscover =====
+
+impl<D> PriorityDiscover<D> {
+    /// Wraps a [`Discover`], wrapping all of its services with
+    /// [`Prioritized`].
+    ///
+    /// # Panics
+    ///
+    /// If `min_ready` is not between 0.0 and 1.0, inclusive.
+    pub fn new(discover: D, min_ready: f64) -> Self
+    where
+        D: Discover,
+        D::Key: Priority,
+    {
+        assert!(
+            (0.0..=1.0).contains(&min_ready),
+            "minimum ready fraction must be between 0.0 and 1.0"
+        );
+        let groups = Arc::new(Groups {
+            min_ready,
+            counts: Mutex::new(BTreeMap::new()),
+            active: AtomicU64::new(NONE_ACTIVE),
+        });
+        Self { discover, groups }
+    }
+}
+
+impl<D> Stream for PriorityDiscover<D>
+where
+    D: Discover,
+    D::Key: Priority,
+{
+    type Item = Result<Change<D::Key, Prioritized<D::Service>>, D::Error>;
+
+    /// Yields the next discovery change set.
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        use self::Change::*;
+
+        let this = self.project();
+        let change = match ready!(this.discover.poll_discover(cx)).transpose()? {
+            None => return Poll::Ready(None),
+            Some(Insert(k, svc)) => {
+                let svc = Prioritized::new(svc, k.priority(), this.groups.clone());
+                Insert(k, svc)
+            }
+            Some(Remove(k)) => Remove(k),
+        };
+
+        Poll::Ready(Some(Ok(change)))
+    }
+}
+
+impl<D: fmt::Debug> fmt::Debug for PriorityDiscover<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PriorityDiscover")
+            .field("discover", &self.discover)
+            .finish()
+    }
+}
+
+// ===== impl Prioritized =====
+
+impl<S> Prioritized<S> {
+    fn new(inner: S, priority: u32, groups: Arc<Groups>) -> Self {
+        groups.update(|counts| counts.entry(priority).or_default().1 += 1);
+        Self {
+            inner,
+            priority,
+            ready: false,
+            groups,
+        }
+    }
+
+    /// Returns the endpoint's priority group.
+    pub fn priority(&self) -> u32 {
+        self.priority
+    }
+
+    /// Get a reference to the inner service
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner service
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Records whether the inner service is ready.
+    fn set_ready(&mut self, ready: bool) {
+        if self.ready == ready {
+            return;
+        }
+        self.ready = ready;
+        let priority = self.priority;
+        self.groups.update(|counts| {
+            let group = counts.entry(priority).or_default();
+            if ready {
+                group.0 += 1;
+            } else {
+                group.0 -= 1;
+            }
+        });
+    }
+}
+
+impl<S: Load> Load for Prioritized<S> {
+    type Metric = S::Metric;
+
+    fn load(&self) -> Self::Metric {
+        self.inner.load()
+    }
+}
+
+impl<S: InFlight> InFlight for Prioritized<S> {
+    fn in_flight(&self) -> usize {
+        self.inner.in_flight()
+    }
+}
+
+impl<S: Inherit> Inherit for Prioritized<S> {
+    fn inherit(&mut self, prior: &Self) {
+        self.inner.inherit(&prior.inner);
+    }
+}
+
+impl<S> Tier for Prioritized<S> {
+    fn tier(&self) -> u32 {
+        let active = self.groups.active.load(Ordering::Acquire);
+        if active == u64::from(self.priority) {
+            0
+        } else {
+            1
+        }
+    }
+}
+
+impl<S, Request> Service<Request> for Prioritized<S>
+where
+    S: Service<Request>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let poll = self.inner.poll_ready(cx);
+        self.set_ready(matches!(poll, Poll::Ready(Ok(()))));
+        poll
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        self.inner.call(req)
+    }
+}
+
+impl<S> Drop for Prioritized<S> {
+    fn drop(&mut self) {
+        let (priority, ready) = (self.priority, self.ready);
+        self.groups.update(|counts| {
+            let group = counts.entry(priority).or_default();
+            group.1 -= 1;
+            if ready {
+                group.0 -= 1;
+            }
+            if group.1 == 0 {
+                counts.remove(&priority);
+            }
+        });
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for Prioritized<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Prioritized")
+            .field("inner", &self.inner)
+            .field("priority", &self.priority)
+            .finish()
+    }
+}
+
+// ===== impl Groups =====
+
+impl Groups {
+    /// Updates the endpoint counts with `f`, and then the active group.
+    fn update(&self, f: impl FnOnce(&mut BTreeMap<u32, (usize, usize)>)) {
+        let mut counts = self.counts.lock().expect("priority groups poisoned");
+        f(&mut counts);
+
+        // The first group with sufficient ready capacity is active. If no
+        // group has sufficient capacity, the first group with any ready
+        // endpoints is active.
+        let sufficient = counts.iter().find(|(_, &(ready, total))| {
+            ready > 0 && ready as f64 >= self.min_ready * total as f64
+        });
+        let available = || counts.iter().find(|(_, &(ready, _))| ready > 0);
+        let active = sufficient
+            .or_else(available)
+            .map_or(NONE_ACTIVE, |(&priority, _)| u64::from(priority));
+        if self.active.swap(active, Ordering::AcqRel) != active {
+            trace!(priority = active, "active priority group changed");
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use super::remake::Remake;
+use crate::discover::{Change, Discover};
+use futures_core::{ready, Stream};
+use futures_util::future::{self, TryFutureExt};
+use pin_project::pin_project;
+use std::{
+    fmt,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+use tower_service::Service;
+use tracing::debug;
+
+/// Makes a [`Quarantined`] endpoint for each target discovered by a
+/// `D`-typed stream, so that the last remaining endpoint is quarantined when
+/// it fails, rather than dropped.
+///
+/// By default, a balancer drops an endpoint that fails. If it was the only
+/// endpoint, this leaves the balancer with no endpoints at all until discovery
+/// provides a new one, turning a degraded endpoint into an outage. The
+/// services discovered by `D` are instead treated as targets, and each
+/// endpoint makes its service for its target with a clone of `make`. When the
+/// service of the last remaining endpoint fails, the failed service is
+/// dropped, and the endpoint is not ready for `backoff`. A new service is then
+/// made for its target, for as long as discovery does not remove it. If other
+/// endpoints remain, failed endpoints fail as usual.
+///
+/// An endpoint remains until the balancer drops it, so endpoints that are
+/// being drained count as remaining endpoints. [`Quarantined`] endpoints do
+/// not measure load, so they are typically wrapped in a load measurement,
+/// such as [`PendingRequestsDiscover`].
+///
+/// [`PendingRequestsDiscover`]: crate::load::PendingRequestsDiscover
+#[pin_project]
+pub struct QuarantineDiscover<D, M> {
+    #[pin]
+    discover: D,
+    make: M,
+    backoff: Duration,
+    live: Arc<AtomicUsize>,
+}
+
+/// An endpoint that is quarantined if it fails while it is the last remaining
+/// endpoint. See [`QuarantineDiscover`].
+pub struct Quarantined<M, Target>
+where
+    M: Service<Target>,
+{
+    inner: Remake<M, Target>,
+    backoff: Duration,
+    /// The number of endpoints that have not been dropped.
+    live: Arc<AtomicUsize>,
+}
+
+// ===== impl QuarantineDiscover =====
+
+impl<D, M> QuarantineDiscover<D, M> {
+    /// Wraps a [`Discover`] of targets, making a [`Quarantined`] endpoint for
+    /// each of them with `make`.
+    ///
+    /// # Panics
+    ///
+    /// If `backoff` is zero.
+    pub fn new(discover: D, make: M, backoff: Duration) -> Self
+    where
+        D: Discover,
+        M: Service<D::Service> + Clone,
+    {
+        assert!(backoff > Duration::from_secs(0), "backoff must be positive");
+        Self {
+            discover,
+            make,
+            backoff,
+            live: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+}
+
+impl<D, M> Stream for QuarantineDiscover<D, M>
+where
+    D: Discover,
+    M: Service<D::Service> + Clone,
+{
+    type Item = Result<Change<D::Key, Quarantined<M, D::Service>>, D::Error>;
+
+    /// Yields the next discovery change set.
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        use self::Change::*;
+
+        let this = self.project();
+        let (make, backoff, live) = (this.make, *this.backoff, this.live);
+        let quarantined = |target| Quarantined::new(make.clone(), target, backoff, live.clone());
+        let change = match ready!(this.discover.poll_discover(cx)).transpose()? {
+            None => return Poll::Ready(None),
+            Some(Insert(k, target)) => Insert(k, quarantined(target)),
+            Some(Remove(k)) => Remove(k),
+        };
+
+        Poll::Ready(Some(Ok(change)))
+    }
+}
+
+impl<D: fmt::Debug, M> fmt::Debug for QuarantineDiscover<D, M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QuarantineDiscover")
+            .field("discover", &self.discover)
+            .field("backoff", &self.backoff)
+            .finish()
+    }
+}
+
+// ===== impl Quarantined =====
+
+impl<M, Target> Quarantined<M, Target>
+where
+    M: Service<Target>,
+{
+    fn new(make: M, target: Target, backoff: Duration, live: Arc<AtomicUsize>) -> Self {
+        live.fetch_add(1, Ordering::AcqRel);
+        Self {
+            inner: Remake::new(make, target),
+            backoff,
+            live,
+        }
+    }
+
+    /// Returns `true` if the endpoint is quarantined.
+    pub fn is_quarantined(&self) -> bool {
+        self.inner.is_waiting()
+    }
+}
+
+impl<M, Target, S, Request> Service<Request> for Quarantined<M, Target>
+where
+    M: Service<Target, Response = S>,
+    M::Error: Into<crate::BoxError>,
+    S: Service<Request>,
+    S::Error: Into<crate::BoxError>,
+    Target: Clone,
+{
+    type Response = S::Response;
+    type Error = crate::BoxError;
+    type Future = future::MapErr<S::Future, fn(S::Error) -> crate::BoxError>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let (live, backoff) = (&self.live, self.backoff);
+        self.inner.poll_ready(cx, || {
+            if live.load(Ordering::Acquire) > 1 {
+                return None;
+            }
+            debug!("quarantining last endpoint");
+            Some(backoff)
+        })
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        self.inner.call(request).map_err(Into::into)
+    }
+}
+
+impl<M, Target> Drop for Quarantined<M, Target>
+where
+    M: Service<Target>,
+{
+    fn drop(&mut self) {
+        self.live.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl<M, Target> fmt::Debug for Quarantined<M, Target>
+where
+    M: Service<Target>,
+    Target: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Quarantined")
+            .field("inner", &self.inner)
+            .field("backoff", &self.backoff)
+            .finish()
+    }
+}
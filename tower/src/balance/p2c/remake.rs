@@ -0,0 +1,140 @@
+use futures_core::ready;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::time::{sleep, Sleep};
+use tower_service::Service;
+use tracing::{debug, trace};
+
+/// Makes a service for a target, and makes a new one when the service fails.
+///
+/// A service that has failed must not be polled again, so a failed service is
+/// always dropped. The new service may be made after a delay.
+pub(super) struct Remake<M, Target>
+where
+    M: Service<Target>,
+{
+    make: M,
+    target: Target,
+    state: State<M::Future, M::Response>,
+}
+
+enum State<F, S> {
+    /// A service must be made.
+    Idle,
+    /// A service is being made.
+    Making(Pin<Box<F>>),
+    /// A service has been made.
+    Made(S),
+    /// Waiting to make a new service after a failure.
+    Waiting(Pin<Box<Sleep>>),
+}
+
+impl<M, Target> Remake<M, Target>
+where
+    M: Service<Target>,
+{
+    pub(super) fn new(make: M, target: Target) -> Self {
+        Self {
+            make,
+            target,
+            state: State::Idle,
+        }
+    }
+
+    /// Returns `true` if a new service will be made once a delay elapses.
+    pub(super) fn is_waiting(&self) -> bool {
+        matches!(self.state, State::Waiting(_))
+    }
+
+    /// Drives the made service to readiness, making one first if necessary.
+    ///
+    /// When making a service fails, or a made service fails, `delay` is
+    /// called. If it returns a delay, the failed service is dropped and a new
+    /// one is made once the delay elapses. Otherwise, the error is returned.
+    /// Errors from the maker's `poll_ready` are always returned.
+    pub(super) fn poll_ready<Request>(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut delay: impl FnMut() -> Option<Duration>,
+    ) -> Poll<Result<(), crate::BoxError>>
+    where
+        M::Error: Into<crate::BoxError>,
+        M::Response: Service<Request>,
+        <M::Response as Service<Request>>::Error: Into<crate::BoxError>,
+        Target: Clone,
+    {
+        loop {
+            let error = match self.state {
+                State::Idle => {
+                    ready!(self.make.poll_ready(cx)).map_err(Into::into)?;
+                    trace!("making service");
+                    let making = self.make.call(self.target.clone());
+                    self.state = State::Making(Box::pin(making));
+                    continue;
+                }
+                State::Making(ref mut making) => match ready!(making.as_mut().poll(cx)) {
+                    Ok(service) => {
+                        self.state = State::Made(service);
+                        continue;
+                    }
+                    Err(error) => error.into(),
+                },
+                State::Made(ref mut service) => match ready!(service.poll_ready(cx)) {
+                    Ok(()) => return Poll::Ready(Ok(())),
+                    Err(error) => error.into(),
+                },
+                State::Waiting(ref mut sleep) => {
+                    ready!(sleep.as_mut().poll(cx));
+                    self.state = State::Idle;
+                    continue;
+                }
+            };
+
+            self.state = State::Idle;
+            match delay() {
+                Some(delay) => {
+                    debug!(%error, ?delay, "service failed; making a new one after a delay");
+                    self.state = State::Waiting(Box::pin(sleep(delay)));
+                }
+                None => return Poll::Ready(Err(error)),
+            }
+        }
+    }
+
+    pub(super) fn call<Request>(
+        &mut self,
+        request: Request,
+    ) -> <M::Response as Service<Request>>::Future
+    where
+        M::Response: Service<Request>,
+    {
+        match self.state {
+            State::Made(ref mut service) => service.call(request),
+            _ => panic!("service not ready; poll_ready must be called first"),
+        }
+    }
+}
+
+impl<M, Target> fmt::Debug for Remake<M, Target>
+where
+    M: Service<Target>,
+    Target: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = match self.state {
+            State::Idle => "Idle",
+            State::Making(_) => "Making",
+            State::Made(_) => "Made",
+            State::Waiting(_) => "Waiting",
+        };
+        f.debug_struct("Remake")
+            .field("target", &self.target)
+            .field("state", &state)
+            .finish()
+    }
+}
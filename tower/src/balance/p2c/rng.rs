@@ -0,0 +1,99 @@
+//! The source of randomness used to select endpoints.
+//!
+//! With the `rand` feature enabled, endpoints are sampled with a [`SmallRng`].
+//! Otherwise (i.e. when only the `balance-no-rand` feature is enabled),
+//! samples are taken deterministically from a counter, so that the balancer
+//! may be used where no entropy source is available.
+//!
+//! [`SmallRng`]: rand::rngs::SmallRng
+
+#[cfg(feature = "rand")]
+use rand::{rngs::SmallRng, Rng as _, SeedableRng};
+
+/// Samples endpoint indices for a [`Balance`](super::Balance).
+#[derive(Debug)]
+pub(super) struct Rng {
+    #[cfg(feature = "rand")]
+    rng: SmallRng,
+    #[cfg(not(feature = "rand"))]
+    counter: u64,
+}
+
+impl Rng {
+    /// Returns a generator whose samples are fully determined by `seed`.
+    #[cfg(feature = "rand")]
+    pub(super) fn from_seed(seed: u64) -> Self {
+        Self {
+            rng: SmallRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Returns a generator whose samples are fully determined by `seed`.
+    #[cfg(not(feature = "rand"))]
+    pub(super) fn from_seed(seed: u64) -> Self {
+        Self { counter: seed }
+    }
+
+    #[cfg(feature = "rand")]
+    pub(super) fn from_rng<R: rand::Rng>(rng: R) -> Result<Self, rand::Error> {
+        Ok(Self {
+            rng: SmallRng::from_rng(rng)?,
+        })
+    }
+
+    /// Returns two distinct indices less than `len`, in no particular order.
+    ///
+    /// `len` must be at least 2.
+    pub(super) fn pair(&mut self, len: usize) -> (usize, usize) {
+        debug_assert!(len >= 2, "a pair requires at least two candidates");
+        #[cfg(feature = "rand")]
+        {
+            let idxs = rand::seq::index::sample(&mut self.rng, len, 2);
+            (idxs.index(0), idxs.index(1))
+        }
+        #[cfg(not(feature = "rand"))]
+        {
+            // Rotate the first index through every candidate, advancing the
+            // offset of the second after each full rotation, so that every
+            // ordered pair is visited once every `len * (len - 1)` samples.
+            let n = self.next() as usize;
+            let a = n % len;
+            let offset = 1 + (n / len) % (len - 1);
+            (a, (a + offset) % len)
+        }
+    }
+
+    /// Returns an index less than `len`, which must be positive.
+    pub(super) fn index(&mut self, len: usize) -> usize {
+        #[cfg(feature = "rand")]
+        {
+            self.rng.gen_range(0..len)
+        }
+        #[cfg(not(feature = "rand"))]
+        {
+            self.next() as usize % len
+        }
+    }
+
+    /// Returns a value in `[0, 1)`.
+    pub(super) fn fraction(&mut self) -> f64 {
+        #[cfg(feature = "rand")]
+        {
+            self.rng.gen::<f64>()
+        }
+        #[cfg(not(feature = "rand"))]
+        {
+            // A Weyl sequence spreads consecutive samples evenly over the
+            // unit interval.
+            let n = self.next().wrapping_mul(0x9E37_79B9_7F4A_7C15);
+            (n >> 11) as f64 / (1u64 << 53) as f64
+        }
+    }
+
+    #[cfg(not(feature = "rand"))]
+    fn next(&mut self) -> u64 {
+        let n = self.counter;
+        self.counter = self.counter.wrapping_add(1);
+        n
+    }
+}
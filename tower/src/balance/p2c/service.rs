@@ -1,11 +1,12 @@
 use super::super::error;
+use super::rng::Rng;
 use crate::discover::{Change, Discover};
-use crate::load::Load;
+use crate::load::{InFlight, Inherit, Load};
 use crate::ready_cache::{error::Failed, ReadyCache};
 use futures_core::ready;
 use futures_util::future::{self, TryFutureExt};
 use pin_project::pin_project;
-use rand::{rngs::SmallRng, Rng, SeedableRng};
+use std::borrow::Borrow;
 use std::hash::Hash;
 use std::marker::PhantomData;
 use std::{
@@ -13,8 +14,10 @@ use std::{
     future::Future,
     pin::Pin,
     task::{Context, Poll},
+    time::Duration,
 };
 use tokio::sync::oneshot;
+use tokio::time::{Instant, Sleep};
 use tower_service::Service;
 use tracing::{debug, trace};
 
@@ -39,7 +42,16 @@ where
     services: ReadyCache<D::Key, D::Service, Req>,
     ready_index: Option<usize>,
 
-    rng: SmallRng,
+    rng: Rng,
+
+    startup: Startup,
+    scan: Scan,
+
+    drain: Option<Drain<D::Service>>,
+    draining: Vec<Draining<D::Service>>,
+
+    inherit: Option<fn(&mut D::Service, &D::Service)>,
+    tier: Option<fn(&D::Service) -> u32>,
 
     _req: PhantomData<Req>,
 }
@@ -55,10 +67,59 @@ where
         f.debug_struct("Balance")
             .field("discover", &self.discover)
             .field("services", &self.services)
+            .field("draining", &self.draining.len())
             .finish()
     }
 }
 
+/// Configures how removed endpoints are drained. See [`Balance::with_drain_timeout`].
+struct Drain<S> {
+    timeout: Duration,
+    in_flight: fn(&S) -> usize,
+}
+
+/// An endpoint that has been removed by discovery but still has requests in flight.
+struct Draining<S> {
+    service: S,
+    timeout: Pin<Box<Sleep>>,
+}
+
+/// How long [`Balance`]'s `poll_ready` searches for a ready endpoint before
+/// yielding. See [`Balance::with_scan`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Scan {
+    /// Select endpoints until one is ready or no endpoints remain ready. This
+    /// is the default.
+    Full,
+    /// Yield once this many selected endpoints have turned out not to be
+    /// ready.
+    Attempts(usize),
+    /// Yield once this much time has been spent selecting endpoints that
+    /// turned out not to be ready.
+    Time(Duration),
+}
+
+/// Ranks endpoints so that a [`Balance`] configured with
+/// [`Balance::with_tiers`] only selects among the ready endpoints in the best
+/// tier.
+///
+/// Tiers are compared each time the balancer selects an endpoint, so an
+/// endpoint's tier may change over time, for instance as other endpoints
+/// become ready or their loads change.
+pub trait Tier {
+    /// Returns the endpoint's tier. Lower tiers are preferred.
+    fn tier(&self) -> u32;
+}
+
+/// Whether an endpoint reported by [`Balance::endpoints`] was ready.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Readiness {
+    /// The endpoint has become ready and may be selected for requests.
+    Ready,
+    /// The endpoint is being driven to readiness.
+    Pending,
+}
+
 /// A Future that becomes satisfied when an `S`-typed service is ready.
 ///
 /// May fail due to cancelation, i.e., if [`Discover`] removes the service from the service set.
@@ -78,6 +139,17 @@ enum Error<E> {
     Canceled,
 }
 
+/// Tracks the initial discovery window configured by [`Balance::with_startup_window`].
+#[derive(Debug)]
+enum Startup {
+    /// Requests may be dispatched.
+    Done,
+    /// A window has been configured, but the balancer has not yet been polled.
+    Configured(Duration),
+    /// Waiting for the window to elapse or for discovery to complete.
+    Waiting(Pin<Box<Sleep>>),
+}
+
 impl<D, Req> Balance<D, Req>
 where
     D: Discover,
@@ -86,23 +158,172 @@ where
     <D::Service as Service<Req>>::Error: Into<crate::BoxError>,
 {
     /// Constructs a load balancer that uses operating system entropy.
+    ///
+    /// Without the `rand` feature, endpoints are instead sampled
+    /// deterministically, as if by [`Balance::from_seed`] with a seed of 0.
     pub fn new(discover: D) -> Self {
-        Self::from_rng(discover, &mut rand::thread_rng()).expect("ThreadRNG must be valid")
+        #[cfg(feature = "rand")]
+        {
+            Self::from_rng(discover, &mut rand::thread_rng()).expect("ThreadRNG must be valid")
+        }
+        #[cfg(not(feature = "rand"))]
+        {
+            Self::from_seed(discover, 0)
+        }
     }
 
     /// Constructs a load balancer seeded with the provided random number generator.
-    pub fn from_rng<R: Rng>(discover: D, rng: R) -> Result<Self, rand::Error> {
-        let rng = SmallRng::from_rng(rng)?;
+    #[cfg(feature = "rand")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rand")))]
+    pub fn from_rng<R: rand::Rng>(discover: D, rng: R) -> Result<Self, rand::Error> {
+        let rng = Rng::from_rng(rng)?;
         Ok(Self {
             rng,
             discover,
             services: ReadyCache::default(),
             ready_index: None,
+            startup: Startup::Done,
+            scan: Scan::Full,
+            drain: None,
+            draining: Vec::new(),
+            inherit: None,
+            tier: None,
 
             _req: PhantomData,
         })
     }
 
+    /// Constructs a load balancer whose endpoint selection is fully determined
+    /// by `seed`.
+    ///
+    /// Given the same seed and the same sequence of discovery updates and
+    /// endpoint readiness, the balancer makes the same selections. Removing an
+    /// endpoint preserves the relative order of the remaining ready endpoints,
+    /// at the cost of making removals linear in the number of ready endpoints.
+    ///
+    /// This is primarily useful for tests and simulations that must produce
+    /// identical results across runs. Note that the sequence of selections for
+    /// a given seed may change between versions of this crate.
+    pub fn from_seed(discover: D, seed: u64) -> Self {
+        let mut services = ReadyCache::default();
+        services.preserve_order(true);
+        Self {
+            rng: Rng::from_seed(seed),
+            discover,
+            services,
+            ready_index: None,
+            startup: Startup::Done,
+            scan: Scan::Full,
+            drain: None,
+            draining: Vec::new(),
+            inherit: None,
+            tier: None,
+
+            _req: PhantomData,
+        }
+    }
+
+    /// Delays dispatching requests until the balancer has had a chance to
+    /// discover its initial set of endpoints.
+    ///
+    /// Without a startup window, the balancer becomes ready as soon as the
+    /// first endpoint is discovered and ready, so an initial burst of requests
+    /// may all be dispatched to whichever endpoint happened to arrive first.
+    /// With a startup window, [`poll_ready`] continues to process discovery
+    /// updates but does not become ready until `window` has elapsed since the
+    /// balancer was first polled. The window ends early if the [`Discover`]
+    /// stream completes and all discovered endpoints are ready.
+    ///
+    /// [`poll_ready`]: crate::Service::poll_ready
+    pub fn with_startup_window(mut self, window: Duration) -> Self {
+        self.startup = Startup::Configured(window);
+        self
+    }
+
+    /// Drains endpoints that are removed by discovery, rather than dropping
+    /// them immediately.
+    ///
+    /// When an endpoint is removed, it no longer receives new requests, but
+    /// the balancer holds on to it until all of its in-flight requests (as
+    /// reported by [`InFlight`]) have completed, or until `timeout` elapses,
+    /// whichever comes first. Draining endpoints are checked each time the
+    /// balancer is polled.
+    ///
+    /// When discovery inserts a new service with the key of an existing
+    /// endpoint, the prior service is drained in the same way, while the new
+    /// one becomes ready.
+    ///
+    /// Draining endpoints are not included in [`Balance::len`].
+    pub fn with_drain_timeout(mut self, timeout: Duration) -> Self
+    where
+        D::Service: InFlight,
+    {
+        self.drain = Some(Drain {
+            timeout,
+            in_flight: InFlight::in_flight,
+        });
+        self
+    }
+
+    /// Carries an endpoint's load measurements over to the new service when
+    /// discovery updates the endpoint with a [`Change::Insert`] of an
+    /// existing key.
+    ///
+    /// Unless the balancer drains endpoints, an update already keeps the prior
+    /// service in the ready set, and in its position, until the new service
+    /// becomes ready. Without inheritance,
+    /// though, the new service starts with fresh load measurements, so an
+    /// endpoint whose service is replaced after, for instance, a credential
+    /// rotation loses its load history. See [`Inherit`].
+    ///
+    /// [`Inherit`]: crate::load::Inherit
+    pub fn with_inherited_load(mut self) -> Self
+    where
+        D::Service: Inherit,
+    {
+        self.inherit = Some(Inherit::inherit);
+        self
+    }
+
+    /// Only selects among the ready endpoints in the lowest [`Tier`].
+    ///
+    /// Two ready endpoints in the lowest tier are compared by their loads, as
+    /// usual. Endpoints in higher tiers only receive requests when no endpoint
+    /// in a lower tier is ready. Finding the lowest tier takes time linear in
+    /// the number of ready endpoints.
+    pub fn with_tiers(mut self) -> Self
+    where
+        D::Service: Tier,
+    {
+        self.tier = Some(Tier::tier);
+        self
+    }
+
+    /// Bounds how long [`poll_ready`] searches for a ready endpoint.
+    ///
+    /// Endpoints that were ready when they were last polled may no longer be
+    /// ready when they are selected. Each such endpoint is moved back to the
+    /// pending set and another is selected. By default, this continues until
+    /// an endpoint is ready or none remain, which may poll many endpoints in a
+    /// large cluster. With [`Scan::Attempts`] or [`Scan::Time`], [`poll_ready`]
+    /// instead yields once the bound is reached, waking the task so that the
+    /// search continues when it is next polled.
+    ///
+    /// # Panics
+    ///
+    /// If the bound is zero.
+    ///
+    /// [`poll_ready`]: crate::Service::poll_ready
+    pub fn with_scan(mut self, scan: Scan) -> Self {
+        match scan {
+            Scan::Full => {}
+            Scan::Attempts(n) => assert!(n > 0, "scan attempts must be positive"),
+            Scan::Time(t) => assert!(t > Duration::from_secs(0), "scan time must be positive"),
+        }
+        self.scan = scan;
+        self
+    }
+
     /// Returns the number of endpoints currently tracked by the balancer.
     pub fn len(&self) -> usize {
         self.services.len()
@@ -114,6 +335,66 @@ where
     }
 }
 
+impl<D, Req> Balance<D, Req>
+where
+    D: Discover,
+    D::Key: Hash,
+    D::Service: Load,
+{
+    /// Returns a snapshot of the endpoints currently tracked by the balancer.
+    ///
+    /// Each endpoint is yielded with its key, its current load, and whether it
+    /// was ready as of the last time the balancer was polled. This is intended
+    /// to allow the balancer's state to be exported, e.g. to a metrics system.
+    pub fn endpoints(
+        &self,
+    ) -> impl Iterator<Item = (&D::Key, <D::Service as Load>::Metric, Readiness)> + '_ {
+        let ready = self
+            .services
+            .iter_ready()
+            .map(|(key, svc)| (key, svc.load(), Readiness::Ready));
+        let pending = self
+            .services
+            .iter_pending()
+            .map(|(key, svc)| (key, svc.load(), Readiness::Pending));
+        ready.chain(pending)
+    }
+
+    /// Returns `true` if the balancer is tracking an endpoint with the given
+    /// key, whether or not it is ready.
+    ///
+    /// The key may be any borrowed form of the endpoint's key type, so that,
+    /// for instance, endpoints keyed by [`String`] may be looked up by `&str`
+    /// without allocating.
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        D::Key: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.services.get_ready(key).is_some() || self.services.pending_contains(key)
+    }
+
+    /// Returns the current load of the endpoint with the given key, if the
+    /// balancer is tracking it.
+    ///
+    /// As with [`Balance::contains`], the key may be any borrowed form of the
+    /// endpoint's key type.
+    pub fn load_of<Q>(&self, key: &Q) -> Option<<D::Service as Load>::Metric>
+    where
+        D::Key: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.services.get_ready(key) {
+            Some((_, _, svc)) => Some(svc.load()),
+            None => self
+                .services
+                .iter_pending()
+                .find(|(k, _)| (*k).borrow() == key)
+                .map(|(_, svc)| svc.load()),
+        }
+    }
+}
+
 impl<D, Req> Balance<D, Req>
 where
     D: Discover + Unpin,
@@ -129,7 +410,10 @@ where
     fn update_pending_from_discover(
         &mut self,
         cx: &mut Context<'_>,
-    ) -> Poll<Option<Result<(), error::Discover>>> {
+    ) -> Poll<Option<Result<(), error::Discover>>>
+    where
+        D::Key: Clone,
+    {
         debug!("updating from discover");
         loop {
             match ready!(Pin::new(&mut self.discover).poll_discover(cx))
@@ -139,12 +423,19 @@ where
                 None => return Poll::Ready(None),
                 Some(Change::Remove(key)) => {
                     trace!("remove");
-                    self.services.evict(&key);
+                    self.remove_endpoint(&key);
                 }
-                Some(Change::Insert(key, svc)) => {
+                Some(Change::Insert(key, mut svc)) => {
                     trace!("insert");
-                    // If this service already existed in the set, it will be
-                    // replaced as the new one becomes ready.
+                    self.inherit_load(&key, &mut svc);
+                    // If this service already existed in the set, it is
+                    // drained if draining is configured. Otherwise, it will
+                    // be replaced as the new one becomes ready.
+                    if self.drain.is_some() {
+                        if let Some(service) = self.services.remove(&key) {
+                            self.drain(service);
+                        }
+                    }
                     self.services.push(key, svc);
                 }
             }
@@ -178,19 +469,121 @@ where
         );
     }
 
+    /// Carries the load measurements of the endpoint's current service, if
+    /// any, over to `svc`, if configured with [`Balance::with_inherited_load`].
+    fn inherit_load(&self, key: &D::Key, svc: &mut D::Service) {
+        let inherit = match self.inherit {
+            Some(inherit) => inherit,
+            None => return,
+        };
+        let prior = match self.services.get_ready(key) {
+            Some((_, _, prior)) => Some(prior),
+            None => self
+                .services
+                .iter_pending()
+                .find(|(k, _)| *k == key)
+                .map(|(_, prior)| prior),
+        };
+        if let Some(prior) = prior {
+            trace!("inheriting load");
+            inherit(svc, prior);
+        }
+    }
+
+    /// Removes the endpoint with the given key from the ready cache, draining
+    /// it if configured with [`Balance::with_drain_timeout`].
+    ///
+    /// Returns `true` if the endpoint was being tracked.
+    fn remove_endpoint<Q>(&mut self, key: &Q) -> bool
+    where
+        D::Key: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.drain.is_none() {
+            return self.services.evict(key);
+        }
+        match self.services.remove(key) {
+            Some(service) => {
+                self.drain(service);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes the endpoint with the given key from the balancer, as though it
+    /// had been removed by discovery.
+    ///
+    /// The endpoint is drained if configured with
+    /// [`Balance::with_drain_timeout`]. If discovery later inserts an
+    /// endpoint with the same key, it is added to the balancer again. The key
+    /// may be any borrowed form of the endpoint's key type, so that callers
+    /// need not allocate an owned key to evict an endpoint.
+    ///
+    /// Returns `true` if the endpoint was being tracked.
+    pub fn evict<Q>(&mut self, key: &Q) -> bool
+    where
+        D::Key: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.remove_endpoint(key)
+    }
+
+    /// Holds a removed endpoint until its in-flight requests complete or the
+    /// drain timeout elapses.
+    fn drain(&mut self, service: D::Service) {
+        let drain = self.drain.as_ref().expect("draining must be configured");
+        let in_flight = (drain.in_flight)(&service);
+        if in_flight > 0 {
+            debug!(in_flight, "draining endpoint");
+            self.draining.push(Draining {
+                service,
+                timeout: Box::pin(tokio::time::sleep(drain.timeout)),
+            });
+        }
+    }
+
+    /// Drops draining endpoints that have no more requests in flight or
+    /// whose drain timeout has elapsed.
+    fn poll_draining(&mut self, cx: &mut Context<'_>) {
+        let in_flight = match self.drain {
+            Some(ref drain) => drain.in_flight,
+            None => return,
+        };
+
+        let mut i = 0;
+        while i < self.draining.len() {
+            let draining = &mut self.draining[i];
+            let remaining = in_flight(&draining.service);
+            if remaining == 0 {
+                trace!("endpoint drained");
+            } else if draining.timeout.as_mut().poll(cx).is_ready() {
+                debug!(in_flight = remaining, "drain timeout elapsed");
+            } else {
+                i += 1;
+                continue;
+            }
+            self.draining.swap_remove(i);
+        }
+    }
+
     /// Performs P2C on inner services to find a suitable endpoint.
     fn p2c_ready_index(&mut self) -> Option<usize> {
-        match self.services.ready_len() {
+        // If endpoints are tiered, only those in the lowest tier are candidates.
+        let candidates = self.tier.map(|tier| self.lowest_tier_indices(tier));
+        let index = |i: usize| candidates.as_ref().map_or(i, |c| c[i]);
+        let len = candidates
+            .as_ref()
+            .map_or(self.services.ready_len(), Vec::len);
+        match len {
             0 => None,
-            1 => Some(0),
+            1 => Some(index(0)),
             len => {
                 // Get two distinct random indexes (in a random order) and
                 // compare the loads of the service at each index.
-                let idxs = rand::seq::index::sample(&mut self.rng, len, 2);
-
-                let aidx = idxs.index(0);
-                let bidx = idxs.index(1);
-                debug_assert_ne!(aidx, bidx, "random indices must be distinct");
+                let (a, b) = self.rng.pair(len);
+                debug_assert_ne!(a, b, "random indices must be distinct");
+                let (aidx, bidx) = (index(a), index(b));
 
                 let aload = self.ready_index_load(aidx);
                 let bload = self.ready_index_load(bidx);
@@ -209,12 +602,63 @@ where
         }
     }
 
+    /// Returns the indices of the ready endpoints in the lowest tier.
+    fn lowest_tier_indices(&self, tier: fn(&D::Service) -> u32) -> Vec<usize> {
+        let mut lowest = u32::MAX;
+        let mut indices = Vec::new();
+        for index in 0..self.services.ready_len() {
+            let (_, svc) = self.services.get_ready_index(index).expect("invalid index");
+            let t = tier(svc);
+            if t < lowest {
+                lowest = t;
+                indices.clear();
+            }
+            if t == lowest {
+                indices.push(index);
+            }
+        }
+        if !indices.is_empty() {
+            trace!(
+                tier = lowest,
+                endpoints = indices.len(),
+                "selecting from tier"
+            );
+        }
+        indices
+    }
+
     /// Accesses a ready endpoint by index and returns its current load.
     fn ready_index_load(&self, index: usize) -> <D::Service as Load>::Metric {
         let (_, svc) = self.services.get_ready_index(index).expect("invalid index");
         svc.load()
     }
 
+    /// Returns `Poll::Pending` while the startup window is still open.
+    ///
+    /// `exhausted` indicates whether the discovery stream has completed.
+    fn poll_startup(&mut self, cx: &mut Context<'_>, exhausted: bool) -> Poll<()> {
+        loop {
+            match self.startup {
+                Startup::Done => return Poll::Ready(()),
+                Startup::Configured(window) => {
+                    trace!(?window, "starting startup window");
+                    self.startup = Startup::Waiting(Box::pin(tokio::time::sleep(window)));
+                }
+                Startup::Waiting(ref mut sleep) => {
+                    if exhausted && self.services.pending_len() == 0 {
+                        debug!("initial discovery complete");
+                    } else if sleep.as_mut().poll(cx).is_ready() {
+                        debug!(endpoints = self.services.len(), "startup window elapsed");
+                    } else {
+                        return Poll::Pending;
+                    }
+                    self.startup = Startup::Done;
+                    return Poll::Ready(());
+                }
+            }
+        }
+    }
+
     pub(crate) fn discover_mut(&mut self) -> &mut D {
         &mut self.discover
     }
@@ -239,9 +683,21 @@ where
     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
         // `ready_index` may have already been set by a prior invocation. These
         // updates cannot disturb the order of existing ready services.
-        let _ = self.update_pending_from_discover(cx)?;
+        let exhausted = self.update_pending_from_discover(cx)?.is_ready();
         self.promote_pending_to_ready(cx);
+        self.poll_draining(cx);
+
+        // Don't select an endpoint until the initial set of endpoints has
+        // been discovered.
+        if self.poll_startup(cx, exhausted).is_pending() {
+            return Poll::Pending;
+        }
 
+        let mut unready = 0;
+        let started = match self.scan {
+            Scan::Time(_) => Some(Instant::now()),
+            _ => None,
+        };
         loop {
             // If a service has already been selected, ensure that it is ready.
             // This ensures that the underlying service is ready immediately
@@ -266,6 +722,18 @@ where
                         debug!(%error, "endpoint failed");
                     }
                 }
+
+                unready += 1;
+                let exceeded = match self.scan {
+                    Scan::Full => false,
+                    Scan::Attempts(max) => unready >= max,
+                    Scan::Time(max) => matches!(started, Some(t) if t.elapsed() >= max),
+                };
+                if exceeded {
+                    trace!(unready, "yielding before finding a ready endpoint");
+                    cx.waker().wake_by_ref();
+                    return Poll::Pending;
+                }
             }
 
             // Select a new service by comparing two at random and using the
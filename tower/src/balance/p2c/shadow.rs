@@ -0,0 +1,296 @@
+use super::rng::Rng;
+use crate::discover::{Change, ChannelList, Discover, ListHandle};
+use crate::ready_cache::{error::Failed, ReadyCache};
+use futures_core::{ready, Stream};
+use pin_project::pin_project;
+use std::hash::Hash;
+use std::{
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tower_service::Service;
+use tracing::{debug, error, trace};
+
+/// Identifies discovered endpoints that should only receive mirrored traffic.
+///
+/// This is implemented by the [`Discover::Key`] of a [`SplitShadows`], which
+/// separates shadow endpoints from the endpoints that serve requests, so that
+/// the shadows may be passed to a [`Mirror`]. This allows a new version of a
+/// backend to be validated against real traffic without affecting the
+/// responses returned to callers.
+///
+/// [`Discover::Key`]: crate::discover::Discover::Key
+pub trait Shadow {
+    /// Returns `true` if the endpoint with this key is a shadow.
+    fn is_shadow(&self) -> bool;
+}
+
+/// Mirrors a sample of requests to a set of shadow endpoints.
+///
+/// Each request is passed to the inner service, typically a [`Balance`]. In
+/// addition, each request is cloned with probability `rate` and the copy is
+/// sent to a randomly chosen ready shadow endpoint. Responses from shadow
+/// endpoints are discarded, and their failures do not affect the inner
+/// service. Shadow endpoints are discovered by a separate [`Discover`], such
+/// as the one returned by [`SplitShadows::new`], and are driven to readiness
+/// each time the [`Mirror`] is polled. If shadow discovery fails, the error is
+/// logged, the current shadows are kept, and discovery is polled again the
+/// next time the [`Mirror`] is polled.
+///
+/// Mirrored requests are spawned onto the Tokio runtime, so that they
+/// complete even while the [`Mirror`] is idle. A [`Mirror`] must therefore be
+/// called from within a Tokio runtime.
+///
+/// [`Balance`]: super::Balance
+pub struct Mirror<S, D, Req>
+where
+    D: Discover,
+    D::Key: Hash,
+    D::Service: Service<Req>,
+{
+    inner: S,
+    rate: f64,
+    rng: Rng,
+
+    discover: Option<D>,
+    shadows: ReadyCache<D::Key, D::Service, Req>,
+}
+
+/// Separates the endpoints discovered by a `D`-typed [`Discover`] whose key is
+/// a [`Shadow`] from the rest.
+///
+/// A [`SplitShadows`] yields the changes to endpoints that are not shadows,
+/// and forwards the changes to shadow endpoints to a [`ChannelList`], which
+/// may be passed to a [`Mirror`]. Shadow endpoints are only forwarded while
+/// the [`SplitShadows`] is polled.
+#[pin_project]
+pub struct SplitShadows<D: Discover> {
+    #[pin]
+    discover: D,
+    shadows: ListHandle<D::Key, D::Service>,
+}
+
+// ===== impl Mirror =====
+
+impl<S, D, Req> Mirror<S, D, Req>
+where
+    D: Discover,
+    D::Key: Hash,
+    D::Service: Service<Req>,
+{
+    /// Mirrors a sample of the requests to `inner` to the shadow endpoints
+    /// discovered by `shadows`.
+    ///
+    /// Without the `rand` feature, requests are instead sampled
+    /// deterministically.
+    ///
+    /// # Panics
+    ///
+    /// If `rate` is not between 0.0 and 1.0, inclusive.
+    pub fn new(inner: S, shadows: D, rate: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&rate),
+            "shadow traffic rate must be between 0.0 and 1.0"
+        );
+        #[cfg(feature = "rand")]
+        let rng = Rng::from_rng(&mut rand::thread_rng()).expect("ThreadRNG must be valid");
+        #[cfg(not(feature = "rand"))]
+        let rng = Rng::from_seed(0);
+        Self {
+            inner,
+            rate,
+            rng,
+            discover: Some(shadows),
+            shadows: ReadyCache::default(),
+        }
+    }
+
+    /// Returns the number of shadow endpoints.
+    pub fn shadows_len(&self) -> usize {
+        self.shadows.len()
+    }
+
+    /// Get a reference to the inner service
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner service
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Consume `self`, returning the inner service
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S, D, Req> Mirror<S, D, Req>
+where
+    D: Discover + Unpin,
+    D::Key: Hash + Clone,
+    D::Error: Into<crate::BoxError>,
+    D::Service: Service<Req>,
+    <D::Service as Service<Req>>::Error: Into<crate::BoxError>,
+{
+    /// Updates the shadow endpoints from discovery, and drives them to
+    /// readiness.
+    fn poll_shadows(&mut self, cx: &mut Context<'_>) {
+        while let Some(ref mut discover) = self.discover {
+            match Pin::new(discover).poll_discover(cx) {
+                Poll::Pending => break,
+                Poll::Ready(None) => {
+                    trace!("shadow discovery complete");
+                    self.discover = None;
+                }
+                Poll::Ready(Some(Err(error))) => {
+                    // Shadows must not affect the inner service, so failed
+                    // discovery leaves the current shadows in place, and is
+                    // retried when the mirror is next polled.
+                    let error = error.into();
+                    error!(%error, "shadow discovery failed");
+                    break;
+                }
+                Poll::Ready(Some(Ok(Change::Insert(key, svc)))) => {
+                    trace!("insert shadow");
+                    self.shadows.push(key, svc);
+                }
+                Poll::Ready(Some(Ok(Change::Remove(key)))) => {
+                    trace!("remove shadow");
+                    self.shadows.evict(&key);
+                }
+            }
+        }
+
+        loop {
+            match self.shadows.poll_pending(cx) {
+                Poll::Ready(Ok(())) | Poll::Pending => break,
+                Poll::Ready(Err(Failed(_, error))) => {
+                    debug!(%error, "dropping failed shadow endpoint");
+                }
+            }
+        }
+    }
+}
+
+impl<S, D, Req> Service<Req> for Mirror<S, D, Req>
+where
+    S: Service<Req>,
+    D: Discover + Unpin,
+    D::Key: Hash + Clone,
+    D::Error: Into<crate::BoxError>,
+    D::Service: Service<Req>,
+    <D::Service as Service<Req>>::Error: Into<crate::BoxError>,
+    <D::Service as Service<Req>>::Future: Send + 'static,
+    Req: Clone,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let ready = self.inner.poll_ready(cx);
+        self.poll_shadows(cx);
+        ready
+    }
+
+    fn call(&mut self, request: Req) -> Self::Future {
+        // Each request is sampled exactly once. Ready shadows remain ready
+        // until they are called, so one may be called without polling it
+        // again.
+        let ready = self.shadows.ready_len();
+        if ready > 0 && self.rng.fraction() < self.rate {
+            trace!("mirroring request");
+            let index = self.rng.index(ready);
+            let fut = self.shadows.call_ready_index(index, request.clone());
+            tokio::spawn(async move {
+                if let Err(error) = fut.await {
+                    let error = error.into();
+                    trace!(%error, "mirrored request failed");
+                }
+            });
+        }
+        self.inner.call(request)
+    }
+}
+
+impl<S, D, Req> fmt::Debug for Mirror<S, D, Req>
+where
+    S: fmt::Debug,
+    D: Discover + fmt::Debug,
+    D::Key: Hash + fmt::Debug,
+    D::Service: Service<Req> + fmt::Debug,
+    Req: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mirror")
+            .field("inner", &self.inner)
+            .field("rate", &self.rate)
+            .field("discover", &self.discover)
+            .field("shadows", &self.shadows)
+            .finish()
+    }
+}
+
+// ===== impl SplitShadows =====
+
+impl<D: Discover> SplitShadows<D> {
+    /// Wraps a [`Discover`], returning it along with a [`ChannelList`] of its
+    /// shadow endpoints.
+    pub fn new(discover: D) -> (Self, ChannelList<D::Key, D::Service>) {
+        let (list, shadows) = ChannelList::pair();
+        (Self { discover, shadows }, list)
+    }
+
+    /// Get a reference to the inner discovery.
+    pub fn get_ref(&self) -> &D {
+        &self.discover
+    }
+
+    /// Get a mutable reference to the inner discovery.
+    pub fn get_mut(&mut self) -> &mut D {
+        &mut self.discover
+    }
+}
+
+impl<D> Stream for SplitShadows<D>
+where
+    D: Discover,
+    D::Key: Shadow,
+{
+    type Item = Result<Change<D::Key, D::Service>, D::Error>;
+
+    /// Yields the next change to an endpoint that is not a shadow.
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        loop {
+            let change = match ready!(this.discover.as_mut().poll_discover(cx)).transpose()? {
+                None => return Poll::Ready(None),
+                Some(change) => change,
+            };
+            // If the mirror has been dropped, its shadows are discarded.
+            match change {
+                Change::Insert(k, svc) if k.is_shadow() => {
+                    this.shadows.insert(k, svc);
+                }
+                Change::Remove(k) if k.is_shadow() => {
+                    this.shadows.remove(k);
+                }
+                change => return Poll::Ready(Some(Ok(change))),
+            }
+        }
+    }
+}
+
+impl<D> fmt::Debug for SplitShadows<D>
+where
+    D: Discover + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SplitShadows")
+            .field("discover", &self.discover)
+            .finish()
+    }
+}
@@ -0,0 +1,238 @@
+use super::events::{Event, Eviction};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    hash::Hash,
+    sync::{Arc, Mutex},
+};
+
+/// Counts how many times each endpoint is selected, so that skew in the
+/// distribution of requests can be detected.
+///
+/// Counts are recorded from the [`Event`]s reported by an
+/// [`EventsDiscover`], by passing each event to [`SelectionCounts::record`].
+/// Clones of a [`SelectionCounts`] share the same counts, so one clone may
+/// record events while another is used to take snapshots. Each endpoint's
+/// counts are kept until it is removed by discovery.
+///
+/// # Examples
+///
+/// ```rust
+/// use tower::balance::p2c::{EventsDiscover, SelectionCounts};
+/// use tower::discover::ChannelList;
+/// # use tower::load::Constant;
+/// # type Endpoint = Constant<(), f64>;
+///
+/// let (list, handle) = ChannelList::<&'static str, Endpoint>::pair();
+/// let counts = SelectionCounts::new();
+/// let discover = EventsDiscover::new(list, {
+///     let counts = counts.clone();
+///     move |event| counts.record(event)
+/// });
+/// # drop((handle, discover));
+///
+/// for (key, count) in counts.snapshot() {
+///     println!("{}: {} selected", key, count.selected());
+/// }
+/// ```
+///
+/// [`EventsDiscover`]: super::EventsDiscover
+pub struct SelectionCounts<K> {
+    counts: Arc<Mutex<HashMap<K, SelectionCount>>>,
+}
+
+/// How often an endpoint has been selected, as returned by
+/// [`SelectionCounts::snapshot`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SelectionCount {
+    selected: u64,
+    failed: u64,
+}
+
+/// Records the loads of recently selected endpoints, so that they may be
+/// summarized by [`LoadSnapshots::snapshot`].
+///
+/// The load of each endpoint the balancer selects is passed to
+/// [`LoadSnapshots::record`], for instance from the [`Event::Selected`] events
+/// reported by an [`EventsDiscover`]. Because a balancer prefers the
+/// lesser-loaded of the endpoints it compares, these loads approximate the
+/// best load currently available. This allows admission control in front of
+/// the balancer to shed load when all endpoints are busy, before requests are
+/// enqueued.
+///
+/// Clones of a [`LoadSnapshots`] share the same recorded loads, so one clone
+/// may record loads while another is held by admission control.
+///
+/// [`Event::Selected`]: super::Event::Selected
+/// [`EventsDiscover`]: super::EventsDiscover
+#[derive(Clone)]
+pub struct LoadSnapshots {
+    window: usize,
+    recent: Arc<Mutex<VecDeque<f64>>>,
+}
+
+/// An aggregate of the loads of recently selected endpoints, as returned by
+/// [`LoadSnapshots::snapshot`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LoadSnapshot {
+    min: f64,
+    median: f64,
+    samples: usize,
+}
+
+// ===== impl SelectionCounts =====
+
+impl<K: Hash + Eq + Clone> SelectionCounts<K> {
+    /// Creates an empty set of counts.
+    pub fn new() -> Self {
+        Self {
+            counts: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Counts the selection or failure reported by `event`.
+    pub fn record(&self, event: &Event<'_, K>) {
+        let mut counts = self.counts.lock().expect("selection counts poisoned");
+        match *event {
+            Event::Selected { key, .. } => {
+                counts.entry(key.clone()).or_default().selected += 1;
+            }
+            Event::Evicted {
+                key,
+                reason: Eviction::FailedWhenSelected,
+            } => {
+                counts.entry(key.clone()).or_default().failed += 1;
+            }
+            Event::Evicted {
+                key,
+                reason: Eviction::Removed,
+            } => {
+                counts.remove(key);
+            }
+            Event::Evicted { .. } => {}
+        }
+    }
+
+    /// Returns a snapshot of how many times each endpoint has been selected.
+    ///
+    /// Endpoints that have never been selected are omitted.
+    pub fn snapshot(&self) -> HashMap<K, SelectionCount> {
+        self.counts
+            .lock()
+            .expect("selection counts poisoned")
+            .clone()
+    }
+}
+
+impl<K: Hash + Eq + Clone> Default for SelectionCounts<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K> Clone for SelectionCounts<K> {
+    fn clone(&self) -> Self {
+        Self {
+            counts: self.counts.clone(),
+        }
+    }
+}
+
+impl<K> fmt::Debug for SelectionCounts<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SelectionCounts").finish()
+    }
+}
+
+// ===== impl SelectionCount =====
+
+impl SelectionCount {
+    /// Returns the number of requests dispatched to the endpoint.
+    pub fn selected(&self) -> u64 {
+        self.selected
+    }
+
+    /// Returns the number of times the endpoint was selected but failed
+    /// before a request could be dispatched to it.
+    pub fn failed(&self) -> u64 {
+        self.failed
+    }
+}
+
+// ===== impl LoadSnapshots =====
+
+impl LoadSnapshots {
+    /// Records the loads of the last `window` selected endpoints.
+    ///
+    /// # Panics
+    ///
+    /// If `window` is zero.
+    pub fn new(window: usize) -> Self {
+        assert!(window > 0, "load snapshot window must be positive");
+        Self {
+            window,
+            recent: Arc::new(Mutex::new(VecDeque::with_capacity(window))),
+        }
+    }
+
+    /// Records the load of a selected endpoint.
+    pub fn record(&self, load: f64) {
+        let mut recent = self.recent.lock().expect("load snapshots poisoned");
+        if recent.len() == self.window {
+            recent.pop_front();
+        }
+        recent.push_back(load);
+    }
+
+    /// Returns an aggregate of the loads of recently selected endpoints.
+    ///
+    /// Returns `None` if no selections have been recorded yet. Computing a
+    /// snapshot takes time proportional to the window, independent of the
+    /// number of endpoints.
+    pub fn snapshot(&self) -> Option<LoadSnapshot> {
+        let mut loads = self
+            .recent
+            .lock()
+            .expect("load snapshots poisoned")
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        if loads.is_empty() {
+            return None;
+        }
+
+        loads.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        Some(LoadSnapshot {
+            min: loads[0],
+            median: loads[loads.len() / 2],
+            samples: loads.len(),
+        })
+    }
+}
+
+impl fmt::Debug for LoadSnapshots {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoadSnapshots")
+            .field("window", &self.window)
+            .finish()
+    }
+}
+
+// ===== impl LoadSnapshot =====
+
+impl LoadSnapshot {
+    /// Returns the lowest load among the recently selected endpoints.
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+
+    /// Returns the median load of the recently selected endpoints.
+    pub fn median(&self) -> f64 {
+        self.median
+    }
+
+    /// Returns the number of selections the snapshot was computed from.
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+}
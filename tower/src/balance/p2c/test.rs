@@ -1,12 +1,32 @@
-use crate::discover::ServiceList;
+use crate::discover::{Change, ServiceList};
 use crate::load;
 use futures_util::pin_mut;
 use std::task::Poll;
 use tokio_test::{assert_pending, assert_ready, assert_ready_ok, task};
+use tower_service::Service;
 use tower_test::{assert_request_eq, mock};
 
 use super::*;
 
+/// Makes services by cloning a mock service, so that services made after a
+/// failure share the mock's handle.
+#[derive(Clone)]
+struct MakeClone<S>(S);
+
+impl<S: Clone, T> Service<T> for MakeClone<S> {
+    type Response = S;
+    type Error = std::convert::Infallible;
+    type Future = futures_util::future::Ready<Result<S, Self::Error>>;
+
+    fn poll_ready(&mut self, _: &mut std::task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _: T) -> Self::Future {
+        futures_util::future::ok(self.0.clone())
+    }
+}
+
 #[tokio::test]
 async fn empty() {
     let empty: Vec<load::Constant<mock::Mock<(), &'static str>, usize>> = vec![];
@@ -123,3 +143,824 @@ async fn two_endpoints_with_equal_load() {
         "balancer must drop failed endpoints",
     );
 }
+
+#[tokio::test]
+async fn startup_window() {
+    tokio::time::pause();
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let disco = tokio_stream::wrappers::UnboundedReceiverStream::new(rx);
+    let mut svc = mock::Spawn::new(
+        Balance::new(disco).with_startup_window(std::time::Duration::from_secs(1)),
+    );
+
+    let (mock_a, mut handle_a) = mock::pair();
+    handle_a.allow(1);
+    tx.send(Ok::<_, std::convert::Infallible>(Change::Insert(
+        "a",
+        load::Constant::new(mock_a, 1),
+    )))
+    .unwrap();
+    assert_pending!(svc.poll_ready(), "must wait for the startup window");
+    assert_eq!(svc.get_ref().len(), 1);
+
+    let (mock_b, mut handle_b) = mock::pair();
+    handle_b.allow(1);
+    tx.send(Ok(Change::Insert("b", load::Constant::new(mock_b, 0))))
+        .unwrap();
+    assert_pending!(svc.poll_ready(), "must wait for the startup window");
+    assert_eq!(svc.get_ref().len(), 2);
+
+    tokio::time::advance(std::time::Duration::from_millis(1001)).await;
+    assert_ready_ok!(svc.poll_ready());
+
+    let mut fut = task::spawn(svc.call(()));
+    assert_request_eq!(handle_b, ()).send_response("b");
+    assert_eq!(assert_ready_ok!(fut.poll()), "b");
+}
+
+#[tokio::test]
+async fn startup_window_ends_when_discovery_completes() {
+    tokio::time::pause();
+
+    let (mock_a, mut handle_a) = mock::pair::<(), &'static str>();
+    let (mock_b, mut handle_b) = mock::pair::<(), &'static str>();
+    let disco = ServiceList::new(vec![
+        load::Constant::new(mock_a, 1),
+        load::Constant::new(mock_b, 1),
+    ]);
+    let mut svc = mock::Spawn::new(
+        Balance::new(disco).with_startup_window(std::time::Duration::from_secs(10)),
+    );
+
+    handle_a.allow(1);
+    handle_b.allow(0);
+    assert_pending!(
+        svc.poll_ready(),
+        "must wait for all discovered endpoints to become ready"
+    );
+
+    handle_b.allow(1);
+    assert_ready_ok!(svc.poll_ready());
+}
+
+#[tokio::test]
+async fn endpoints_snapshot() {
+    let (mock_a, mut handle_a) = mock::pair::<(), &'static str>();
+    let (mock_b, mut handle_b) = mock::pair::<(), &'static str>();
+    let disco = ServiceList::new(vec![
+        load::Constant::new(mock_a, 1),
+        load::Constant::new(mock_b, 2),
+    ]);
+    let mut svc = mock::Spawn::new(Balance::new(disco));
+
+    handle_a.allow(1);
+    handle_b.allow(0);
+    assert_ready_ok!(svc.poll_ready());
+
+    let mut endpoints = svc.get_ref().endpoints().collect::<Vec<_>>();
+    endpoints.sort_by_key(|(k, _, _)| **k);
+    assert_eq!(
+        endpoints,
+        vec![(&0, 1, Readiness::Ready), (&1, 2, Readiness::Pending)]
+    );
+}
+
+#[test]
+fn load_snapshot() {
+    let snapshots = LoadSnapshots::new(3);
+    assert_eq!(snapshots.snapshot(), None);
+
+    snapshots.record(2.0);
+    let snapshot = snapshots.snapshot().expect("snapshot");
+    assert_eq!(snapshot.min(), 2.0);
+    assert_eq!(snapshot.median(), 2.0);
+    assert_eq!(snapshot.samples(), 1);
+
+    for load in &[1.0, 3.0, 5.0] {
+        snapshots.clone().record(*load);
+    }
+    let snapshot = snapshots.snapshot().expect("snapshot");
+    assert_eq!(snapshot.min(), 1.0, "the oldest load must be forgotten");
+    assert_eq!(snapshot.median(), 3.0);
+    assert_eq!(snapshot.samples(), 3);
+}
+
+#[tokio::test]
+async fn reports_events() {
+    use std::sync::{Arc, Mutex};
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let disco = tokio_stream::wrappers::UnboundedReceiverStream::new(rx);
+    let disco = EventsDiscover::new(disco, {
+        let events = events.clone();
+        move |event: &Event<'_, &'static str>| {
+            let event = match *event {
+                Event::Selected { key, load } => format!("selected {} {}", key, load),
+                Event::Evicted { key, reason } => format!("evicted {} {:?}", key, reason),
+            };
+            events.lock().unwrap().push(event);
+        }
+    });
+    let mut svc = mock::Spawn::new(Balance::new(disco));
+
+    let (mock_a, mut handle_a) = mock::pair::<(), &'static str>();
+    let (mock_b, mut handle_b) = mock::pair::<(), &'static str>();
+    handle_a.allow(1);
+    handle_b.allow(1);
+    for (key, mock) in [("a", mock_a), ("b", mock_b)] {
+        let mock = load::Constant::new(mock, 1.0);
+        tx.send(Ok::<_, std::convert::Infallible>(Change::Insert(key, mock)))
+            .unwrap();
+    }
+    assert_ready_ok!(svc.poll_ready());
+    let _rsp = svc.call(());
+    let selected = events.lock().unwrap().pop().expect("selection");
+    let removed = if selected == "selected a 1" { "b" } else { "a" };
+
+    tx.send(Ok(Change::Remove(removed))).unwrap();
+    handle_a.send_error("failed");
+    handle_b.send_error("failed");
+    assert_pending!(svc.poll_ready());
+
+    let selected = if removed == "a" { "b" } else { "a" };
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![
+            format!("evicted {} Removed", removed),
+            format!("evicted {} Failed", selected),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn reports_failures_of_selected_endpoints() {
+    use std::sync::{Arc, Mutex};
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let (mut svc, mut handle) = mock::spawn_with(|s: mock::Mock<(), &'static str>| {
+        let disco = ServiceList::new(vec![load::Constant::new(s, 0)]);
+        let disco = EventsDiscover::new(disco, {
+            let events = events.clone();
+            move |event: &Event<'_, usize>| {
+                if let Event::Evicted { reason, .. } = *event {
+                    events.lock().unwrap().push(reason);
+                }
+            }
+        });
+        Balance::new(disco)
+    });
+
+    handle.allow(1);
+    assert_ready_ok!(svc.poll_ready());
+    handle.send_error("endpoint lost");
+    assert_pending!(svc.poll_ready());
+    assert_eq!(*events.lock().unwrap(), vec![Eviction::FailedWhenSelected]);
+}
+
+#[tokio::test]
+async fn looks_up_and_evicts_by_borrowed_key() {
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let disco = tokio_stream::wrappers::UnboundedReceiverStream::new(rx);
+    let mut svc = mock::Spawn::new(Balance::new(disco));
+
+    let (mock_a, mut handle_a) = mock::pair::<(), &'static str>();
+    let (mock_b, mut handle_b) = mock::pair::<(), &'static str>();
+    handle_a.allow(1);
+    handle_b.allow(0);
+    for (key, mock, load) in [("a", mock_a, 1.0), ("b", mock_b, 2.0)] {
+        let mock = load::Constant::new(mock, load);
+        tx.send(Ok::<_, std::convert::Infallible>(Change::Insert(
+            key.to_string(),
+            mock,
+        )))
+        .unwrap();
+    }
+    assert_ready_ok!(svc.poll_ready());
+
+    // Endpoints are found whether or not they are ready.
+    assert!(svc.get_ref().contains("a"));
+    assert!(svc.get_ref().contains("b"));
+    assert!(!svc.get_ref().contains("c"));
+    assert_eq!(svc.get_ref().load_of("a"), Some(1.0));
+    assert_eq!(svc.get_ref().load_of("b"), Some(2.0));
+    assert_eq!(svc.get_ref().load_of("c"), None);
+
+    assert!(svc.get_mut().evict("b"));
+    assert!(!svc.get_mut().evict("b"));
+    assert!(!svc.get_ref().contains("b"));
+}
+
+#[tokio::test]
+async fn drains_removed_endpoints() {
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let disco = tokio_stream::wrappers::UnboundedReceiverStream::new(rx);
+    let mut svc = mock::Spawn::new(
+        Balance::new(disco).with_drain_timeout(std::time::Duration::from_secs(10)),
+    );
+
+    let (mock_a, mut handle_a) = mock::pair::<(), &'static str>();
+    handle_a.allow(1);
+    let mock_a = load::PendingRequests::new(mock_a, load::CompleteOnResponse::default());
+    tx.send(Ok::<_, std::convert::Infallible>(Change::Insert(
+        "a", mock_a,
+    )))
+    .unwrap();
+    assert_ready_ok!(svc.poll_ready());
+
+    let mut fut = task::spawn(svc.call(()));
+    let (_, rsp) = assert_ready!(handle_a.poll_request()).expect("request");
+
+    tx.send(Ok(Change::Remove("a"))).unwrap();
+    assert_pending!(svc.poll_ready());
+    assert_eq!(svc.get_ref().len(), 0, "drained endpoints must not be used");
+    assert_pending!(
+        handle_a.poll_request(),
+        "endpoint must not be dropped while requests are in flight"
+    );
+
+    rsp.send_response("a");
+    assert_eq!(assert_ready_ok!(fut.poll()), "a");
+    assert_pending!(svc.poll_ready());
+    assert!(
+        assert_ready!(handle_a.poll_request()).is_none(),
+        "endpoint must be dropped once drained"
+    );
+}
+
+#[tokio::test]
+async fn drains_replaced_endpoints() {
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let disco = tokio_stream::wrappers::UnboundedReceiverStream::new(rx);
+    let mut svc = mock::Spawn::new(
+        Balance::new(disco).with_drain_timeout(std::time::Duration::from_secs(10)),
+    );
+
+    let (old, mut handle_old) = mock::pair::<(), &'static str>();
+    handle_old.allow(1);
+    let old = load::PendingRequests::new(old, load::CompleteOnResponse::default());
+    tx.send(Ok::<_, std::convert::Infallible>(Change::Insert("a", old)))
+        .unwrap();
+    assert_ready_ok!(svc.poll_ready());
+
+    let mut fut = task::spawn(svc.call(()));
+    let (_, rsp) = assert_ready!(handle_old.poll_request()).expect("request");
+
+    let (new, mut handle_new) = mock::pair::<(), &'static str>();
+    handle_new.allow(1);
+    let new = load::PendingRequests::new(new, load::CompleteOnResponse::default());
+    tx.send(Ok(Change::Insert("a", new))).unwrap();
+    assert_ready_ok!(svc.poll_ready());
+    assert_eq!(svc.get_ref().len(), 1);
+
+    // New requests are dispatched to the replacement...
+    let mut fut2 = task::spawn(svc.call(()));
+    assert_request_eq!(handle_new, ()).send_response("new");
+    assert_eq!(assert_ready_ok!(fut2.poll()), "new");
+
+    // ...while the old service completes its in-flight request.
+    rsp.send_response("old");
+    assert_eq!(assert_ready_ok!(fut.poll()), "old");
+    let _ = svc.poll_ready();
+    assert!(
+        assert_ready!(handle_old.poll_request()).is_none(),
+        "replaced endpoint must be dropped once drained"
+    );
+}
+
+#[tokio::test]
+async fn drain_timeout() {
+    tokio::time::pause();
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let disco = tokio_stream::wrappers::UnboundedReceiverStream::new(rx);
+    let mut svc = mock::Spawn::new(
+        Balance::new(disco).with_drain_timeout(std::time::Duration::from_secs(10)),
+    );
+
+    let (mock_a, mut handle_a) = mock::pair::<(), &'static str>();
+    handle_a.allow(1);
+    let mock_a = load::PendingRequests::new(mock_a, load::CompleteOnResponse::default());
+    tx.send(Ok::<_, std::convert::Infallible>(Change::Insert(
+        "a", mock_a,
+    )))
+    .unwrap();
+    assert_ready_ok!(svc.poll_ready());
+
+    let _fut = svc.call(());
+    let _rsp = assert_ready!(handle_a.poll_request()).expect("request");
+
+    tx.send(Ok(Change::Remove("a"))).unwrap();
+    assert_pending!(svc.poll_ready());
+    assert_pending!(handle_a.poll_request());
+
+    tokio::time::advance(std::time::Duration::from_millis(10_001)).await;
+    assert!(svc.is_woken());
+    assert_pending!(svc.poll_ready());
+    assert!(
+        assert_ready!(handle_a.poll_request()).is_none(),
+        "endpoint must be dropped once the drain timeout elapses"
+    );
+}
+
+// Not run on a runtime, so that tokio's cooperative scheduling budget does not
+// cause the mock endpoints to appear unready.
+#[test]
+fn seeded_selection_is_deterministic() {
+    fn selections(seed: u64) -> Vec<usize> {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let disco = tokio_stream::wrappers::UnboundedReceiverStream::new(rx);
+        let mut svc = mock::Spawn::new(Balance::from_seed(disco, seed));
+
+        let mut handles = Vec::new();
+        for i in 0..8 {
+            let (mock, mut handle) = mock::pair::<(), ()>();
+            handle.allow(100);
+            handles.push(handle);
+            tx.send(Ok::<_, std::convert::Infallible>(Change::Insert(
+                i,
+                load::Constant::new(mock, 0),
+            )))
+            .unwrap();
+        }
+
+        let mut selected = Vec::new();
+        for round in 0..32 {
+            if round == 16 {
+                tx.send(Ok(Change::Remove(3))).unwrap();
+            }
+
+            assert_ready_ok!(svc.poll_ready());
+            let _fut = svc.call(());
+            let i = handles
+                .iter_mut()
+                .position(|h| matches!(h.poll_request(), Poll::Ready(Some(_))))
+                .expect("request must be dispatched to an endpoint");
+            if round >= 16 {
+                assert_ne!(i, 3, "removed endpoint must not be selected");
+            }
+            selected.push(i);
+        }
+        selected
+    }
+
+    assert_eq!(selections(7), selections(7));
+    assert_eq!(selections(42), selections(42));
+}
+
+#[tokio::test]
+async fn mirrors_requests_to_shadow_endpoints() {
+    #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+    struct Key(&'static str, bool);
+
+    impl Shadow for Key {
+        fn is_shadow(&self) -> bool {
+            self.1
+        }
+    }
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let disco = tokio_stream::wrappers::UnboundedReceiverStream::new(rx);
+    let (disco, shadows) = SplitShadows::new(disco);
+    let mut svc = mock::Spawn::new(Mirror::new(Balance::new(disco), shadows, 1.0));
+
+    let (mock_a, mut handle_a) = mock::pair::<&'static str, &'static str>();
+    let (mock_b, mut handle_b) = mock::pair::<&'static str, &'static str>();
+    handle_a.allow(1);
+    handle_b.allow(1);
+    tx.send(Ok::<_, std::convert::Infallible>(Change::Insert(
+        Key("a", false),
+        load::Constant::new(mock_a, 0),
+    )))
+    .unwrap();
+    tx.send(Ok(Change::Insert(
+        Key("b", true),
+        load::Constant::new(mock_b, 0),
+    )))
+    .unwrap();
+
+    assert_ready_ok!(svc.poll_ready());
+    assert_ready_ok!(svc.poll_ready());
+    assert_eq!(
+        svc.get_ref().get_ref().len(),
+        1,
+        "shadows must not be balanced"
+    );
+    assert_eq!(svc.get_ref().shadows_len(), 1);
+
+    let mut fut = task::spawn(svc.call("hello"));
+    let (req, rsp) = assert_ready!(handle_a.poll_request()).expect("request");
+    assert_eq!(req, "hello");
+    let (mirrored, shadow_rsp) = assert_ready!(handle_b.poll_request()).expect("mirrored");
+    assert_eq!(mirrored, "hello");
+    assert_pending!(
+        handle_b.poll_request(),
+        "each request must be mirrored at most once"
+    );
+
+    // The shadow's failure does not affect the caller.
+    shadow_rsp.send_error("shadow failed");
+    rsp.send_response("a");
+    assert_eq!(assert_ready_ok!(fut.poll()), "a");
+
+    // The shadow endpoint is never selected to serve requests.
+    tx.send(Ok(Change::Remove(Key("a", false)))).unwrap();
+    handle_b.allow(1);
+    assert_pending!(svc.poll_ready());
+    assert!(svc.get_ref().get_ref().is_empty());
+}
+
+#[tokio::test]
+async fn fails_over_to_lower_priority_endpoints() {
+    #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+    struct Key(&'static str, u32);
+
+    impl Priority for Key {
+        fn priority(&self) -> u32 {
+            self.1
+        }
+    }
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let disco = tokio_stream::wrappers::UnboundedReceiverStream::new(rx);
+    let disco = PriorityDiscover::new(disco, 0.75);
+    let mut svc = mock::Spawn::new(Balance::new(disco).with_tiers());
+
+    let (mock_a0, mut handle_a0) = mock::pair::<(), &'static str>();
+    let (mock_a1, mut handle_a1) = mock::pair::<(), &'static str>();
+    let (mock_b, mut handle_b) = mock::pair::<(), &'static str>();
+    handle_a0.allow(1);
+    handle_a1.allow(0);
+    handle_b.allow(1);
+    let insert = |key, mock| {
+        tx.send(Ok::<_, std::convert::Infallible>(Change::Insert(
+            key,
+            load::Constant::new(mock, 0),
+        )))
+        .unwrap()
+    };
+    insert(Key("a0", 0), mock_a0);
+    insert(Key("a1", 0), mock_a1);
+    insert(Key("b", 1), mock_b);
+
+    // Only half of the primary endpoints are ready, so the backup is used.
+    assert_ready_ok!(svc.poll_ready());
+    let _fut = svc.call(());
+    assert_ready!(handle_b.poll_request()).expect("request to backup");
+    assert_pending!(handle_a0.poll_request());
+
+    // Once the primary group recovers, it is preferred again.
+    handle_a1.allow(1);
+    handle_b.allow(1);
+    assert_ready_ok!(svc.poll_ready());
+    let _fut = svc.call(());
+    assert_pending!(handle_b.poll_request());
+    assert!(
+        handle_a0.poll_request().is_ready() || handle_a1.poll_request().is_ready(),
+        "request must be dispatched to a primary endpoint"
+    );
+}
+
+#[tokio::test]
+async fn quarantines_last_endpoint() {
+    tokio::time::pause();
+
+    let (mut svc, mut handle) = mock::spawn_with(|s| {
+        let disco = futures_util::stream::iter(vec![Ok::<_, std::convert::Infallible>(
+            Change::Insert("a", ()),
+        )]);
+        let disco = QuarantineDiscover::new(disco, MakeClone(s), std::time::Duration::from_secs(1));
+        let disco =
+            crate::discover::DiscoverMap::new(disco, |_: &&str, svc| load::Constant::new(svc, 0));
+        Balance::new(disco)
+    });
+
+    handle.allow(1);
+    assert_ready_ok!(svc.poll_ready());
+
+    handle.send_error("endpoint lost");
+    assert_pending!(svc.poll_ready());
+    assert_eq!(
+        svc.get_ref().len(),
+        1,
+        "last endpoint must not be dropped when it fails"
+    );
+
+    tokio::time::advance(std::time::Duration::from_millis(1_001)).await;
+    assert!(svc.is_woken());
+    assert_ready_ok!(svc.poll_ready());
+
+    let mut fut = task::spawn(svc.call(()));
+    assert_request_eq!(handle, ()).send_response(1);
+    assert_eq!(assert_ready_ok!(fut.poll()), 1);
+}
+
+#[tokio::test]
+async fn counts_selections() {
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let disco = tokio_stream::wrappers::UnboundedReceiverStream::new(rx);
+    let counts = SelectionCounts::new();
+    let disco = EventsDiscover::new(disco, {
+        let counts = counts.clone();
+        move |event| counts.record(event)
+    });
+    let mut svc = mock::Spawn::new(Balance::new(disco));
+
+    let (mock, mut handle) = mock::pair::<(), &'static str>();
+    let mock = load::Constant::new(mock, 1.0);
+    tx.send(Ok::<_, std::convert::Infallible>(Change::Insert("a", mock)))
+        .unwrap();
+
+    for _ in 0..2 {
+        handle.allow(1);
+        assert_ready_ok!(svc.poll_ready());
+        let _rsp = svc.call(());
+    }
+
+    // The endpoint is selected, but fails before a request is dispatched.
+    handle.allow(1);
+    assert_ready_ok!(svc.poll_ready());
+    handle.send_error("failed");
+    assert_pending!(svc.poll_ready());
+
+    let snapshot = counts.snapshot();
+    assert_eq!(snapshot["a"].selected(), 2);
+    assert_eq!(snapshot["a"].failed(), 1);
+
+    tx.send(Ok(Change::Remove("a"))).unwrap();
+    assert_pending!(svc.poll_ready());
+    assert!(counts.snapshot().is_empty());
+}
+
+#[test]
+fn rng_pairs_are_distinct() {
+    let mut rng = super::rng::Rng::from_seed(7);
+    for len in 2..8 {
+        for _ in 0..64 {
+            let (a, b) = rng.pair(len);
+            assert!(a < len && b < len);
+            assert_ne!(a, b);
+        }
+    }
+}
+
+#[tokio::test]
+async fn backs_off_failed_endpoints() {
+    use std::time::Duration;
+    tokio::time::pause();
+
+    let (mut svc, mut handle) = mock::spawn_with(|s| {
+        let endpoint = Backoff::new(
+            MakeClone(s),
+            (),
+            Duration::from_secs(1),
+            Duration::from_secs(10),
+        );
+        let endpoint = load::Constant::new(endpoint, 0);
+        Balance::new(ServiceList::new(vec![endpoint].into_iter()))
+    });
+
+    handle.allow(1);
+    assert_ready_ok!(svc.poll_ready());
+
+    handle.send_error("endpoint lost");
+    assert_pending!(svc.poll_ready());
+    assert_eq!(svc.get_ref().len(), 1, "failed endpoint must be retained");
+
+    tokio::time::advance(Duration::from_millis(1_001)).await;
+    assert!(svc.is_woken());
+    assert_ready_ok!(svc.poll_ready());
+
+    // A second consecutive failure doubles the backoff.
+    handle.send_error("endpoint lost again");
+    assert_pending!(svc.poll_ready());
+    tokio::time::advance(Duration::from_millis(1_001)).await;
+    assert_pending!(svc.poll_ready());
+    tokio::time::advance(Duration::from_millis(1_000)).await;
+    assert!(svc.is_woken());
+    assert_ready_ok!(svc.poll_ready());
+
+    let mut fut = task::spawn(svc.call(()));
+    assert_request_eq!(handle, ()).send_response(1);
+    assert_eq!(assert_ready_ok!(fut.poll()), 1);
+}
+
+#[tokio::test]
+async fn damps_new_endpoints_while_unstable() {
+    use std::time::Duration;
+    tokio::time::pause();
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let disco = tokio_stream::wrappers::UnboundedReceiverStream::new(rx);
+    let disco = DampedDiscover::new(disco, Duration::from_secs(10), 0, 10.0);
+    let mut svc = mock::Spawn::new(Balance::new(disco));
+    let insert = |key, load| {
+        let (mock, handle) = mock::pair::<(), &'static str>();
+        let mock = load::Constant::new(mock, load);
+        tx.send(Ok::<_, std::convert::Infallible>(Change::Insert(key, mock)))
+            .unwrap();
+        handle
+    };
+
+    let mut old = insert("old", 2.0);
+    old.allow(1);
+    assert_ready_ok!(svc.poll_ready());
+    tokio::time::advance(Duration::from_secs(11)).await;
+
+    // A new endpoint is added as another fails.
+    let mut new = insert("new", 1.0);
+    new.allow(1);
+    let mut bad = insert("bad", 0.0);
+    bad.send_error("endpoint lost");
+    assert_ready_ok!(svc.poll_ready());
+    assert_eq!(svc.get_ref().len(), 2);
+    let _rsp = svc.call(());
+    assert_request_eq!(old, ()).send_response("old");
+
+    // Once failures subside, the new endpoint is compared by its load alone.
+    tokio::time::advance(Duration::from_secs(11)).await;
+    old.allow(1);
+    assert_ready_ok!(svc.poll_ready());
+    let _rsp = svc.call(());
+    assert_request_eq!(new, ()).send_response("new");
+}
+
+#[tokio::test]
+async fn prefers_local_endpoints() {
+    #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+    struct Key(&'static str, &'static str);
+
+    impl Locality for Key {
+        fn locality(&self) -> &str {
+            self.1
+        }
+    }
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let disco = tokio_stream::wrappers::UnboundedReceiverStream::new(rx);
+    let disco = LocalityDiscover::new(disco, "east", 5.0);
+    let mut svc = mock::Spawn::new(Balance::new(disco).with_tiers());
+
+    let insert = |key, load| {
+        let (mock, handle) = mock::pair::<(), &'static str>();
+        tx.send(Ok::<_, std::convert::Infallible>(Change::Insert(
+            key,
+            load::Constant::new(mock, load),
+        )))
+        .unwrap();
+        handle
+    };
+    let mut local = insert(Key("a", "east"), 2.0);
+    let mut remote = insert(Key("b", "west"), 0.0);
+
+    // The local endpoint is preferred, though the remote one is less loaded.
+    local.allow(1);
+    remote.allow(1);
+    assert_ready_ok!(svc.poll_ready());
+    let _fut = svc.call(());
+    assert_ready!(local.poll_request()).expect("request to local endpoint");
+    assert_pending!(remote.poll_request());
+
+    // Traffic spills over when the local endpoint is not ready...
+    local.allow(0);
+    assert_ready_ok!(svc.poll_ready());
+    let _fut = svc.call(());
+    assert_ready!(remote.poll_request()).expect("request to remote endpoint");
+
+    // ...or when it is overloaded.
+    let mut busy = insert(Key("a", "east"), 10.0);
+    busy.allow(1);
+    remote.allow(1);
+    assert_ready_ok!(svc.poll_ready());
+    let _fut = svc.call(());
+    assert_ready!(remote.poll_request()).expect("request to remote endpoint");
+    assert_pending!(busy.poll_request());
+}
+
+#[tokio::test]
+async fn counts_in_flight_request_costs() {
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let disco = tokio_stream::wrappers::UnboundedReceiverStream::new(rx);
+    let mut svc = mock::Spawn::new(Balance::new(disco));
+
+    let insert = |key| {
+        let (mock, mut handle) = mock::pair::<u32, &'static str>();
+        handle.allow(10);
+        let mock = load::Constant::new(mock, 0.0);
+        let cost = |req: &u32| *req as f64;
+        let mock = load::RequestCost::new(mock, cost, load::CompleteOnResponse::default());
+        tx.send(Ok::<_, std::convert::Infallible>(Change::Insert(key, mock)))
+            .unwrap();
+        handle
+    };
+    let mut a = insert("a");
+    let mut b = insert("b");
+
+    // A heavy request is dispatched to one of the endpoints...
+    assert_ready_ok!(svc.poll_ready());
+    let mut heavy = task::spawn(svc.call(10));
+    let (mut heavy_handle, mut light_handle) = match a.poll_request() {
+        Poll::Ready(Some((_, rsp))) => {
+            rsp.send_response("heavy");
+            (a, b)
+        }
+        _ => {
+            let (_, rsp) = assert_ready!(b.poll_request()).unwrap();
+            rsp.send_response("heavy");
+            (b, a)
+        }
+    };
+
+    // ...so light requests are dispatched to the other while it is in flight.
+    let mut light = Vec::new();
+    for _ in 0..3 {
+        assert_ready_ok!(svc.poll_ready());
+        light.push(svc.call(1));
+        assert_ready!(light_handle.poll_request()).expect("light request");
+        assert_pending!(heavy_handle.poll_request());
+    }
+
+    // Once the heavy request completes, its endpoint is preferred again.
+    assert_eq!(assert_ready_ok!(heavy.poll()), "heavy");
+    assert_ready_ok!(svc.poll_ready());
+    let _rsp = svc.call(1);
+    assert_ready!(heavy_handle.poll_request()).expect("request to idle endpoint");
+}
+
+#[tokio::test]
+async fn inherits_load_of_updated_endpoints() {
+    use crate::discover::ChannelList;
+    use std::time::Duration;
+
+    tokio::time::pause();
+    let peak_ewma = |svc| {
+        let decay = Duration::from_secs(10).as_nanos() as f64;
+        load::PeakEwma::new(
+            svc,
+            Duration::from_millis(10),
+            decay,
+            load::CompleteOnResponse::default(),
+        )
+    };
+    let (list, handle) = ChannelList::pair();
+    let (mock_a, mut handle_a) = mock::pair::<(), &'static str>();
+    handle.insert(0, peak_ewma(mock_a));
+    let mut svc = mock::Spawn::new(Balance::new(list).with_inherited_load());
+
+    // A slow response raises the endpoint's latency estimate.
+    handle_a.allow(1);
+    assert_ready_ok!(svc.poll_ready());
+    let mut rsp = task::spawn(svc.call(()));
+    tokio::time::advance(Duration::from_secs(1)).await;
+    assert_request_eq!(handle_a, ()).send_response("a");
+    assert_ready_ok!(rsp.poll());
+
+    // The service that replaces it keeps the estimate.
+    let (mock_b, mut handle_b) = mock::pair::<(), &'static str>();
+    handle.insert(0, peak_ewma(mock_b));
+    handle_b.allow(1);
+    assert_ready_ok!(svc.poll_ready());
+    let endpoints = svc.get_ref().endpoints().collect::<Vec<_>>();
+    assert_eq!(endpoints.len(), 1);
+    let (_, cost, readiness) = endpoints[0];
+    assert_eq!(readiness, Readiness::Ready);
+    assert!(f64::from(cost) > Duration::from_millis(500).as_nanos() as f64);
+
+    let mut rsp = task::spawn(svc.call(()));
+    assert_request_eq!(handle_b, ()).send_response("b");
+    assert_eq!(assert_ready_ok!(rsp.poll()), "b");
+}
+
+#[tokio::test]
+async fn bounds_scan_for_ready_endpoint() {
+    let (mock_a, mut handle_a) = mock::pair::<(), i32>();
+    let (mock_b, mut handle_b) = mock::pair::<(), i32>();
+    let (mock_c, mut handle_c) = mock::pair::<(), i32>();
+    let disco = ServiceList::new(vec![
+        load::Constant::new(mock_a, 0),
+        load::Constant::new(mock_b, 0),
+        load::Constant::new(mock_c, 0),
+    ]);
+    let mut svc = mock::Spawn::new(Balance::from_seed(disco, 0).with_scan(Scan::Attempts(1)));
+
+    handle_a.allow(1);
+    handle_b.allow(1);
+    handle_c.allow(1);
+    assert_ready_ok!(svc.poll_ready());
+    assert_eq!(svc.get_ref().len(), 3);
+
+    handle_a.send_error("endpoint lost");
+    handle_b.send_error("endpoint lost");
+    handle_c.send_error("endpoint lost");
+
+    // Each poll gives up after a single failed endpoint, waking the task so
+    // that the search continues.
+    for remaining in (0..3).rev() {
+        assert_pending!(svc.poll_ready());
+        assert_eq!(svc.get_ref().len(), remaining);
+        if remaining > 0 {
+            assert!(svc.is_woken());
+        }
+    }
+}
@@ -8,32 +8,75 @@
 //! samples, which gives an estimate of how often the underlying service has been ready when it was
 //! needed "recently" (see [`Builder::urgency`]). If the service is loaded (see
 //! [`Builder::loaded_above`]), a new service is created and added to the underlying [`Balance`].
-//! If the service is underutilized (see [`Builder::underutilized_below`]) and there are two or
-//! more services, then the latest added service is removed. In either case, the load estimate is
+//! If the service is underutilized (see [`Builder::underutilized_below`]) and there are more
+//! services than the configured minimum (see [`Builder::min_services`]), then a service is
+//! removed. In either case, the load estimate is
 //! reset to its initial value (see [`Builder::initial`] to prevent services from being rapidly
 //! added or removed.
+//!
+//! By default, one service is added or removed at a time. [`Builder::scale_up`] and
+//! [`Builder::scale_down`] may be used to instead add or remove several services, or a fraction of
+//! the pool, each time the pool scales, so that a pool serving spiky workloads can grow more
+//! quickly than one service at a time.
+//!
+//! By default, the service with the lowest key is removed when the pool is underutilized. [`Builder::eviction`]
+//! may be used to instead remove the service with the fewest pending requests, to wait for a
+//! service to become idle so that no in-flight requests are disrupted, or to recycle the oldest or
+//! most-used service.
+//!
+//! Alternatively, or in addition, services that have not been dispatched a request for longer than
+//! [`Builder::idle_timeout`] are removed, down to the minimum number of services, regardless of the
+//! load estimate.
+//!
+//! The [`PoolDiscoverer`] that adds and removes services may also be used on its own, with the
+//! decision to add or remove a service driven by a [`LevelHandle`] rather than by the pool's load
+//! estimate. See [`Builder::discover`].
+//!
+//! A service that is ready but fails every request it receives would otherwise remain in the pool
+//! indefinitely. If [`Builder::max_consecutive_failures`] is set, a service whose responses fail
+//! that many times in a row is removed from the pool and replaced with a newly made service.
+//!
+//! Newly made services may be required to successfully serve a probe request before they are
+//! added to the pool, so that a broken backend is not added and then immediately inflate the load
+//! estimate. See [`Pool::with_probe`].
+//!
+//! A pool's scaling decisions may be reported with [`Pool::with_observer`]. With
+//! [`Builder::dry_run`], decisions are only reported, and no services are added or removed due to
+//! load, so that thresholds may be tuned against real traffic before scaling is enabled.
 #![deny(missing_docs)]
 
 use super::p2c::Balance;
 use crate::discover::Change;
-use crate::load::Load;
+use crate::load::{InFlight, Load};
 use crate::make::MakeService;
 use futures_core::{ready, Stream};
 use pin_project::pin_project;
 use slab::Slab;
 use std::{
+    error::Error,
     fmt,
     future::Future,
     pin::Pin,
-    task::{Context, Poll},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Waker},
+    time::Duration,
 };
+use tokio::time::{Instant, Sleep};
 use tower_service::Service;
 
+/// Used in place of an idle timeout too large to be represented as an
+/// [`Instant`]; roughly 30 years.
+const FAR_FUTURE: Duration = Duration::from_secs(86400 * 365 * 30);
+
 #[cfg(test)]
 mod test;
 
+/// Whether a [`PoolDiscoverer`] should add or remove services.
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
-enum Level {
+pub enum Level {
     /// Load is low -- remove a service instance.
     Low,
     /// Load is normal -- keep the service set as it is.
@@ -42,8 +85,66 @@ enum Level {
     High,
 }
 
+/// Determines which service is removed when a pool is underutilized.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Eviction {
+    /// Remove the service with the lowest key.
+    First,
+    /// Remove the service with the fewest pending requests.
+    LeastPending,
+    /// Remove a service only once it has no pending requests.
+    ///
+    /// If every service has pending requests, no service is removed until one of them becomes
+    /// idle.
+    Idle,
+    /// Remove the service that was made the longest time ago.
+    ///
+    /// This recycles long-lived services, such as connections, as load fluctuates.
+    Oldest,
+    /// Remove the service that has been dispatched the most requests.
+    MostUsed,
+}
+
+/// How many services a [`PoolDiscoverer`] adds or removes each time it scales.
+///
+/// See [`Builder::scale_up`] and [`Builder::scale_down`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Step {
+    /// Add or remove this many services.
+    Services(usize),
+    /// Add or remove this fraction of the current number of services, rounded up.
+    ///
+    /// For instance, `Step::Fraction(1.0)` doubles the number of services when scaling up, and
+    /// `Step::Fraction(0.5)` halves it when scaling down.
+    Fraction(f64),
+}
+
+/// Controls the [`Level`] of a [`PoolDiscoverer`].
+///
+/// When the level is set to [`Level::High`], the discoverer makes new services; when it is set to
+/// [`Level::Low`], the discoverer removes services (unless doing so would leave fewer than the
+/// minimum number of services). How many services are added or removed is configured with
+/// [`Builder::scale_up`] and [`Builder::scale_down`]. In either case, the level is reset to
+/// [`Level::Normal`] once the first service has been added or removed, and each change of the
+/// level adds or removes at most one step of services.
+///
+/// Cloning a handle returns a handle to the same level.
+#[derive(Clone, Debug)]
+pub struct LevelHandle {
+    shared: Arc<Mutex<Signal>>,
+}
+
+#[derive(Debug)]
+struct Signal {
+    level: Level,
+    waker: Option<Waker>,
+}
+
 /// A wrapper around `MakeService` that discovers a new service when load is high, and removes a
 /// service when load is low. See [`Pool`].
+///
+/// A [`PoolDiscoverer`] may also be used on its own (for instance, with a [`Balance`]), with its
+/// [`LevelHandle`] driven by external logic. See [`Builder::discover`].
 #[pin_project]
 pub struct PoolDiscoverer<MS, Target, Request>
 where
@@ -53,12 +154,47 @@ where
     #[pin]
     making: Option<MS::Future>,
     target: Target,
-    load: Level,
-    services: Slab<()>,
-    died_tx: tokio::sync::mpsc::UnboundedSender<usize>,
+    load: LevelHandle,
+    services: Slab<Member>,
+    min: usize,
+    eviction: Eviction,
+    scale_up: Step,
+    scale_down: Step,
+    /// The number of services left to add in the current scale-up step.
+    adding: usize,
+    /// The number of services left to remove in the current scale-down step.
+    removing: usize,
+    idle_timeout: Option<Duration>,
+    /// Fires when the next service becomes idle, if an idle timeout is set.
+    idle: Option<Pin<Box<Sleep>>>,
+    died_tx: tokio::sync::mpsc::UnboundedSender<Died>,
     #[pin]
-    died_rx: tokio::sync::mpsc::UnboundedReceiver<usize>,
+    died_rx: tokio::sync::mpsc::UnboundedReceiver<Died>,
     limit: Option<usize>,
+    max_failures: Option<usize>,
+    /// The number of failed services that have yet to be replaced.
+    replace: usize,
+    probe: Option<Box<dyn Fn() -> Request + Send + Sync>>,
+    /// A newly made service that is serving its probe request.
+    probing: Option<Probing<MS::Service, Request>>,
+}
+
+/// A newly made service and the probe request it must serve before it is added to the pool.
+struct Probing<S, Request>
+where
+    S: Service<Request>,
+{
+    service: S,
+    request: Option<Request>,
+    response: Option<Pin<Box<S::Future>>>,
+}
+
+/// Sent by a [`DropNotifyService`] when it is dropped.
+#[derive(Debug)]
+struct Died {
+    id: usize,
+    /// Whether the service was removed because it failed too many requests.
+    failed: bool,
 }
 
 impl<MS, Target, Request> fmt::Debug for PoolDiscoverer<MS, Target, Request>
@@ -73,11 +209,46 @@ where
             .field("target", &self.target)
             .field("load", &self.load)
             .field("services", &self.services)
+            .field("min", &self.min)
+            .field("eviction", &self.eviction)
+            .field("scale_up", &self.scale_up)
+            .field("scale_down", &self.scale_down)
+            .field("adding", &self.adding)
+            .field("removing", &self.removing)
+            .field("idle_timeout", &self.idle_timeout)
             .field("limit", &self.limit)
+            .field("max_failures", &self.max_failures)
+            .field("replace", &self.replace)
+            .field("probe", &self.probe.is_some())
+            .field("probing", &self.probing.is_some())
             .finish()
     }
 }
 
+impl<MS, Target, Request> PoolDiscoverer<MS, Target, Request>
+where
+    MS: MakeService<Target, Request>,
+{
+    /// Returns a handle that controls whether this discoverer adds or removes services.
+    pub fn level_handle(&self) -> LevelHandle {
+        self.load.clone()
+    }
+
+    /// Requires each newly made service to successfully serve a request returned by `probe`
+    /// before it is discovered.
+    ///
+    /// A service whose probe fails, either because the service fails to become ready or because
+    /// its response is an error, is dropped, and another service is made in its place if one is
+    /// still needed. Services are not probed by default.
+    pub fn with_probe<F>(mut self, probe: F) -> Self
+    where
+        F: Fn() -> Request + Send + Sync + 'static,
+    {
+        self.probe = Some(Box::new(probe));
+        self
+    }
+}
+
 impl<MS, Target, Request> Stream for PoolDiscoverer<MS, Target, Request>
 where
     MS: MakeService<Target, Request>,
@@ -89,34 +260,71 @@ where
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let mut this = self.project();
+        this.load.register(cx.waker());
 
-        while let Poll::Ready(Some(sid)) = this.died_rx.as_mut().poll_recv(cx) {
-            this.services.remove(sid);
+        while let Poll::Ready(Some(died)) = this.died_rx.as_mut().poll_recv(cx) {
+            this.services.remove(died.id);
+            if died.failed {
+                *this.replace += 1;
+            }
             tracing::trace!(
                 pool.services = this.services.len(),
+                failed = died.failed,
                 message = "removing dropped service"
             );
         }
 
-        if this.services.is_empty() && this.making.is_none() {
+        if this.services.len() < *this.min && this.making.is_none() && this.probing.is_none() {
             let _ = ready!(this.maker.poll_ready(cx))?;
-            tracing::trace!("construct initial pool connection");
+            tracing::trace!(
+                pool.services = this.services.len(),
+                message = "construct initial pool connection"
+            );
+            // The initial service also replaces a failed service.
+            *this.replace = this.replace.saturating_sub(1);
             this.making
                 .set(Some(this.maker.make_service(this.target.clone())));
         }
 
-        if let Level::High = this.load {
-            if this.making.is_none() {
-                if this
-                    .limit
-                    .map(|limit| this.services.len() >= limit)
-                    .unwrap_or(false)
-                {
+        if *this.replace > 0 && this.making.is_none() && this.probing.is_none() {
+            tracing::trace!(
+                pool.services = this.services.len(),
+                message = "replacing failed service"
+            );
+            ready!(this.maker.poll_ready(cx))?;
+            *this.replace -= 1;
+            this.making
+                .set(Some(this.maker.make_service(this.target.clone())));
+        }
+
+        // A change of level cancels the remainder of a step in the other direction.
+        let level = this.load.get();
+        match level {
+            Level::High => *this.removing = 0,
+            Level::Low => *this.adding = 0,
+            Level::Normal => {}
+        }
+
+        if (level == Level::High || *this.adding > 0)
+            && this.making.is_none()
+            && this.probing.is_none()
+        {
+            if this
+                .limit
+                .map(|limit| this.services.len() >= limit)
+                .unwrap_or(false)
+            {
+                *this.adding = 0;
+                if level == Level::High {
                     return Poll::Pending;
                 }
-
+            } else {
+                if *this.adding == 0 {
+                    *this.adding = this.scale_up.services(this.services.len());
+                }
                 tracing::trace!(
                     pool.services = this.services.len(),
+                    pool.adding = *this.adding,
                     message = "decided to add service to loaded pool"
                 );
                 ready!(this.maker.poll_ready(cx))?;
@@ -127,43 +335,149 @@ where
             }
         }
 
+        let mut made = None;
         if let Some(fut) = this.making.as_mut().as_pin_mut() {
             let svc = ready!(fut.poll(cx))?;
             this.making.set(None);
+            match *this.probe {
+                Some(ref probe) => {
+                    tracing::trace!("probing new service");
+                    *this.probing = Some(Probing {
+                        service: svc,
+                        request: Some(probe()),
+                        response: None,
+                    });
+                }
+                None => made = Some(svc),
+            }
+        }
 
-            let id = this.services.insert(());
+        if let Some(ref mut probing) = *this.probing {
+            let probed = ready!(probing.poll(cx));
+            let svc = this.probing.take().expect("probing must be set").service;
+            match probed {
+                Ok(()) => made = Some(svc),
+                Err(error) => {
+                    tracing::debug!(%error, "new service failed its probe");
+                    // Services are made again while the pool is below its minimum size or
+                    // scaling up; otherwise, the service was replacing a failed one.
+                    if this.services.len() >= *this.min
+                        && this.load.get() != Level::High
+                        && *this.adding == 0
+                    {
+                        *this.replace += 1;
+                    }
+                    cx.waker().wake_by_ref();
+                    return Poll::Pending;
+                }
+            }
+        }
+
+        if let Some(svc) = made {
+            let now = Instant::now();
+            let pending = Pending(Arc::new(Mutex::new(Activity {
+                last_call: now,
+                served: 0,
+            })));
+            let id = this.services.insert(Member {
+                pending: pending.clone(),
+                created: now,
+                removed: false,
+            });
             let svc = DropNotifyService {
                 svc,
                 id,
+                pending,
                 notify: this.died_tx.clone(),
+                failures: this.max_failures.map(|max| Failures {
+                    max,
+                    count: Arc::new(AtomicUsize::new(0)),
+                }),
             };
             tracing::trace!(
                 pool.services = this.services.len(),
                 message = "finished creating new service"
             );
-            *this.load = Level::Normal;
+            *this.adding = this.adding.saturating_sub(1);
+            this.load.set(Level::Normal);
             return Poll::Ready(Some(Ok(Change::Insert(id, svc))));
         }
 
-        match this.load {
-            Level::High => {
-                unreachable!("found high load but no Service being made");
+        if let Some(timeout) = *this.idle_timeout {
+            let active = this.services.iter().filter(|(_, m)| !m.removed).count();
+            if active > *this.min {
+                let now = Instant::now();
+                let idle = this
+                    .services
+                    .iter()
+                    .filter(|(_, m)| !m.removed && m.pending.count() == 0)
+                    .map(|(id, m)| {
+                        // A timeout too large to represent never elapses.
+                        let last_call = m.pending.last_call();
+                        let deadline = last_call
+                            .checked_add(timeout)
+                            .unwrap_or_else(|| last_call + FAR_FUTURE);
+                        (id, deadline)
+                    })
+                    .min_by_key(|&(_, deadline)| deadline);
+                if let Some((id, deadline)) = idle {
+                    if deadline <= now {
+                        this.services[id].removed = true;
+                        tracing::trace!(pool.services = active, message = "removing idle service");
+                        return Poll::Ready(Some(Ok(Change::Remove(id))));
+                    }
+                    let idle = match this.idle {
+                        Some(ref mut idle) => {
+                            idle.as_mut().reset(deadline);
+                            idle
+                        }
+                        None => this
+                            .idle
+                            .get_or_insert(Box::pin(tokio::time::sleep_until(deadline))),
+                    };
+                    let _ = idle.as_mut().poll(cx);
+                }
+            }
+        }
+
+        let active = this.services.iter().filter(|(_, m)| !m.removed).count();
+        if *this.removing == 0 {
+            match this.load.get() {
+                Level::High => {
+                    unreachable!("found high load but no Service being made");
+                }
+                Level::Normal => return Poll::Pending,
+                Level::Low if active <= *this.min => return Poll::Pending,
+                Level::Low => {
+                    *this.removing = this.scale_down.services(active).min(active - *this.min);
+                }
             }
-            Level::Normal => Poll::Pending,
-            Level::Low if this.services.len() == 1 => Poll::Pending,
-            Level::Low => {
-                *this.load = Level::Normal;
-                // NOTE: this is a little sad -- we'd prefer to kill short-living services
-                let rm = this.services.iter().next().unwrap().0;
-                // note that we _don't_ remove from self.services here
-                // that'll happen automatically on drop
+        } else if active <= *this.min {
+            *this.removing = 0;
+            return Poll::Pending;
+        }
+
+        let rm = match this.eviction.select(this.services) {
+            Some(rm) => rm,
+            None => {
                 tracing::trace!(
                     pool.services = this.services.len(),
-                    message = "removing service for over-provisioned pool"
+                    message = "waiting for an idle service to remove"
                 );
-                Poll::Ready(Some(Ok(Change::Remove(rm))))
+                return Poll::Pending;
             }
-        }
+        };
+        *this.removing -= 1;
+        this.load.set(Level::Normal);
+        this.services[rm].removed = true;
+        // note that we _don't_ remove from self.services here
+        // that'll happen automatically on drop
+        tracing::trace!(
+            pool.services = this.services.len(),
+            pool.removing = *this.removing,
+            message = "removing service for over-provisioned pool"
+        );
+        Poll::Ready(Some(Ok(Change::Remove(rm))))
     }
 }
 
@@ -178,7 +492,14 @@ pub struct Builder {
     high: f64,
     init: f64,
     alpha: f64,
+    min: usize,
     limit: Option<usize>,
+    max_failures: Option<usize>,
+    eviction: Eviction,
+    scale_up: Step,
+    scale_down: Step,
+    idle_timeout: Option<Duration>,
+    dry_run: bool,
 }
 
 impl Default for Builder {
@@ -188,7 +509,14 @@ impl Default for Builder {
             low: 0.00001,
             high: 0.2,
             alpha: 0.03,
+            min: 1,
             limit: None,
+            max_failures: None,
+            eviction: Eviction::First,
+            scale_up: Step::Services(1),
+            scale_down: Step::Services(1),
+            idle_timeout: None,
+            dry_run: false,
         }
     }
 }
@@ -202,7 +530,8 @@ impl Builder {
     }
 
     /// When the estimated load (see the [module-level docs](self)) drops below this
-    /// threshold, and there are at least two services active, a service is removed.
+    /// threshold, and there are more services active than the minimum (see
+    /// [`Builder::min_services`]), a service is removed.
     ///
     /// The default value is 0.01. That is, when one in every 100 `poll_ready` calls return
     /// `Pending`, then the underlying service is considered underutilized.
@@ -251,17 +580,124 @@ impl Builder {
         self
     }
 
+    /// The minimum number of backing `Service` instances to maintain.
+    ///
+    /// The pool eagerly makes services until this many are active, regardless of the load
+    /// estimate, and never removes a service due to low load if doing so would leave fewer than
+    /// this many. This allows a floor of warm capacity to be kept available.
+    ///
+    /// The default value is 1.
+    ///
+    /// # Panics
+    ///
+    /// If `min` is 0.
+    pub fn min_services(&mut self, min: usize) -> &mut Self {
+        assert!(min > 0, "pool must maintain at least one service");
+        self.min = min;
+        self
+    }
+
     /// The maximum number of backing `Service` instances to maintain.
     ///
     /// When the limit is reached, the load estimate is clamped to the high load threshhold, and no
     /// new service is spawned.
     ///
     /// No maximum limit is imposed by default.
+    ///
+    /// Note that the minimum number of services (see [`Builder::min_services`]) is maintained
+    /// even if it exceeds this limit.
     pub fn max_services(&mut self, limit: Option<usize>) -> &mut Self {
         self.limit = limit;
         self
     }
 
+    /// The number of consecutive failed responses after which a backing `Service` is replaced.
+    ///
+    /// When a service's responses fail this many times in a row, it is removed from the pool and
+    /// a new service is made in its place, regardless of the current load estimate.
+    ///
+    /// Services are not replaced due to failed responses by default.
+    ///
+    /// # Panics
+    ///
+    /// If `failures` is `Some(0)`.
+    pub fn max_consecutive_failures(&mut self, failures: Option<usize>) -> &mut Self {
+        assert_ne!(failures, Some(0), "failure limit must be positive");
+        self.max_failures = failures;
+        self
+    }
+
+    /// Determines which service is removed when the pool is underutilized.
+    ///
+    /// With [`Eviction::Idle`], a service is only removed once it has no pending requests, so
+    /// that in-flight requests are allowed to complete.
+    ///
+    /// The default policy is [`Eviction::First`].
+    pub fn eviction(&mut self, eviction: Eviction) -> &mut Self {
+        self.eviction = eviction;
+        self
+    }
+
+    /// How many services are added each time the pool is loaded.
+    ///
+    /// The services are made one after another, up to the maximum number of services (see
+    /// [`Builder::max_services`]), and the load estimate is not sampled while a service is being
+    /// made. A larger step lets the pool react in proportion to a sudden spike in load, rather than
+    /// adding one service per spell of high load.
+    ///
+    /// The default step is `Step::Services(1)`.
+    ///
+    /// # Panics
+    ///
+    /// If `step` would add no services.
+    pub fn scale_up(&mut self, step: Step) -> &mut Self {
+        step.validate();
+        self.scale_up = step;
+        self
+    }
+
+    /// How many services are removed each time the pool is underutilized.
+    ///
+    /// Services are removed one after another, as selected by the [`Eviction`] policy, but never
+    /// below the minimum number of services (see [`Builder::min_services`]).
+    ///
+    /// The default step is `Step::Services(1)`.
+    ///
+    /// # Panics
+    ///
+    /// If `step` would remove no services.
+    pub fn scale_down(&mut self, step: Step) -> &mut Self {
+        step.validate();
+        self.scale_down = step;
+        self
+    }
+
+    /// How long a backing `Service` may go without being dispatched a request before it is
+    /// removed.
+    ///
+    /// Idle services are removed regardless of the load estimate, but never while doing so would
+    /// leave fewer than the minimum number of services (see [`Builder::min_services`]). A service
+    /// with pending requests is not considered idle.
+    ///
+    /// Services are not removed for being idle by default.
+    pub fn idle_timeout(&mut self, timeout: Option<Duration>) -> &mut Self {
+        self.idle_timeout = timeout;
+        self
+    }
+
+    /// Whether the pool only reports its scaling decisions, rather than acting on them.
+    ///
+    /// In dry-run mode, the pool maintains its load estimate and reports each change in the
+    /// [`Level`] it would set to the observer configured with [`Pool::with_observer`], but no
+    /// services are added or removed due to load. The minimum number of services is still made,
+    /// and failing and idle services are still replaced or removed as configured.
+    ///
+    /// Dry-run mode is disabled by default.
+    pub fn dry_run(&mut self, dry_run: bool) -> &mut Self {
+        self.dry_run = dry_run;
+        self
+    }
+
     /// See [`Pool::new`].
     pub fn build<MS, Target, Request>(
         &self,
@@ -275,23 +711,53 @@ impl Builder {
         MS::MakeError: Into<crate::BoxError>,
         MS::Error: Into<crate::BoxError>,
         Target: Clone,
+    {
+        Pool {
+            balance: Balance::new(Box::pin(self.discover(make_service, target))),
+            options: *self,
+            ewma: self.init,
+            level: Level::Normal,
+            observer: None,
+        }
+    }
+
+    /// Returns a [`PoolDiscoverer`] that makes services using `make_service`, without the load
+    /// estimate that a [`Pool`] uses to decide when to add or remove services.
+    ///
+    /// Instead, services are added and removed as directed by the discoverer's [`LevelHandle`]
+    /// (see [`PoolDiscoverer::level_handle`]), which allows external logic, such as an
+    /// autoscaler, to control the number of services. The minimum and maximum number of
+    /// services, and the replacement of failing services, are configured as for a [`Pool`].
+    pub fn discover<MS, Target, Request>(
+        &self,
+        make_service: MS,
+        target: Target,
+    ) -> PoolDiscoverer<MS, Target, Request>
+    where
+        MS: MakeService<Target, Request>,
     {
         let (died_tx, died_rx) = tokio::sync::mpsc::unbounded_channel();
-        let d = PoolDiscoverer {
+        PoolDiscoverer {
             maker: make_service,
             making: None,
             target,
-            load: Level::Normal,
+            load: LevelHandle::new(),
             services: Slab::new(),
+            min: self.min,
+            eviction: self.eviction,
+            scale_up: self.scale_up,
+            scale_down: self.scale_down,
+            adding: 0,
+            removing: 0,
+            idle_timeout: self.idle_timeout,
+            idle: None,
             died_tx,
             died_rx,
             limit: self.limit,
-        };
-
-        Pool {
-            balance: Balance::new(Box::pin(d)),
-            options: *self,
-            ewma: self.init,
+            max_failures: self.max_failures,
+            replace: 0,
+            probe: None,
+            probing: None,
         }
     }
 }
@@ -308,6 +774,9 @@ where
     balance: Balance<Pin<Box<PoolDiscoverer<MS, Target, Request>>>, Request>,
     options: Builder,
     ewma: f64,
+    /// The level most recently decided on by the pool.
+    level: Level,
+    observer: Option<Box<dyn Fn(Level) + Send + Sync>>,
 }
 
 impl<MS, Target, Request> fmt::Debug for Pool<MS, Target, Request>
@@ -324,6 +793,8 @@ where
             .field("balance", &self.balance)
             .field("options", &self.options)
             .field("ewma", &self.ewma)
+            .field("level", &self.level)
+            .field("observer", &self.observer.is_some())
             .finish()
     }
 }
@@ -346,6 +817,56 @@ where
     pub fn new(make_service: MS, target: Target) -> Self {
         Builder::new().build(make_service, target)
     }
+
+    /// Calls `observer` with the new [`Level`] each time the pool's scaling decision changes.
+    ///
+    /// [`Level::High`] and [`Level::Low`] are reported when the pool decides to add or remove a
+    /// service, and [`Level::Normal`] when it decides the pool is appropriately provisioned. The
+    /// observer is called inline from `poll_ready`, so it should not block.
+    ///
+    /// See also [`Builder::dry_run`].
+    pub fn with_observer<F>(mut self, observer: F) -> Self
+    where
+        F: Fn(Level) + Send + Sync + 'static,
+    {
+        self.observer = Some(Box::new(observer));
+        self
+    }
+
+    /// Requires each newly made service to successfully serve a request returned by `probe`
+    /// before it is added to the pool and counted toward its capacity.
+    ///
+    /// See [`PoolDiscoverer::with_probe`].
+    pub fn with_probe<F>(mut self, probe: F) -> Self
+    where
+        F: Fn() -> Request + Send + Sync + 'static,
+    {
+        *self.balance.discover_mut().as_mut().project().probe = Some(Box::new(probe));
+        self
+    }
+
+    /// Records the pool's scaling decision, directing the discoverer to act on it unless this is
+    /// a dry run.
+    fn decide(&mut self, level: Level) {
+        if self.level != level {
+            match level {
+                Level::Low => tracing::trace!({ ewma = %self.ewma }, "pool is over-provisioned"),
+                Level::Normal => {
+                    tracing::trace!({ ewma = %self.ewma }, "pool is appropriately provisioned")
+                }
+                Level::High => tracing::trace!({ ewma = %self.ewma }, "pool is under-provisioned"),
+            }
+            if let Some(ref observer) = self.observer {
+                observer(level);
+            }
+        }
+        self.level = level;
+
+        if !self.options.dry_run {
+            let discover = self.balance.discover_mut().as_mut().project();
+            discover.load.set(level);
+        }
+    }
 }
 
 type PinBalance<S, Request> = Balance<Pin<Box<S>>, Request>;
@@ -369,38 +890,29 @@ where
             // update ewma with a 0 sample
             self.ewma *= 1.0 - self.options.alpha;
 
-            let discover = self.balance.discover_mut().as_mut().project();
             if self.ewma < self.options.low {
-                if *discover.load != Level::Low {
-                    tracing::trace!({ ewma = %self.ewma }, "pool is over-provisioned");
-                }
-                *discover.load = Level::Low;
+                self.decide(Level::Low);
 
-                if discover.services.len() > 1 {
+                let discover = self.balance.discover_mut().as_mut().project();
+                if discover.services.len() > *discover.min {
                     // reset EWMA so we don't immediately try to remove another service
                     self.ewma = self.options.init;
                 }
             } else {
-                if *discover.load != Level::Normal {
-                    tracing::trace!({ ewma = %self.ewma }, "pool is appropriately provisioned");
-                }
-                *discover.load = Level::Normal;
+                self.decide(Level::Normal);
             }
 
             return Poll::Ready(Ok(()));
         }
 
         let discover = self.balance.discover_mut().as_mut().project();
-        if discover.making.is_none() {
+        if discover.making.is_none() && discover.probing.is_none() {
             // no services are ready -- we're overloaded
             // update ewma with a 1 sample
             self.ewma = self.options.alpha + (1.0 - self.options.alpha) * self.ewma;
 
             if self.ewma > self.options.high {
-                if *discover.load != Level::High {
-                    tracing::trace!({ ewma = %self.ewma }, "pool is under-provisioned");
-                }
-                *discover.load = Level::High;
+                self.decide(Level::High);
 
                 // don't reset the EWMA -- in theory, poll_ready should now start returning
                 // `Ready`, so we won't try to launch another service immediately.
@@ -409,9 +921,11 @@ where
 
                 // we need to call balance again for PoolDiscover to realize
                 // it can make a new service
-                return self.balance.poll_ready(cx);
+                if !self.options.dry_run {
+                    return self.balance.poll_ready(cx);
+                }
             } else {
-                *discover.load = Level::Normal;
+                self.decide(Level::Normal);
             }
         }
 
@@ -423,17 +937,171 @@ where
     }
 }
 
+// ===== impl Eviction =====
+
+impl Eviction {
+    fn select(&self, services: &Slab<Member>) -> Option<usize> {
+        let mut services = services.iter().filter(|(_, m)| !m.removed);
+        match self {
+            Eviction::First => services.next(),
+            Eviction::LeastPending => services.min_by_key(|(_, m)| m.pending.count()),
+            Eviction::Idle => services.find(|(_, m)| m.pending.count() == 0),
+            Eviction::Oldest => services.min_by_key(|(_, m)| m.created),
+            Eviction::MostUsed => services.max_by_key(|(_, m)| m.pending.served()),
+        }
+        .map(|(id, _)| id)
+    }
+}
+
+// ===== impl Step =====
+
+impl Step {
+    /// Returns the number of services to add or remove from a pool of `current` services.
+    fn services(&self, current: usize) -> usize {
+        match *self {
+            Step::Services(n) => n,
+            Step::Fraction(fraction) => ((current as f64 * fraction).ceil() as usize).max(1),
+        }
+    }
+
+    fn validate(&self) {
+        match *self {
+            Step::Services(n) => assert!(n > 0, "scaling step must be positive"),
+            Step::Fraction(fraction) => assert!(fraction > 0.0, "scaling step must be positive"),
+        }
+    }
+}
+
+// ===== impl Probing =====
+
+impl<S, Request> Probing<S, Request>
+where
+    S: Service<Request>,
+    S::Error: Into<crate::BoxError>,
+{
+    fn poll(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), crate::BoxError>> {
+        loop {
+            if let Some(ref mut response) = self.response {
+                let response = ready!(response.as_mut().poll(cx));
+                return Poll::Ready(response.map(|_| ()).map_err(Into::into));
+            }
+            ready!(self.service.poll_ready(cx)).map_err(Into::into)?;
+            let request = self.request.take().expect("probe must only be sent once");
+            self.response = Some(Box::pin(self.service.call(request)));
+        }
+    }
+}
+
+// ===== impl LevelHandle =====
+
+impl LevelHandle {
+    fn new() -> Self {
+        Self {
+            shared: Arc::new(Mutex::new(Signal {
+                level: Level::Normal,
+                waker: None,
+            })),
+        }
+    }
+
+    /// Returns the current level.
+    pub fn get(&self) -> Level {
+        self.shared.lock().expect("pool level").level
+    }
+
+    /// Sets the level, waking the [`PoolDiscoverer`] if it has changed.
+    pub fn set(&self, level: Level) {
+        let mut signal = self.shared.lock().expect("pool level");
+        if signal.level != level {
+            signal.level = level;
+            if let Some(waker) = signal.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+
+    fn register(&self, waker: &Waker) {
+        let mut signal = self.shared.lock().expect("pool level");
+        match signal.waker {
+            Some(ref w) if w.will_wake(waker) => {}
+            _ => signal.waker = Some(waker.clone()),
+        }
+    }
+}
+
 #[doc(hidden)]
 #[derive(Debug)]
 pub struct DropNotifyService<Svc> {
     svc: Svc,
     id: usize,
-    notify: tokio::sync::mpsc::UnboundedSender<usize>,
+    pending: Pending,
+    notify: tokio::sync::mpsc::UnboundedSender<Died>,
+    failures: Option<Failures>,
+}
+
+/// A service in the pool, as tracked by the [`PoolDiscoverer`].
+#[derive(Debug)]
+struct Member {
+    pending: Pending,
+    /// When the service was made.
+    created: Instant,
+    /// Whether the service has been removed, but not yet dropped.
+    removed: bool,
+}
+
+/// Counts a service's pending requests and records its [`Activity`].
+///
+/// A reference is held by the [`PoolDiscoverer`], the service, and each of the service's response
+/// futures.
+#[derive(Clone, Debug)]
+struct Pending(Arc<Mutex<Activity>>);
+
+/// Records when a service was last dispatched a request, and how many requests it has been
+/// dispatched.
+#[derive(Debug)]
+struct Activity {
+    last_call: Instant,
+    served: usize,
+}
+
+/// Counts a service's consecutive failed responses.
+#[derive(Clone, Debug)]
+struct Failures {
+    max: usize,
+    count: Arc<AtomicUsize>,
+}
+
+#[doc(hidden)]
+#[pin_project]
+#[derive(Debug)]
+pub struct DropNotifyFuture<F> {
+    #[pin]
+    inner: F,
+    failures: Option<Failures>,
+    _pending: Pending,
+}
+
+/// Returned by [`DropNotifyService::poll_ready`] so that a service that has failed too many
+/// requests is removed from the pool.
+#[derive(Debug)]
+struct TooManyFailures(usize);
+
+impl<Svc> DropNotifyService<Svc> {
+    fn has_failed(&self) -> bool {
+        match self.failures {
+            Some(ref f) => f.count.load(Ordering::Acquire) >= f.max,
+            None => false,
+        }
+    }
 }
 
 impl<Svc> Drop for DropNotifyService<Svc> {
     fn drop(&mut self) {
-        let _ = self.notify.send(self.id).is_ok();
+        let died = Died {
+            id: self.id,
+            failed: self.has_failed(),
+        };
+        let _ = self.notify.send(died).is_ok();
     }
 }
 
@@ -444,16 +1112,88 @@ impl<Svc: Load> Load for DropNotifyService<Svc> {
     }
 }
 
-impl<Request, Svc: Service<Request>> Service<Request> for DropNotifyService<Svc> {
+impl<Svc: InFlight> InFlight for DropNotifyService<Svc> {
+    fn in_flight(&self) -> usize {
+        self.svc.in_flight()
+    }
+}
+
+impl<Request, Svc> Service<Request> for DropNotifyService<Svc>
+where
+    Svc: Service<Request>,
+    Svc::Error: Into<crate::BoxError>,
+{
     type Response = Svc::Response;
-    type Future = Svc::Future;
-    type Error = Svc::Error;
+    type Future = DropNotifyFuture<Svc::Future>;
+    type Error = crate::BoxError;
 
     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        self.svc.poll_ready(cx)
+        if let Some(ref f) = self.failures {
+            let count = f.count.load(Ordering::Acquire);
+            if count >= f.max {
+                tracing::debug!(failures = count, "removing failing service from pool");
+                return Poll::Ready(Err(TooManyFailures(count).into()));
+            }
+        }
+        self.svc.poll_ready(cx).map_err(Into::into)
     }
 
     fn call(&mut self, req: Request) -> Self::Future {
-        self.svc.call(req)
+        self.pending.dispatched();
+        DropNotifyFuture {
+            inner: self.svc.call(req),
+            failures: self.failures.clone(),
+            _pending: self.pending.clone(),
+        }
+    }
+}
+
+impl<F, T, E> Future for DropNotifyFuture<F>
+where
+    F: Future<Output = Result<T, E>>,
+    E: Into<crate::BoxError>,
+{
+    type Output = Result<T, crate::BoxError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let res = ready!(this.inner.poll(cx));
+        if let Some(ref f) = this.failures {
+            if res.is_ok() {
+                f.count.store(0, Ordering::Release);
+            } else {
+                f.count.fetch_add(1, Ordering::AcqRel);
+            }
+        }
+        Poll::Ready(res.map_err(Into::into))
+    }
+}
+
+impl Pending {
+    fn count(&self) -> usize {
+        // Don't count the references held by the discoverer and the service.
+        Arc::strong_count(&self.0).saturating_sub(2)
+    }
+
+    fn last_call(&self) -> Instant {
+        self.0.lock().expect("pool service activity").last_call
+    }
+
+    fn served(&self) -> usize {
+        self.0.lock().expect("pool service activity").served
+    }
+
+    fn dispatched(&self) {
+        let mut activity = self.0.lock().expect("pool service activity");
+        activity.last_call = Instant::now();
+        activity.served += 1;
+    }
+}
+
+impl fmt::Display for TooManyFailures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "service failed {} consecutive requests", self.0)
     }
 }
+
+impl Error for TooManyFailures {}
@@ -188,3 +188,481 @@ async fn failing_service() {
     assert_request_eq!(svc2, ()).send_response("bar");
     assert_eq!(assert_ready_ok!(fut.poll()), "bar");
 }
+
+#[tokio::test]
+async fn replaces_repeatedly_failing_service() {
+    // start the pool
+    let (mock, handle) = mock::pair::<(), load::Constant<mock::Mock<(), &'static str>, usize>>();
+    pin_mut!(handle);
+
+    let pool = Builder::new()
+        .underutilized_below(0.0) // so no Ready will remove a service
+        .max_consecutive_failures(Some(2))
+        .build(mock, ());
+    let mut pool = mock::Spawn::new(pool);
+    assert_pending!(pool.poll_ready());
+
+    // give the pool a backing service that remains ready
+    let (svc1_m, svc1) = mock::pair();
+    pin_mut!(svc1);
+
+    svc1.allow(10);
+    assert_request_eq!(handle, ()).send_response(load::Constant::new(svc1_m, 0));
+
+    // a single failure does not cause the service to be replaced
+    assert_ready_ok!(pool.poll_ready());
+    let mut fut = task::spawn(pool.call(()));
+    assert_request_eq!(svc1, ()).send_error("ouch");
+    assert!(fut.poll().is_ready());
+
+    // ...nor does a failure after a success
+    assert_ready_ok!(pool.poll_ready());
+    let mut fut = task::spawn(pool.call(()));
+    assert_request_eq!(svc1, ()).send_response("foo");
+    assert_eq!(assert_ready_ok!(fut.poll()), "foo");
+
+    assert_ready_ok!(pool.poll_ready());
+    let mut fut = task::spawn(pool.call(()));
+    assert_request_eq!(svc1, ()).send_error("ouch");
+    assert!(fut.poll().is_ready());
+
+    // a second consecutive failure causes the service to be replaced, even
+    // though it is still ready
+    assert_ready_ok!(pool.poll_ready());
+    let mut fut = task::spawn(pool.call(()));
+    assert_request_eq!(svc1, ()).send_error("ouch");
+    assert!(fut.poll().is_ready());
+
+    assert_pending!(pool.poll_ready());
+    assert!(
+        assert_ready!(svc1.as_mut().poll_request()).is_none(),
+        "failing service must be dropped"
+    );
+
+    // dropping the service wakes the pool to make a replacement
+    assert!(pool.is_woken());
+    assert_pending!(pool.poll_ready());
+
+    let (svc2_m, svc2) = mock::pair();
+    pin_mut!(svc2);
+
+    svc2.allow(1);
+    assert_request_eq!(handle, ()).send_response(load::Constant::new(svc2_m, 0));
+
+    assert_ready_ok!(pool.poll_ready());
+    let mut fut = task::spawn(pool.call(()));
+    assert_request_eq!(svc2, ()).send_response("bar");
+    assert_eq!(assert_ready_ok!(fut.poll()), "bar");
+}
+
+#[tokio::test]
+async fn min_services() {
+    // start the pool
+    let (mock, handle) = mock::pair::<(), load::Constant<mock::Mock<(), &'static str>, usize>>();
+    pin_mut!(handle);
+
+    let pool = Builder::new()
+        .urgency(1.0) // so any Ready would remove a service
+        .min_services(2)
+        .build(mock, ());
+    let mut pool = mock::Spawn::new(pool);
+    assert_pending!(pool.poll_ready());
+
+    // the pool should make two services without any load
+    let (svc1_m, svc1) = mock::pair();
+    pin_mut!(svc1);
+    svc1.allow(1);
+    assert_request_eq!(handle, ()).send_response(load::Constant::new(svc1_m, 0));
+    assert_ready_ok!(pool.poll_ready());
+
+    let (svc2_m, svc2) = mock::pair();
+    pin_mut!(svc2);
+    svc2.allow(1);
+    assert_request_eq!(handle, ()).send_response(load::Constant::new(svc2_m, 0));
+    assert_ready_ok!(pool.poll_ready());
+
+    // even though the pool is underutilized, neither service is removed
+    assert_ready_ok!(pool.poll_ready());
+    assert_ready_ok!(pool.poll_ready());
+    assert_pending!(handle.as_mut().poll_request());
+
+    let mut fut1 = task::spawn(pool.call(()));
+    assert_ready_ok!(pool.poll_ready());
+    let mut fut2 = task::spawn(pool.call(()));
+
+    assert_request_eq!(svc1, ()).send_response("foo");
+    assert_request_eq!(svc2, ()).send_response("bar");
+    assert_eq!(assert_ready_ok!(fut1.poll()), "foo");
+    assert_eq!(assert_ready_ok!(fut2.poll()), "bar");
+}
+
+#[tokio::test]
+async fn discover_with_level_handle() {
+    use crate::discover::Discover;
+
+    let (mock, handle) = mock::pair::<(), mock::Mock<(), &'static str>>();
+    pin_mut!(handle);
+
+    let discover = Builder::new().discover(mock, ());
+    let level = discover.level_handle();
+    let mut discover = task::spawn(Box::pin(discover));
+
+    // the initial service is made regardless of the level
+    assert_pending!(discover.enter(|cx, d| d.poll_discover(cx)));
+    let (svc1, _svc1) = mock::pair();
+    assert_request_eq!(handle, ()).send_response(svc1);
+    let change = assert_ready!(discover.enter(|cx, d| d.poll_discover(cx)));
+    let id1 = match change {
+        Some(Ok(Change::Insert(id, _))) => id,
+        _ => panic!("expected an inserted service"),
+    };
+    assert_pending!(discover.enter(|cx, d| d.poll_discover(cx)));
+
+    // raising the level adds a service
+    level.set(Level::High);
+    assert!(discover.is_woken());
+    assert_pending!(discover.enter(|cx, d| d.poll_discover(cx)));
+    let (svc2, _svc2) = mock::pair();
+    assert_request_eq!(handle, ()).send_response(svc2);
+    let change = assert_ready!(discover.enter(|cx, d| d.poll_discover(cx)));
+    assert!(matches!(change, Some(Ok(Change::Insert(_, _)))));
+    assert_eq!(level.get(), Level::Normal);
+    assert_pending!(discover.enter(|cx, d| d.poll_discover(cx)));
+
+    // lowering the level removes a service
+    level.set(Level::Low);
+    assert!(discover.is_woken());
+    let change = assert_ready!(discover.enter(|cx, d| d.poll_discover(cx)));
+    assert!(matches!(change, Some(Ok(Change::Remove(id))) if id == id1));
+    assert_eq!(level.get(), Level::Normal);
+    assert_pending!(handle.as_mut().poll_request());
+}
+
+#[tokio::test]
+async fn scales_in_steps() {
+    use crate::discover::Discover;
+
+    let (mock, handle) = mock::pair::<(), mock::Mock<(), &'static str>>();
+    pin_mut!(handle);
+
+    let discover = Builder::new()
+        .min_services(2)
+        .scale_up(Step::Services(3))
+        .scale_down(Step::Fraction(0.5))
+        .discover(mock, ());
+    let level = discover.level_handle();
+    let mut discover = task::spawn(Box::pin(discover));
+
+    let mut services = Vec::new();
+    for _ in 0..2 {
+        assert_pending!(discover.enter(|cx, d| d.poll_discover(cx)));
+        let (svc, _) = mock::pair();
+        assert_request_eq!(handle, ()).send_response(svc);
+        match assert_ready!(discover.enter(|cx, d| d.poll_discover(cx))) {
+            Some(Ok(Change::Insert(id, svc))) => services.push((id, svc)),
+            _ => panic!("expected an inserted service"),
+        }
+    }
+    assert_pending!(discover.enter(|cx, d| d.poll_discover(cx)));
+
+    // raising the level once adds a step of three services
+    level.set(Level::High);
+    for _ in 0..3 {
+        assert_pending!(discover.enter(|cx, d| d.poll_discover(cx)));
+        let (svc, _) = mock::pair();
+        assert_request_eq!(handle, ()).send_response(svc);
+        match assert_ready!(discover.enter(|cx, d| d.poll_discover(cx))) {
+            Some(Ok(Change::Insert(id, svc))) => services.push((id, svc)),
+            _ => panic!("expected an inserted service"),
+        }
+        assert_eq!(level.get(), Level::Normal);
+    }
+    assert_pending!(discover.enter(|cx, d| d.poll_discover(cx)));
+    assert_pending!(handle.as_mut().poll_request());
+
+    // lowering the level once removes half of the five services, rounded up
+    level.set(Level::Low);
+    for _ in 0..3 {
+        let change = assert_ready!(discover.enter(|cx, d| d.poll_discover(cx)));
+        let id = match change {
+            Some(Ok(Change::Remove(id))) => id,
+            _ => panic!("expected a removed service"),
+        };
+        services.retain(|(i, _)| *i != id);
+    }
+    assert_pending!(discover.enter(|cx, d| d.poll_discover(cx)));
+    assert_eq!(services.len(), 2);
+
+    // the minimum number of services is kept
+    level.set(Level::Low);
+    assert_pending!(discover.enter(|cx, d| d.poll_discover(cx)));
+}
+
+#[tokio::test]
+async fn evicts_idle_service() {
+    use crate::discover::Discover;
+
+    let (mock, handle) = mock::pair::<(), mock::Mock<(), &'static str>>();
+    pin_mut!(handle);
+
+    let discover = Builder::new()
+        .min_services(2)
+        .eviction(Eviction::Idle)
+        .discover(mock, ());
+    let level = discover.level_handle();
+    let mut discover = task::spawn(Box::pin(discover));
+
+    let mut services = Vec::new();
+    let mut backends = Vec::new();
+    for _ in 0..3 {
+        level.set(Level::High);
+        assert_pending!(discover.enter(|cx, d| d.poll_discover(cx)));
+        let (svc, backend) = mock::pair();
+        assert_request_eq!(handle, ()).send_response(svc);
+        match assert_ready!(discover.enter(|cx, d| d.poll_discover(cx))) {
+            Some(Ok(Change::Insert(id, svc))) => services.push((id, mock::Spawn::new(svc))),
+            _ => panic!("expected an inserted service"),
+        }
+        backends.push(backend);
+    }
+
+    // every service has a pending request, so none is removed
+    let mut pending = Vec::new();
+    for (backend, (_, svc)) in backends.iter_mut().zip(services.iter_mut()) {
+        backend.allow(1);
+        assert_ready_ok!(svc.poll_ready());
+        pending.push(svc.call(()));
+    }
+    level.set(Level::Low);
+    assert_pending!(discover.enter(|cx, d| d.poll_discover(cx)));
+    assert_eq!(level.get(), Level::Low);
+
+    // once the second service's request completes, it is removed
+    drop(pending.remove(1));
+    let change = assert_ready!(discover.enter(|cx, d| d.poll_discover(cx)));
+    assert!(matches!(change, Some(Ok(Change::Remove(id))) if id == services[1].0));
+    assert_eq!(level.get(), Level::Normal);
+}
+
+#[tokio::test]
+async fn removes_idle_services() {
+    use crate::discover::Discover;
+    use std::time::Duration;
+
+    tokio::time::pause();
+
+    let (mock, handle) = mock::pair::<(), mock::Mock<(), &'static str>>();
+    pin_mut!(handle);
+
+    let discover = Builder::new()
+        .idle_timeout(Some(Duration::from_secs(10)))
+        .discover(mock, ());
+    let level = discover.level_handle();
+    let mut discover = task::spawn(Box::pin(discover));
+
+    let mut services = Vec::new();
+    let mut backends = Vec::new();
+    for _ in 0..2 {
+        level.set(Level::High);
+        assert_pending!(discover.enter(|cx, d| d.poll_discover(cx)));
+        let (svc, backend) = mock::pair();
+        assert_request_eq!(handle, ()).send_response(svc);
+        match assert_ready!(discover.enter(|cx, d| d.poll_discover(cx))) {
+            Some(Ok(Change::Insert(id, svc))) => services.push((id, mock::Spawn::new(svc))),
+            _ => panic!("expected an inserted service"),
+        }
+        backends.push(backend);
+    }
+    assert_pending!(discover.enter(|cx, d| d.poll_discover(cx)));
+
+    // dispatch a request to the first service, so the second is idle first
+    tokio::time::advance(Duration::from_secs(5)).await;
+    backends[0].allow(1);
+    assert_ready_ok!(services[0].1.poll_ready());
+    let rsp = services[0].1.call(());
+    assert_request_eq!(backends[0], ()).send_response("ok");
+    rsp.await.unwrap();
+
+    tokio::time::advance(Duration::from_millis(5_001)).await;
+    assert!(discover.is_woken());
+    let change = assert_ready!(discover.enter(|cx, d| d.poll_discover(cx)));
+    assert!(matches!(change, Some(Ok(Change::Remove(id))) if id == services[1].0));
+    drop(services.pop());
+
+    // the last service is not removed, however long it is idle
+    tokio::time::advance(Duration::from_secs(60)).await;
+    assert_pending!(discover.enter(|cx, d| d.poll_discover(cx)));
+}
+
+#[tokio::test]
+async fn huge_idle_timeout_does_not_overflow() {
+    use crate::discover::Discover;
+    use std::time::Duration;
+
+    let (mock, handle) = mock::pair::<(), mock::Mock<(), &'static str>>();
+    pin_mut!(handle);
+
+    let discover = Builder::new()
+        .idle_timeout(Some(Duration::from_secs(u64::MAX)))
+        .discover(mock, ());
+    let level = discover.level_handle();
+    let mut discover = task::spawn(Box::pin(discover));
+
+    let mut services = Vec::new();
+    for _ in 0..2 {
+        level.set(Level::High);
+        assert_pending!(discover.enter(|cx, d| d.poll_discover(cx)));
+        let (svc, _backend) = mock::pair::<(), &'static str>();
+        assert_request_eq!(handle, ()).send_response(svc);
+        match assert_ready!(discover.enter(|cx, d| d.poll_discover(cx))) {
+            Some(Ok(Change::Insert(id, svc))) => services.push((id, svc)),
+            _ => panic!("expected an inserted service"),
+        }
+    }
+
+    // neither service is ever idle for long enough to be removed
+    assert_pending!(discover.enter(|cx, d| d.poll_discover(cx)));
+}
+
+#[tokio::test]
+async fn evicts_oldest_and_most_used_services() {
+    use crate::discover::Discover;
+    use std::time::Duration;
+
+    tokio::time::pause();
+
+    for &eviction in &[Eviction::Oldest, Eviction::MostUsed] {
+        let (mock, handle) = mock::pair::<(), mock::Mock<(), &'static str>>();
+        pin_mut!(handle);
+
+        let discover = Builder::new().eviction(eviction).discover(mock, ());
+        let level = discover.level_handle();
+        let mut discover = task::spawn(Box::pin(discover));
+
+        let mut services = Vec::new();
+        let mut backends = Vec::new();
+        for _ in 0..3 {
+            level.set(Level::High);
+            assert_pending!(discover.enter(|cx, d| d.poll_discover(cx)));
+            let (svc, backend) = mock::pair();
+            assert_request_eq!(handle, ()).send_response(svc);
+            match assert_ready!(discover.enter(|cx, d| d.poll_discover(cx))) {
+                Some(Ok(Change::Insert(id, svc))) => services.push((id, mock::Spawn::new(svc))),
+                _ => panic!("expected an inserted service"),
+            }
+            backends.push(backend);
+            tokio::time::advance(Duration::from_secs(1)).await;
+        }
+
+        // replace the first service, so that the service with the lowest key is the newest
+        let (id, svc) = services.remove(0);
+        drop(svc);
+        backends.remove(0);
+        level.set(Level::High);
+        assert_pending!(discover.enter(|cx, d| d.poll_discover(cx)));
+        let (svc, backend) = mock::pair();
+        assert_request_eq!(handle, ()).send_response(svc);
+        match assert_ready!(discover.enter(|cx, d| d.poll_discover(cx))) {
+            Some(Ok(Change::Insert(new, svc))) => {
+                assert_eq!(new, id);
+                services.push((new, mock::Spawn::new(svc)));
+            }
+            _ => panic!("expected an inserted service"),
+        }
+        backends.push(backend);
+
+        // the last (and newest) service serves the most requests
+        for _ in 0..2 {
+            backends[2].allow(1);
+            assert_ready_ok!(services[2].1.poll_ready());
+            let rsp = services[2].1.call(());
+            assert_request_eq!(backends[2], ()).send_response("ok");
+            rsp.await.unwrap();
+        }
+        backends[1].allow(1);
+        assert_ready_ok!(services[1].1.poll_ready());
+        let rsp = services[1].1.call(());
+        assert_request_eq!(backends[1], ()).send_response("ok");
+        rsp.await.unwrap();
+
+        let expected = match eviction {
+            Eviction::Oldest => services[0].0,
+            _ => services[2].0,
+        };
+        level.set(Level::Low);
+        let change = assert_ready!(discover.enter(|cx, d| d.poll_discover(cx)));
+        assert!(matches!(change, Some(Ok(Change::Remove(id))) if id == expected));
+    }
+}
+
+#[tokio::test]
+async fn dry_run_reports_decisions() {
+    let (mock, handle) = mock::pair::<(), load::Constant<mock::Mock<(), &'static str>, usize>>();
+    pin_mut!(handle);
+
+    let levels = Arc::new(Mutex::new(Vec::new()));
+    let pool = Builder::new()
+        .urgency(1.0) // so _any_ Pending will add a service
+        .underutilized_below(0.0) // so no Ready will remove a service
+        .dry_run(true)
+        .build(mock, ())
+        .with_observer({
+            let levels = levels.clone();
+            move |level| levels.lock().unwrap().push(level)
+        });
+    let mut pool = mock::Spawn::new(pool);
+    assert_pending!(pool.poll_ready());
+
+    // the minimum number of services is still made
+    let (svc1_m, svc1) = mock::pair();
+    pin_mut!(svc1);
+    svc1.allow(1);
+    assert_request_eq!(handle, ()).send_response(load::Constant::new(svc1_m, 0));
+    assert_ready_ok!(pool.poll_ready());
+    let mut fut = task::spawn(pool.call(()));
+
+    // the pool decides that it is under-provisioned...
+    assert_pending!(pool.poll_ready());
+    assert_eq!(*levels.lock().unwrap(), vec![Level::High]);
+
+    // ...but does not make a new service
+    assert_pending!(handle.as_mut().poll_request());
+
+    assert_request_eq!(svc1, ()).send_response("foo");
+    assert_eq!(assert_ready_ok!(fut.poll()), "foo");
+    svc1.allow(1);
+    assert_ready_ok!(pool.poll_ready());
+    assert_eq!(*levels.lock().unwrap(), vec![Level::High, Level::Normal]);
+}
+
+#[tokio::test]
+async fn probes_new_services() {
+    let (mock, handle) = mock::pair::<(), load::Constant<mock::Mock<(), &'static str>, usize>>();
+    pin_mut!(handle);
+
+    let pool = Builder::new().build(mock, ()).with_probe(|| ());
+    let mut pool = mock::Spawn::new(pool);
+    assert_pending!(pool.poll_ready());
+
+    // the first service fails its probe, so it is not added to the pool
+    let (svc1_m, svc1) = mock::pair::<(), &'static str>();
+    pin_mut!(svc1);
+    assert_request_eq!(handle, ()).send_response(load::Constant::new(svc1_m, 0));
+    assert_pending!(pool.poll_ready());
+    assert_request_eq!(svc1, ()).send_error("broken");
+    assert_pending!(pool.poll_ready());
+    assert!(pool.is_woken());
+    assert_pending!(pool.poll_ready());
+
+    // so another service is made in its place
+    let (svc2_m, svc2) = mock::pair();
+    pin_mut!(svc2);
+    assert_request_eq!(handle, ()).send_response(load::Constant::new(svc2_m, 0));
+    assert_pending!(pool.poll_ready());
+    assert_request_eq!(svc2, ()).send_response("ok");
+    assert_ready_ok!(pool.poll_ready());
+
+    let mut fut = task::spawn(pool.call(()));
+    assert_request_eq!(svc2, ()).send_response("foobar");
+    assert_eq!(assert_ready_ok!(fut.poll()), "foobar");
+}
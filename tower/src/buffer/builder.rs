@@ -0,0 +1,133 @@
+use super::{
+    message::{DispatchHook, Envelope},
+    policy::Policy,
+    service::Buffer,
+    worker::Worker,
+};
+use std::{sync::Arc, time::Duration};
+use tower_service::Service;
+
+/// Builds [`Buffer`]s with additional configuration.
+///
+/// [`Buffer::new`] and [`Buffer::pair`] are equivalent to building with the
+/// default configuration.
+#[derive(Clone, Debug)]
+pub struct Builder {
+    bound: usize,
+    on_dispatch: Option<DispatchHook>,
+    policy: Policy,
+    queue_timeout: Option<Duration>,
+}
+
+impl Builder {
+    /// Creates a new [`Builder`] for buffers that queue at most `bound`
+    /// requests.
+    ///
+    /// See [`Buffer::new`] for advice on choosing a `bound`.
+    pub fn new(bound: usize) -> Self {
+        Self {
+            bound,
+            on_dispatch: None,
+            policy: Policy::Backpressure,
+            queue_timeout: None,
+        }
+    }
+
+    /// Sets how the buffer handles requests when its queue is full.
+    ///
+    /// By default, [`Policy::Backpressure`] is used.
+    pub fn policy(mut self, policy: Policy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Sets how long requests may wait in the queue before they are failed
+    /// with an [`Expired`] error.
+    ///
+    /// This may be overridden for each handle with
+    /// [`Buffer::set_queue_timeout`]. By default, requests may be queued
+    /// indefinitely. If requests have queue timeouts, the worker must run on
+    /// the Tokio runtime, as it tracks them with the runtime's timer.
+    ///
+    /// [`Expired`]: crate::buffer::error::Expired
+    pub fn queue_timeout(mut self, timeout: Duration) -> Self {
+        self.queue_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets a hook that the worker invokes with each request's [`Envelope`]
+    /// immediately before the request is dispatched to the inner service.
+    ///
+    /// This may be used, for instance, to record how long requests spend in
+    /// the queue.
+    ///
+    /// The hook is invoked on the worker task, so it should not block.
+    pub fn on_dispatch<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&Envelope) + Send + Sync + 'static,
+    {
+        self.on_dispatch = Some(DispatchHook(Arc::new(hook)));
+        self
+    }
+
+    /// Creates a new [`Buffer`] wrapping `service`, spawning its worker on the
+    /// default Tokio executor.
+    ///
+    /// This method must be called while on the Tokio runtime.
+    pub fn build<T, Request>(&self, service: T) -> Buffer<T, Request>
+    where
+        T: Service<Request> + Send + 'static,
+        T::Future: Send,
+        T::Error: Into<crate::BoxError> + Send + Sync,
+        Request: Send + 'static,
+    {
+        let (service, worker) = self.pair(service);
+        tokio::spawn(worker);
+        service
+    }
+
+    /// Creates a new [`Buffer`] wrapping `service`, spawning its worker onto
+    /// `tasks`.
+    ///
+    /// The worker is aborted, failing any queued requests, once every handle
+    /// to `tasks` has been dropped. This method must be called while on the
+    /// Tokio runtime.
+    #[cfg(feature = "task-set")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "task-set")))]
+    pub fn build_on<T, Request>(
+        &self,
+        service: T,
+        tasks: &crate::util::TaskSet,
+    ) -> Buffer<T, Request>
+    where
+        T: Service<Request> + Send + 'static,
+        T::Future: Send,
+        T::Error: Into<crate::BoxError> + Send + Sync,
+        Request: Send + 'static,
+    {
+        let (service, worker) = self.pair(service);
+        tasks.spawn(worker);
+        service
+    }
+
+    /// Creates a new [`Buffer`] wrapping `service`, but returns the background
+    /// worker.
+    ///
+    /// See [`Buffer::pair`].
+    pub fn pair<T, Request>(&self, service: T) -> (Buffer<T, Request>, Worker<T, Request>)
+    where
+        T: Service<Request> + Send + 'static,
+        T::Error: Into<crate::BoxError> + Send + Sync,
+        Request: Send + 'static,
+    {
+        let (mut service, worker) = Buffer::from_builder(
+            service,
+            self.bound,
+            self.on_dispatch.clone(),
+            self.policy,
+            false,
+        );
+        service.set_queue_timeout(self.queue_timeout);
+        (service, worker)
+    }
+}
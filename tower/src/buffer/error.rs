@@ -1,7 +1,7 @@
 //! Error types for the `Buffer` middleware.
 
 use crate::BoxError;
-use std::{fmt, sync::Arc};
+use std::{any::Any, backtrace::Backtrace, fmt, sync::Arc};
 
 /// An error produced by a [`Service`] wrapped by a [`Buffer`]
 ///
@@ -17,6 +17,82 @@ pub struct Closed {
     _p: (),
 }
 
+/// An error produced when a request is dropped because a buffer's queue is
+/// full.
+///
+/// This is only produced by buffers configured with [`Policy::DropNewest`] or
+/// [`Policy::DropOldest`], and by [`PriorityBuffer`]s whose priorities have
+/// limited capacity.
+///
+/// [`Policy::DropNewest`]: crate::buffer::Policy::DropNewest
+/// [`Policy::DropOldest`]: crate::buffer::Policy::DropOldest
+/// [`PriorityBuffer`]: crate::buffer::PriorityBuffer
+pub struct Overloaded {
+    _p: (),
+}
+
+/// An error produced when a request's queue timeout elapses before the
+/// buffer's worker dispatches it.
+///
+/// See [`Buffer::set_queue_timeout`] and [`Builder::queue_timeout`].
+///
+/// [`Buffer::set_queue_timeout`]: crate::buffer::Buffer::set_queue_timeout
+/// [`Builder::queue_timeout`]: crate::buffer::Builder::queue_timeout
+pub struct Expired {
+    _p: (),
+}
+
+/// An error produced when a request is rejected by a buffer's admission hook.
+///
+/// See [`Buffer::with_admission`]. The hook's reason for rejecting the request
+/// is available as the error's [`source`].
+///
+/// [`Buffer::with_admission`]: crate::buffer::Buffer::with_admission
+/// [`source`]: std::error::Error::source
+pub struct Rejected {
+    reason: BoxError,
+}
+
+/// An error produced when a request cannot be recorded in a buffer's
+/// [`Journal`].
+///
+/// See [`Buffer::with_journal`]. The journal's error is available as the
+/// error's [`source`].
+///
+/// [`Journal`]: crate::buffer::Journal
+/// [`Buffer::with_journal`]: crate::buffer::Buffer::with_journal
+/// [`source`]: std::error::Error::source
+pub struct Unrecorded {
+    source: BoxError,
+}
+
+/// An error produced when a [`Service`] wrapped by a [`Buffer`] panics.
+///
+/// If the inner service panics while the buffer's worker is polling it for
+/// readiness or dispatching a request to it, the worker catches the panic and
+/// fails all pending and future requests with this error, rather than just
+/// hanging up.
+///
+/// [`Service`]: crate::Service
+/// [`Buffer`]: crate::buffer::Buffer
+pub struct WorkerError {
+    inner: Arc<Panic>,
+}
+
+struct Panic {
+    message: Option<String>,
+    backtrace: Backtrace,
+}
+
+/// The reason a request could not be dispatched by a buffer's worker.
+#[derive(Debug)]
+pub(crate) enum Failed {
+    Service(ServiceError),
+    Panicked(WorkerError),
+    Overloaded,
+    Expired,
+}
+
 // ===== impl ServiceError =====
 
 impl ServiceError {
@@ -45,6 +121,204 @@ impl std::error::Error for ServiceError {
     }
 }
 
+// ===== impl Overloaded =====
+
+impl Overloaded {
+    pub(crate) fn new() -> Self {
+        Overloaded { _p: () }
+    }
+}
+
+impl fmt::Debug for Overloaded {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_tuple("Overloaded").finish()
+    }
+}
+
+impl fmt::Display for Overloaded {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("buffer full; request dropped")
+    }
+}
+
+impl std::error::Error for Overloaded {}
+
+// ===== impl Expired =====
+
+impl Expired {
+    pub(crate) fn new() -> Self {
+        Expired { _p: () }
+    }
+}
+
+impl fmt::Debug for Expired {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_tuple("Expired").finish()
+    }
+}
+
+impl fmt::Display for Expired {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("request expired while queued in buffer")
+    }
+}
+
+impl std::error::Error for Expired {}
+
+// ===== impl Rejected =====
+
+impl Rejected {
+    pub(crate) fn new(reason: BoxError) -> Self {
+        Rejected { reason }
+    }
+}
+
+impl fmt::Debug for Rejected {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_tuple("Rejected").field(&self.reason).finish()
+    }
+}
+
+impl fmt::Display for Rejected {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "request rejected by buffer: {}", self.reason)
+    }
+}
+
+impl std::error::Error for Rejected {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&*self.reason)
+    }
+}
+
+// ===== impl Unrecorded =====
+
+impl Unrecorded {
+    pub(crate) fn new(source: BoxError) -> Self {
+        Unrecorded { source }
+    }
+}
+
+impl fmt::Debug for Unrecorded {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_tuple("Unrecorded").field(&self.source).finish()
+    }
+}
+
+impl fmt::Display for Unrecorded {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "request could not be recorded in buffer journal: {}",
+            self.source
+        )
+    }
+}
+
+impl std::error::Error for Unrecorded {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&*self.source)
+    }
+}
+
+// ===== impl WorkerError =====
+
+impl WorkerError {
+    pub(crate) fn new(payload: Box<dyn Any + Send>) -> WorkerError {
+        let message = if let Some(message) = payload.downcast_ref::<&'static str>() {
+            Some((*message).to_string())
+        } else {
+            payload.downcast_ref::<String>().cloned()
+        };
+        let inner = Arc::new(Panic {
+            message,
+            backtrace: Backtrace::capture(),
+        });
+        WorkerError { inner }
+    }
+
+    /// Returns the panic's message, if it was a string.
+    pub fn message(&self) -> Option<&str> {
+        self.inner.message.as_deref()
+    }
+
+    /// Returns a backtrace of the worker at the point where the panic was
+    /// caught, if backtraces are enabled.
+    ///
+    /// See [`Backtrace::capture`] for how backtraces are enabled.
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        use std::backtrace::BacktraceStatus;
+
+        match self.inner.backtrace.status() {
+            BacktraceStatus::Captured => Some(&self.inner.backtrace),
+            _ => None,
+        }
+    }
+
+    // Private to avoid exposing `Clone` trait as part of the public API
+    pub(crate) fn clone(&self) -> WorkerError {
+        WorkerError {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for WorkerError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("WorkerError")
+            .field("message", &self.inner.message)
+            .field("backtrace", &self.backtrace())
+            .finish()
+    }
+}
+
+impl fmt::Display for WorkerError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self.message() {
+            Some(message) => write!(fmt, "buffered service panicked: {}", message),
+            None => fmt.write_str("buffered service panicked"),
+        }
+    }
+}
+
+impl std::error::Error for WorkerError {}
+
+// ===== impl Failed =====
+
+impl Failed {
+    // Private to avoid exposing `Clone` trait as part of the public API
+    pub(crate) fn clone(&self) -> Failed {
+        match self {
+            Failed::Service(error) => Failed::Service(error.clone()),
+            Failed::Panicked(error) => Failed::Panicked(error.clone()),
+            Failed::Overloaded => Failed::Overloaded,
+            Failed::Expired => Failed::Expired,
+        }
+    }
+}
+
+impl fmt::Display for Failed {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Failed::Service(error) => error.fmt(fmt),
+            Failed::Panicked(error) => error.fmt(fmt),
+            Failed::Overloaded => Overloaded::new().fmt(fmt),
+            Failed::Expired => Expired::new().fmt(fmt),
+        }
+    }
+}
+
+impl From<Failed> for BoxError {
+    fn from(failed: Failed) -> BoxError {
+        match failed {
+            Failed::Service(error) => error.into(),
+            Failed::Panicked(error) => error.into(),
+            Failed::Overloaded => Overloaded::new().into(),
+            Failed::Expired => Expired::new().into(),
+        }
+    }
+}
+
 // ===== impl Closed =====
 
 impl Closed {
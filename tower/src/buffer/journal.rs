@@ -0,0 +1,75 @@
+use std::{fmt, sync::Arc};
+
+/// Durable storage for the requests queued in a [`Buffer`].
+///
+/// A [`Buffer`] configured with [`Buffer::with_journal`] records each request
+/// in its journal before enqueueing it, and removes the record once the
+/// request has been handed off to the inner service. Requests that were
+/// recorded but never handed off, for instance because the process exited
+/// while they were queued, are replayed from the journal the next time it is
+/// attached to a buffer.
+///
+/// The journal is responsible for serializing requests, and for making its
+/// records durable before [`Journal::append`] returns.
+///
+/// [`Buffer`]: crate::buffer::Buffer
+/// [`Buffer::with_journal`]: crate::buffer::Buffer::with_journal
+pub trait Journal<Request> {
+    /// Durably records `request`, returning an ID that identifies the record.
+    ///
+    /// If the request cannot be recorded, it is not enqueued, and its response
+    /// future fails with an [`Unrecorded`] error wrapping the returned error.
+    ///
+    /// [`Unrecorded`]: crate::buffer::error::Unrecorded
+    fn append(&self, request: &Request) -> Result<u64, crate::BoxError>;
+
+    /// Removes the record identified by `id`, so that it is not replayed.
+    fn remove(&self, id: u64);
+
+    /// Returns the recorded requests that have not been removed, with their
+    /// IDs, in the order in which they were recorded.
+    fn replay(&self) -> Vec<(u64, Request)>;
+}
+
+/// A [`Journal`] shared by a buffer's handles and its worker.
+pub(crate) struct Shared<Request>(pub(crate) Arc<dyn Journal<Request> + Send + Sync>);
+
+/// The record of a queued request, which is removed once the request no
+/// longer needs to be replayed.
+pub(crate) struct Journaled<Request> {
+    id: u64,
+    journal: Shared<Request>,
+}
+
+// ===== impl Shared =====
+
+impl<Request> Clone for Shared<Request> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<Request> fmt::Debug for Shared<Request> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Journal").finish()
+    }
+}
+
+// ===== impl Journaled =====
+
+impl<Request> Journaled<Request> {
+    pub(crate) fn new(id: u64, journal: Shared<Request>) -> Self {
+        Self { id, journal }
+    }
+
+    /// Removes the request's record from the journal.
+    pub(crate) fn remove(self) {
+        self.journal.0.remove(self.id);
+    }
+}
+
+impl<Request> fmt::Debug for Journaled<Request> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Journaled").field("id", &self.id).finish()
+    }
+}
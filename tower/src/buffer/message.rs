@@ -1,17 +1,230 @@
-use super::error::ServiceError;
+use super::error::Failed;
+use super::journal::Journaled;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::{fmt, time::Duration};
 use tokio::sync::{oneshot, OwnedSemaphorePermit};
+use tokio::time::Instant;
+use tokio_util::time::delay_queue::Key;
 
 /// Message sent over buffer
 #[derive(Debug)]
 pub(crate) struct Message<Request, Fut> {
     pub(crate) request: Request,
+    pub(crate) envelope: Envelope,
     pub(crate) tx: Tx<Fut>,
     pub(crate) span: tracing::Span,
-    pub(super) _permit: OwnedSemaphorePermit,
+    pub(crate) priority: u8,
+    pub(super) _permit: Option<OwnedSemaphorePermit>,
+    // Held while the request is queued if its priority's capacity is limited.
+    pub(super) _priority_permit: Option<OwnedSemaphorePermit>,
+    pub(super) _queued: Queued,
+    // The request's record in the buffer's journal, if it has one.
+    pub(crate) journal: Option<Journaled<Request>>,
+    // Set by the worker while it tracks the request's queue timeout.
+    pub(super) expiration: Option<Key>,
 }
 
+/// Metadata recorded for each request as it is enqueued in a [`Buffer`].
+///
+/// The worker passes each request's envelope to the hook configured by
+/// [`Builder::on_dispatch`] immediately before dispatching the request to the
+/// inner service.
+///
+/// [`Buffer`]: crate::buffer::Buffer
+/// [`Builder::on_dispatch`]: crate::buffer::Builder::on_dispatch
+#[derive(Clone, Debug)]
+pub struct Envelope {
+    enqueued_at: Instant,
+    caller: usize,
+    deadline: Option<Instant>,
+    tag: Option<u64>,
+}
+
+/// Describes a request that is about to be enqueued in a [`Buffer`], as passed
+/// to the hook configured by [`Buffer::with_admission`].
+///
+/// [`Buffer`]: crate::buffer::Buffer
+/// [`Buffer::with_admission`]: crate::buffer::Buffer::with_admission
+#[derive(Clone, Debug)]
+pub struct Admission {
+    queue_depth: usize,
+    caller: usize,
+    tag: Option<u64>,
+}
+
+/// Invoked by the worker before each request is dispatched.
+#[derive(Clone)]
+pub(crate) struct DispatchHook(pub(crate) Arc<dyn Fn(&Envelope) + Send + Sync>);
+
+/// Invoked by each handle before a request is enqueued.
+pub(crate) struct AdmissionHook<Request>(pub(crate) Arc<AdmitFn<Request>>);
+
+type AdmitFn<Request> =
+    dyn Fn(Request, &mut Admission) -> Result<Request, crate::BoxError> + Send + Sync;
+
+/// Counts a request as queued until it is dropped.
+#[derive(Debug)]
+pub(crate) struct Queued(Arc<AtomicUsize>);
+
 /// Response sender
-pub(crate) type Tx<Fut> = oneshot::Sender<Result<Fut, ServiceError>>;
+pub(crate) type Tx<Fut> = oneshot::Sender<Result<Fut, Failed>>;
 
 /// Response receiver
-pub(crate) type Rx<Fut> = oneshot::Receiver<Result<Fut, ServiceError>>;
+pub(crate) type Rx<Fut> = oneshot::Receiver<Result<Fut, Failed>>;
+
+// ===== impl Message =====
+
+impl<Request, Fut> Message<Request, Fut> {
+    /// Returns `true` if the caller is no longer waiting for the response, so
+    /// that the request need not be dispatched.
+    ///
+    /// The request's record is removed from the buffer's journal, as the
+    /// caller has abandoned it.
+    pub(crate) fn check_canceled(&mut self) -> bool {
+        if !self.tx.is_closed() {
+            return false;
+        }
+        self.unjournal();
+        true
+    }
+
+    /// Removes the request's record from the buffer's journal, if it has one.
+    pub(crate) fn unjournal(&mut self) {
+        if let Some(journaled) = self.journal.take() {
+            journaled.remove();
+        }
+    }
+}
+
+// ===== impl Envelope =====
+
+impl Envelope {
+    pub(crate) fn new(caller: usize, timeout: Option<Duration>, tag: Option<u64>) -> Self {
+        let enqueued_at = Instant::now();
+        Self {
+            enqueued_at,
+            caller,
+            deadline: timeout.map(|t| enqueued_at + t),
+            tag,
+        }
+    }
+
+    /// Returns the time at which the request was enqueued.
+    pub fn enqueued_at(&self) -> Instant {
+        self.enqueued_at
+    }
+
+    /// Returns how long the request has been queued.
+    pub fn queue_time(&self) -> Duration {
+        self.enqueued_at.elapsed()
+    }
+
+    /// Returns the ID of the [`Buffer`] handle that enqueued the request.
+    ///
+    /// See [`Buffer::caller_id`].
+    ///
+    /// [`Buffer`]: crate::buffer::Buffer
+    /// [`Buffer::caller_id`]: crate::buffer::Buffer::caller_id
+    pub fn caller(&self) -> usize {
+        self.caller
+    }
+
+    /// Returns the time by which the request should have been dispatched, if
+    /// the enqueuing handle configured a queue timeout.
+    ///
+    /// See [`Buffer::set_queue_timeout`].
+    ///
+    /// [`Buffer::set_queue_timeout`]: crate::buffer::Buffer::set_queue_timeout
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Returns the tag assigned to the request by the buffer's admission hook,
+    /// if any.
+    ///
+    /// See [`Admission::set_tag`].
+    pub fn tag(&self) -> Option<u64> {
+        self.tag
+    }
+}
+
+// ===== impl Admission =====
+
+impl Admission {
+    pub(crate) fn new(queue_depth: usize, caller: usize) -> Self {
+        Self {
+            queue_depth,
+            caller,
+            tag: None,
+        }
+    }
+
+    /// Returns the number of requests that are queued in the buffer and have
+    /// not yet been dispatched to the inner service.
+    pub fn queue_depth(&self) -> usize {
+        self.queue_depth
+    }
+
+    /// Returns the ID of the [`Buffer`] handle that is enqueuing the request.
+    ///
+    /// See [`Buffer::caller_id`].
+    ///
+    /// [`Buffer`]: crate::buffer::Buffer
+    /// [`Buffer::caller_id`]: crate::buffer::Buffer::caller_id
+    pub fn caller(&self) -> usize {
+        self.caller
+    }
+
+    /// Returns the tag that will be recorded in the request's [`Envelope`].
+    pub fn tag(&self) -> Option<u64> {
+        self.tag
+    }
+
+    /// Tags the request, so that the tag is recorded in its [`Envelope`].
+    ///
+    /// This may be used, for instance, to classify requests at admission and
+    /// observe their classes when they are dispatched.
+    pub fn set_tag(&mut self, tag: u64) {
+        self.tag = Some(tag);
+    }
+}
+
+// ===== impl AdmissionHook =====
+
+impl<Request> Clone for AdmissionHook<Request> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<Request> fmt::Debug for AdmissionHook<Request> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AdmissionHook").finish()
+    }
+}
+
+// ===== impl Queued =====
+
+impl Queued {
+    pub(crate) fn new(depth: &Arc<AtomicUsize>) -> Self {
+        depth.fetch_add(1, Ordering::Relaxed);
+        Self(depth.clone())
+    }
+}
+
+impl Drop for Queued {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+// ===== impl DispatchHook =====
+
+impl fmt::Debug for DispatchHook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DispatchHook").finish()
+    }
+}
@@ -8,6 +8,16 @@
 //! request is enqueued alongside a response channel that allows the service to report the result
 //! of the request back to the caller.
 //!
+//! Each queued request also carries an [`Envelope`] recording when and by which handle it was
+//! enqueued. A [`Builder`] may be used to configure a hook that observes each envelope as its
+//! request is dispatched to the inner service, and [`Buffer::with_admission`] configures a hook
+//! that may reject, tag, or transform each request before it is enqueued.
+//!
+//! Queued requests are lost if the process exits before they are dispatched. Where that is not
+//! acceptable, [`Buffer::with_journal`] records each request in a durable [`Journal`] until it is
+//! dispatched, so that requests left over by a previous process may be resent with
+//! [`Buffer::replay`].
+//!
 //! # Examples
 //!
 //! ```rust
@@ -35,13 +45,23 @@
 //! ```
 //!
 //! [`Service`]: crate::Service
+//! [`Overloaded`]: error::Overloaded
 
+mod builder;
 pub mod error;
 pub mod future;
+mod journal;
 mod layer;
 mod message;
+mod policy;
+mod priority;
 mod service;
 mod worker;
 
+pub use self::builder::Builder;
+pub use self::journal::Journal;
 pub use self::layer::BufferLayer;
+pub use self::message::{Admission, Envelope};
+pub use self::policy::Policy;
+pub use self::priority::PriorityBuffer;
 pub use self::service::Buffer;
@@ -0,0 +1,32 @@
+/// Determines what a [`Buffer`] does when its queue is full.
+///
+/// [`Buffer`]: crate::buffer::Buffer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Policy {
+    /// Applies backpressure: [`poll_ready`] returns [`Poll::Pending`] until
+    /// there is capacity in the queue.
+    ///
+    /// This is the default.
+    ///
+    /// [`poll_ready`]: crate::Service::poll_ready
+    /// [`Poll::Pending`]: std::task::Poll::Pending
+    Backpressure,
+
+    /// Fails new requests with an [`Overloaded`] error while the queue is
+    /// full.
+    ///
+    /// The buffer is always ready, so callers never wait for capacity.
+    ///
+    /// [`Overloaded`]: crate::buffer::error::Overloaded
+    DropNewest,
+
+    /// Fails the oldest queued request with an [`Overloaded`] error to make
+    /// room for new requests while the queue is full.
+    ///
+    /// The buffer is always ready, so callers never wait for capacity. The
+    /// worker enforces the bound as it receives requests, so the queue may
+    /// briefly hold more than `bound` requests until the worker runs.
+    ///
+    /// [`Overloaded`]: crate::buffer::error::Overloaded
+    DropOldest,
+}
@@ -0,0 +1,155 @@
+use super::{
+    error::Overloaded, future::ResponseFuture, policy::Policy, service::Buffer, worker::Worker,
+};
+use std::{
+    fmt,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::sync::Semaphore;
+use tower_service::Service;
+
+/// Adds a priority queue in front of an inner service.
+///
+/// Like a [`Buffer`], but each request is assigned a priority by the
+/// user-provided `priority` function when it is called, and the worker
+/// dispatches queued requests with higher priorities first. Requests with the
+/// same priority are dispatched in the order they were enqueued. This allows
+/// important requests, such as health checks or control-plane calls, to jump
+/// the queue while the service is under load.
+///
+/// As with [`Buffer`], capacity in the queue is reserved in [`poll_ready`],
+/// before the request's priority is known. To ensure that capacity remains for
+/// important requests, the number of queued requests of a given priority may
+/// be limited with [`PriorityBuffer::with_limit`]. Requests exceeding their
+/// priority's limit fail with an [`Overloaded`] error.
+///
+/// [`poll_ready`]: crate::Service::poll_ready
+pub struct PriorityBuffer<T, Request, F>
+where
+    T: Service<Request>,
+{
+    buffer: Buffer<T, Request>,
+    priority: F,
+    limits: Arc<Vec<(u8, Arc<Semaphore>)>>,
+}
+
+impl<T, Request, F> PriorityBuffer<T, Request, F>
+where
+    T: Service<Request>,
+    T::Error: Into<crate::BoxError>,
+    F: Fn(&Request) -> u8,
+{
+    /// Creates a new [`PriorityBuffer`] wrapping `service`, which queues at
+    /// most `bound` requests and orders them by `priority`.
+    ///
+    /// The default Tokio executor is used to run the given service, which
+    /// means that this method must be called while on the Tokio runtime. See
+    /// [`Buffer::new`] for advice on choosing a `bound`.
+    pub fn new(service: T, bound: usize, priority: F) -> Self
+    where
+        T: Send + 'static,
+        T::Future: Send,
+        T::Error: Send + Sync,
+        Request: Send + 'static,
+    {
+        let (service, worker) = Self::pair(service, bound, priority);
+        tokio::spawn(worker);
+        service
+    }
+
+    /// Creates a new [`PriorityBuffer`] wrapping `service`, but returns the
+    /// background worker.
+    ///
+    /// See [`Buffer::pair`].
+    pub fn pair(
+        service: T,
+        bound: usize,
+        priority: F,
+    ) -> (PriorityBuffer<T, Request, F>, Worker<T, Request>)
+    where
+        T: Send + 'static,
+        T::Error: Send + Sync,
+        Request: Send + 'static,
+    {
+        let (buffer, worker) =
+            Buffer::from_builder(service, bound, None, Policy::Backpressure, true);
+        let buffer = PriorityBuffer {
+            buffer,
+            priority,
+            limits: Arc::new(Vec::new()),
+        };
+        (buffer, worker)
+    }
+
+    /// Limits the number of queued requests with the given `priority` to
+    /// `capacity`.
+    ///
+    /// Limits are shared by all clones of this handle that are made after the
+    /// limit is set.
+    pub fn with_limit(mut self, priority: u8, capacity: usize) -> Self {
+        let limits = Arc::make_mut(&mut self.limits);
+        limits.retain(|(p, _)| *p != priority);
+        limits.push((priority, Arc::new(Semaphore::new(capacity))));
+        self
+    }
+}
+
+impl<T, Request, F> Service<Request> for PriorityBuffer<T, Request, F>
+where
+    T: Service<Request>,
+    T::Error: Into<crate::BoxError>,
+    F: Fn(&Request) -> u8,
+{
+    type Response = T::Response;
+    type Error = crate::BoxError;
+    type Future = ResponseFuture<T::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.buffer.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let priority = (self.priority)(&request);
+        let limit = self.limits.iter().find(|(p, _)| *p == priority);
+        let permit = match limit {
+            Some((_, semaphore)) => match semaphore.clone().try_acquire_owned() {
+                Ok(permit) => Some(permit),
+                Err(_) => {
+                    tracing::debug!(priority, "priority limit reached; dropping request");
+                    return ResponseFuture::failed(Overloaded::new().into());
+                }
+            },
+            None => None,
+        };
+        self.buffer.send(request, priority, permit)
+    }
+}
+
+impl<T, Request, F> Clone for PriorityBuffer<T, Request, F>
+where
+    T: Service<Request>,
+    F: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            buffer: self.buffer.clone(),
+            priority: self.priority.clone(),
+            limits: self.limits.clone(),
+        }
+    }
+}
+
+impl<T, Request, F> fmt::Debug for PriorityBuffer<T, Request, F>
+where
+    T: Service<Request> + fmt::Debug,
+    T::Future: fmt::Debug,
+    Request: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PriorityBuffer")
+            .field("buffer", &self.buffer)
+            .field("limits", &self.limits)
+            .finish()
+    }
+}
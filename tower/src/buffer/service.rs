@@ -1,12 +1,19 @@
 use super::{
+    error::{Overloaded, Rejected, Unrecorded},
     future::ResponseFuture,
-    message::Message,
-    worker::{Handle, Worker},
+    journal::{Journal, Journaled, Shared},
+    message::{Admission, AdmissionHook, DispatchHook, Envelope, Message, Queued},
+    policy::Policy,
+    worker::{Handle, Queue, Worker},
 };
 
 use futures_core::ready;
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
 use std::task::{Context, Poll};
+use std::time::Duration;
 use tokio::sync::{mpsc, oneshot, OwnedSemaphorePermit, Semaphore};
 use tokio_util::sync::PollSemaphore;
 use tower_service::Service;
@@ -37,6 +44,16 @@ where
     // This is acquired in `poll_ready` and taken in `call`.
     permit: Option<OwnedSemaphorePermit>,
     handle: Handle,
+    // Identifies this handle in the envelopes of the requests it enqueues.
+    caller: usize,
+    // Allocates caller IDs to clones of this handle.
+    next_caller: Arc<AtomicUsize>,
+    queue_timeout: Option<Duration>,
+    policy: Policy,
+    // The number of requests queued by all handles.
+    depth: Arc<AtomicUsize>,
+    admission: Option<AdmissionHook<Request>>,
+    journal: Option<Shared<Request>>,
 }
 
 impl<T, Request> Buffer<T, Request>
@@ -72,7 +89,22 @@ where
         T::Error: Send + Sync,
         Request: Send + 'static,
     {
-        let (service, worker) = Self::pair(service, bound);
+        Self::with_policy(service, bound, Policy::Backpressure)
+    }
+
+    /// Creates a new [`Buffer`] wrapping `service`, which handles requests
+    /// that overflow its queue according to `policy`.
+    ///
+    /// `bound` gives the maximal number of requests that can be queued for the
+    /// service. See [`Buffer::new`] for details.
+    pub fn with_policy(service: T, bound: usize, policy: Policy) -> Self
+    where
+        T: Send + 'static,
+        T::Future: Send,
+        T::Error: Send + Sync,
+        Request: Send + 'static,
+    {
+        let (service, worker) = Self::from_builder(service, bound, None, policy, false);
         tokio::spawn(worker);
         service
     }
@@ -88,21 +120,281 @@ where
         T::Error: Send + Sync,
         Request: Send + 'static,
     {
+        Self::from_builder(service, bound, None, Policy::Backpressure, false)
+    }
+
+    /// Creates a new [`Buffer`] wrapping `service`, returning a worker that
+    /// must be driven alongside the buffer handles.
+    ///
+    /// Unlike [`Buffer::new`] and [`Buffer::pair`], this does not require the
+    /// service or its requests to be `Send` or `'static`. This allows requests
+    /// that borrow from their surroundings (such as borrowed buffers) to be
+    /// used behind a buffer, provided that the worker is polled on the same
+    /// thread and within the scope of those borrows, for instance by joining
+    /// it with the futures that use the buffer.
+    ///
+    /// The worker completes once all of the buffer handles have been dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "util")]
+    /// # async fn scoped() {
+    /// use tower::buffer::Buffer;
+    /// use tower::{service_fn, ServiceExt};
+    ///
+    /// let data = String::from("hello");
+    /// let svc = service_fn(|req: &str| {
+    ///     let len = req.len();
+    ///     async move { Ok::<_, tower::BoxError>(len) }
+    /// });
+    ///
+    /// let (buffer, worker) = Buffer::scoped(svc, 1);
+    /// let request = async {
+    ///     let len = buffer.oneshot(&data[..]).await.unwrap();
+    ///     assert_eq!(len, 5);
+    /// };
+    /// futures::join!(worker, request);
+    /// # }
+    /// ```
+    pub fn scoped(service: T, bound: usize) -> (Buffer<T, Request>, Worker<T, Request>) {
+        Self::from_builder(service, bound, None, Policy::Backpressure, false)
+    }
+
+    pub(crate) fn from_builder(
+        service: T,
+        bound: usize,
+        on_dispatch: Option<DispatchHook>,
+        policy: Policy,
+        prioritize: bool,
+    ) -> (Buffer<T, Request>, Worker<T, Request>) {
         let (tx, rx) = mpsc::unbounded_channel();
         let semaphore = Arc::new(Semaphore::new(bound));
-        let (handle, worker) = Worker::new(service, rx, &semaphore);
+        let queue = Queue {
+            // When dropping the oldest requests, the worker enforces the bound.
+            shed_oldest: if policy == Policy::DropOldest {
+                Some(bound)
+            } else {
+                None
+            },
+            prioritize,
+        };
+        let (handle, worker) = Worker::new(service, rx, &semaphore, on_dispatch, queue);
         let buffer = Buffer {
             tx,
             handle,
             semaphore: PollSemaphore::new(semaphore),
             permit: None,
+            caller: 0,
+            next_caller: Arc::new(AtomicUsize::new(1)),
+            queue_timeout: None,
+            policy,
+            depth: Arc::new(AtomicUsize::new(0)),
+            admission: None,
+            journal: None,
         };
         (buffer, worker)
     }
 
+    /// Returns the ID identifying this handle in the [`Envelope`]s of the
+    /// requests it enqueues.
+    ///
+    /// Each clone of a [`Buffer`] is assigned a distinct ID.
+    pub fn caller_id(&self) -> usize {
+        self.caller
+    }
+
+    /// Sets how long requests sent through this handle should be allowed to
+    /// wait in the queue.
+    ///
+    /// Each request's deadline is recorded in its [`Envelope`]. If the worker
+    /// has not dispatched a request to the inner service by its deadline, the
+    /// request is discarded and its response future fails with an
+    /// [`Expired`] error, even if other requests are queued ahead of it. The
+    /// buffer's worker must run on the Tokio runtime to track queue timeouts.
+    /// Clones of this handle inherit its queue timeout.
+    ///
+    /// [`Expired`]: crate::buffer::error::Expired
+    pub fn set_queue_timeout(&mut self, timeout: Option<Duration>) {
+        self.queue_timeout = timeout;
+    }
+
+    /// Sets a hook that this handle invokes with each request before it is
+    /// enqueued.
+    ///
+    /// The hook is passed the request along with an [`Admission`] describing
+    /// the current depth of the queue and the enqueuing handle. It may return
+    /// the request unchanged, transform it, or tag it with
+    /// [`Admission::set_tag`] so that the tag is recorded in the request's
+    /// [`Envelope`]. If the hook returns an error, the request is not enqueued
+    /// and its response future fails with a [`Rejected`] error wrapping it.
+    /// This is a building block for admission control at the buffer boundary,
+    /// such as quota or deadline checks.
+    ///
+    /// The hook is invoked by [`call`], so it should not block. Clones of this
+    /// handle inherit its hook.
+    ///
+    /// [`Rejected`]: crate::buffer::error::Rejected
+    /// [`call`]: crate::Service::call
+    pub fn with_admission<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(Request, &mut Admission) -> Result<Request, crate::BoxError> + Send + Sync + 'static,
+    {
+        self.admission = Some(AdmissionHook(Arc::new(hook)));
+        self
+    }
+
+    /// Records each request sent through this handle in `journal` before it
+    /// is enqueued.
+    ///
+    /// Each request's record is removed once the worker has dispatched the
+    /// request to the inner service, or once the request has been discarded
+    /// because its caller dropped its response future, it expired, or the
+    /// queue overflowed. Records of requests that are still queued when the
+    /// worker fails or is dropped, for instance because the process exits,
+    /// are retained, so that the requests may be resent with
+    /// [`Buffer::replay`]. Requests are therefore delivered at least once:
+    /// a request may be replayed even though it was dispatched, if the process
+    /// exits before its record is removed.
+    ///
+    /// Clones of this handle inherit its journal.
+    pub fn with_journal<J>(mut self, journal: J) -> Self
+    where
+        J: Journal<Request> + Send + Sync + 'static,
+    {
+        self.journal = Some(Shared(Arc::new(journal)));
+        self
+    }
+
+    /// Resends the requests recorded in this handle's journal, returning
+    /// their response futures.
+    ///
+    /// This should be called once, when a buffer is first created with a
+    /// journal that may hold requests left by a previous process. Replayed
+    /// requests are enqueued ahead of any requests sent afterwards, and are
+    /// not subject to the buffer's bound or queue timeout. As with any other
+    /// request, a replayed request is discarded if its response future is
+    /// dropped, so the returned futures should be driven to completion.
+    ///
+    /// Returns no futures if no journal was configured with
+    /// [`Buffer::with_journal`].
+    pub fn replay(&mut self) -> Vec<ResponseFuture<T::Future>> {
+        let journal = match self.journal {
+            Some(ref journal) => journal.clone(),
+            None => return Vec::new(),
+        };
+        let recorded = journal.0.replay();
+        tracing::debug!(requests = recorded.len(), "replaying journaled requests");
+        recorded
+            .into_iter()
+            .map(|(id, request)| {
+                let envelope = Envelope::new(self.caller, None, None);
+                let journaled = Journaled::new(id, journal.clone());
+                self.enqueue(request, envelope, 0, None, None, Some(journaled))
+            })
+            .collect()
+    }
+
     fn get_worker_error(&self) -> crate::BoxError {
         self.handle.get_error_on_closed()
     }
+
+    /// Sends a request to the worker with the given priority.
+    pub(crate) fn send(
+        &mut self,
+        request: Request,
+        priority: u8,
+        priority_permit: Option<OwnedSemaphorePermit>,
+    ) -> ResponseFuture<T::Future> {
+        tracing::trace!("sending request to buffer worker");
+        let _permit = match self.policy {
+            Policy::Backpressure => Some(
+                self.permit
+                    .take()
+                    .expect("buffer full; poll_ready must be called first"),
+            ),
+            Policy::DropNewest => match self.permit.take() {
+                Some(permit) => Some(permit),
+                None => {
+                    tracing::debug!("buffer full; dropping request");
+                    return ResponseFuture::failed(Overloaded::new().into());
+                }
+            },
+            Policy::DropOldest => None,
+        };
+
+        let mut admission = Admission::new(self.depth.load(Ordering::Relaxed), self.caller);
+        let request = match self.admission {
+            Some(ref hook) => match (hook.0)(request, &mut admission) {
+                Ok(request) => request,
+                Err(reason) => {
+                    tracing::debug!(%reason, "request rejected by admission hook");
+                    return ResponseFuture::failed(Rejected::new(reason).into());
+                }
+            },
+            None => request,
+        };
+
+        let journaled = match self.journal {
+            Some(ref journal) => match journal.0.append(&request) {
+                Ok(id) => Some(Journaled::new(id, journal.clone())),
+                Err(error) => {
+                    tracing::debug!(%error, "failed to record request in journal");
+                    return ResponseFuture::failed(Unrecorded::new(error).into());
+                }
+            },
+            None => None,
+        };
+
+        let envelope = Envelope::new(self.caller, self.queue_timeout, admission.tag());
+        self.enqueue(
+            request,
+            envelope,
+            priority,
+            _permit,
+            priority_permit,
+            journaled,
+        )
+    }
+
+    fn enqueue(
+        &self,
+        request: Request,
+        envelope: Envelope,
+        priority: u8,
+        _permit: Option<OwnedSemaphorePermit>,
+        priority_permit: Option<OwnedSemaphorePermit>,
+        journal: Option<Journaled<Request>>,
+    ) -> ResponseFuture<T::Future> {
+        // get the current Span so that we can explicitly propagate it to the worker
+        // if we didn't do this, events on the worker related to this span wouldn't be counted
+        // towards that span since the worker would have no way of entering it.
+        let span = tracing::Span::current();
+
+        // If we've made it here, then a semaphore permit has already been
+        // acquired, so we can freely allocate a oneshot.
+        let (tx, rx) = oneshot::channel();
+
+        match self.tx.send(Message {
+            request,
+            envelope,
+            span,
+            tx,
+            priority,
+            _permit,
+            _priority_permit: priority_permit,
+            _queued: Queued::new(&self.depth),
+            journal,
+            expiration: None,
+        }) {
+            Err(mpsc::error::SendError(mut msg)) => {
+                // The request was never queued, so it need not be replayed.
+                msg.unjournal();
+                ResponseFuture::failed(self.get_worker_error())
+            }
+            Ok(_) => ResponseFuture::new(rx),
+        }
+    }
 }
 
 impl<T, Request> Service<Request> for Buffer<T, Request>
@@ -127,42 +419,30 @@ where
             return Poll::Ready(Ok(()));
         }
 
-        // Finally, if we haven't already acquired a permit, poll the semaphore
-        // to acquire one. If we acquire a permit, then there's enough buffer
-        // capacity to send a new request. Otherwise, we need to wait for
-        // capacity.
-        let permit =
-            ready!(self.semaphore.poll_acquire(cx)).ok_or_else(|| self.get_worker_error())?;
-        self.permit = Some(permit);
+        match self.policy {
+            // Finally, if we haven't already acquired a permit, poll the semaphore
+            // to acquire one. If we acquire a permit, then there's enough buffer
+            // capacity to send a new request. Otherwise, we need to wait for
+            // capacity.
+            Policy::Backpressure => {
+                let permit = ready!(self.semaphore.poll_acquire(cx))
+                    .ok_or_else(|| self.get_worker_error())?;
+                self.permit = Some(permit);
+            }
+            // If there's no capacity, the request will be failed when it's
+            // called.
+            Policy::DropNewest => {
+                self.permit = self.semaphore.clone_inner().try_acquire_owned().ok();
+            }
+            // The worker makes room for new requests as they arrive.
+            Policy::DropOldest => {}
+        }
 
         Poll::Ready(Ok(()))
     }
 
     fn call(&mut self, request: Request) -> Self::Future {
-        tracing::trace!("sending request to buffer worker");
-        let _permit = self
-            .permit
-            .take()
-            .expect("buffer full; poll_ready must be called first");
-
-        // get the current Span so that we can explicitly propagate it to the worker
-        // if we didn't do this, events on the worker related to this span wouldn't be counted
-        // towards that span since the worker would have no way of entering it.
-        let span = tracing::Span::current();
-
-        // If we've made it here, then a semaphore permit has already been
-        // acquired, so we can freely allocate a oneshot.
-        let (tx, rx) = oneshot::channel();
-
-        match self.tx.send(Message {
-            request,
-            span,
-            tx,
-            _permit,
-        }) {
-            Err(_) => ResponseFuture::failed(self.get_worker_error()),
-            Ok(_) => ResponseFuture::new(rx),
-        }
+        self.send(request, 0, None)
     }
 }
 
@@ -178,6 +458,13 @@ where
             // The new clone hasn't acquired a permit yet. It will when it's
             // next polled ready.
             permit: None,
+            caller: self.next_caller.fetch_add(1, Ordering::Relaxed),
+            next_caller: self.next_caller.clone(),
+            queue_timeout: self.queue_timeout,
+            policy: self.policy,
+            depth: self.depth.clone(),
+            admission: self.admission.clone(),
+            journal: self.journal.clone(),
         }
     }
 }
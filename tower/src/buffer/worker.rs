@@ -1,16 +1,23 @@
 use super::{
-    error::{Closed, ServiceError},
-    message::Message,
+    error::{Closed, Failed, ServiceError, WorkerError},
+    message::{DispatchHook, Message},
 };
 use futures_core::ready;
 use pin_project::pin_project;
 use std::sync::{Arc, Mutex, Weak};
 use std::{
+    cmp::Reverse,
+    collections::{HashSet, VecDeque},
     future::Future,
+    mem,
+    panic::{self, AssertUnwindSafe},
     pin::Pin,
     task::{Context, Poll},
+    time::Duration,
 };
 use tokio::sync::{mpsc, Semaphore};
+use tokio::time::Instant;
+use tokio_util::time::DelayQueue;
 use tower_service::Service;
 
 /// Task that handles processing the buffer. This type should not be used
@@ -29,17 +36,40 @@ where
 {
     current_message: Option<Message<Request, T::Future>>,
     rx: mpsc::UnboundedReceiver<Message<Request, T::Future>>,
+    // Requests are received eagerly into this queue, so that they may be
+    // failed as soon as their queue timeouts elapse.
+    queued: VecDeque<Message<Request, T::Future>>,
+    queue: Queue,
+    rx_closed: bool,
     service: T,
     finish: bool,
-    failed: Option<ServiceError>,
+    failed: Option<Failed>,
     handle: Handle,
     close: Option<Weak<Semaphore>>,
+    on_dispatch: Option<DispatchHook>,
+    // Fires as the queue timeouts of queued messages elapse.
+    expirations: DelayQueue<()>,
+}
+
+/// Queue timeouts that elapse later than this are not tracked, as a
+/// `DelayQueue` cannot represent them. Such requests expire if they reach the
+/// head of the queue after their deadlines.
+const MAX_TRACKED_TIMEOUT: Duration = Duration::from_secs(60 * 60 * 24 * 365);
+
+/// Determines how the worker orders and bounds queued requests.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Queue {
+    /// If set, the oldest requests are dropped so that at most this many
+    /// requests are queued.
+    pub(crate) shed_oldest: Option<usize>,
+    /// Whether requests with higher priorities are dispatched first.
+    pub(crate) prioritize: bool,
 }
 
 /// Get the error out
 #[derive(Debug)]
 pub(crate) struct Handle {
-    inner: Arc<Mutex<Option<ServiceError>>>,
+    inner: Arc<Mutex<Option<Failed>>>,
 }
 
 impl<T, Request> Worker<T, Request>
@@ -51,6 +81,8 @@ where
         service: T,
         rx: mpsc::UnboundedReceiver<Message<Request, T::Future>>,
         semaphore: &Arc<Semaphore>,
+        on_dispatch: Option<DispatchHook>,
+        queue: Queue,
     ) -> (Handle, Worker<T, Request>) {
         let handle = Handle {
             inner: Arc::new(Mutex::new(None)),
@@ -59,12 +91,17 @@ where
         let semaphore = Arc::downgrade(semaphore);
         let worker = Worker {
             current_message: None,
+            queued: VecDeque::new(),
+            queue,
+            rx_closed: false,
             finish: false,
             failed: None,
             rx,
             service,
             handle: handle.clone(),
             close: Some(semaphore),
+            on_dispatch,
+            expirations: DelayQueue::new(),
         };
 
         (handle, worker)
@@ -74,6 +111,10 @@ where
     ///
     /// If a `Message` is returned, the `bool` is true if this is the first time we received this
     /// message, and false otherwise (i.e., we tried to forward it to the backing service before).
+    ///
+    /// All sent requests are received, so that the oldest may be dropped if
+    /// the queue has overflowed, the next request may be chosen by priority,
+    /// and requests may be failed as soon as their queue timeouts elapse.
     fn poll_next_msg(
         &mut self,
         cx: &mut Context<'_>,
@@ -84,33 +125,121 @@ where
         }
 
         tracing::trace!("worker polling for next message");
+        while !self.rx_closed {
+            match Pin::new(&mut self.rx).poll_recv(cx) {
+                Poll::Ready(Some(mut msg)) => {
+                    self.track_expiration(&mut msg);
+                    self.queued.push_back(msg);
+                }
+                Poll::Ready(None) => self.rx_closed = true,
+                Poll::Pending => break,
+            }
+        }
+
+        // The current message hasn't been dispatched yet, so it's the oldest
+        // queued request, and it may be preempted by a higher priority request.
+        let mut resumed = false;
         if let Some(msg) = self.current_message.take() {
-            // If the oneshot sender is closed, then the receiver is dropped,
-            // and nobody cares about the response. If this is the case, we
-            // should continue to the next request.
-            if !msg.tx.is_closed() {
-                tracing::trace!("resuming buffered request");
-                return Poll::Ready(Some((msg, false)));
+            resumed = true;
+            self.queued.push_front(msg);
+        }
+        self.poll_expirations(cx);
+
+        if let (Some(bound), None) = (self.queue.shed_oldest, &self.failed) {
+            while self.queued.len() > bound {
+                let mut msg = self.queued.pop_front().expect("queue must not be empty");
+                self.untrack_expiration(&mut msg);
+                resumed = false;
+                // Canceled requests are dropped without displacing others.
+                if msg.check_canceled() {
+                    continue;
+                }
+                tracing::debug!("buffer full; dropping oldest request");
+                msg.unjournal();
+                let _ = msg.tx.send(Err(Failed::Overloaded));
             }
+        }
 
-            tracing::trace!("dropping cancelled buffered request");
+        // Canceled requests are only dropped once they are chosen, so that
+        // the queue need not be scanned for them each time the worker is
+        // polled.
+        loop {
+            let index = if self.queue.prioritize {
+                // Prefer the oldest request with the highest priority.
+                self.queued
+                    .iter()
+                    .enumerate()
+                    .max_by_key(|(i, msg)| (msg.priority, Reverse(*i)))
+                    .map_or(0, |(i, _)| i)
+            } else {
+                0
+            };
+            let mut msg = match self.queued.remove(index) {
+                Some(msg) => msg,
+                None => break,
+            };
+            self.untrack_expiration(&mut msg);
+            let first = !(resumed && index == 0);
+            if index == 0 {
+                resumed = false;
+            }
+            if msg.check_canceled() {
+                tracing::trace!("dropping cancelled request");
+                continue;
+            }
+            tracing::trace!(first, priority = msg.priority, "processing request");
+            return Poll::Ready(Some((msg, first)));
+        }
+        if self.rx_closed {
+            return Poll::Ready(None);
         }
+        Poll::Pending
+    }
 
-        // Get the next request
-        while let Some(msg) = ready!(Pin::new(&mut self.rx).poll_recv(cx)) {
-            if !msg.tx.is_closed() {
-                tracing::trace!("processing new request");
-                return Poll::Ready(Some((msg, true)));
+    /// Schedules the message to be failed once its queue timeout elapses.
+    fn track_expiration(&mut self, msg: &mut Message<Request, T::Future>) {
+        if let Some(deadline) = msg.envelope.deadline() {
+            if deadline <= Instant::now() + MAX_TRACKED_TIMEOUT {
+                msg.expiration = Some(self.expirations.insert_at((), deadline));
             }
-            // Otherwise, request is canceled, so pop the next one.
-            tracing::trace!("dropping cancelled request");
         }
+    }
 
-        Poll::Ready(None)
+    /// Cancels the message's expiration, as it has left the queue.
+    fn untrack_expiration(&mut self, msg: &mut Message<Request, T::Future>) {
+        if let Some(key) = msg.expiration.take() {
+            self.expirations.remove(&key);
+        }
     }
 
-    fn failed(&mut self, error: crate::BoxError) {
-        // The underlying service failed when we called `poll_ready` on it with the given `error`. We
+    /// Fails each queued message whose queue timeout has elapsed.
+    fn poll_expirations(&mut self, cx: &mut Context<'_>) {
+        let mut expired = HashSet::new();
+        while let Poll::Ready(Some(Ok(expiration))) = self.expirations.poll_expired(cx) {
+            expired.insert(expiration.key());
+        }
+        if expired.is_empty() {
+            return;
+        }
+
+        for mut msg in mem::take(&mut self.queued) {
+            match msg.expiration {
+                Some(key) if expired.contains(&key) => {
+                    let _guard = msg.span.enter();
+                    tracing::debug!("request expired while queued");
+                    if let Some(journaled) = msg.journal.take() {
+                        journaled.remove();
+                    }
+                    let _ = msg.tx.send(Err(Failed::Expired));
+                }
+                _ => self.queued.push_back(msg),
+            }
+        }
+    }
+
+    fn failed(&mut self, error: Failed) {
+        // The underlying service failed when we called `poll_ready` on it with the given `error`
+        // (or it panicked while we were polling or calling it). We
         // need to communicate this to all the `Buffer` handles. To do so, we wrap up the error in
         // an `Arc`, send that `Arc<E>` to all pending requests, and store it so that subsequent
         // requests will also fail with the same error.
@@ -122,8 +251,6 @@ where
         // request. We do this by *first* exposing the error, *then* closing the channel used to
         // send more requests (so the client will see the error when the send fails), and *then*
         // sending the error to all outstanding requests.
-        let error = ServiceError::new(error);
-
         let mut inner = self.handle.inner.lock().unwrap();
 
         if inner.is_some() {
@@ -168,7 +295,7 @@ where
 
         loop {
             match ready!(self.poll_next_msg(cx)) {
-                Some((msg, first)) => {
+                Some((mut msg, first)) => {
                     let _guard = msg.span.enter();
                     if let Some(ref failed) = self.failed {
                         tracing::trace!("notifying caller about worker failure");
@@ -176,44 +303,82 @@ where
                         continue;
                     }
 
+                    if matches!(msg.envelope.deadline(), Some(d) if d <= Instant::now()) {
+                        tracing::debug!("request expired while queued");
+                        if let Some(journaled) = msg.journal.take() {
+                            journaled.remove();
+                        }
+                        let _ = msg.tx.send(Err(Failed::Expired));
+                        continue;
+                    }
+
                     // Wait for the service to be ready
                     tracing::trace!(
                         resumed = !first,
                         message = "worker received request; waiting for service readiness"
                     );
-                    match self.service.poll_ready(cx) {
+                    let ready =
+                        match panic::catch_unwind(AssertUnwindSafe(|| self.service.poll_ready(cx)))
+                        {
+                            Ok(ready) => {
+                                ready.map_err(|e| Failed::Service(ServiceError::new(e.into())))
+                            }
+                            Err(panic) => {
+                                Poll::Ready(Err(Failed::Panicked(WorkerError::new(panic))))
+                            }
+                        };
+                    let error = match ready {
                         Poll::Ready(Ok(())) => {
                             tracing::debug!(service.ready = true, message = "processing request");
-                            let response = self.service.call(msg.request);
-
-                            // Send the response future back to the sender.
-                            //
-                            // An error means the request had been canceled in-between
-                            // our calls, the response future will just be dropped.
-                            tracing::trace!("returning response future");
-                            let _ = msg.tx.send(Ok(response));
+                            let request = msg.request;
+                            let envelope = &msg.envelope;
+                            let this = &mut *self;
+                            let called = panic::catch_unwind(AssertUnwindSafe(|| {
+                                if let Some(DispatchHook(ref hook)) = this.on_dispatch {
+                                    hook(envelope);
+                                }
+                                this.service.call(request)
+                            }));
+                            match called {
+                                Ok(response) => {
+                                    // The request has been handed off, so it
+                                    // need not be replayed.
+                                    if let Some(journaled) = msg.journal.take() {
+                                        journaled.remove();
+                                    }
+
+                                    // Send the response future back to the sender.
+                                    //
+                                    // An error means the request had been canceled in-between
+                                    // our calls, the response future will just be dropped.
+                                    tracing::trace!("returning response future");
+                                    let _ = msg.tx.send(Ok(response));
+                                    continue;
+                                }
+                                Err(panic) => Failed::Panicked(WorkerError::new(panic)),
+                            }
                         }
                         Poll::Pending => {
                             tracing::trace!(service.ready = false, message = "delay");
                             // Put out current message back in its slot.
                             drop(_guard);
+                            self.track_expiration(&mut msg);
                             self.current_message = Some(msg);
                             return Poll::Pending;
                         }
-                        Poll::Ready(Err(e)) => {
-                            let error = e.into();
-                            tracing::debug!({ %error }, "service failed");
-                            drop(_guard);
-                            self.failed(error);
-                            let _ = msg.tx.send(Err(self
-                                .failed
-                                .as_ref()
-                                .expect("Worker::failed did not set self.failed?")
-                                .clone()));
-                            // Wake any tasks waiting on channel capacity.
-                            self.close_semaphore();
-                        }
-                    }
+                        Poll::Ready(Err(error)) => error,
+                    };
+
+                    tracing::debug!({ %error }, "service failed");
+                    drop(_guard);
+                    self.failed(error);
+                    let _ = msg.tx.send(Err(self
+                        .failed
+                        .as_ref()
+                        .expect("Worker::failed did not set self.failed?")
+                        .clone()));
+                    // Wake any tasks waiting on channel capacity.
+                    self.close_semaphore();
                 }
                 None => {
                     // No more more requests _ever_.
@@ -242,7 +407,7 @@ impl Handle {
             .lock()
             .unwrap()
             .as_ref()
-            .map(|svc_err| svc_err.clone().into())
+            .map(|failed| failed.clone().into())
             .unwrap_or_else(|| Closed::new().into())
     }
 }
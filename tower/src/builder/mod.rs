@@ -203,6 +203,23 @@ impl<L> ServiceBuilder<L> {
         self.layer(crate::limit::ConcurrencyLimitLayer::new(max))
     }
 
+    /// Stop accepting requests once the [`Drain`] watched by `watch` is
+    /// signaled.
+    ///
+    /// This wraps the inner service with an instance of the [`Drainable`]
+    /// middleware.
+    ///
+    /// [`Drain`]: crate::drain::Drain
+    /// [`Drainable`]: crate::drain::Drainable
+    #[cfg(feature = "drain")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "drain")))]
+    pub fn drain(
+        self,
+        watch: crate::drain::Watch,
+    ) -> ServiceBuilder<Stack<crate::drain::DrainLayer, L>> {
+        self.layer(crate::drain::DrainLayer::new(watch))
+    }
+
     /// Drop requests when the next layer is unable to respond to requests.
     ///
     /// Usually, when a service or middleware does not have capacity to process a
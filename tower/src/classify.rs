@@ -0,0 +1,145 @@
+//! Classifying errors by how middleware should respond to them.
+//!
+//! Several middleware need to decide what an error means: a retry policy needs
+//! to know whether the request may succeed if it is retried, a circuit
+//! breaker whether the error reflects on the health of its target, and a load
+//! balancer whether the endpoint should be evicted. Rather than expressing
+//! these decisions separately for each middleware, a single [`Classify`]
+//! implementation maps each error to an [`ErrorClass`], from which each
+//! middleware derives its decision.
+//!
+//! Classifiers are consumed by [`RetryableErrors`] and by [`EvictOnError`].
+//!
+//! [`RetryableErrors`]: crate::retry::RetryableErrors
+//! [`EvictOnError`]: crate::balance::health::EvictOnError
+
+use std::{error::Error, io};
+
+/// What an error indicates about the request that failed and the target that
+/// failed it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorClass {
+    /// The request itself is at fault, such as a malformed request. It
+    /// should not be retried, and the error does not reflect on the target.
+    Request,
+    /// The request failed for a reason that may not recur, such as a timeout
+    /// or the target being overloaded. It may be retried, and the error
+    /// counts against the target's health.
+    Transient,
+    /// The target has failed, such as when its connection has been lost. The
+    /// request may be retried against another target, and the target should
+    /// no longer be used.
+    Endpoint,
+}
+
+/// Classifies errors of type `E`.
+///
+/// This trait is implemented for closures taking `&E` and returning an
+/// [`ErrorClass`].
+pub trait Classify<E> {
+    /// Returns the class of `error`.
+    fn classify(&self, error: &E) -> ErrorClass;
+}
+
+/// A [`Classify`] implementation that recognizes the errors of [`std::io`]
+/// and of Tower's middleware.
+///
+/// Boxed errors are classified by the first error in their [`source`] chain
+/// that is recognized:
+///
+/// - [`io::Error`]s indicating that a connection was refused or lost are
+///   [`ErrorClass::Endpoint`] errors.
+/// - [`io::Error`]s indicating that the input was invalid are
+///   [`ErrorClass::Request`] errors.
+/// - Elapsed [timeouts] and [overloaded] services are
+///   [`ErrorClass::Transient`] errors.
+///
+/// Errors that are not recognized are [`ErrorClass::Transient`].
+///
+/// [`source`]: std::error::Error::source
+/// [timeouts]: crate::timeout::error::Elapsed
+/// [overloaded]: crate::load_shed::error::Overloaded
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultClassify;
+
+// ===== impl ErrorClass =====
+
+impl ErrorClass {
+    /// Returns `true` if a request that failed with this class of error may
+    /// be retried.
+    pub fn is_retryable(&self) -> bool {
+        !matches!(self, ErrorClass::Request)
+    }
+
+    /// Returns `true` if this class of error counts against the health of the
+    /// target that failed the request, as tracked by a circuit breaker.
+    pub fn is_failure(&self) -> bool {
+        !matches!(self, ErrorClass::Request)
+    }
+
+    /// Returns `true` if the target that failed the request should be evicted
+    /// from a load balancer.
+    pub fn evicts(&self) -> bool {
+        matches!(self, ErrorClass::Endpoint)
+    }
+}
+
+// ===== impl Classify =====
+
+impl<F, E> Classify<E> for F
+where
+    F: Fn(&E) -> ErrorClass,
+{
+    fn classify(&self, error: &E) -> ErrorClass {
+        (self)(error)
+    }
+}
+
+// ===== impl DefaultClassify =====
+
+impl DefaultClassify {
+    fn classify_io(error: &io::Error) -> ErrorClass {
+        match error.kind() {
+            io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::NotConnected
+            | io::ErrorKind::BrokenPipe
+            | io::ErrorKind::AddrNotAvailable => ErrorClass::Endpoint,
+            io::ErrorKind::InvalidInput | io::ErrorKind::InvalidData => ErrorClass::Request,
+            _ => ErrorClass::Transient,
+        }
+    }
+
+    fn classify_dyn(error: &(dyn Error + 'static)) -> ErrorClass {
+        let mut next = Some(error);
+        while let Some(error) = next {
+            if let Some(error) = error.downcast_ref::<io::Error>() {
+                return Self::classify_io(error);
+            }
+            #[cfg(feature = "timeout")]
+            if error.is::<crate::timeout::error::Elapsed>() {
+                return ErrorClass::Transient;
+            }
+            #[cfg(feature = "load-shed")]
+            if error.is::<crate::load_shed::error::Overloaded>() {
+                return ErrorClass::Transient;
+            }
+            next = error.source();
+        }
+        ErrorClass::Transient
+    }
+}
+
+impl Classify<crate::BoxError> for DefaultClassify {
+    fn classify(&self, error: &crate::BoxError) -> ErrorClass {
+        Self::classify_dyn(&**error)
+    }
+}
+
+impl Classify<io::Error> for DefaultClassify {
+    fn classify(&self, error: &io::Error) -> ErrorClass {
+        Self::classify_io(error)
+    }
+}
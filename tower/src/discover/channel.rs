@@ -0,0 +1,95 @@
+use super::{error::Never, Change};
+use futures_core::Stream;
+use std::{
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::sync::mpsc;
+
+/// Service discovery based on a list of services that is updated at runtime.
+///
+/// A [`ChannelList`] is created alongside a [`ListHandle`] with
+/// [`ChannelList::pair`]. Services inserted and removed through the handle (or
+/// any of its clones) are yielded as [`Change`]s, bridging services managed by
+/// application code and components that consume a [`Discover`], such as a
+/// load balancer. Discovery completes once all handles have been dropped.
+///
+/// [`Discover`]: super::Discover
+#[cfg_attr(docsrs, doc(cfg(feature = "discover-channel")))]
+pub struct ChannelList<K, S> {
+    rx: mpsc::UnboundedReceiver<Change<K, S>>,
+}
+
+/// Inserts services into, and removes services from, a [`ChannelList`].
+#[cfg_attr(docsrs, doc(cfg(feature = "discover-channel")))]
+pub struct ListHandle<K, S> {
+    tx: mpsc::UnboundedSender<Change<K, S>>,
+}
+
+// ===== impl ChannelList =====
+
+impl<K, S> ChannelList<K, S> {
+    /// Creates a new, empty [`ChannelList`] and a [`ListHandle`] that updates
+    /// it.
+    pub fn pair() -> (Self, ListHandle<K, S>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (ChannelList { rx }, ListHandle { tx })
+    }
+}
+
+impl<K, S> Stream for ChannelList<K, S> {
+    type Item = Result<Change<K, S>, Never>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx).map(|change| change.map(Ok))
+    }
+}
+
+impl<K, S> fmt::Debug for ChannelList<K, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChannelList").finish()
+    }
+}
+
+// ===== impl ListHandle =====
+
+impl<K, S> ListHandle<K, S> {
+    /// Inserts a service identified by `key`.
+    ///
+    /// If a service with the same key has already been inserted, it is
+    /// replaced.
+    ///
+    /// Returns `false` if the [`ChannelList`] has been dropped.
+    pub fn insert(&self, key: K, service: S) -> bool {
+        self.tx.send(Change::Insert(key, service)).is_ok()
+    }
+
+    /// Removes the service identified by `key`.
+    ///
+    /// Returns `false` if the [`ChannelList`] has been dropped.
+    pub fn remove(&self, key: K) -> bool {
+        self.tx.send(Change::Remove(key)).is_ok()
+    }
+
+    /// Returns `true` if the [`ChannelList`] has been dropped.
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+}
+
+impl<K, S> Clone for ListHandle<K, S> {
+    fn clone(&self) -> Self {
+        ListHandle {
+            tx: self.tx.clone(),
+        }
+    }
+}
+
+impl<K, S> fmt::Debug for ListHandle<K, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ListHandle")
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
@@ -0,0 +1,162 @@
+use super::{Change, Discover};
+use futures_core::Stream;
+use pin_project::pin_project;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+    hash::Hash,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Suppresses redundant changes yielded by an inner [`Discover`].
+///
+/// Noisy discovery sources may yield changes that have no effect, or that
+/// cancel each other out, each of which would otherwise cause a balancer to
+/// rebuild or re-probe endpoints. [`Dedup`] drops:
+///
+/// - An [`Insert`] of a service equal to the one already discovered for the
+///   same key.
+/// - A [`Remove`] of a key that has not been discovered.
+/// - An [`Insert`] of a new key that is removed before it is yielded, along
+///   with the [`Remove`].
+///
+/// Changes are yielded in batches: all of the changes that the inner
+/// discovery has ready are collected, and coalesced, before the first of them
+/// is yielded. Changes to a key that was inserted earlier in the same batch
+/// update the pending [`Insert`] in place.
+///
+/// To compare services, [`Dedup`] holds a clone of each discovered service.
+/// It is intended for discovery that yields lightweight service descriptions,
+/// such as addresses or configurations, rather than connected services.
+///
+/// [`Insert`]: Change::Insert
+/// [`Remove`]: Change::Remove
+#[pin_project]
+pub struct Dedup<D>
+where
+    D: Discover,
+{
+    #[pin]
+    discover: D,
+    /// The services that have been discovered once the pending changes have
+    /// been yielded.
+    discovered: HashMap<D::Key, D::Service>,
+    /// Keys first inserted by the pending changes.
+    fresh: HashSet<D::Key>,
+    changes: VecDeque<Change<D::Key, D::Service>>,
+    /// Whether the inner discovery has ended.
+    done: bool,
+}
+
+impl<D> Dedup<D>
+where
+    D: Discover,
+    D::Key: Hash + Clone,
+    D::Service: PartialEq + Clone,
+{
+    /// Creates a [`Dedup`] that suppresses redundant changes yielded by
+    /// `discover`.
+    pub fn new(discover: D) -> Self {
+        Self {
+            discover,
+            discovered: HashMap::new(),
+            fresh: HashSet::new(),
+            changes: VecDeque::new(),
+            done: false,
+        }
+    }
+}
+
+fn key<K, V>(change: &Change<K, V>) -> &K {
+    match change {
+        Change::Insert(key, _) | Change::Remove(key) => key,
+    }
+}
+
+impl<D> Stream for Dedup<D>
+where
+    D: Discover,
+    D::Key: Hash + Clone,
+    D::Service: PartialEq + Clone,
+{
+    type Item = Result<Change<D::Key, D::Service>, D::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        // Collect and coalesce all of the changes that are ready before
+        // yielding any of them.
+        if this.changes.is_empty() {
+            this.fresh.clear();
+            while !*this.done {
+                let change = match this.discover.as_mut().poll_discover(cx) {
+                    Poll::Pending => break,
+                    Poll::Ready(None) => {
+                        *this.done = true;
+                        break;
+                    }
+                    Poll::Ready(Some(Err(error))) => return Poll::Ready(Some(Err(error))),
+                    Poll::Ready(Some(Ok(change))) => change,
+                };
+
+                match change {
+                    Change::Insert(ref key, ref svc) if this.discovered.get(key) == Some(svc) => {
+                        // The service is unchanged.
+                    }
+                    Change::Insert(key, svc) if this.fresh.contains(&key) => {
+                        // Update the pending insert in place.
+                        for change in this.changes.iter_mut() {
+                            if let Change::Insert(k, s) = change {
+                                if *k == key {
+                                    *s = svc.clone();
+                                }
+                            }
+                        }
+                        this.discovered.insert(key, svc);
+                    }
+                    Change::Insert(key, svc) => {
+                        if this.discovered.insert(key.clone(), svc.clone()).is_none() {
+                            this.fresh.insert(key.clone());
+                        }
+                        this.changes.push_back(Change::Insert(key, svc));
+                    }
+                    Change::Remove(key) => {
+                        if this.discovered.remove(&key).is_none() {
+                            // The key was never discovered.
+                            continue;
+                        }
+                        // Pending changes to the key are superseded by its
+                        // removal.
+                        this.changes.retain(|change| *self::key(change) != key);
+                        // An insert and a removal in the same batch cancel
+                        // out.
+                        if !this.fresh.remove(&key) {
+                            this.changes.push_back(Change::Remove(key));
+                        }
+                    }
+                }
+            }
+        }
+
+        match this.changes.pop_front() {
+            Some(change) => Poll::Ready(Some(Ok(change))),
+            None if *this.done => Poll::Ready(None),
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl<D> fmt::Debug for Dedup<D>
+where
+    D: Discover + fmt::Debug,
+    D::Key: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Dedup")
+            .field("discover", &self.discover)
+            .field("discovered", &self.discovered.keys().collect::<Vec<_>>())
+            .field("pending", &self.changes.len())
+            .finish()
+    }
+}
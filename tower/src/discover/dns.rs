@@ -0,0 +1,170 @@
+use super::{error::Never, Change};
+use futures_core::Stream;
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt,
+    future::Future,
+    net::SocketAddr,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::time::{sleep, Sleep};
+use tracing::{debug, trace};
+use trust_dns_resolver::{error::ResolveError, lookup_ip::LookupIp, TokioAsyncResolver};
+
+type Lookup = Pin<Box<dyn Future<Output = Result<LookupIp, ResolveError>> + Send + 'static>>;
+
+/// Service discovery by periodically resolving a hostname.
+///
+/// Each address that the name resolves to (including both `A` and `AAAA`
+/// records) is combined with a port and passed to a function that constructs a
+/// service for that address. The resulting services are yielded as
+/// [`Change::Insert`]s keyed by their [`SocketAddr`]; addresses that are no
+/// longer returned by a later resolution are yielded as [`Change::Remove`]s.
+///
+/// The name is re-resolved every [`interval`]. If a resolution fails, the
+/// previously resolved set of addresses is retained until the next resolution
+/// succeeds.
+///
+/// [`interval`]: DnsDiscover::with_interval
+#[cfg_attr(docsrs, doc(cfg(feature = "dns")))]
+pub struct DnsDiscover<F, S> {
+    resolver: TokioAsyncResolver,
+    name: String,
+    port: u16,
+    new_service: F,
+    interval: Duration,
+    state: State,
+    addrs: HashSet<SocketAddr>,
+    changes: VecDeque<Change<SocketAddr, S>>,
+}
+
+enum State {
+    /// Waiting to re-resolve the name.
+    Waiting(Pin<Box<Sleep>>),
+    /// Resolving the name.
+    Resolving(Lookup),
+}
+
+impl<F, S> DnsDiscover<F, S>
+where
+    F: FnMut(SocketAddr) -> S,
+{
+    /// The default interval at which the name is re-resolved.
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(30);
+
+    /// Creates a [`DnsDiscover`] that resolves `name` with `resolver` and
+    /// constructs a service for each resolved address and `port` with
+    /// `new_service`.
+    ///
+    /// The first resolution begins when the discovery stream is first polled.
+    pub fn new(
+        resolver: TokioAsyncResolver,
+        name: impl Into<String>,
+        port: u16,
+        new_service: F,
+    ) -> Self {
+        let name = name.into();
+        let state = State::Resolving(Self::lookup(&resolver, &name));
+        Self {
+            resolver,
+            name,
+            port,
+            new_service,
+            interval: Self::DEFAULT_INTERVAL,
+            state,
+            addrs: HashSet::new(),
+            changes: VecDeque::new(),
+        }
+    }
+
+    /// Sets the interval at which the name is re-resolved.
+    ///
+    /// The default interval is [`DnsDiscover::DEFAULT_INTERVAL`].
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Returns the set of currently discovered addresses.
+    pub fn addrs(&self) -> &HashSet<SocketAddr> {
+        &self.addrs
+    }
+
+    fn lookup(resolver: &TokioAsyncResolver, name: &str) -> Lookup {
+        let resolver = resolver.clone();
+        let name = name.to_owned();
+        Box::pin(async move { resolver.lookup_ip(name.as_str()).await })
+    }
+
+    /// Enqueues the changes needed to update the set of discovered addresses
+    /// to `addrs`.
+    fn update(&mut self, addrs: HashSet<SocketAddr>) {
+        for addr in self.addrs.difference(&addrs) {
+            trace!(%addr, "removing address");
+            self.changes.push_back(Change::Remove(*addr));
+        }
+        for addr in addrs.difference(&self.addrs) {
+            trace!(%addr, "adding address");
+            let svc = (self.new_service)(*addr);
+            self.changes.push_back(Change::Insert(*addr, svc));
+        }
+        self.addrs = addrs;
+    }
+}
+
+impl<F, S> Stream for DnsDiscover<F, S>
+where
+    F: FnMut(SocketAddr) -> S + Unpin,
+    S: Unpin,
+{
+    type Item = Result<Change<SocketAddr, S>, Never>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(change) = self.changes.pop_front() {
+                return Poll::Ready(Some(Ok(change)));
+            }
+
+            let next = match self.state {
+                State::Waiting(ref mut delay) => {
+                    if delay.as_mut().poll(cx).is_pending() {
+                        return Poll::Pending;
+                    }
+                    trace!(name = %self.name, "resolving");
+                    State::Resolving(Self::lookup(&self.resolver, &self.name))
+                }
+                State::Resolving(ref mut lookup) => {
+                    let result = match lookup.as_mut().poll(cx) {
+                        Poll::Ready(result) => result,
+                        Poll::Pending => return Poll::Pending,
+                    };
+                    match result {
+                        Ok(lookup) => {
+                            let port = self.port;
+                            let addrs = lookup.iter().map(|ip| SocketAddr::new(ip, port)).collect();
+                            self.update(addrs);
+                        }
+                        Err(error) => {
+                            debug!(name = %self.name, %error, "resolution failed");
+                        }
+                    }
+                    State::Waiting(Box::pin(sleep(self.interval)))
+                }
+            };
+            self.state = next;
+        }
+    }
+}
+
+impl<F, S> fmt::Debug for DnsDiscover<F, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DnsDiscover")
+            .field("name", &self.name)
+            .field("port", &self.port)
+            .field("interval", &self.interval)
+            .field("addrs", &self.addrs)
+            .finish()
+    }
+}
@@ -0,0 +1,109 @@
+use super::{Change, Discover};
+use futures_core::{ready, Stream};
+use pin_project::pin_project;
+use std::{
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Wraps each service discovered by an inner [`Discover`] with a function.
+///
+/// The function is called with the key and the service of each
+/// [`Change::Insert`], and its result is yielded in place of the service. This
+/// allows per-endpoint middleware, such as authentication or logging tagged
+/// with the endpoint's key, to be applied to every discovered service. For example, a [`Layer`] may be applied to each
+/// service with `DiscoverMap::new(discover, move |_, svc| layer.layer(svc))`,
+/// or each service may be tagged with its key:
+///
+/// ```rust
+/// use futures_util::stream;
+/// use tower::discover::{Change, DiscoverMap, StreamDiscover};
+///
+/// struct Tagged<S> {
+///     key: String,
+///     inner: S,
+/// }
+///
+/// # struct Endpoint;
+/// let changes = stream::iter(vec![Change::Insert("a".to_string(), Endpoint)]);
+/// let discover = DiscoverMap::new(StreamDiscover::new(changes), |key: &String, inner| {
+///     Tagged {
+///         key: key.clone(),
+///         inner,
+///     }
+/// });
+/// # drop(discover);
+/// ```
+///
+/// [`Layer`]: crate::Layer
+#[pin_project]
+pub struct DiscoverMap<D, F> {
+    #[pin]
+    discover: D,
+    f: F,
+}
+
+impl<D, F> DiscoverMap<D, F> {
+    /// Creates a [`DiscoverMap`] that wraps each service discovered by
+    /// `discover` with `f`.
+    pub fn new<S>(discover: D, f: F) -> Self
+    where
+        D: Discover,
+        F: FnMut(&D::Key, D::Service) -> S,
+    {
+        DiscoverMap { discover, f }
+    }
+
+    /// Get a reference to the inner discovery.
+    pub fn get_ref(&self) -> &D {
+        &self.discover
+    }
+
+    /// Get a mutable reference to the inner discovery.
+    pub fn get_mut(&mut self) -> &mut D {
+        &mut self.discover
+    }
+
+    /// Consume `self`, returning the inner discovery.
+    pub fn into_inner(self) -> D {
+        self.discover
+    }
+}
+
+impl<D, F, S> Stream for DiscoverMap<D, F>
+where
+    D: Discover,
+    F: FnMut(&D::Key, D::Service) -> S,
+{
+    type Item = Result<Change<D::Key, S>, D::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let change = match ready!(this.discover.poll_discover(cx)).transpose()? {
+            None => return Poll::Ready(None),
+            Some(change) => change,
+        };
+        let f = this.f;
+        let change = match change {
+            Change::Remove(key) => Change::Remove(key),
+            Change::Insert(key, svc) => {
+                let svc = f(&key, svc);
+                Change::Insert(key, svc)
+            }
+        };
+
+        Poll::Ready(Some(Ok(change)))
+    }
+}
+
+impl<D, F> fmt::Debug for DiscoverMap<D, F>
+where
+    D: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DiscoverMap")
+            .field("discover", &self.discover)
+            .finish()
+    }
+}
@@ -7,7 +7,20 @@
 //!
 //! Every discovered service is assigned an identifier that is distinct among the currently active
 //! services. If that service later goes away, a [`Change::Remove`] is yielded with that service's
-//! identifier. From that point forward, the identifier may be re-used.
+//! identifier. From that point forward, the identifier may be re-used. A [`Change::Insert`] with
+//! the identifier of an active service replaces that service, for instance when its backend is
+//! restarted.
+//!
+//! A [`ServiceList`] yields a fixed set of services. With the `discover-channel` feature enabled,
+//! the services in a `ChannelList` are inserted and removed at runtime through a `ListHandle`.
+//! A [`StreamDiscover`] may be used to
+//! drive discovery from any infallible [`Stream`] of [`Change`]s. With the `dns` feature enabled,
+//! `DnsDiscover` discovers services by periodically resolving a hostname. With the `resolve`
+//! feature enabled, `ResolveDiscover` does the same with any resolver service, making a service
+//! for each resolved address with a [`MakeService`]. A [`Subset`] limits a client to a bounded,
+//! stable subset of the services discovered by another [`Discover`], and [`Dedup`] suppresses
+//! redundant changes from a noisy one. A [`DiscoverMap`] wraps each discovered service, for
+//! instance with per-endpoint middleware.
 //!
 //! # Examples
 //!
@@ -31,12 +44,34 @@
 //! }
 //! ```
 //!
+//! [`MakeService`]: crate::MakeService
 //! [`TryStream`]: https://docs.rs/futures/latest/futures/stream/trait.TryStream.html
+//! [`Stream`]: https://docs.rs/futures/latest/futures/stream/trait.Stream.html
 
+#[cfg(feature = "discover-channel")]
+mod channel;
+mod dedup;
+#[cfg(feature = "dns")]
+mod dns;
 mod error;
 mod list;
+mod map;
+#[cfg(feature = "resolve")]
+mod resolve;
+mod stream;
+mod subset;
 
+#[cfg(feature = "discover-channel")]
+pub use self::channel::{ChannelList, ListHandle};
+pub use self::dedup::Dedup;
+#[cfg(feature = "dns")]
+pub use self::dns::DnsDiscover;
 pub use self::list::ServiceList;
+pub use self::map::DiscoverMap;
+#[cfg(feature = "resolve")]
+pub use self::resolve::ResolveDiscover;
+pub use self::stream::StreamDiscover;
+pub use self::subset::Subset;
 
 use crate::sealed::Sealed;
 use futures_core::TryStream;
@@ -0,0 +1,268 @@
+use super::Change;
+use futures_core::{ready, Stream};
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt,
+    future::Future,
+    hash::Hash,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::time::{sleep, Sleep};
+use tower_service::Service;
+use tracing::{debug, trace};
+
+/// Service discovery by periodically resolving a target to a set of
+/// addresses and making a service for each address.
+///
+/// This combines the two halves of the usual client bootstrap flow:
+///
+/// - A *resolver*, a [`Service`] that resolves a target (such as a name) to
+///   the set of addresses currently serving it.
+/// - A *maker*, a [`Service`] that makes a service for an address, such as a
+///   [`MakeService`] that connects to it.
+///
+/// Each resolved address is passed to the maker, and the resulting services
+/// are yielded as [`Change::Insert`]s keyed by their address. Addresses that
+/// are no longer returned by a later resolution are yielded as
+/// [`Change::Remove`]s, and services still being made for them are dropped.
+///
+/// The target is re-resolved every [`interval`]. If a resolution fails, the
+/// previously resolved set of addresses is retained until the next resolution
+/// succeeds. If making the service for an address fails, the failure is
+/// logged and the address is tried again after the next resolution. Only
+/// failures of the maker to become ready are yielded as errors.
+///
+/// [`MakeService`]: crate::MakeService
+/// [`interval`]: ResolveDiscover::with_interval
+#[cfg_attr(docsrs, doc(cfg(feature = "resolve")))]
+pub struct ResolveDiscover<R, M, T, A>
+where
+    R: Service<T>,
+    M: Service<A>,
+{
+    resolver: R,
+    target: T,
+    make: M,
+    interval: Duration,
+    state: State<R::Future>,
+    /// The addresses from the latest successful resolution.
+    resolved: HashSet<A>,
+    /// The addresses for which services have been inserted.
+    inserted: HashSet<A>,
+    /// Resolved addresses waiting for the maker to become ready.
+    unmade: VecDeque<A>,
+    making: Vec<(A, Pin<Box<M::Future>>)>,
+    changes: VecDeque<Change<A, M::Response>>,
+}
+
+enum State<F> {
+    /// Waiting to re-resolve the target.
+    Waiting(Pin<Box<Sleep>>),
+    /// Waiting for the resolver to become ready.
+    Ready,
+    /// Resolving the target.
+    Resolving(Pin<Box<F>>),
+}
+
+impl<R, M, T, A> ResolveDiscover<R, M, T, A>
+where
+    R: Service<T>,
+    R::Response: IntoIterator<Item = A>,
+    R::Error: Into<crate::BoxError>,
+    M: Service<A>,
+    M::Error: Into<crate::BoxError>,
+    T: Clone,
+    A: Eq + Hash + Clone + fmt::Debug,
+{
+    /// The default interval at which the target is re-resolved.
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(30);
+
+    /// Creates a [`ResolveDiscover`] that resolves `target` with `resolver`
+    /// and makes a service for each resolved address with `make`.
+    ///
+    /// The first resolution begins when the discovery stream is first polled.
+    pub fn new(resolver: R, target: T, make: M) -> Self {
+        Self {
+            resolver,
+            target,
+            make,
+            interval: Self::DEFAULT_INTERVAL,
+            state: State::Ready,
+            resolved: HashSet::new(),
+            inserted: HashSet::new(),
+            unmade: VecDeque::new(),
+            making: Vec::new(),
+            changes: VecDeque::new(),
+        }
+    }
+
+    /// Sets the interval at which the target is re-resolved.
+    ///
+    /// The default interval is [`ResolveDiscover::DEFAULT_INTERVAL`].
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Returns the set of addresses from the latest successful resolution.
+    pub fn addrs(&self) -> &HashSet<A> {
+        &self.resolved
+    }
+
+    /// Enqueues the changes needed to update the set of discovered addresses
+    /// to `addrs`, and schedules services to be made for new addresses.
+    fn update(&mut self, addrs: HashSet<A>) {
+        let removed = self
+            .inserted
+            .difference(&addrs)
+            .cloned()
+            .collect::<Vec<_>>();
+        for addr in removed {
+            trace!(?addr, "removing address");
+            self.inserted.remove(&addr);
+            self.changes.push_back(Change::Remove(addr));
+        }
+        self.making.retain(|(addr, _)| addrs.contains(addr));
+
+        let inserted = &self.inserted;
+        let making = &self.making;
+        self.unmade = addrs
+            .iter()
+            .filter(|addr| !inserted.contains(*addr) && !making.iter().any(|(a, _)| a == *addr))
+            .cloned()
+            .collect();
+        self.resolved = addrs;
+    }
+
+    /// Starts making services for new addresses, and collects the services
+    /// that have been made.
+    fn poll_make(&mut self, cx: &mut Context<'_>) -> Result<(), crate::BoxError> {
+        while !self.unmade.is_empty() {
+            match self.make.poll_ready(cx) {
+                Poll::Pending => break,
+                Poll::Ready(Err(error)) => return Err(error.into()),
+                Poll::Ready(Ok(())) => {
+                    let addr = self.unmade.pop_front().expect("unmade must not be empty");
+                    trace!(?addr, "making service");
+                    let fut = self.make.call(addr.clone());
+                    self.making.push((addr, Box::pin(fut)));
+                }
+            }
+        }
+
+        let mut i = 0;
+        while i < self.making.len() {
+            let made = match self.making[i].1.as_mut().poll(cx) {
+                Poll::Pending => {
+                    i += 1;
+                    continue;
+                }
+                Poll::Ready(made) => made,
+            };
+            let (addr, _) = self.making.swap_remove(i);
+            match made {
+                Ok(svc) => {
+                    trace!(?addr, "adding address");
+                    self.inserted.insert(addr.clone());
+                    self.changes.push_back(Change::Insert(addr, svc));
+                }
+                Err(error) => {
+                    let error = error.into();
+                    debug!(?addr, %error, "failed to make service");
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Safety: This is safe because we do not use `Pin::new_unchecked`.
+impl<R, M, T, A> Unpin for ResolveDiscover<R, M, T, A>
+where
+    R: Service<T>,
+    M: Service<A>,
+{
+}
+
+impl<R, M, T, A> Stream for ResolveDiscover<R, M, T, A>
+where
+    R: Service<T>,
+    R::Response: IntoIterator<Item = A>,
+    R::Error: Into<crate::BoxError>,
+    M: Service<A>,
+    M::Error: Into<crate::BoxError>,
+    T: Clone,
+    A: Eq + Hash + Clone + fmt::Debug,
+{
+    type Item = Result<Change<A, M::Response>, crate::BoxError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(change) = self.changes.pop_front() {
+                return Poll::Ready(Some(Ok(change)));
+            }
+
+            if let Err(error) = self.poll_make(cx) {
+                return Poll::Ready(Some(Err(error)));
+            }
+            if !self.changes.is_empty() {
+                continue;
+            }
+
+            let this = &mut *self;
+            let next = match this.state {
+                State::Waiting(ref mut delay) => {
+                    if delay.as_mut().poll(cx).is_pending() {
+                        return Poll::Pending;
+                    }
+                    State::Ready
+                }
+                State::Ready => match this.resolver.poll_ready(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(Ok(())) => {
+                        trace!("resolving");
+                        State::Resolving(Box::pin(this.resolver.call(this.target.clone())))
+                    }
+                    Poll::Ready(Err(error)) => {
+                        let error = error.into();
+                        debug!(%error, "resolver failed");
+                        State::Waiting(Box::pin(sleep(this.interval)))
+                    }
+                },
+                State::Resolving(ref mut resolving) => {
+                    match ready!(resolving.as_mut().poll(cx)) {
+                        Ok(addrs) => this.update(addrs.into_iter().collect()),
+                        Err(error) => {
+                            let error = error.into();
+                            debug!(%error, "resolution failed");
+                        }
+                    }
+                    State::Waiting(Box::pin(sleep(this.interval)))
+                }
+            };
+            this.state = next;
+        }
+    }
+}
+
+impl<R, M, T, A> fmt::Debug for ResolveDiscover<R, M, T, A>
+where
+    R: Service<T> + fmt::Debug,
+    M: Service<A> + fmt::Debug,
+    T: fmt::Debug,
+    A: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResolveDiscover")
+            .field("resolver", &self.resolver)
+            .field("target", &self.target)
+            .field("make", &self.make)
+            .field("interval", &self.interval)
+            .field("resolved", &self.resolved)
+            .field("inserted", &self.inserted)
+            .finish()
+    }
+}
@@ -0,0 +1,67 @@
+use super::{error::Never, Change};
+use futures_core::Stream;
+use pin_project::pin_project;
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Service discovery driven by a [`Stream`] of [`Change`]s.
+///
+/// [`Discover`] is implemented for any [`TryStream`] of [`Change`]s, but
+/// many sources of changes (such as channels) cannot fail. [`StreamDiscover`]
+/// adapts an infallible [`Stream`] of [`Change`]s so that it may be used
+/// wherever a [`Discover`] is expected.
+///
+/// [`Discover`]: super::Discover
+/// [`TryStream`]: https://docs.rs/futures/latest/futures/stream/trait.TryStream.html
+#[pin_project]
+#[derive(Clone, Debug)]
+pub struct StreamDiscover<S> {
+    #[pin]
+    inner: S,
+}
+
+impl<S> StreamDiscover<S> {
+    /// Creates a [`StreamDiscover`] that yields each change produced by
+    /// `stream`.
+    pub fn new<K, V>(stream: S) -> Self
+    where
+        S: Stream<Item = Change<K, V>>,
+    {
+        StreamDiscover { inner: stream }
+    }
+
+    /// Get a reference to the inner stream.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner stream.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Consume `self`, returning the inner stream.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S, K, V> Stream for StreamDiscover<S>
+where
+    S: Stream<Item = Change<K, V>>,
+{
+    type Item = Result<Change<K, V>, Never>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.project()
+            .inner
+            .poll_next(cx)
+            .map(|change| change.map(Ok))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
@@ -0,0 +1,168 @@
+use super::{Change, Discover};
+use futures_core::Stream;
+use pin_project::pin_project;
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet, VecDeque},
+    fmt,
+    hash::{Hash, Hasher},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Forwards a bounded, stable subset of the services discovered by an inner
+/// [`Discover`].
+///
+/// When discovery returns a large number of endpoints, holding an active
+/// service (and its connection) for every endpoint in each client is
+/// wasteful. [`Subset`] forwards at most `size` of the discovered services,
+/// so that a balancer built on it only drives that many endpoints.
+///
+/// The subset is chosen deterministically from a client ID: each endpoint is
+/// ranked by a hash of its key and the client ID, and the highest ranked
+/// endpoints are selected. Clients with different IDs therefore select
+/// different subsets, spreading their load across all of the endpoints.
+/// Once selected, an endpoint remains in the subset until discovery removes
+/// it; the vacancy is then filled by the highest ranked endpoint that is not
+/// yet selected. Vacancies are only filled once the inner discovery has no
+/// more changes ready, so that the initial subset is chosen from all of the
+/// endpoints discovered at once rather than from whichever came first. Endpoints discovered later do not displace selected ones,
+/// which keeps the subset stable as membership changes.
+///
+/// Services for endpoints that are not selected are held, without being
+/// polled, until they are needed to fill a vacancy. Discovery should
+/// therefore yield services that do not connect until they are first polled,
+/// such as [`Reconnect`].
+///
+/// [`Reconnect`]: crate::reconnect::Reconnect
+#[pin_project]
+pub struct Subset<D>
+where
+    D: Discover,
+{
+    #[pin]
+    discover: D,
+    size: usize,
+    client: u64,
+    selected: HashSet<D::Key>,
+    /// Endpoints that are not selected, with their ranks.
+    held: HashMap<D::Key, (u64, D::Service)>,
+    changes: VecDeque<Change<D::Key, D::Service>>,
+    /// Whether the inner discovery has ended.
+    done: bool,
+}
+
+impl<D> Subset<D>
+where
+    D: Discover,
+    D::Key: Hash + Clone,
+{
+    /// Creates a [`Subset`] that forwards at most `size` of the services
+    /// discovered by `discover`, ranking endpoints for the client identified
+    /// by `client`.
+    ///
+    /// Ranks are computed with the standard library's default hasher, so
+    /// clients built with the same version of Rust select the same subset for
+    /// the same client ID.
+    ///
+    /// # Panics
+    ///
+    /// If `size` is zero.
+    pub fn new(discover: D, size: usize, client: u64) -> Self {
+        assert!(size > 0, "subset size must be positive");
+        Self {
+            discover,
+            size,
+            client,
+            selected: HashSet::new(),
+            held: HashMap::new(),
+            changes: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    /// Returns the keys of the endpoints currently in the subset.
+    pub fn selected(&self) -> &HashSet<D::Key> {
+        &self.selected
+    }
+}
+
+fn rank<K: Hash>(client: u64, key: &K) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    client.hash(&mut hasher);
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl<D> Stream for Subset<D>
+where
+    D: Discover,
+    D::Key: Hash + Clone,
+{
+    type Item = Result<Change<D::Key, D::Service>, D::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        loop {
+            if let Some(change) = this.changes.pop_front() {
+                return Poll::Ready(Some(Ok(change)));
+            }
+            if *this.done {
+                return Poll::Ready(None);
+            }
+
+            let change = match this.discover.as_mut().poll_discover(cx) {
+                Poll::Ready(Some(Err(error))) => return Poll::Ready(Some(Err(error))),
+                Poll::Ready(Some(Ok(change))) => change,
+                // Fill vacancies once all ready changes have been processed.
+                poll => {
+                    *this.done = poll.is_ready();
+                    while this.selected.len() < *this.size {
+                        let key = match this.held.iter().max_by_key(|(_, (rank, _))| *rank) {
+                            Some((key, _)) => key.clone(),
+                            None => break,
+                        };
+                        let (_, svc) = this.held.remove(&key).expect("held endpoint must exist");
+                        this.selected.insert(key.clone());
+                        this.changes.push_back(Change::Insert(key, svc));
+                    }
+                    if this.changes.is_empty() && !*this.done {
+                        return Poll::Pending;
+                    }
+                    continue;
+                }
+            };
+            match change {
+                // Changes to selected endpoints are forwarded as-is.
+                Change::Insert(ref key, _) if this.selected.contains(key) => {
+                    return Poll::Ready(Some(Ok(change)));
+                }
+                Change::Insert(key, svc) => {
+                    let rank = rank(*this.client, &key);
+                    this.held.insert(key, (rank, svc));
+                }
+                Change::Remove(key) => {
+                    if this.selected.remove(&key) {
+                        return Poll::Ready(Some(Ok(Change::Remove(key))));
+                    }
+                    this.held.remove(&key);
+                }
+            }
+        }
+    }
+}
+
+impl<D> fmt::Debug for Subset<D>
+where
+    D: Discover + fmt::Debug,
+    D::Key: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subset")
+            .field("discover", &self.discover)
+            .field("size", &self.size)
+            .field("client", &self.client)
+            .field("selected", &self.selected)
+            .field("held", &self.held.len())
+            .finish()
+    }
+}
@@ -0,0 +1,33 @@
+//! Error types
+
+use std::fmt;
+
+/// An error returned by [`Drainable`] when it is polled for readiness after
+/// its [`Drain`] has been signaled, if it is configured to reject requests
+/// while draining.
+///
+/// [`Drainable`]: crate::drain::Drainable
+/// [`Drain`]: crate::drain::Drain
+pub struct Draining {
+    _p: (),
+}
+
+impl Draining {
+    pub(crate) fn new() -> Self {
+        Draining { _p: () }
+    }
+}
+
+impl fmt::Debug for Draining {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Draining")
+    }
+}
+
+impl fmt::Display for Draining {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("service draining")
+    }
+}
+
+impl std::error::Error for Draining {}
@@ -0,0 +1,47 @@
+//! Future types
+
+use super::signal::Outstanding;
+use futures_core::ready;
+use pin_project::pin_project;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Future for the [`Drainable`] service.
+///
+/// [`Drainable`]: crate::drain::Drainable
+#[pin_project]
+#[derive(Debug)]
+pub struct ResponseFuture<F> {
+    #[pin]
+    inner: F,
+    // Dropped when the response completes, rather than when the future is
+    // dropped, in case it is held after completing.
+    outstanding: Option<Outstanding>,
+}
+
+impl<F> ResponseFuture<F> {
+    pub(crate) fn new(inner: F, outstanding: Outstanding) -> Self {
+        ResponseFuture {
+            inner,
+            outstanding: Some(outstanding),
+        }
+    }
+}
+
+impl<F, T, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<T, E>>,
+    E: Into<crate::BoxError>,
+{
+    type Output = Result<T, crate::BoxError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let res = ready!(this.inner.poll(cx));
+        this.outstanding.take();
+        Poll::Ready(res.map_err(Into::into))
+    }
+}
@@ -0,0 +1,41 @@
+use super::{Drainable, Watch};
+use tower_layer::Layer;
+
+/// A [`Layer`] to wrap services in [`Drainable`] middleware.
+///
+/// [`Layer`]: crate::Layer
+#[derive(Clone, Debug)]
+pub struct DrainLayer {
+    watch: Watch,
+    reject: bool,
+}
+
+impl DrainLayer {
+    /// Creates a new layer whose services watch `watch`.
+    pub fn new(watch: Watch) -> Self {
+        DrainLayer {
+            watch,
+            reject: false,
+        }
+    }
+
+    /// Configures services to fail readiness while draining. See
+    /// [`Drainable::reject_when_draining`].
+    pub fn reject_when_draining(mut self) -> Self {
+        self.reject = true;
+        self
+    }
+}
+
+impl<S> Layer<S> for DrainLayer {
+    type Service = Drainable<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        let svc = Drainable::new(service, self.watch.clone());
+        if self.reject {
+            svc.reject_when_draining()
+        } else {
+            svc
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Middleware for draining a stack of services before shutting down.
+//!
+//! A [`Drain`] is a handle used to tell a stack of services to stop accepting
+//! new requests and to finish the requests it has already accepted. Each
+//! [`Drainable`] service in the stack holds a [`Watch`] obtained from the
+//! [`Drain`]. Once [`Drain::signal`] is called, [`Drainable`] services no
+//! longer become ready, and the returned [`Drained`] future completes once
+//! every response future they have returned has completed or been dropped.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # use tower::{service_fn, BoxError, Service, ServiceExt};
+//! use tower::drain::{Drain, Drainable};
+//!
+//! # async fn doc() -> Result<(), BoxError> {
+//! let drain = Drain::new();
+//! let svc = service_fn(|req: &'static str| async move { Ok::<_, BoxError>(req.len()) });
+//! let mut svc = Drainable::new(svc, drain.watch()).reject_when_draining();
+//!
+//! let response = svc.ready().await?.call("hello");
+//!
+//! // The drain completes once the response above has completed.
+//! let drained = drain.signal();
+//! assert!(svc.ready().await.is_err());
+//! assert_eq!(response.await?, 5);
+//! drained.await;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod error;
+pub mod future;
+mod layer;
+mod signal;
+
+pub use self::layer::DrainLayer;
+pub use self::signal::{Drain, Drained, Watch};
+
+use self::{error::Draining, future::ResponseFuture};
+use std::task::{Context, Poll};
+use tower_service::Service;
+
+/// A [`Service`] that stops accepting requests once its [`Drain`] is
+/// signaled.
+///
+/// Once draining, [`poll_ready`] returns [`Pending`] indefinitely or, if
+/// configured with [`reject_when_draining`], fails with a [`Draining`] error.
+/// Responses to requests that were already accepted are counted toward the
+/// [`Drain`]'s outstanding responses.
+///
+/// [`poll_ready`]: Service::poll_ready
+/// [`Pending`]: std::task::Poll::Pending
+/// [`reject_when_draining`]: Drainable::reject_when_draining
+#[derive(Clone, Debug)]
+pub struct Drainable<S> {
+    inner: S,
+    watch: Watch,
+    reject: bool,
+}
+
+// ===== impl Drainable =====
+
+impl<S> Drainable<S> {
+    /// Wraps `inner` so that it stops accepting requests once the [`Drain`]
+    /// watched by `watch` is signaled.
+    pub fn new(inner: S, watch: Watch) -> Self {
+        Drainable {
+            inner,
+            watch,
+            reject: false,
+        }
+    }
+
+    /// Fails readiness with a [`Draining`] error while draining, rather than
+    /// never becoming ready.
+    ///
+    /// Callers that wait for readiness, such as a [`Buffer`]'s worker, then
+    /// fail their pending requests instead of holding them until they are
+    /// dropped.
+    ///
+    /// [`Buffer`]: crate::buffer::Buffer
+    pub fn reject_when_draining(mut self) -> Self {
+        self.reject = true;
+        self
+    }
+
+    /// Get a reference to the inner service
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner service
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Consume `self`, returning the inner service
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S, Request> Service<Request> for Drainable<S>
+where
+    S: Service<Request>,
+    S::Error: Into<crate::BoxError>,
+{
+    type Response = S::Response;
+    type Error = crate::BoxError;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        if self.watch.is_draining() {
+            if self.reject {
+                return Poll::Ready(Err(Draining::new().into()));
+            }
+            // New requests are never accepted once draining.
+            return Poll::Pending;
+        }
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        // Requests accepted before the drain was signaled are tracked even if
+        // they are only dispatched afterwards.
+        let outstanding = self.watch.outstanding();
+        ResponseFuture::new(self.inner.call(request), outstanding)
+    }
+}
@@ -0,0 +1,155 @@
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+use tokio::sync::Notify;
+
+/// Signals a stack of [`Drainable`] services to drain.
+///
+/// See the [module documentation](crate::drain) for details.
+///
+/// [`Drainable`]: super::Drainable
+#[derive(Debug)]
+pub struct Drain {
+    shared: Arc<Shared>,
+}
+
+/// Observes whether a [`Drain`] has been signaled.
+///
+/// A [`Watch`] is obtained with [`Drain::watch`] and may be cloned freely.
+#[derive(Clone, Debug)]
+pub struct Watch {
+    shared: Arc<Shared>,
+}
+
+/// A future that completes once a signaled [`Drain`] has no outstanding
+/// responses.
+///
+/// Returned by [`Drain::signal`].
+pub struct Drained {
+    inner: Pin<Box<dyn Future<Output = ()> + Send>>,
+}
+
+/// Counts a response toward a [`Drain`]'s outstanding responses until it is
+/// dropped.
+#[derive(Debug)]
+pub(crate) struct Outstanding {
+    shared: Arc<Shared>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    draining: AtomicBool,
+    outstanding: AtomicUsize,
+    quiescent: Notify,
+}
+
+// ===== impl Drain =====
+
+impl Drain {
+    /// Creates a new [`Drain`] that has not been signaled.
+    pub fn new() -> Self {
+        Drain {
+            shared: Arc::new(Shared {
+                draining: AtomicBool::new(false),
+                outstanding: AtomicUsize::new(0),
+                quiescent: Notify::new(),
+            }),
+        }
+    }
+
+    /// Returns a [`Watch`] for this drain, to be held by [`Drainable`]
+    /// services.
+    ///
+    /// [`Drainable`]: super::Drainable
+    pub fn watch(&self) -> Watch {
+        Watch {
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Returns the number of responses that have not yet completed.
+    pub fn outstanding(&self) -> usize {
+        self.shared.outstanding.load(Ordering::Acquire)
+    }
+
+    /// Signals all [`Drainable`] services watching this drain to stop
+    /// accepting requests.
+    ///
+    /// The returned future completes once all of their outstanding responses
+    /// have completed or been dropped.
+    ///
+    /// [`Drainable`]: super::Drainable
+    pub fn signal(self) -> Drained {
+        let shared = self.shared;
+        shared.draining.store(true, Ordering::Release);
+        Drained {
+            inner: Box::pin(async move {
+                loop {
+                    // The notification is registered before the count is
+                    // checked, so that the last response completing in between
+                    // is not missed.
+                    let notified = shared.quiescent.notified();
+                    if shared.outstanding.load(Ordering::Acquire) == 0 {
+                        return;
+                    }
+                    notified.await;
+                }
+            }),
+        }
+    }
+}
+
+impl Default for Drain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ===== impl Watch =====
+
+impl Watch {
+    /// Returns `true` if the [`Drain`] has been signaled.
+    pub fn is_draining(&self) -> bool {
+        self.shared.draining.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn outstanding(&self) -> Outstanding {
+        self.shared.outstanding.fetch_add(1, Ordering::AcqRel);
+        Outstanding {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+// ===== impl Drained =====
+
+impl Future for Drained {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        self.inner.as_mut().poll(cx)
+    }
+}
+
+impl fmt::Debug for Drained {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Drained").finish()
+    }
+}
+
+// ===== impl Outstanding =====
+
+impl Drop for Outstanding {
+    fn drop(&mut self) {
+        if self.shared.outstanding.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.shared.quiescent.notify_waiters();
+        }
+    }
+}
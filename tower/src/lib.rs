@@ -157,15 +157,21 @@
 
 #[macro_use]
 pub(crate) mod macros;
-#[cfg(feature = "balance")]
-#[cfg_attr(docsrs, doc(cfg(feature = "balance")))]
+#[cfg(feature = "balance-no-rand")]
+#[cfg_attr(docsrs, doc(cfg(feature = "balance-no-rand")))]
 pub mod balance;
 #[cfg(feature = "buffer")]
 #[cfg_attr(docsrs, doc(cfg(feature = "buffer")))]
 pub mod buffer;
+#[cfg(feature = "classify")]
+#[cfg_attr(docsrs, doc(cfg(feature = "classify")))]
+pub mod classify;
 #[cfg(feature = "discover")]
 #[cfg_attr(docsrs, doc(cfg(feature = "discover")))]
 pub mod discover;
+#[cfg(feature = "drain")]
+#[cfg_attr(docsrs, doc(cfg(feature = "drain")))]
+pub mod drain;
 #[cfg(feature = "filter")]
 #[cfg_attr(docsrs, doc(cfg(feature = "filter")))]
 pub mod filter;
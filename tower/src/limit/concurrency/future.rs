@@ -1,40 +1,100 @@
 //! [`Future`] types
 //!
 //! [`Future`]: std::future::Future
-use futures_core::ready;
+use super::release::{Permit, ReleaseOnResponse, TrackRelease};
+use crate::limit::observe::Observed;
 use pin_project::pin_project;
 use std::{
     future::Future,
     pin::Pin,
     task::{Context, Poll},
+    time::Duration,
 };
 use tokio::sync::OwnedSemaphorePermit;
+use tokio::time::{Instant, Sleep};
 
 /// Future for the [`ConcurrencyLimit`] service.
 ///
 /// [`ConcurrencyLimit`]: crate::limit::ConcurrencyLimit
 #[pin_project]
 #[derive(Debug)]
-pub struct ResponseFuture<T> {
+pub struct ResponseFuture<T, R = ReleaseOnResponse> {
     #[pin]
     inner: T,
-    // Keep this around so that it is dropped when the future completes
-    _permit: OwnedSemaphorePermit,
+    // Keep this around so that it is dropped when the future completes, unless
+    // it is forwarded to the response.
+    permit: Option<OwnedSemaphorePermit>,
+    release: R,
+    release_timeout: Option<Duration>,
+    hold: Option<Hold>,
 }
 
-impl<T> ResponseFuture<T> {
-    pub(crate) fn new(inner: T, _permit: OwnedSemaphorePermit) -> ResponseFuture<T> {
-        ResponseFuture { inner, _permit }
+/// Reclaims a request's permit if it is held for longer than the limiter's
+/// maximum hold duration.
+#[derive(Debug)]
+pub(crate) struct Hold {
+    since: Instant,
+    expiry: Pin<Box<Sleep>>,
+    observed: Observed,
+}
+
+impl<T, R> ResponseFuture<T, R> {
+    pub(crate) fn new(
+        inner: T,
+        permit: OwnedSemaphorePermit,
+        release: R,
+        release_timeout: Option<Duration>,
+        hold: Option<Hold>,
+    ) -> ResponseFuture<T, R> {
+        ResponseFuture {
+            inner,
+            permit: Some(permit),
+            release,
+            release_timeout,
+            hold,
+        }
+    }
+}
+
+impl Hold {
+    pub(crate) fn new(max: Duration, observed: Observed) -> Self {
+        Self {
+            since: Instant::now(),
+            expiry: Box::pin(tokio::time::sleep(max)),
+            observed,
+        }
     }
 }
 
-impl<F, T, E> Future for ResponseFuture<F>
+impl<F, R, T, E> Future for ResponseFuture<F, R>
 where
     F: Future<Output = Result<T, E>>,
+    R: TrackRelease<T>,
 {
-    type Output = Result<T, E>;
+    type Output = Result<R::Output, E>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        Poll::Ready(ready!(self.project().inner.poll(cx)))
+        let this = self.project();
+        let rsp = match this.inner.poll(cx) {
+            Poll::Ready(rsp) => rsp,
+            Poll::Pending => {
+                if let Some(mut hold) = this.hold.take() {
+                    if hold.expiry.as_mut().poll(cx).is_pending() {
+                        *this.hold = Some(hold);
+                    } else {
+                        tracing::debug!("permit held too long; reclaiming");
+                        hold.observed.reclaimed(hold.since.elapsed());
+                        this.permit.take();
+                    }
+                }
+                return Poll::Pending;
+            }
+        };
+        let rsp = rsp?;
+        let permit = match this.permit.take() {
+            Some(permit) => Permit::new(permit, *this.release_timeout),
+            None => Permit::reclaimed(),
+        };
+        Poll::Ready(Ok(this.release.track_release(permit, rsp)))
     }
 }
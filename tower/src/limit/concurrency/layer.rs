@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use super::ConcurrencyLimit;
+use super::{ConcurrencyLimit, MakeConcurrencyLimit};
 use tokio::sync::Semaphore;
 use tower_layer::Layer;
 
@@ -58,3 +58,29 @@ impl<S> Layer<S> for GlobalConcurrencyLimitLayer {
         ConcurrencyLimit::with_semaphore(service, self.semaphore.clone())
     }
 }
+
+/// Enforces a limit on the number of services that the underlying
+/// [`MakeService`] has made at once.
+///
+/// See [`MakeConcurrencyLimit`] for more details.
+///
+/// [`MakeService`]: crate::MakeService
+#[derive(Debug, Clone)]
+pub struct MakeConcurrencyLimitLayer {
+    max: usize,
+}
+
+impl MakeConcurrencyLimitLayer {
+    /// Create a new make concurrency limit layer.
+    pub fn new(max: usize) -> Self {
+        MakeConcurrencyLimitLayer { max }
+    }
+}
+
+impl<M> Layer<M> for MakeConcurrencyLimitLayer {
+    type Service = MakeConcurrencyLimit<M>;
+
+    fn layer(&self, make: M) -> Self::Service {
+        MakeConcurrencyLimit::new(make, self.max)
+    }
+}
@@ -0,0 +1,211 @@
+use crate::limit::observe::{Observe, Observed};
+use futures_core::ready;
+use pin_project::pin_project;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio_util::sync::PollSemaphore;
+use tower_service::Service;
+
+/// Enforces a limit on the number of services that an underlying
+/// [`MakeService`] has made (or is making) at once.
+///
+/// A permit is acquired in [`poll_ready`] before a service is made, and is
+/// held by the resulting [`Limited`] service until it is dropped. This may be
+/// used, for instance, to limit the number of connections a client maintains,
+/// rather than the number of requests it has in flight.
+///
+/// [`MakeService`]: crate::MakeService
+/// [`poll_ready`]: crate::Service::poll_ready
+#[derive(Debug)]
+pub struct MakeConcurrencyLimit<M> {
+    inner: M,
+    semaphore: PollSemaphore,
+    /// The currently acquired semaphore permit, if there is sufficient
+    /// capacity to make a new service.
+    permit: Option<OwnedSemaphorePermit>,
+    observed: Observed,
+}
+
+/// A service made by a [`MakeConcurrencyLimit`].
+///
+/// The [`MakeConcurrencyLimit`]'s permit is released when this service is
+/// dropped.
+#[derive(Debug)]
+pub struct Limited<S> {
+    inner: S,
+    _permit: OwnedSemaphorePermit,
+}
+
+/// Future for the [`MakeConcurrencyLimit`] service.
+#[pin_project]
+#[derive(Debug)]
+pub struct MakeFuture<F> {
+    #[pin]
+    inner: F,
+    permit: Option<OwnedSemaphorePermit>,
+}
+
+// ===== impl MakeConcurrencyLimit =====
+
+impl<M> MakeConcurrencyLimit<M> {
+    /// Create a new limiter that allows at most `max` services to exist at
+    /// once.
+    pub fn new(inner: M, max: usize) -> Self {
+        Self::with_semaphore(inner, Arc::new(Semaphore::new(max)))
+    }
+
+    /// Create a new limiter with a provided shared semaphore.
+    pub fn with_semaphore(inner: M, semaphore: Arc<Semaphore>) -> Self {
+        MakeConcurrencyLimit {
+            inner,
+            semaphore: PollSemaphore::new(semaphore),
+            permit: None,
+            observed: Observed::default(),
+        }
+    }
+
+    /// Reports how services are admitted by this limiter to `observer`.
+    ///
+    /// The observer is shared by clones of this service.
+    pub fn with_observer<O>(mut self, observer: O) -> Self
+    where
+        O: Observe + Send + Sync + 'static,
+    {
+        self.observed = Observed::new(observer);
+        self
+    }
+
+    /// Get a reference to the inner service
+    pub fn get_ref(&self) -> &M {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner service
+    pub fn get_mut(&mut self) -> &mut M {
+        &mut self.inner
+    }
+
+    /// Consume `self`, returning the inner service
+    pub fn into_inner(self) -> M {
+        self.inner
+    }
+}
+
+impl<M, Target> Service<Target> for MakeConcurrencyLimit<M>
+where
+    M: Service<Target>,
+{
+    type Response = Limited<M::Response>;
+    type Error = M::Error;
+    type Future = MakeFuture<M::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        if self.permit.is_none() {
+            self.permit = match self.semaphore.poll_acquire(cx) {
+                Poll::Ready(permit) => permit,
+                Poll::Pending => {
+                    self.observed.wait();
+                    return Poll::Pending;
+                }
+            };
+            self.observed.ready();
+            debug_assert!(
+                self.permit.is_some(),
+                "MakeConcurrencyLimit semaphore is never closed, so `poll_acquire` \
+                 should never fail",
+            );
+        }
+
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, target: Target) -> Self::Future {
+        let permit = self
+            .permit
+            .take()
+            .expect("max services made; poll_ready must be called first");
+        self.observed.acquired();
+
+        MakeFuture {
+            inner: self.inner.call(target),
+            permit: Some(permit),
+        }
+    }
+}
+
+impl<M: Clone> Clone for MakeConcurrencyLimit<M> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            semaphore: self.semaphore.clone(),
+            permit: None,
+            observed: self.observed.fresh(),
+        }
+    }
+}
+
+// ===== impl MakeFuture =====
+
+impl<F, S, E> Future for MakeFuture<F>
+where
+    F: Future<Output = Result<S, E>>,
+{
+    type Output = Result<Limited<S>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        // If the service could not be made, the permit is released when this
+        // future is dropped.
+        let inner = ready!(this.inner.poll(cx))?;
+        let _permit = this.permit.take().expect("polled after complete");
+        Poll::Ready(Ok(Limited { inner, _permit }))
+    }
+}
+
+// ===== impl Limited =====
+
+impl<S> Limited<S> {
+    /// Get a reference to the inner service
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner service
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+}
+
+impl<S, Request> Service<Request> for Limited<S>
+where
+    S: Service<Request>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        self.inner.call(request)
+    }
+}
+
+#[cfg(feature = "load")]
+#[cfg_attr(docsrs, doc(cfg(feature = "load")))]
+impl<S> crate::load::Load for Limited<S>
+where
+    S: crate::load::Load,
+{
+    type Metric = S::Metric;
+    fn load(&self) -> Self::Metric {
+        self.inner.load()
+    }
+}
@@ -2,9 +2,13 @@
 
 pub mod future;
 mod layer;
+mod make;
+mod release;
 mod service;
 
 pub use self::{
-    layer::{ConcurrencyLimitLayer, GlobalConcurrencyLimitLayer},
+    layer::{ConcurrencyLimitLayer, GlobalConcurrencyLimitLayer, MakeConcurrencyLimitLayer},
+    make::{Limited, MakeConcurrencyLimit, MakeFuture},
+    release::{Permit, ReleaseOnResponse, TrackRelease},
     service::ConcurrencyLimit,
 };
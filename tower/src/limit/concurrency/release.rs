@@ -0,0 +1,130 @@
+//! Application-specific permit release semantics.
+
+use pin_project::pin_project;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::sync::{oneshot, OwnedSemaphorePermit};
+use tokio::time::Sleep;
+
+/// Attaches a concurrency limit [`Permit`] to `V`-typed responses.
+///
+/// By default, a [`ConcurrencyLimit`] releases a request's permit as soon as
+/// its response future completes. When a response is a stream, such as an
+/// HTTP response with a streaming body, the inner service may still be doing
+/// work after the response is returned. Implementations of this trait may
+/// "forward" the permit into the response (for example, by wrapping the
+/// body), so that the permit is only released when the body completes or is
+/// dropped.
+///
+/// A base `impl<V> TrackRelease<V> for ReleaseOnResponse` is provided to
+/// release the permit once the response future is resolved.
+///
+/// [`ConcurrencyLimit`]: super::ConcurrencyLimit
+pub trait TrackRelease<V>: Clone {
+    /// The instrumented response type.
+    type Output;
+
+    /// Attaches a [`Permit`] to a `V`-typed response.
+    fn track_release(&self, permit: Permit, value: V) -> Self::Output;
+}
+
+/// A [`TrackRelease`] implementation that releases permits as soon as the
+/// response future is resolved.
+#[derive(Clone, Copy, Debug, Default)]
+#[non_exhaustive]
+pub struct ReleaseOnResponse;
+
+/// A concurrency limit permit that is released when dropped.
+///
+/// If the [`ConcurrencyLimit`] was configured with a release timeout, the
+/// permit is also released once the timeout elapses, even if the [`Permit`]
+/// is still held, so that abandoned responses cannot hold the limiter's
+/// capacity indefinitely.
+///
+/// [`ConcurrencyLimit`]: super::ConcurrencyLimit
+#[derive(Debug)]
+pub struct Permit {
+    _permit: Option<OwnedSemaphorePermit>,
+    // If a release timeout is configured, the permit is instead held by a
+    // background task, which releases it when this sender is dropped or when
+    // the timeout elapses.
+    _release: Option<oneshot::Sender<()>>,
+}
+
+/// Releases a permit when its [`Permit`] is dropped or its timeout elapses.
+#[pin_project]
+struct Expire {
+    #[pin]
+    sleep: Sleep,
+    dropped: oneshot::Receiver<()>,
+    _permit: OwnedSemaphorePermit,
+}
+
+// ===== impl ReleaseOnResponse =====
+
+impl<V> TrackRelease<V> for ReleaseOnResponse {
+    type Output = V;
+
+    fn track_release(&self, _: Permit, value: V) -> V {
+        value
+    }
+}
+
+// ===== impl Permit =====
+
+impl Permit {
+    pub(crate) fn new(permit: OwnedSemaphorePermit, timeout: Option<Duration>) -> Self {
+        let timeout = match timeout {
+            Some(timeout) => timeout,
+            None => {
+                return Self {
+                    _permit: Some(permit),
+                    _release: None,
+                }
+            }
+        };
+
+        let (tx, rx) = oneshot::channel();
+        tokio::spawn(Expire {
+            sleep: tokio::time::sleep(timeout),
+            dropped: rx,
+            _permit: permit,
+        });
+        Self {
+            _permit: None,
+            _release: Some(tx),
+        }
+    }
+
+    /// Returns a permit whose capacity has already been reclaimed by the
+    /// limiter.
+    pub(crate) fn reclaimed() -> Self {
+        Self {
+            _permit: None,
+            _release: None,
+        }
+    }
+}
+
+// ===== impl Expire =====
+
+impl Future for Expire {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.project();
+        if Pin::new(this.dropped).poll(cx).is_ready() {
+            tracing::trace!("permit released");
+            return Poll::Ready(());
+        }
+        if this.sleep.poll(cx).is_ready() {
+            tracing::debug!("permit release timeout elapsed");
+            return Poll::Ready(());
+        }
+        Poll::Pending
+    }
+}
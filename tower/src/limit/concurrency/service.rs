@@ -1,18 +1,30 @@
-use super::future::ResponseFuture;
+use super::future::{Hold, ResponseFuture};
+use super::release::{ReleaseOnResponse, TrackRelease};
+use crate::limit::observe::{Observe, Observed};
 use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use tokio_util::sync::PollSemaphore;
 use tower_service::Service;
 
-use futures_core::ready;
 use std::{
     sync::Arc,
     task::{Context, Poll},
+    time::Duration,
 };
 
 /// Enforces a limit on the concurrent number of requests the underlying
 /// service can handle.
+///
+/// By default, a request's permit is released when its response future
+/// completes. [`ConcurrencyLimit::with_release`] may be used to hold permits
+/// for longer, for instance until a streaming response body completes.
+///
+/// Clones of a [`ConcurrencyLimit`] share its limit. When capacity becomes
+/// available, clones waiting in [`poll_ready`] are woken in the order in which
+/// they began waiting, so that no caller is starved.
+///
+/// [`poll_ready`]: crate::Service::poll_ready
 #[derive(Debug)]
-pub struct ConcurrencyLimit<T> {
+pub struct ConcurrencyLimit<T, R = ReleaseOnResponse> {
     inner: T,
     semaphore: PollSemaphore,
     /// The currently acquired semaphore permit, if there is sufficient
@@ -21,12 +33,20 @@ pub struct ConcurrencyLimit<T> {
     /// The permit is acquired in `poll_ready`, and taken in `call` when sending
     /// a new request.
     permit: Option<OwnedSemaphorePermit>,
+    release: R,
+    release_timeout: Option<Duration>,
+    max_hold: Option<Duration>,
+    observed: Observed,
+    /// The size of the limit, if it is known.
+    max: Option<usize>,
 }
 
 impl<T> ConcurrencyLimit<T> {
     /// Create a new concurrency limiter.
     pub fn new(inner: T, max: usize) -> Self {
-        Self::with_semaphore(inner, Arc::new(Semaphore::new(max)))
+        let mut limit = Self::with_semaphore(inner, Arc::new(Semaphore::new(max)));
+        limit.max = Some(max);
+        limit
     }
 
     /// Create a new concurrency limiter with a provided shared semaphore
@@ -35,6 +55,108 @@ impl<T> ConcurrencyLimit<T> {
             inner,
             semaphore: PollSemaphore::new(semaphore),
             permit: None,
+            release: ReleaseOnResponse,
+            release_timeout: None,
+            max_hold: None,
+            observed: Observed::default(),
+            max: None,
+        }
+    }
+}
+
+impl<T, R> ConcurrencyLimit<T, R> {
+    /// Forwards each request's permit to its response using `release`, so
+    /// that the permit is held until the response has been consumed.
+    ///
+    /// A permit is always released once `timeout` has elapsed since its
+    /// response was received, even if the response has not been dropped, so
+    /// that abandoned responses do not hold the limit's capacity
+    /// indefinitely.
+    ///
+    /// Because such permits are released by a background task, responses
+    /// must be received from within a Tokio runtime.
+    pub fn with_release<R2>(self, release: R2, timeout: Duration) -> ConcurrencyLimit<T, R2> {
+        ConcurrencyLimit {
+            inner: self.inner,
+            semaphore: self.semaphore,
+            permit: self.permit,
+            release,
+            release_timeout: Some(timeout),
+            max_hold: self.max_hold,
+            observed: self.observed,
+            max: self.max,
+        }
+    }
+
+    /// Reclaims a request's permit if its response has not been received
+    /// within `max_hold` of the request being dispatched.
+    ///
+    /// This bounds the capacity that can be leaked by an inner service whose
+    /// responses never complete. The request itself is not canceled: its
+    /// response future continues to be polled, but it no longer counts toward
+    /// the limit. Each reclaimed permit is reported to the limiter's observer
+    /// (see [`Observe::reclaimed`]), so that leaks can be detected.
+    ///
+    /// This only applies until the response is received. A permit forwarded
+    /// into the response by [`ConcurrencyLimit::with_release`] is bounded by
+    /// its release timeout instead.
+    ///
+    /// [`Observe::reclaimed`]: crate::limit::Observe::reclaimed
+    pub fn with_max_hold(mut self, max_hold: Duration) -> Self {
+        self.max_hold = Some(max_hold);
+        self
+    }
+
+    /// Reports how requests are admitted by this limiter to `observer`.
+    ///
+    /// The observer is shared by clones of this service.
+    pub fn with_observer<O>(mut self, observer: O) -> Self
+    where
+        O: Observe + Send + Sync + 'static,
+    {
+        self.observed = Observed::new(observer);
+        self
+    }
+
+    /// Returns `true` if the concurrency limit would currently admit a
+    /// request.
+    ///
+    /// Unlike [`poll_ready`], this does not acquire a permit. If the
+    /// semaphore is shared with other services, they may acquire the
+    /// remaining permits before this service does. Similarly, permits that
+    /// are released while a call to [`poll_ready`] is waiting are assigned to
+    /// that waiter, so they are not reflected here. Note that this only
+    /// reflects the concurrency limit itself; the inner service may still not
+    /// be ready.
+    ///
+    /// [`poll_ready`]: crate::Service::poll_ready
+    pub fn check(&self) -> bool {
+        let admit = self.permit.is_some() || self.semaphore.clone_inner().available_permits() > 0;
+        if !admit {
+            self.observed.rejected();
+        }
+        admit
+    }
+
+    /// Returns the fraction of the concurrency limit that is in use, from
+    /// 0.0 to 1.0.
+    ///
+    /// Permits that have been acquired by [`poll_ready`] but not yet used
+    /// count as in use. If the semaphore is shared with other services, their
+    /// permits count as well.
+    ///
+    /// The size of a limit created with [`ConcurrencyLimit::with_semaphore`]
+    /// is not known, so its utilization is 0.0 while the limit would admit a
+    /// request and 1.0 otherwise.
+    ///
+    /// [`poll_ready`]: crate::Service::poll_ready
+    pub fn utilization(&self) -> f64 {
+        let available = self.semaphore.clone_inner().available_permits();
+        match self.max {
+            Some(0) => 1.0,
+            Some(max) => 1.0 - (available.min(max) as f64 / max as f64),
+            None if self.permit.is_some() || available > 0 => 0.0,
+            None => 1.0,
         }
     }
 
@@ -54,19 +176,27 @@ impl<T> ConcurrencyLimit<T> {
     }
 }
 
-impl<S, Request> Service<Request> for ConcurrencyLimit<S>
+impl<S, R, Request> Service<Request> for ConcurrencyLimit<S, R>
 where
     S: Service<Request>,
+    R: TrackRelease<S::Response>,
 {
-    type Response = S::Response;
+    type Response = R::Output;
     type Error = S::Error;
-    type Future = ResponseFuture<S::Future>;
+    type Future = ResponseFuture<S::Future, R>;
 
     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
         // If we haven't already acquired a permit from the semaphore, try to
         // acquire one first.
         if self.permit.is_none() {
-            self.permit = ready!(self.semaphore.poll_acquire(cx));
+            self.permit = match self.semaphore.poll_acquire(cx) {
+                Poll::Ready(permit) => permit,
+                Poll::Pending => {
+                    self.observed.wait();
+                    return Poll::Pending;
+                }
+            };
+            self.observed.ready();
             debug_assert!(
                 self.permit.is_some(),
                 "ConcurrencyLimit semaphore is never closed, so `poll_acquire` \
@@ -85,15 +215,25 @@ where
             .permit
             .take()
             .expect("max requests in-flight; poll_ready must be called first");
+        self.observed.acquired();
 
         // Call the inner service
         let future = self.inner.call(request);
 
-        ResponseFuture::new(future, permit)
+        let hold = self
+            .max_hold
+            .map(|max_hold| Hold::new(max_hold, self.observed.fresh()));
+        ResponseFuture::new(
+            future,
+            permit,
+            self.release.clone(),
+            self.release_timeout,
+            hold,
+        )
     }
 }
 
-impl<T: Clone> Clone for ConcurrencyLimit<T> {
+impl<T: Clone, R: Clone> Clone for ConcurrencyLimit<T, R> {
     fn clone(&self) -> Self {
         // Since we hold an `OwnedSemaphorePermit`, we can't derive `Clone`.
         // Instead, when cloning the service, create a new service with the
@@ -102,13 +242,18 @@ impl<T: Clone> Clone for ConcurrencyLimit<T> {
             inner: self.inner.clone(),
             semaphore: self.semaphore.clone(),
             permit: None,
+            release: self.release.clone(),
+            release_timeout: self.release_timeout,
+            max_hold: self.max_hold,
+            observed: self.observed.fresh(),
+            max: self.max,
         }
     }
 }
 
 #[cfg(feature = "load")]
 #[cfg_attr(docsrs, doc(cfg(feature = "load")))]
-impl<S> crate::load::Load for ConcurrencyLimit<S>
+impl<S, R> crate::load::Load for ConcurrencyLimit<S, R>
 where
     S: crate::load::Load,
 {
@@ -117,3 +262,9 @@ where
         self.inner.load()
     }
 }
+
+impl<S, R> crate::limit::Utilization for ConcurrencyLimit<S, R> {
+    fn utilization(&self) -> f64 {
+        ConcurrencyLimit::utilization(self)
+    }
+}
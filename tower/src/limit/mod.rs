@@ -1,9 +1,21 @@
 //! Tower middleware for limiting requests.
 
 pub mod concurrency;
+mod observe;
 pub mod rate;
+pub mod route;
+mod utilization;
 
 pub use self::{
-    concurrency::{ConcurrencyLimit, ConcurrencyLimitLayer, GlobalConcurrencyLimitLayer},
+    concurrency::{
+        ConcurrencyLimit, ConcurrencyLimitLayer, GlobalConcurrencyLimitLayer, MakeConcurrencyLimit,
+        MakeConcurrencyLimitLayer,
+    },
+    observe::Observe,
     rate::{RateLimit, RateLimitLayer},
+    route::{RouteLimit, RouteLimitLayer, RouteLimits},
+    utilization::Utilization,
 };
+
+#[cfg(feature = "load")]
+pub use self::utilization::UtilizationLoad;
@@ -0,0 +1,122 @@
+//! Instrumentation for limiters.
+
+use std::{fmt, sync::Arc, time::Duration};
+use tokio::time::Instant;
+
+/// Observes how requests are admitted by a limiter.
+///
+/// An observer may be attached to the limiters in this module (see
+/// [`ConcurrencyLimit::with_observer`], [`MakeConcurrencyLimit::with_observer`]
+/// and [`RateLimit::with_observer`]) so that saturation and queueing delays
+/// can be exported to a metrics system. All methods have empty default
+/// implementations, so implementations need only override the events they
+/// care about.
+///
+/// Observers are invoked inline by the limiter and should not block.
+///
+/// [`ConcurrencyLimit::with_observer`]: super::ConcurrencyLimit::with_observer
+/// [`MakeConcurrencyLimit::with_observer`]: super::MakeConcurrencyLimit::with_observer
+/// [`RateLimit::with_observer`]: super::RateLimit::with_observer
+pub trait Observe {
+    /// Called when a request is admitted by the limiter.
+    fn acquired(&self) {}
+
+    /// Called when a limiter begins waiting for capacity.
+    fn wait_started(&self) {}
+
+    /// Called when a limiter that was waiting for capacity obtains it, with
+    /// the time it spent waiting.
+    fn wait_ended(&self, waited: Duration) {
+        let _ = waited;
+    }
+
+    /// Called when a limiter reports that it would not admit a request.
+    ///
+    /// The limiters in this module wait for capacity rather than failing
+    /// requests, so this is called when their `check` method returns `false`,
+    /// e.g. when a caller routes a request elsewhere.
+    fn rejected(&self) {}
+
+    /// Called when a [`ConcurrencyLimit`] reclaims a request's permit because
+    /// the request was held for longer than its maximum hold duration, with
+    /// the time the permit was held.
+    ///
+    /// See [`ConcurrencyLimit::with_max_hold`].
+    ///
+    /// [`ConcurrencyLimit`]: super::ConcurrencyLimit
+    /// [`ConcurrencyLimit::with_max_hold`]: super::ConcurrencyLimit::with_max_hold
+    fn reclaimed(&self, held: Duration) {
+        let _ = held;
+    }
+}
+
+/// Tracks a limiter's waits and reports them to its observer, if any.
+#[derive(Default)]
+pub(crate) struct Observed {
+    observer: Option<Arc<dyn Observe + Send + Sync>>,
+    waiting_since: Option<Instant>,
+}
+
+impl Observed {
+    pub(crate) fn new<O>(observer: O) -> Self
+    where
+        O: Observe + Send + Sync + 'static,
+    {
+        Self {
+            observer: Some(Arc::new(observer)),
+            waiting_since: None,
+        }
+    }
+
+    /// Records that the limiter is waiting for capacity.
+    pub(crate) fn wait(&mut self) {
+        if let Some(ref observer) = self.observer {
+            if self.waiting_since.is_none() {
+                self.waiting_since = Some(Instant::now());
+                observer.wait_started();
+            }
+        }
+    }
+
+    /// Records that the limiter has capacity, ending any wait.
+    pub(crate) fn ready(&mut self) {
+        if let (Some(observer), Some(since)) = (&self.observer, self.waiting_since.take()) {
+            observer.wait_ended(since.elapsed());
+        }
+    }
+
+    pub(crate) fn acquired(&self) {
+        if let Some(ref observer) = self.observer {
+            observer.acquired();
+        }
+    }
+
+    pub(crate) fn rejected(&self) {
+        if let Some(ref observer) = self.observer {
+            observer.rejected();
+        }
+    }
+
+    pub(crate) fn reclaimed(&self, held: Duration) {
+        if let Some(ref observer) = self.observer {
+            observer.reclaimed(held);
+        }
+    }
+
+    /// Returns a copy of this that shares its observer but is not waiting.
+    pub(crate) fn fresh(&self) -> Self {
+        Self {
+            observer: self.observer.clone(),
+            waiting_since: None,
+        }
+    }
+}
+
+impl fmt::Debug for Observed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Observed")
+            .field("observer", &self.observer.is_some())
+            .field("waiting_since", &self.waiting_since)
+            .finish()
+    }
+}
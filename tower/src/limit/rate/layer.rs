@@ -15,6 +15,14 @@ impl RateLimitLayer {
         let rate = Rate::new(num, per);
         RateLimitLayer { rate }
     }
+
+    /// Enforces the rate as a token bucket that holds up to `burst` tokens.
+    ///
+    /// See [`Rate::with_burst`] for details.
+    pub fn with_burst(mut self, burst: u64) -> Self {
+        self.rate = self.rate.with_burst(burst);
+        self
+    }
 }
 
 impl<S> Layer<S> for RateLimitLayer {
@@ -1,10 +1,16 @@
 use std::time::Duration;
 
 /// A rate of requests per time period.
+///
+/// By default, at most `num` requests are admitted in each consecutive period
+/// of `per`. A rate [with a burst] is instead enforced as a token bucket.
+///
+/// [with a burst]: Rate::with_burst
 #[derive(Debug, Copy, Clone)]
 pub struct Rate {
     num: u64,
     per: Duration,
+    burst: Option<u64>,
 }
 
 impl Rate {
@@ -17,7 +23,30 @@ impl Rate {
         assert!(num > 0);
         assert!(per > Duration::from_millis(0));
 
-        Rate { num, per }
+        Rate {
+            num,
+            per,
+            burst: None,
+        }
+    }
+
+    /// Enforces this rate as a token bucket that holds up to `burst` tokens.
+    ///
+    /// Tokens are added to the bucket evenly, at `num` tokens per `per`, and
+    /// each request takes a token. The bucket starts full, so up to `burst`
+    /// requests may be admitted at once after a period of inactivity, after
+    /// which requests are admitted at the steady rate. When the bucket is
+    /// empty, [`poll_ready`] waits until the next token is added.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `burst` is 0.
+    ///
+    /// [`poll_ready`]: crate::Service::poll_ready
+    pub fn with_burst(mut self, burst: u64) -> Self {
+        assert!(burst > 0);
+        self.burst = Some(burst);
+        self
     }
 
     pub(crate) fn num(&self) -> u64 {
@@ -27,4 +56,8 @@ impl Rate {
     pub(crate) fn per(&self) -> Duration {
         self.per
     }
+
+    pub(crate) fn burst(&self) -> Option<u64> {
+        self.burst
+    }
 }
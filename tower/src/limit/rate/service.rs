@@ -1,9 +1,11 @@
 use super::Rate;
+use crate::limit::observe::{Observe, Observed};
 use futures_core::ready;
 use std::{
     future::Future,
     pin::Pin,
     task::{Context, Poll},
+    time::Duration,
 };
 use tokio::time::{Instant, Sleep};
 use tower_service::Service;
@@ -15,7 +17,22 @@ pub struct RateLimit<T> {
     inner: T,
     rate: Rate,
     state: State,
+    bucket: Option<Bucket>,
     sleep: Pin<Box<Sleep>>,
+    observed: Observed,
+}
+
+/// A token bucket, tracked as the time at which the bucket will next be empty
+/// (its "theoretical arrival time").
+#[derive(Debug)]
+struct Bucket {
+    /// How often a token is added to the bucket.
+    interval: Duration,
+    /// How long it takes to fill the bucket, less one token.
+    tolerance: Duration,
+    /// The number of tokens the bucket holds.
+    burst: u64,
+    tat: Instant,
 }
 
 #[derive(Debug)]
@@ -34,17 +51,76 @@ impl<T> RateLimit<T> {
             rem: rate.num(),
         };
 
+        let bucket = rate.burst().map(|burst| Bucket::new(&rate, burst, until));
         RateLimit {
             inner,
             rate,
             state,
+            bucket,
             // The sleep won't actually be used with this duration, but
             // we create it eagerly so that we can reset it in place rather than
             // `Box::pin`ning a new `Sleep` every time we need one.
             sleep: Box::pin(tokio::time::sleep_until(until)),
+            observed: Observed::default(),
         }
     }
 
+    /// Reports how requests are admitted by this limiter to `observer`.
+    pub fn with_observer<O>(mut self, observer: O) -> Self
+    where
+        O: Observe + Send + Sync + 'static,
+    {
+        self.observed = Observed::new(observer);
+        self
+    }
+
+    /// Returns `true` if the rate limit would currently admit a request.
+    ///
+    /// Unlike [`poll_ready`], this does not consume any of the rate limit's
+    /// capacity, so it may be used to make routing decisions based on the
+    /// limiter's state. Note that this only reflects the rate limit itself;
+    /// the inner service may still not be ready.
+    ///
+    /// [`poll_ready`]: crate::Service::poll_ready
+    pub fn check(&self) -> bool {
+        let admit = match (&self.bucket, &self.state) {
+            (Some(bucket), _) => bucket.admits(Instant::now()),
+            (None, State::Ready { .. }) => true,
+            (None, State::Limited) => Instant::now() >= self.sleep.deadline(),
+        };
+        if !admit {
+            self.observed.rejected();
+        }
+        admit
+    }
+
+    /// Returns the number of requests the rate limit would currently admit.
+    ///
+    /// For a rate with a burst, this is the number of tokens in the bucket.
+    /// Otherwise, it is the number of requests remaining in the current
+    /// period.
+    pub fn remaining(&self) -> u64 {
+        let now = Instant::now();
+        match (&self.bucket, &self.state) {
+            (Some(bucket), _) => bucket.tokens(now),
+            (None, State::Ready { until, .. }) if now >= *until => self.rate.num(),
+            (None, State::Ready { rem, .. }) => *rem,
+            (None, State::Limited) if now >= self.sleep.deadline() => self.rate.num(),
+            (None, State::Limited) => 0,
+        }
+    }
+
+    /// Returns the fraction of the rate limit's capacity that has been used,
+    /// from 0.0 to 1.0.
+    ///
+    /// This is the fraction of the bucket's tokens that have been taken, for
+    /// a rate with a burst, or of the current period's requests that have
+    /// been admitted, otherwise.
+    pub fn utilization(&self) -> f64 {
+        let capacity = self.bucket.as_ref().map_or(self.rate.num(), |b| b.burst);
+        1.0 - (self.remaining() as f64 / capacity as f64)
+    }
+
     /// Get a reference to the inner service
     pub fn get_ref(&self) -> &T {
         &self.inner
@@ -70,13 +146,32 @@ where
     type Future = S::Future;
 
     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        if let Some(ref bucket) = self.bucket {
+            let now = Instant::now();
+            if !bucket.admits(now) {
+                let at = bucket.admits_at();
+                if self.sleep.deadline() != at {
+                    self.sleep.as_mut().reset(at);
+                }
+                if self.sleep.as_mut().poll(cx).is_pending() {
+                    tracing::trace!("rate limit exceeded; sleeping.");
+                    self.observed.wait();
+                    return Poll::Pending;
+                }
+            }
+            self.observed.ready();
+            return Poll::Ready(ready!(self.inner.poll_ready(cx)));
+        }
+
         match self.state {
             State::Ready { .. } => return Poll::Ready(ready!(self.inner.poll_ready(cx))),
             State::Limited => {
-                if let Poll::Pending = Pin::new(&mut self.sleep).poll(cx) {
+                if Pin::new(&mut self.sleep).poll(cx).is_pending() {
                     tracing::trace!("rate limit exceeded; sleeping.");
+                    self.observed.wait();
                     return Poll::Pending;
                 }
+                self.observed.ready();
             }
         }
 
@@ -89,6 +184,17 @@ where
     }
 
     fn call(&mut self, request: Request) -> Self::Future {
+        if let Some(ref mut bucket) = self.bucket {
+            let now = Instant::now();
+            assert!(
+                bucket.admits(now),
+                "service not ready; poll_ready must be called first"
+            );
+            bucket.take(now);
+            self.observed.acquired();
+            return self.inner.call(request);
+        }
+
         match self.state {
             State::Ready { mut until, mut rem } => {
                 let now = Instant::now();
@@ -111,6 +217,7 @@ where
                 }
 
                 // Call the inner future
+                self.observed.acquired();
                 self.inner.call(request)
             }
             State::Limited => panic!("service not ready; poll_ready must be called first"),
@@ -118,6 +225,51 @@ where
     }
 }
 
+impl Bucket {
+    fn new(rate: &Rate, burst: u64, now: Instant) -> Self {
+        let interval = rate.per().as_nanos() / u128::from(rate.num());
+        let tolerance = interval.saturating_mul(u128::from(burst - 1));
+        Self {
+            interval: nanos(interval),
+            tolerance: nanos(tolerance),
+            burst,
+            tat: now,
+        }
+    }
+
+    /// Returns `true` if the bucket has a token at `now`.
+    fn admits(&self, now: Instant) -> bool {
+        self.tat <= now + self.tolerance
+    }
+
+    /// Returns the time at which the bucket will next have a token.
+    fn admits_at(&self) -> Instant {
+        self.tat - self.tolerance
+    }
+
+    /// Returns the number of tokens in the bucket at `now`.
+    fn tokens(&self, now: Instant) -> u64 {
+        if !self.admits(now) {
+            return 0;
+        }
+        let interval = self.interval.as_nanos().max(1);
+        let elapsed = (now + self.tolerance).saturating_duration_since(self.tat);
+        let tokens = elapsed.as_nanos() / interval + 1;
+        tokens.min(u128::from(self.burst)) as u64
+    }
+
+    /// Takes a token from the bucket.
+    fn take(&mut self, now: Instant) {
+        self.tat = self.tat.max(now) + self.interval;
+    }
+}
+
+fn nanos(nanos: u128) -> Duration {
+    use std::convert::TryFrom;
+
+    Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX))
+}
+
 #[cfg(feature = "load")]
 #[cfg_attr(docsrs, doc(cfg(feature = "load")))]
 impl<S> crate::load::Load for RateLimit<S>
@@ -129,3 +281,9 @@ where
         self.inner.load()
     }
 }
+
+impl<S> crate::limit::Utilization for RateLimit<S> {
+    fn utilization(&self) -> f64 {
+        RateLimit::utilization(self)
+    }
+}
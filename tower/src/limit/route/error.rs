@@ -0,0 +1,33 @@
+//! Error types for the [`RouteLimit`] middleware.
+//!
+//! [`RouteLimit`]: super::RouteLimit
+
+use std::fmt;
+
+/// An error returned by a [`RouteLimit`] when a request's route is at its
+/// limit.
+///
+/// [`RouteLimit`]: super::RouteLimit
+pub struct RouteLimited {
+    _p: (),
+}
+
+impl RouteLimited {
+    pub(crate) fn new() -> Self {
+        RouteLimited { _p: () }
+    }
+}
+
+impl fmt::Debug for RouteLimited {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RouteLimited").finish()
+    }
+}
+
+impl fmt::Display for RouteLimited {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("route limit reached")
+    }
+}
+
+impl std::error::Error for RouteLimited {}
@@ -0,0 +1,71 @@
+//! [`Future`] types
+//!
+//! [`Future`]: std::future::Future
+use super::error::RouteLimited;
+use pin_project::pin_project;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::sync::OwnedSemaphorePermit;
+
+/// Future for the [`RouteLimit`] service.
+///
+/// [`RouteLimit`]: crate::limit::route::RouteLimit
+#[pin_project]
+#[derive(Debug)]
+pub struct ResponseFuture<T> {
+    #[pin]
+    state: State<T>,
+}
+
+#[pin_project(project = StateProj)]
+#[derive(Debug)]
+enum State<T> {
+    Called {
+        #[pin]
+        inner: T,
+        // Held until the response completes.
+        _global: Option<OwnedSemaphorePermit>,
+        _route: Option<OwnedSemaphorePermit>,
+    },
+    Limited,
+}
+
+impl<T> ResponseFuture<T> {
+    pub(crate) fn called(
+        inner: T,
+        global: Option<OwnedSemaphorePermit>,
+        route: Option<OwnedSemaphorePermit>,
+    ) -> Self {
+        ResponseFuture {
+            state: State::Called {
+                inner,
+                _global: global,
+                _route: route,
+            },
+        }
+    }
+
+    pub(crate) fn limited() -> Self {
+        ResponseFuture {
+            state: State::Limited,
+        }
+    }
+}
+
+impl<F, T, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<T, E>>,
+    E: Into<crate::BoxError>,
+{
+    type Output = Result<T, crate::BoxError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project().state.project() {
+            StateProj::Called { inner, .. } => inner.poll(cx).map_err(Into::into),
+            StateProj::Limited => Poll::Ready(Err(RouteLimited::new().into())),
+        }
+    }
+}
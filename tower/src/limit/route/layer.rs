@@ -0,0 +1,50 @@
+use super::{RouteLimit, RouteLimits, Shared};
+use std::{fmt, hash::Hash, sync::Arc};
+use tower_layer::Layer;
+
+/// Enforces a table of global and per-route concurrency limits.
+///
+/// All services produced by this layer share the same limits. Cloning this
+/// layer does not reset them.
+///
+/// See [`RouteLimit`] for details.
+pub struct RouteLimitLayer<K, F> {
+    shared: Arc<Shared<K>>,
+    route: F,
+}
+
+impl<K: Eq + Hash, F> RouteLimitLayer<K, F> {
+    /// Creates a layer that enforces `limits`, resolving each request's route
+    /// with `route`.
+    pub fn new(limits: RouteLimits<K>, route: F) -> Self {
+        RouteLimitLayer {
+            shared: limits.into_shared(),
+            route,
+        }
+    }
+}
+
+impl<S, K: Eq + Hash, F: Clone> Layer<S> for RouteLimitLayer<K, F> {
+    type Service = RouteLimit<S, K, F>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        RouteLimit::from_shared(service, self.shared.clone(), self.route.clone())
+    }
+}
+
+impl<K, F: Clone> Clone for RouteLimitLayer<K, F> {
+    fn clone(&self) -> Self {
+        RouteLimitLayer {
+            shared: self.shared.clone(),
+            route: self.route.clone(),
+        }
+    }
+}
+
+impl<K: fmt::Debug, F> fmt::Debug for RouteLimitLayer<K, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RouteLimitLayer")
+            .field("shared", &self.shared)
+            .finish()
+    }
+}
@@ -0,0 +1,84 @@
+//! Limit the number of concurrent requests to each route, and overall.
+//!
+//! A [`RouteLimit`] enforces a table of limits, as configured by
+//! [`RouteLimits`]: an optional global cap on the number of in-flight
+//! requests, plus caps for individual routes. Each request's route is resolved
+//! by a user-provided function when the request is dispatched, so a single
+//! middleware can enforce an API gateway's entire quota table.
+
+pub mod error;
+pub mod future;
+mod layer;
+mod service;
+
+pub use self::{layer::RouteLimitLayer, service::RouteLimit};
+
+use std::{collections::HashMap, hash::Hash, sync::Arc};
+use tokio::sync::Semaphore;
+
+/// Configures the limits enforced by a [`RouteLimit`].
+///
+/// By default, requests are not limited. Routes without a configured limit
+/// are only subject to the global limit, if any.
+#[derive(Clone, Debug)]
+pub struct RouteLimits<K> {
+    global: Option<usize>,
+    routes: HashMap<K, usize>,
+}
+
+/// The semaphores enforcing a [`RouteLimits`] table, shared by all services
+/// built from it.
+#[derive(Debug)]
+pub(crate) struct Shared<K> {
+    pub(crate) global: Option<Arc<Semaphore>>,
+    pub(crate) routes: HashMap<K, Arc<Semaphore>>,
+}
+
+impl<K: Eq + Hash> RouteLimits<K> {
+    /// Creates an empty set of limits.
+    pub fn new() -> Self {
+        Self {
+            global: None,
+            routes: HashMap::new(),
+        }
+    }
+
+    /// Limits the total number of in-flight requests, across all routes, to
+    /// `max`.
+    pub fn global(mut self, max: usize) -> Self {
+        self.global = Some(max);
+        self
+    }
+
+    /// Limits the number of in-flight requests to `route` to `max`.
+    pub fn route(mut self, route: K, max: usize) -> Self {
+        self.routes.insert(route, max);
+        self
+    }
+
+    pub(crate) fn into_shared(self) -> Arc<Shared<K>> {
+        Arc::new(Shared {
+            global: self.global.map(|max| Arc::new(Semaphore::new(max))),
+            routes: self
+                .routes
+                .into_iter()
+                .map(|(route, max)| (route, Arc::new(Semaphore::new(max))))
+                .collect(),
+        })
+    }
+}
+
+impl<K: Eq + Hash> Default for RouteLimits<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Eq + Hash> std::iter::FromIterator<(K, usize)> for RouteLimits<K> {
+    fn from_iter<I: IntoIterator<Item = (K, usize)>>(routes: I) -> Self {
+        Self {
+            global: None,
+            routes: routes.into_iter().collect(),
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use super::{future::ResponseFuture, RouteLimits, Shared};
+use futures_core::ready;
+use std::{
+    fmt,
+    hash::Hash,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::sync::OwnedSemaphorePermit;
+use tokio_util::sync::PollSemaphore;
+use tower_service::Service;
+
+/// Enforces a table of global and per-route concurrency limits.
+///
+/// The global limit is enforced like a [`ConcurrencyLimit`]: capacity is
+/// acquired in [`poll_ready`], which waits until fewer than the global
+/// maximum number of requests are in flight. A request's route is only known
+/// once it is dispatched, so per-route limits are checked in [`call`]. A
+/// request whose route is at its limit fails immediately with a
+/// [`RouteLimited`] error rather than waiting, so that busy routes shed load
+/// without holding up requests to other routes.
+///
+/// Capacity is released when each request's response future completes.
+///
+/// [`ConcurrencyLimit`]: crate::limit::ConcurrencyLimit
+/// [`poll_ready`]: crate::Service::poll_ready
+/// [`call`]: crate::Service::call
+/// [`RouteLimited`]: super::error::RouteLimited
+pub struct RouteLimit<T, K, F> {
+    inner: T,
+    route: F,
+    shared: Arc<Shared<K>>,
+    global: Option<PollSemaphore>,
+    /// The currently acquired global permit, if there is a global limit and
+    /// there is sufficient capacity to send a new request.
+    permit: Option<OwnedSemaphorePermit>,
+}
+
+impl<T, K: Eq + Hash, F> RouteLimit<T, K, F> {
+    /// Creates a new limiter enforcing `limits`, resolving each request's
+    /// route with `route`.
+    pub fn new(inner: T, limits: RouteLimits<K>, route: F) -> Self {
+        Self::from_shared(inner, limits.into_shared(), route)
+    }
+
+    pub(crate) fn from_shared(inner: T, shared: Arc<Shared<K>>, route: F) -> Self {
+        RouteLimit {
+            inner,
+            route,
+            global: shared.global.clone().map(PollSemaphore::new),
+            shared,
+            permit: None,
+        }
+    }
+
+    /// Get a reference to the inner service
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner service
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Consume `self`, returning the inner service
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T, K, F, Request> Service<Request> for RouteLimit<T, K, F>
+where
+    T: Service<Request>,
+    T::Error: Into<crate::BoxError>,
+    K: Eq + Hash,
+    F: Fn(&Request) -> K,
+{
+    type Response = T::Response;
+    type Error = crate::BoxError;
+    type Future = ResponseFuture<T::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        if let Some(ref mut global) = self.global {
+            if self.permit.is_none() {
+                self.permit = ready!(global.poll_acquire(cx));
+                debug_assert!(
+                    self.permit.is_some(),
+                    "RouteLimit semaphore is never closed, so `poll_acquire` \
+                     should never fail",
+                );
+            }
+        }
+
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        assert!(
+            self.global.is_none() || self.permit.is_some(),
+            "max requests in-flight; poll_ready must be called first"
+        );
+
+        let route = (self.route)(&request);
+        let route = match self.shared.routes.get(&route) {
+            Some(semaphore) => match semaphore.clone().try_acquire_owned() {
+                Ok(permit) => Some(permit),
+                Err(_) => {
+                    tracing::debug!("route limit reached; rejecting request");
+                    return ResponseFuture::limited();
+                }
+            },
+            None => None,
+        };
+
+        let global = self.permit.take();
+        ResponseFuture::called(self.inner.call(request), global, route)
+    }
+}
+
+impl<T: Clone, K, F: Clone> Clone for RouteLimit<T, K, F> {
+    fn clone(&self) -> Self {
+        // As with `ConcurrencyLimit`, clones share the limits, but start
+        // without a permit.
+        RouteLimit {
+            inner: self.inner.clone(),
+            route: self.route.clone(),
+            shared: self.shared.clone(),
+            global: self.global.clone(),
+            permit: None,
+        }
+    }
+}
+
+impl<T: fmt::Debug, K: fmt::Debug, F> fmt::Debug for RouteLimit<T, K, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RouteLimit")
+            .field("inner", &self.inner)
+            .field("shared", &self.shared)
+            .field("permit", &self.permit)
+            .finish()
+    }
+}
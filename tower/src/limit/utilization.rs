@@ -0,0 +1,100 @@
+#[cfg(feature = "load")]
+use crate::load::{InFlight, Load, Nested};
+#[cfg(feature = "load")]
+use std::task::{Context, Poll};
+#[cfg(feature = "load")]
+use tower_service::Service;
+
+/// Limiters that can report how much of their capacity is in use.
+///
+/// This is implemented by [`ConcurrencyLimit`] and [`RateLimit`].
+///
+/// [`ConcurrencyLimit`]: super::ConcurrencyLimit
+/// [`RateLimit`]: super::RateLimit
+pub trait Utilization {
+    /// Returns the fraction of the limiter's capacity that is in use, from 0.0
+    /// to 1.0.
+    fn utilization(&self) -> f64;
+}
+
+/// Measures the load of a limited service by its limiter's [`Utilization`].
+///
+/// The limiters' own [`Load`] implementations report the load of the service
+/// they wrap. Wrapping a limited endpoint in [`UtilizationLoad`] instead
+/// causes a balancer to steer requests away from endpoints that are close to
+/// their limits. Since the limiter's metric remains available through
+/// [`Nested`], the two may also be combined with [`CombinedLoad`].
+///
+/// [`CombinedLoad`]: crate::load::CombinedLoad
+#[cfg(feature = "load")]
+#[cfg_attr(docsrs, doc(cfg(feature = "load")))]
+#[derive(Clone, Debug)]
+pub struct UtilizationLoad<S> {
+    inner: S,
+}
+
+#[cfg(feature = "load")]
+impl<S> UtilizationLoad<S> {
+    /// Wraps a limited service so that its load is its limiter's utilization.
+    pub fn new(inner: S) -> Self {
+        Self { inner }
+    }
+
+    /// Get a reference to the inner service
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner service
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Consume `self`, returning the inner service
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+#[cfg(feature = "load")]
+impl<S: Utilization> Load for UtilizationLoad<S> {
+    type Metric = f64;
+
+    fn load(&self) -> f64 {
+        self.inner.utilization()
+    }
+}
+
+#[cfg(feature = "load")]
+impl<S> Nested for UtilizationLoad<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &S {
+        &self.inner
+    }
+}
+
+#[cfg(feature = "load")]
+impl<S: InFlight> InFlight for UtilizationLoad<S> {
+    fn in_flight(&self) -> usize {
+        self.inner.in_flight()
+    }
+}
+
+#[cfg(feature = "load")]
+impl<S, Request> Service<Request> for UtilizationLoad<S>
+where
+    S: Service<Request>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        self.inner.call(req)
+    }
+}
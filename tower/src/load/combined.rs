@@ -0,0 +1,134 @@
+//! A [`Load`] implementation that combines the metrics of nested load measurements.
+
+use super::{InFlight, Load};
+use std::fmt;
+use std::task::{Context, Poll};
+use tower_service::Service;
+
+/// Combines the load metrics of two nested load measurements over the same
+/// service.
+///
+/// Load measurements such as [`PeakEwma`] and [`PendingRequests`] wrap the
+/// service they measure, so they may be nested to track several metrics at
+/// once: each request passes through every layer, and each layer observes the
+/// request's completion. [`CombinedLoad`] wraps such a stack and computes its
+/// load by passing the metrics of the outer measurement and of the one it
+/// wraps to a user-provided function.
+///
+/// The inner measurement's [`TrackCompletion`] output is the response type seen
+/// by the outer measurement, so completion tracking composes as well.
+///
+/// # Examples
+///
+/// Balancing on a weighted combination of pending requests and latency:
+///
+/// ```
+/// use std::time::Duration;
+/// use tower::load::{
+///     peak_ewma, pending_requests, CombinedLoad, CompleteOnResponse, Load, PeakEwma,
+///     PendingRequests,
+/// };
+/// # let (service, _handle) = tower_test::mock::pair::<(), ()>();
+///
+/// let pending = PendingRequests::new(service, CompleteOnResponse::default());
+/// let ewma = PeakEwma::new(
+///     pending,
+///     Duration::from_millis(10),
+///     1e9,
+///     CompleteOnResponse::default(),
+/// );
+/// let svc = CombinedLoad::new(ewma, |cost: peak_ewma::Cost, count: pending_requests::Count| {
+///     f64::from(cost) + 1e6 * usize::from(count) as f64
+/// });
+/// assert!(svc.load() > 0.0);
+/// ```
+///
+/// [`PeakEwma`]: super::PeakEwma
+/// [`PendingRequests`]: super::PendingRequests
+/// [`TrackCompletion`]: super::TrackCompletion
+pub struct CombinedLoad<S, F> {
+    service: S,
+    combine: F,
+}
+
+/// A load measurement that wraps another service.
+///
+/// This is implemented by the load measurements in this module, allowing
+/// [`CombinedLoad`] to access the metric of the measurement they wrap.
+pub trait Nested {
+    /// The wrapped service.
+    type Inner;
+
+    /// Returns a reference to the wrapped service.
+    fn inner(&self) -> &Self::Inner;
+}
+
+// ===== impl CombinedLoad =====
+
+impl<S, F> CombinedLoad<S, F> {
+    /// Wraps a nested load measurement so that its load is computed by
+    /// `combine` from the metrics of its outer and inner measurements.
+    pub fn new(service: S, combine: F) -> Self {
+        Self { service, combine }
+    }
+
+    /// Get a reference to the inner service
+    pub fn get_ref(&self) -> &S {
+        &self.service
+    }
+
+    /// Get a mutable reference to the inner service
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.service
+    }
+
+    /// Consume `self`, returning the inner service
+    pub fn into_inner(self) -> S {
+        self.service
+    }
+}
+
+impl<S, F, M> Load for CombinedLoad<S, F>
+where
+    S: Load + Nested,
+    S::Inner: Load,
+    F: Fn(S::Metric, <S::Inner as Load>::Metric) -> M,
+    M: PartialOrd,
+{
+    type Metric = M;
+
+    fn load(&self) -> M {
+        (self.combine)(self.service.load(), self.service.inner().load())
+    }
+}
+
+impl<S: InFlight, F> InFlight for CombinedLoad<S, F> {
+    fn in_flight(&self) -> usize {
+        self.service.in_flight()
+    }
+}
+
+impl<S, F, Request> Service<Request> for CombinedLoad<S, F>
+where
+    S: Service<Request>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        self.service.call(req)
+    }
+}
+
+impl<S: fmt::Debug, F> fmt::Debug for CombinedLoad<S, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CombinedLoad")
+            .field("service", &self.service)
+            .finish()
+    }
+}
@@ -18,7 +18,12 @@ use std::{
 /// This utility allows load metrics to have a protocol-agnostic means to track streams past their
 /// initial response future. For example, if `V` represents an HTTP response type, an
 /// implementation could add `H`-typed handles to each response's extensions to detect when all the
-/// response's extensions have been dropped.
+/// response's extensions have been dropped. Similarly, an implementation could wrap a streaming
+/// response body so that the handle is held until the body has been fully transmitted, rather
+/// than until the response headers have been received.
+///
+/// Requests that fail are passed to [`TrackCompletion::track_failure`] instead, so that load
+/// metrics may distinguish failures from successful responses.
 ///
 /// A base `impl<H, V> TrackCompletion<H, V> for CompleteOnResponse` is provided to drop the handle
 /// once the response future is resolved. This is appropriate when a response is discrete and
@@ -36,6 +41,17 @@ pub trait TrackCompletion<H, V>: Clone {
 
     /// Attaches a `H`-typed handle to a `V`-typed value.
     fn track_completion(&self, handle: H, value: V) -> Self::Output;
+
+    /// Called with the handle of a request that failed, instead of
+    /// [`TrackCompletion::track_completion`].
+    ///
+    /// This allows implementations to distinguish failed requests from
+    /// successful ones, for instance to record failures in the handle before
+    /// it is dropped. By default, the handle is simply dropped, completing the
+    /// request.
+    fn track_failure(&self, handle: H) {
+        drop(handle);
+    }
 }
 
 /// A [`TrackCompletion`] implementation that considers the request completed when the response
@@ -76,9 +92,15 @@ where
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.project();
-        let rsp = ready!(this.future.poll(cx))?;
+        let result = ready!(this.future.poll(cx));
         let h = this.handle.take().expect("handle");
-        Poll::Ready(Ok(this.completion.track_completion(h, rsp)))
+        match result {
+            Ok(rsp) => Poll::Ready(Ok(this.completion.track_completion(h, rsp))),
+            Err(e) => {
+                this.completion.track_failure(h);
+                Poll::Ready(Err(e))
+            }
+        }
     }
 }
 
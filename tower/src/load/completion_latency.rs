@@ -0,0 +1,398 @@
+//! A [`Load`] implementation that measures load using the average time taken
+//! to complete requests.
+
+#[cfg(feature = "discover")]
+use crate::discover::{Change, Discover};
+use futures_core::ready;
+#[cfg(feature = "discover")]
+use futures_core::Stream;
+use pin_project::pin_project;
+use std::pin::Pin;
+
+use super::completion::{CompleteOnResponse, TrackCompletion};
+use super::{InFlight, Inherit, Load, Nested};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::Instant;
+use tower_service::Service;
+use tracing::trace;
+
+/// Measures the load of the underlying service using a moving average of the
+/// time taken to complete requests.
+///
+/// Each time a request completes, the time elapsed since it was dispatched is
+/// folded into an exponentially-weighted moving average (EWMA). Older samples
+/// decay according to how much time has passed since the previous sample, so
+/// the average follows changes in an endpoint's behavior over roughly the
+/// configured decay period. Unlike [`PeakEwma`], the metric is not scaled by
+/// the number of pending requests and does not favor worst-case latencies, so
+/// it reflects which endpoint has historically been fastest.
+///
+/// Failed requests may be weighted more heavily than successful ones (see
+/// [`CompletionLatency::with_error_weight`]), so that an endpoint which fails
+/// quickly does not appear to be the fastest.
+///
+/// When no requests have completed, the provided default latency is used.
+///
+/// [`PeakEwma`]: super::PeakEwma
+#[derive(Debug)]
+pub struct CompletionLatency<S, C = CompleteOnResponse> {
+    service: S,
+    decay_ns: f64,
+    error_weight: f64,
+    estimate: Arc<Mutex<Estimate>>,
+    completion: C,
+}
+
+/// Wraps a `D`-typed stream of discovered services with [`CompletionLatency`].
+#[pin_project]
+#[derive(Debug)]
+#[cfg(feature = "discover")]
+#[cfg_attr(docsrs, doc(cfg(feature = "discover")))]
+pub struct CompletionLatencyDiscover<D, C = CompleteOnResponse> {
+    #[pin]
+    discover: D,
+    default: Duration,
+    decay: Duration,
+    error_weight: f64,
+    completion: C,
+}
+
+/// The average time taken by a service to complete requests.
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+pub struct Latency(f64);
+
+/// Tracks an in-flight request and updates the latency estimate on Drop.
+#[derive(Debug)]
+pub struct Handle {
+    sent_at: Instant,
+    decay_ns: f64,
+    weight: f64,
+    estimate: Arc<Mutex<Estimate>>,
+}
+
+/// Response future for [`CompletionLatency`].
+#[pin_project]
+#[derive(Debug)]
+pub struct ResponseFuture<F, C> {
+    #[pin]
+    future: F,
+    handle: Option<Handle>,
+    error_weight: f64,
+    completion: C,
+}
+
+/// Holds the current latency estimate and the last time it was updated.
+#[derive(Debug)]
+struct Estimate {
+    update_at: Instant,
+    latency_ns: f64,
+}
+
+// ===== impl CompletionLatency =====
+
+impl<S, C> CompletionLatency<S, C> {
+    /// Wraps an `S`-typed service so that its load is tracked by the moving
+    /// average of its request completion times.
+    ///
+    /// The `default` latency is used until a request completes. The `decay`
+    /// value determines over what time period older samples are discounted.
+    pub fn new(service: S, default: Duration, decay: Duration, completion: C) -> Self {
+        Self {
+            service,
+            decay_ns: nanos(decay),
+            error_weight: 1.0,
+            estimate: Arc::new(Mutex::new(Estimate::new(nanos(default)))),
+            completion,
+        }
+    }
+
+    /// Scales the latency of failed requests by `weight`.
+    ///
+    /// By default, failed requests are weighted the same as successful ones.
+    ///
+    /// # Panics
+    ///
+    /// If `weight` is negative.
+    pub fn with_error_weight(mut self, weight: f64) -> Self {
+        assert!(weight >= 0.0, "error weight must not be negative");
+        self.error_weight = weight;
+        self
+    }
+
+    /// Get a reference to the inner service
+    pub fn get_ref(&self) -> &S {
+        &self.service
+    }
+
+    /// Get a mutable reference to the inner service
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.service
+    }
+
+    /// Consume `self`, returning the inner service
+    pub fn into_inner(self) -> S {
+        self.service
+    }
+
+    fn handle(&self) -> Handle {
+        Handle {
+            sent_at: Instant::now(),
+            decay_ns: self.decay_ns,
+            weight: 1.0,
+            estimate: self.estimate.clone(),
+        }
+    }
+}
+
+impl<S, C, Request> Service<Request> for CompletionLatency<S, C>
+where
+    S: Service<Request>,
+    C: TrackCompletion<Handle, S::Response>,
+{
+    type Response = C::Output;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future, C>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        ResponseFuture {
+            future: self.service.call(req),
+            handle: Some(self.handle()),
+            error_weight: self.error_weight,
+            completion: self.completion.clone(),
+        }
+    }
+}
+
+impl<S, C> Load for CompletionLatency<S, C> {
+    type Metric = Latency;
+
+    fn load(&self) -> Self::Metric {
+        let estimate = self.estimate.lock().expect("completion latency estimate");
+        Latency(estimate.latency_ns)
+    }
+}
+
+impl<S, C> Nested for CompletionLatency<S, C> {
+    type Inner = S;
+
+    fn inner(&self) -> &S {
+        &self.service
+    }
+}
+
+impl<S, C> InFlight for CompletionLatency<S, C> {
+    fn in_flight(&self) -> usize {
+        Arc::strong_count(&self.estimate) - 1
+    }
+}
+
+impl<S, C> Inherit for CompletionLatency<S, C> {
+    fn inherit(&mut self, prior: &Self) {
+        let prior = prior.estimate.lock().expect("completion latency estimate");
+        let mut estimate = self.estimate.lock().expect("completion latency estimate");
+        estimate.update_at = prior.update_at;
+        estimate.latency_ns = prior.latency_ns;
+    }
+}
+
+// ===== impl CompletionLatencyDiscover =====
+
+#[cfg(feature = "discover")]
+impl<D, C> CompletionLatencyDiscover<D, C> {
+    /// Wraps a [`Discover`], wrapping all of its services with
+    /// [`CompletionLatency`].
+    ///
+    /// See [`CompletionLatency::new`] for the meaning of `default` and `decay`.
+    pub fn new<Request>(discover: D, default: Duration, decay: Duration, completion: C) -> Self
+    where
+        D: Discover,
+        D::Service: Service<Request>,
+        C: TrackCompletion<Handle, <D::Service as Service<Request>>::Response>,
+    {
+        Self {
+            discover,
+            default,
+            decay,
+            error_weight: 1.0,
+            completion,
+        }
+    }
+
+    /// Scales the latency of failed requests to discovered services by
+    /// `weight`.
+    ///
+    /// See [`CompletionLatency::with_error_weight`] for details.
+    pub fn with_error_weight(mut self, weight: f64) -> Self {
+        assert!(weight >= 0.0, "error weight must not be negative");
+        self.error_weight = weight;
+        self
+    }
+}
+
+#[cfg(feature = "discover")]
+impl<D, C> Stream for CompletionLatencyDiscover<D, C>
+where
+    D: Discover,
+    C: Clone,
+{
+    type Item = Result<Change<D::Key, CompletionLatency<D::Service, C>>, D::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let change = match ready!(this.discover.poll_discover(cx)).transpose()? {
+            None => return Poll::Ready(None),
+            Some(Change::Remove(k)) => Change::Remove(k),
+            Some(Change::Insert(k, svc)) => {
+                let svc = CompletionLatency::new(
+                    svc,
+                    *this.default,
+                    *this.decay,
+                    this.completion.clone(),
+                )
+                .with_error_weight(*this.error_weight);
+                Change::Insert(k, svc)
+            }
+        };
+
+        Poll::Ready(Some(Ok(change)))
+    }
+}
+
+// ===== impl Latency =====
+
+impl From<Latency> for f64 {
+    fn from(Latency(value): Latency) -> f64 {
+        value
+    }
+}
+
+// ===== impl ResponseFuture =====
+
+impl<F, C, T, E> Future for ResponseFuture<F, C>
+where
+    F: Future<Output = Result<T, E>>,
+    C: TrackCompletion<Handle, T>,
+{
+    type Output = Result<C::Output, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let result = ready!(this.future.poll(cx));
+        let mut handle = this.handle.take().expect("polled after complete");
+        match result {
+            Ok(rsp) => Poll::Ready(Ok(this.completion.track_completion(handle, rsp))),
+            Err(e) => {
+                handle.weight = *this.error_weight;
+                this.completion.track_failure(handle);
+                Poll::Ready(Err(e))
+            }
+        }
+    }
+}
+
+// ===== impl Estimate =====
+
+impl Estimate {
+    fn new(latency_ns: f64) -> Self {
+        Self {
+            latency_ns,
+            update_at: Instant::now(),
+        }
+    }
+
+    /// Folds a completed request's latency into the moving average.
+    fn update(&mut self, latency_ns: f64, decay_ns: f64) {
+        let now = Instant::now();
+        let elapsed = nanos(now.saturating_duration_since(self.update_at));
+        let decay = (-elapsed / decay_ns).exp();
+        let next = (self.latency_ns * decay) + (latency_ns * (1.0 - decay));
+        trace!(
+            "update latency={:.0}ns prior={:.0}ns next={:.0}ns",
+            latency_ns,
+            self.latency_ns,
+            next,
+        );
+        self.latency_ns = next;
+        self.update_at = now;
+    }
+}
+
+// ===== impl Handle =====
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        let latency_ns = nanos(self.sent_at.elapsed()) * self.weight;
+        if let Ok(mut estimate) = self.estimate.lock() {
+            estimate.update(latency_ns, self.decay_ns);
+        }
+    }
+}
+
+// Utility that converts durations to nanos in f64.
+fn nanos(d: Duration) -> f64 {
+    const NANOS_PER_SEC: u64 = 1_000_000_000;
+    let n = f64::from(d.subsec_nanos());
+    let s = d.as_secs().saturating_mul(NANOS_PER_SEC) as f64;
+    n + s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time;
+    use tokio_test::{assert_ready_err, assert_ready_ok, task};
+    use tower_test::mock;
+
+    const MILLI: f64 = 1_000_000.0;
+
+    #[tokio::test]
+    async fn tracks_completion_latency() {
+        time::pause();
+
+        let (mock, mut handle) = mock::pair::<(), ()>();
+        let mut svc = mock::Spawn::new(
+            CompletionLatency::new(
+                mock,
+                Duration::from_millis(10),
+                Duration::from_millis(100),
+                CompleteOnResponse,
+            )
+            .with_error_weight(4.0),
+        );
+        assert_eq!(svc.get_ref().load(), Latency(10.0 * MILLI));
+
+        // A slow request raises the average once it completes.
+        time::advance(Duration::from_millis(100)).await;
+        assert_ready_ok!(svc.poll_ready());
+        let mut rsp = task::spawn(svc.call(()));
+        let (_, send) = handle.next_request().await.unwrap();
+        time::advance(Duration::from_millis(50)).await;
+        assert_eq!(
+            svc.get_ref().load(),
+            Latency(10.0 * MILLI),
+            "pending requests are not counted"
+        );
+        send.send_response(());
+        assert_ready_ok!(rsp.poll());
+        let Latency(after_success) = svc.get_ref().load();
+        assert!(10.0 * MILLI < after_success && after_success < 50.0 * MILLI);
+
+        // Failures are weighted more heavily.
+        time::advance(Duration::from_millis(100)).await;
+        assert_ready_ok!(svc.poll_ready());
+        let mut rsp = task::spawn(svc.call(()));
+        let (_, send) = handle.next_request().await.unwrap();
+        time::advance(Duration::from_millis(50)).await;
+        send.send_error("fail");
+        assert_ready_err!(rsp.poll());
+        let Latency(after_failure) = svc.get_ref().load();
+        assert!(50.0 * MILLI < after_failure && after_failure < 200.0 * MILLI);
+    }
+}
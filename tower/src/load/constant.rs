@@ -7,7 +7,7 @@ use futures_core::{ready, Stream};
 #[cfg(feature = "discover")]
 use std::pin::Pin;
 
-use super::Load;
+use super::{InFlight, Load, Nested};
 use pin_project::pin_project;
 use std::task::{Context, Poll};
 use tower_service::Service;
@@ -39,6 +39,20 @@ impl<T, M: Copy + PartialOrd> Load for Constant<T, M> {
     }
 }
 
+impl<T, M> Nested for Constant<T, M> {
+    type Inner = T;
+
+    fn inner(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T: InFlight, M> InFlight for Constant<T, M> {
+    fn in_flight(&self) -> usize {
+        self.inner.in_flight()
+    }
+}
+
 impl<S, M, Request> Service<Request> for Constant<S, M>
 where
     S: Service<Request>,
@@ -0,0 +1,359 @@
+//! A [`Load`] implementation that scales another load measurement by an externally-provided
+//! health score.
+
+#[cfg(feature = "discover")]
+use crate::discover::{Change, Discover};
+#[cfg(feature = "discover")]
+use futures_core::{ready, Stream};
+#[cfg(feature = "discover")]
+use pin_project::pin_project;
+#[cfg(feature = "discover")]
+use std::{collections::HashMap, hash::Hash, pin::Pin, sync::Mutex};
+
+use super::{InFlight, Inherit, Load, Nested};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower_service::Service;
+
+/// Scales the load of a service by a health score that is set from outside of the balancer.
+///
+/// A health score is a value between 0.0 and 1.0, typically published by a control plane or a
+/// health checker alongside service discovery. An endpoint with a score of 1.0 is weighted
+/// normally; an endpoint with a score of 0.5 appears to be twice as loaded as its measured load
+/// would indicate, and so receives roughly half as many requests; and an endpoint with a score of
+/// 0.0 appears infinitely loaded, and is only chosen if every other endpoint is as well. Lowering
+/// or raising an endpoint's score over time shifts traffic away from or towards it gradually.
+///
+/// The weighted load is `(load + 1.0) / score`, where `load` is the metric of the wrapped load
+/// measurement. The added `1.0` ensures that idle endpoints are weighted by their scores as well.
+///
+/// Scores are updated through the [`HealthScore`] handle passed to [`HealthWeighted::new`], or
+/// through [`HealthScores`] when services are wrapped by [`HealthWeightedDiscover`].
+#[derive(Debug)]
+pub struct HealthWeighted<S> {
+    service: S,
+    score: HealthScore,
+}
+
+/// A handle to an endpoint's health score.
+///
+/// Clones of a [`HealthScore`] share the same score, so the handle used to set the score may be
+/// held by a discovery source or a health checker while a clone is held by a [`HealthWeighted`]
+/// service.
+#[derive(Clone)]
+pub struct HealthScore(Arc<AtomicU64>);
+
+/// Wraps a `D`-typed stream of discovered services with [`HealthWeighted`].
+///
+/// Each discovered service's score is looked up by its key in a shared [`HealthScores`]
+/// registry, which is used to push score updates for each endpoint.
+#[pin_project]
+#[derive(Debug)]
+#[cfg(feature = "discover")]
+#[cfg_attr(docsrs, doc(cfg(feature = "discover")))]
+pub struct HealthWeightedDiscover<D: Discover> {
+    #[pin]
+    discover: D,
+    scores: HealthScores<D::Key>,
+}
+
+/// A registry of the health scores of discovered endpoints, by key.
+///
+/// Clones of a [`HealthScores`] share the same registry. An endpoint's score may be set before
+/// or after it is discovered; endpoints that have not been given a score have a score of 1.0.
+/// An endpoint's score is forgotten once it is removed from discovery.
+#[cfg(feature = "discover")]
+#[cfg_attr(docsrs, doc(cfg(feature = "discover")))]
+pub struct HealthScores<K> {
+    scores: Arc<Mutex<HashMap<K, HealthScore>>>,
+}
+
+// ===== impl HealthWeighted =====
+
+impl<S> HealthWeighted<S> {
+    /// Wraps a load measurement so that its load is scaled by `score`.
+    pub fn new(service: S, score: HealthScore) -> Self {
+        Self { service, score }
+    }
+
+    /// Returns the service's health score.
+    pub fn score(&self) -> &HealthScore {
+        &self.score
+    }
+
+    /// Get a reference to the inner service
+    pub fn get_ref(&self) -> &S {
+        &self.service
+    }
+
+    /// Get a mutable reference to the inner service
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.service
+    }
+
+    /// Consume `self`, returning the inner service
+    pub fn into_inner(self) -> S {
+        self.service
+    }
+}
+
+impl<S> Load for HealthWeighted<S>
+where
+    S: Load,
+    S::Metric: Into<f64>,
+{
+    type Metric = f64;
+
+    fn load(&self) -> f64 {
+        let score = self.score.get();
+        if score == 0.0 {
+            return f64::INFINITY;
+        }
+        (self.service.load().into() + 1.0) / score
+    }
+}
+
+impl<S> Nested for HealthWeighted<S> {
+    type Inner = S;
+
+    fn inner(&self) -> &S {
+        &self.service
+    }
+}
+
+impl<S: InFlight> InFlight for HealthWeighted<S> {
+    fn in_flight(&self) -> usize {
+        self.service.in_flight()
+    }
+}
+
+/// Only the wrapped load measurements are inherited: the health score belongs to the endpoint,
+/// not to the service that is replaced.
+impl<S: Inherit> Inherit for HealthWeighted<S> {
+    fn inherit(&mut self, prior: &Self) {
+        self.service.inherit(&prior.service);
+    }
+}
+
+impl<S, Request> Service<Request> for HealthWeighted<S>
+where
+    S: Service<Request>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        self.service.call(req)
+    }
+}
+
+// ===== impl HealthScore =====
+
+impl HealthScore {
+    /// Creates a handle to a new health score.
+    ///
+    /// `score` is clamped between 0.0 and 1.0.
+    pub fn new(score: f64) -> Self {
+        Self(Arc::new(AtomicU64::new(clamp(score).to_bits())))
+    }
+
+    /// Returns the current score.
+    pub fn get(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    /// Updates the score.
+    ///
+    /// `score` is clamped between 0.0 and 1.0. A NaN score is treated as 0.0.
+    pub fn set(&self, score: f64) {
+        self.0.store(clamp(score).to_bits(), Ordering::Relaxed);
+    }
+}
+
+impl Default for HealthScore {
+    /// Returns a handle to a new score of 1.0.
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+impl fmt::Debug for HealthScore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("HealthScore").field(&self.get()).finish()
+    }
+}
+
+fn clamp(score: f64) -> f64 {
+    if score.is_nan() {
+        return 0.0;
+    }
+    score.clamp(0.0, 1.0)
+}
+
+// ===== impl HealthWeightedDiscover =====
+
+#[cfg(feature = "discover")]
+impl<D: Discover> HealthWeightedDiscover<D> {
+    /// Wraps a [`Discover`], wrapping all of its services with [`HealthWeighted`], using the
+    /// scores in `scores`.
+    pub fn new(discover: D, scores: HealthScores<D::Key>) -> Self {
+        Self { discover, scores }
+    }
+
+    /// Returns the registry of health scores for the discovered services.
+    pub fn scores(&self) -> &HealthScores<D::Key> {
+        &self.scores
+    }
+}
+
+#[cfg(feature = "discover")]
+impl<D> Stream for HealthWeightedDiscover<D>
+where
+    D: Discover,
+    D::Key: Hash + Clone,
+{
+    type Item = Result<Change<D::Key, HealthWeighted<D::Service>>, D::Error>;
+
+    /// Yields the next discovery change set.
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        use self::Change::*;
+
+        let this = self.project();
+        let change = match ready!(this.discover.poll_discover(cx)).transpose()? {
+            None => return Poll::Ready(None),
+            Some(Insert(k, svc)) => {
+                let score = this.scores.handle(&k);
+                Insert(k, HealthWeighted::new(svc, score))
+            }
+            Some(Remove(k)) => {
+                this.scores.remove(&k);
+                Remove(k)
+            }
+        };
+
+        Poll::Ready(Some(Ok(change)))
+    }
+}
+
+// ===== impl HealthScores =====
+
+#[cfg(feature = "discover")]
+impl<K: Hash + Eq + Clone> HealthScores<K> {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self {
+            scores: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Sets the health score of the endpoint identified by `key`.
+    ///
+    /// `score` is clamped between 0.0 and 1.0.
+    pub fn set(&self, key: &K, score: f64) {
+        self.handle(key).set(score);
+    }
+
+    /// Returns the health score of the endpoint identified by `key`.
+    pub fn get(&self, key: &K) -> f64 {
+        let scores = self.scores.lock().expect("health scores poisoned");
+        scores.get(key).map(HealthScore::get).unwrap_or(1.0)
+    }
+
+    /// Returns a handle to the health score of the endpoint identified by `key`.
+    pub fn handle(&self, key: &K) -> HealthScore {
+        let mut scores = self.scores.lock().expect("health scores poisoned");
+        scores.entry(key.clone()).or_default().clone()
+    }
+
+    fn remove(&self, key: &K) {
+        self.scores
+            .lock()
+            .expect("health scores poisoned")
+            .remove(key);
+    }
+}
+
+#[cfg(feature = "discover")]
+impl<K: Hash + Eq + Clone> Default for HealthScores<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "discover")]
+impl<K> Clone for HealthScores<K> {
+    fn clone(&self) -> Self {
+        Self {
+            scores: self.scores.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "discover")]
+impl<K> fmt::Debug for HealthScores<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HealthScores").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load::Constant;
+
+    #[test]
+    fn scales_load_by_score() {
+        let healthy = HealthWeighted::new(Constant::new((), 1.0), HealthScore::new(1.0));
+        let degraded = HealthWeighted::new(Constant::new((), 1.0), HealthScore::new(0.5));
+        assert_eq!(healthy.load(), 2.0);
+        assert_eq!(degraded.load(), 4.0);
+
+        degraded.score().set(0.0);
+        assert_eq!(degraded.load(), f64::INFINITY);
+
+        degraded.score().set(2.0);
+        assert_eq!(degraded.load(), healthy.load());
+    }
+
+    #[test]
+    fn idle_endpoints_are_weighted() {
+        let healthy = HealthWeighted::new(Constant::new((), 0.0), HealthScore::new(1.0));
+        let degraded = HealthWeighted::new(Constant::new((), 0.0), HealthScore::new(0.25));
+        assert!(healthy.load() < degraded.load());
+    }
+
+    #[cfg(feature = "discover")]
+    #[test]
+    fn discover_looks_up_scores_by_key() {
+        use futures_util::{stream, StreamExt};
+        use std::convert::Infallible;
+
+        let scores = HealthScores::new();
+        scores.set(&1, 0.5);
+
+        let changes = stream::iter(vec![
+            Ok::<_, Infallible>(Change::Insert(0, Constant::new((), 1.0))),
+            Ok(Change::Insert(1, Constant::new((), 1.0))),
+        ]);
+        let mut discover = HealthWeightedDiscover::new(changes, scores.clone());
+        let mut services = Vec::new();
+        while let Some(change) = tokio_test::block_on(discover.next()) {
+            match change.unwrap() {
+                Change::Insert(_, svc) => services.push(svc),
+                _ => unreachable!(),
+            }
+        }
+        assert_eq!(services[0].load(), 2.0);
+        assert_eq!(services[1].load(), 4.0);
+
+        scores.set(&0, 0.25);
+        assert_eq!(services[0].load(), 8.0);
+    }
+}
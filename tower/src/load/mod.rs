@@ -5,7 +5,17 @@
 //!
 //! - [`Constant`] — Always returns the same constant load value for a service.
 //! - [`PendingRequests`] — Measures load by tracking the number of in-flight requests.
+//! - [`PendingBytes`] — Measures load by tracking the size of in-flight requests.
 //! - [`PeakEwma`] — Measures load using a moving average of the peak latency for the service.
+//! - [`CompletionLatency`] — Measures load using a moving average of request completion times.
+//! - [`CombinedLoad`] — Combines the metrics of nested load measurements.
+//! - [`HealthWeighted`] — Scales another load measurement by an externally-provided health score.
+//! - [`RequestCost`] — Adds the estimated costs of in-flight requests to another load measurement.
+//!
+//! [`PendingRequests`], [`PendingBytes`], [`PeakEwma`], and [`CompletionLatency`] also implement
+//! the [`InFlight`] trait, which reports the number of requests a service is currently processing.
+//! [`PeakEwma`] and [`CompletionLatency`] implement the [`Inherit`] trait, which carries their
+//! latency estimates over to a service that replaces them.
 //!
 //! In general, you will want to use one of these when using the types in [`tower::balance`] which
 //! balance services depending on their load. Which load metric to use depends on your exact
@@ -59,20 +69,37 @@
 //! [`CompleteOnResponse`]: crate::load::completion::CompleteOnResponse
 // TODO: a custom completion example would be good here
 
+mod combined;
 pub mod completion;
+pub mod completion_latency;
 mod constant;
+pub mod health_weighted;
 pub mod peak_ewma;
+pub mod pending_bytes;
 pub mod pending_requests;
+pub mod request_cost;
 
 pub use self::{
+    combined::{CombinedLoad, Nested},
     completion::{CompleteOnResponse, TrackCompletion},
+    completion_latency::CompletionLatency,
     constant::Constant,
-    peak_ewma::PeakEwma,
+    health_weighted::{HealthScore, HealthWeighted},
+    peak_ewma::{PeakEwma, PeakEwmaConfig},
+    pending_bytes::PendingBytes,
     pending_requests::PendingRequests,
+    request_cost::RequestCost,
 };
 
 #[cfg(feature = "discover")]
-pub use self::{peak_ewma::PeakEwmaDiscover, pending_requests::PendingRequestsDiscover};
+pub use self::{
+    completion_latency::CompletionLatencyDiscover,
+    health_weighted::{HealthScores, HealthWeightedDiscover},
+    peak_ewma::PeakEwmaDiscover,
+    pending_bytes::PendingBytesDiscover,
+    pending_requests::PendingRequestsDiscover,
+    request_cost::RequestCostDiscover,
+};
 
 /// Types that implement this trait can give an estimate of how loaded they are.
 ///
@@ -87,3 +114,29 @@ pub trait Load {
     /// Estimate the service's current load.
     fn load(&self) -> Self::Metric;
 }
+
+/// Types that implement this trait can carry load measurements over from a service they replace.
+///
+/// This is used by [`Balance::with_inherited_load`] so that an endpoint whose service is updated
+/// by service discovery keeps its load history, rather than starting over as though it were a new
+/// endpoint.
+///
+/// [`Balance::with_inherited_load`]: crate::balance::p2c::Balance::with_inherited_load
+pub trait Inherit {
+    /// Adopts the load measurements of `prior`, the service that `self` replaces.
+    ///
+    /// Requests in flight on `prior` continue to be counted by `prior`, not by `self`.
+    fn inherit(&mut self, prior: &Self);
+}
+
+/// Types that implement this trait can report how many requests they are currently processing.
+///
+/// This is used, for instance, by [`Balance::with_drain_timeout`] to determine when an endpoint
+/// that has been removed from service discovery no longer has any requests in flight.
+///
+/// [`Balance::with_drain_timeout`]: crate::balance::p2c::Balance::with_drain_timeout
+pub trait InFlight {
+    /// Returns the number of requests that have been dispatched to the service but have not yet
+    /// completed.
+    fn in_flight(&self) -> usize;
+}
@@ -10,10 +10,14 @@ use pin_project::pin_project;
 use std::pin::Pin;
 
 use super::completion::{CompleteOnResponse, TrackCompletion, TrackCompletionFuture};
-use super::Load;
+use super::{InFlight, Inherit, Load, Nested};
 use std::task::{Context, Poll};
 use std::{
-    sync::{Arc, Mutex},
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
     time::Duration,
 };
 use tokio::time::Instant;
@@ -43,7 +47,7 @@ use tracing::trace;
 #[derive(Debug)]
 pub struct PeakEwma<S, C = CompleteOnResponse> {
     service: S,
-    decay_ns: f64,
+    config: PeakEwmaConfig,
     rtt_estimate: Arc<Mutex<RttEstimate>>,
     completion: C,
 }
@@ -56,11 +60,31 @@ pub struct PeakEwma<S, C = CompleteOnResponse> {
 pub struct PeakEwmaDiscover<D, C = CompleteOnResponse> {
     #[pin]
     discover: D,
-    decay_ns: f64,
-    default_rtt: Duration,
+    config: PeakEwmaConfig,
     completion: C,
 }
 
+/// A shared handle to the parameters used by [`PeakEwma`] load tracking.
+///
+/// Cloning a `PeakEwmaConfig` produces a handle to the same parameters, so
+/// updates made through any handle are observed by every [`PeakEwma`] service
+/// constructed with it. This allows latency sensitivity to be tuned while a
+/// balancer is running.
+///
+/// Changes to the decay period take effect for all subsequent load
+/// measurements and requests. Changes to the default RTT only apply to
+/// services constructed after the change, since existing services have
+/// already established an estimate.
+#[derive(Clone)]
+pub struct PeakEwmaConfig {
+    inner: Arc<Config>,
+}
+
+struct Config {
+    default_rtt_ns: AtomicU64,
+    decay_ns: AtomicU64,
+}
+
 /// Represents the relative cost of communicating with a service.
 ///
 /// The underlying value estimates the amount of pending work to a service: the Peak-EWMA
@@ -90,17 +114,45 @@ const NANOS_PER_MILLI: f64 = 1_000_000.0;
 impl<S, C> PeakEwma<S, C> {
     /// Wraps an `S`-typed service so that its load is tracked by the EWMA of its peak latency.
     pub fn new(service: S, default_rtt: Duration, decay_ns: f64, completion: C) -> Self {
+        let config = PeakEwmaConfig::from_nanos(nanos(default_rtt), decay_ns);
+        Self::with_config(service, config, completion)
+    }
+
+    /// Wraps an `S`-typed service so that its load is tracked by the EWMA of
+    /// its peak latency, using the parameters in a shared [`PeakEwmaConfig`].
+    pub fn with_config(service: S, config: PeakEwmaConfig, completion: C) -> Self {
+        let rtt_estimate = RttEstimate::new(config.default_rtt_ns());
         Self {
             service,
-            decay_ns,
-            rtt_estimate: Arc::new(Mutex::new(RttEstimate::new(nanos(default_rtt)))),
+            config,
+            rtt_estimate: Arc::new(Mutex::new(rtt_estimate)),
             completion,
         }
     }
 
+    /// Returns the configuration used by this service.
+    pub fn config(&self) -> &PeakEwmaConfig {
+        &self.config
+    }
+
+    /// Get a reference to the inner service
+    pub fn get_ref(&self) -> &S {
+        &self.service
+    }
+
+    /// Get a mutable reference to the inner service
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.service
+    }
+
+    /// Consume `self`, returning the inner service
+    pub fn into_inner(self) -> S {
+        self.service
+    }
+
     fn handle(&self) -> Handle {
         Handle {
-            decay_ns: self.decay_ns,
+            decay_ns: self.config.decay_ns(),
             sent_at: Instant::now(),
             rtt_estimate: self.rtt_estimate.clone(),
         }
@@ -150,10 +202,33 @@ impl<S, C> Load for PeakEwma<S, C> {
     }
 }
 
+impl<S, C> Nested for PeakEwma<S, C> {
+    type Inner = S;
+
+    fn inner(&self) -> &S {
+        &self.service
+    }
+}
+
+impl<S, C> InFlight for PeakEwma<S, C> {
+    fn in_flight(&self) -> usize {
+        Arc::strong_count(&self.rtt_estimate) - 1
+    }
+}
+
+impl<S, C> Inherit for PeakEwma<S, C> {
+    fn inherit(&mut self, prior: &Self) {
+        let prior = prior.rtt_estimate.lock().expect("peak ewma prior_estimate");
+        let mut rtt = self.rtt_estimate.lock().expect("peak ewma prior_estimate");
+        rtt.update_at = prior.update_at;
+        rtt.rtt_ns = prior.rtt_ns;
+    }
+}
+
 impl<S, C> PeakEwma<S, C> {
     fn update_estimate(&self) -> f64 {
         let mut rtt = self.rtt_estimate.lock().expect("peak ewma prior_estimate");
-        rtt.decay(self.decay_ns)
+        rtt.decay(self.config.decay_ns())
     }
 }
 
@@ -174,13 +249,30 @@ impl<D, C> PeakEwmaDiscover<D, C> {
         D::Service: Service<Request>,
         C: TrackCompletion<Handle, <D::Service as Service<Request>>::Response>,
     {
+        Self::with_config(
+            discover,
+            PeakEwmaConfig::new(default_rtt, decay),
+            completion,
+        )
+    }
+
+    /// Wraps a `D`-typed [`Discover`] so that services have a [`PeakEwma`] load
+    /// metric whose parameters are read from a shared [`PeakEwmaConfig`].
+    ///
+    /// All services produced by this discover share `config`, so updating it
+    /// affects every discovered endpoint.
+    pub fn with_config(discover: D, config: PeakEwmaConfig, completion: C) -> Self {
         PeakEwmaDiscover {
             discover,
-            decay_ns: nanos(decay),
-            default_rtt,
+            config,
             completion,
         }
     }
+
+    /// Returns the configuration shared by discovered services.
+    pub fn config(&self) -> &PeakEwmaConfig {
+        &self.config
+    }
 }
 
 #[cfg(feature = "discover")]
@@ -198,12 +290,8 @@ where
             None => return Poll::Ready(None),
             Some(Change::Remove(k)) => Change::Remove(k),
             Some(Change::Insert(k, svc)) => {
-                let peak_ewma = PeakEwma::new(
-                    svc,
-                    *this.default_rtt,
-                    *this.decay_ns,
-                    this.completion.clone(),
-                );
+                let peak_ewma =
+                    PeakEwma::with_config(svc, this.config.clone(), this.completion.clone());
                 Change::Insert(k, peak_ewma)
             }
         };
@@ -212,6 +300,70 @@ where
     }
 }
 
+// ===== impl PeakEwmaConfig =====
+
+impl PeakEwmaConfig {
+    /// Creates a new configuration.
+    ///
+    /// The `default_rtt` is used as the RTT estimate for services that have not
+    /// yet observed a response. The `decay` value determines over what time
+    /// period a RTT estimate should decay.
+    pub fn new(default_rtt: Duration, decay: Duration) -> Self {
+        Self::from_nanos(nanos(default_rtt), nanos(decay))
+    }
+
+    fn from_nanos(default_rtt_ns: f64, decay_ns: f64) -> Self {
+        Self {
+            inner: Arc::new(Config {
+                default_rtt_ns: AtomicU64::new(default_rtt_ns.to_bits()),
+                decay_ns: AtomicU64::new(decay_ns.to_bits()),
+            }),
+        }
+    }
+
+    /// Returns the default RTT estimate.
+    pub fn default_rtt(&self) -> Duration {
+        Duration::from_nanos(self.default_rtt_ns() as u64)
+    }
+
+    /// Sets the default RTT estimate used by services constructed after this
+    /// call.
+    pub fn set_default_rtt(&self, default_rtt: Duration) {
+        self.inner
+            .default_rtt_ns
+            .store(nanos(default_rtt).to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns the period over which RTT estimates decay.
+    pub fn decay(&self) -> Duration {
+        Duration::from_nanos(self.decay_ns() as u64)
+    }
+
+    /// Sets the period over which RTT estimates decay.
+    pub fn set_decay(&self, decay: Duration) {
+        self.inner
+            .decay_ns
+            .store(nanos(decay).to_bits(), Ordering::Relaxed);
+    }
+
+    fn default_rtt_ns(&self) -> f64 {
+        f64::from_bits(self.inner.default_rtt_ns.load(Ordering::Relaxed))
+    }
+
+    fn decay_ns(&self) -> f64 {
+        f64::from_bits(self.inner.decay_ns.load(Ordering::Relaxed))
+    }
+}
+
+impl fmt::Debug for PeakEwmaConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PeakEwmaConfig")
+            .field("default_rtt", &self.default_rtt())
+            .field("decay", &self.decay())
+            .finish()
+    }
+}
+
 // ===== impl RttEstimate =====
 
 impl RttEstimate {
@@ -295,6 +447,12 @@ impl Drop for Handle {
 
 // ===== impl Cost =====
 
+impl From<Cost> for f64 {
+    fn from(Cost(value): Cost) -> f64 {
+        value
+    }
+}
+
 // Utility that converts durations to nanos in f64.
 //
 // Due to a lossy transformation, the maximum value that can be represented is ~585 years,
@@ -392,6 +550,25 @@ mod tests {
         assert!(svc.load() < Cost(100_000.0));
     }
 
+    #[tokio::test]
+    async fn config_updates_apply_to_existing_services() {
+        time::pause();
+
+        let config = PeakEwmaConfig::new(Duration::from_millis(10), Duration::from_secs(1));
+        let svc = PeakEwma::with_config(Svc, config.clone(), CompleteOnResponse);
+        assert_eq!(svc.load(), Cost(10.0 * NANOS_PER_MILLI));
+
+        // With a much shorter decay period, the default estimate decays quickly.
+        config.set_decay(Duration::from_millis(10));
+        time::advance(Duration::from_millis(100)).await;
+        assert!(svc.load() < Cost(NANOS_PER_MILLI));
+
+        // New services use the updated default RTT.
+        config.set_default_rtt(Duration::from_millis(50));
+        let svc = PeakEwma::with_config(Svc, config, CompleteOnResponse);
+        assert_eq!(svc.load(), Cost(50.0 * NANOS_PER_MILLI));
+    }
+
     #[test]
     fn nanos() {
         assert_eq!(super::nanos(Duration::new(0, 0)), 0.0);
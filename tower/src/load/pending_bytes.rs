@@ -0,0 +1,318 @@
+//! A [`Load`] implementation that measures load using the number of in-flight bytes.
+
+#[cfg(feature = "discover")]
+use crate::discover::{Change, Discover};
+#[cfg(feature = "discover")]
+use futures_core::{ready, Stream};
+#[cfg(feature = "discover")]
+use pin_project::pin_project;
+#[cfg(feature = "discover")]
+use std::pin::Pin;
+
+use super::completion::{CompleteOnResponse, TrackCompletion, TrackCompletionFuture};
+use super::{InFlight, Load, Nested};
+use std::fmt;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::task::{Context, Poll};
+use tower_service::Service;
+
+/// Measures the load of the underlying service using the number of bytes in
+/// currently-pending requests.
+///
+/// Unlike [`PendingRequests`], which treats every request as equally costly,
+/// [`PendingBytes`] weighs each request by its size, as computed by a
+/// user-supplied sizer function. This is appropriate for workloads where the
+/// cost of a request varies greatly.
+///
+/// A request's bytes are counted from when it is dispatched until its
+/// [`Handle`] is dropped. A [`TrackCompletion`] implementation may also
+/// [record] the size of a response as it is received, so that large responses
+/// contribute to the load until they have been consumed.
+///
+/// [`PendingRequests`]: super::PendingRequests
+/// [record]: Handle::record
+pub struct PendingBytes<S, F, C = CompleteOnResponse> {
+    service: S,
+    sizer: F,
+    pending: Arc<AtomicUsize>,
+    completion: C,
+}
+
+/// Wraps a `D`-typed stream of discovered services with [`PendingBytes`].
+#[pin_project]
+#[cfg(feature = "discover")]
+#[cfg_attr(docsrs, doc(cfg(feature = "discover")))]
+pub struct PendingBytesDiscover<D, F, C = CompleteOnResponse> {
+    #[pin]
+    discover: D,
+    sizer: F,
+    completion: C,
+}
+
+/// Represents the number of bytes in currently-pending requests to a given
+/// service.
+#[derive(Clone, Copy, Debug, Default, PartialOrd, PartialEq, Ord, Eq)]
+pub struct Bytes(usize);
+
+/// Tracks the bytes of an in-flight request, releasing them when dropped.
+#[derive(Debug)]
+pub struct Handle {
+    bytes: usize,
+    pending: Arc<AtomicUsize>,
+}
+
+// ===== impl PendingBytes =====
+
+impl<S, F, C> PendingBytes<S, F, C> {
+    /// Wraps an `S`-typed service so that its load is tracked by the number of
+    /// bytes in pending requests, as computed by `sizer`.
+    pub fn new(service: S, sizer: F, completion: C) -> Self {
+        Self {
+            service,
+            sizer,
+            pending: Arc::new(AtomicUsize::new(0)),
+            completion,
+        }
+    }
+
+    /// Get a reference to the inner service
+    pub fn get_ref(&self) -> &S {
+        &self.service
+    }
+
+    /// Get a mutable reference to the inner service
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.service
+    }
+
+    /// Consume `self`, returning the inner service
+    pub fn into_inner(self) -> S {
+        self.service
+    }
+
+    fn handle(&self, bytes: usize) -> Handle {
+        self.pending.fetch_add(bytes, Ordering::AcqRel);
+        Handle {
+            bytes,
+            pending: self.pending.clone(),
+        }
+    }
+}
+
+impl<S, F, C> Load for PendingBytes<S, F, C> {
+    type Metric = Bytes;
+
+    fn load(&self) -> Bytes {
+        Bytes(self.pending.load(Ordering::Acquire))
+    }
+}
+
+impl<S, F, C> Nested for PendingBytes<S, F, C> {
+    type Inner = S;
+
+    fn inner(&self) -> &S {
+        &self.service
+    }
+}
+
+impl<S, F, C> InFlight for PendingBytes<S, F, C> {
+    fn in_flight(&self) -> usize {
+        // Count the number of handles that aren't held by `self`.
+        Arc::strong_count(&self.pending) - 1
+    }
+}
+
+impl<S, F, C, Request> Service<Request> for PendingBytes<S, F, C>
+where
+    S: Service<Request>,
+    F: Fn(&Request) -> usize,
+    C: TrackCompletion<Handle, S::Response>,
+{
+    type Response = C::Output;
+    type Error = S::Error;
+    type Future = TrackCompletionFuture<S::Future, C, Handle>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let handle = self.handle((self.sizer)(&req));
+        TrackCompletionFuture::new(self.completion.clone(), handle, self.service.call(req))
+    }
+}
+
+impl<S: fmt::Debug, F, C: fmt::Debug> fmt::Debug for PendingBytes<S, F, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PendingBytes")
+            .field("service", &self.service)
+            .field("pending", &self.pending)
+            .field("completion", &self.completion)
+            .finish()
+    }
+}
+
+// ===== impl PendingBytesDiscover =====
+
+#[cfg(feature = "discover")]
+impl<D, F, C> PendingBytesDiscover<D, F, C> {
+    /// Wraps a [`Discover`], wrapping all of its services with [`PendingBytes`].
+    pub fn new<Request>(discover: D, sizer: F, completion: C) -> Self
+    where
+        D: Discover,
+        D::Service: Service<Request>,
+        F: Fn(&Request) -> usize,
+        C: TrackCompletion<Handle, <D::Service as Service<Request>>::Response>,
+    {
+        Self {
+            discover,
+            sizer,
+            completion,
+        }
+    }
+}
+
+#[cfg(feature = "discover")]
+impl<D, F, C> Stream for PendingBytesDiscover<D, F, C>
+where
+    D: Discover,
+    F: Clone,
+    C: Clone,
+{
+    type Item = Result<Change<D::Key, PendingBytes<D::Service, F, C>>, D::Error>;
+
+    /// Yields the next discovery change set.
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        use self::Change::*;
+
+        let this = self.project();
+        let change = match ready!(this.discover.poll_discover(cx)).transpose()? {
+            None => return Poll::Ready(None),
+            Some(Insert(k, svc)) => Insert(
+                k,
+                PendingBytes::new(svc, this.sizer.clone(), this.completion.clone()),
+            ),
+            Some(Remove(k)) => Remove(k),
+        };
+
+        Poll::Ready(Some(Ok(change)))
+    }
+}
+
+#[cfg(feature = "discover")]
+impl<D: fmt::Debug, F, C: fmt::Debug> fmt::Debug for PendingBytesDiscover<D, F, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PendingBytesDiscover")
+            .field("discover", &self.discover)
+            .field("completion", &self.completion)
+            .finish()
+    }
+}
+
+// ===== impl Bytes =====
+
+impl From<Bytes> for usize {
+    fn from(Bytes(value): Bytes) -> usize {
+        value
+    }
+}
+
+impl From<Bytes> for f64 {
+    fn from(Bytes(value): Bytes) -> f64 {
+        value as f64
+    }
+}
+
+// ===== impl Handle =====
+
+impl Handle {
+    /// Adds `bytes` to the load of the service until this handle is dropped.
+    ///
+    /// This may be used by a [`TrackCompletion`] implementation to account for
+    /// the size of a response as it is received.
+    pub fn record(&mut self, bytes: usize) {
+        self.bytes += bytes;
+        self.pending.fetch_add(bytes, Ordering::AcqRel);
+    }
+
+    /// Returns the number of bytes tracked by this handle.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+}
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        self.pending.fetch_sub(self.bytes, Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::future;
+    use std::task::{Context, Poll};
+
+    struct Svc;
+    impl Service<&'static str> for Svc {
+        type Response = usize;
+        type Error = ();
+        type Future = future::Ready<Result<usize, ()>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), ()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: &'static str) -> Self::Future {
+            future::ok(req.len() * 2)
+        }
+    }
+
+    #[test]
+    fn default() {
+        let mut svc = PendingBytes::new(Svc, |req: &&str| req.len(), CompleteOnResponse);
+        assert_eq!(svc.load(), Bytes(0));
+
+        let rsp0 = svc.call("hello");
+        assert_eq!(svc.load(), Bytes(5));
+
+        let rsp1 = svc.call("hi");
+        assert_eq!(svc.load(), Bytes(7));
+        assert_eq!(svc.in_flight(), 2);
+
+        tokio_test::block_on(rsp0).unwrap();
+        assert_eq!(svc.load(), Bytes(2));
+
+        tokio_test::block_on(rsp1).unwrap();
+        assert_eq!(svc.load(), Bytes(0));
+        assert_eq!(svc.in_flight(), 0);
+    }
+
+    #[test]
+    fn with_response_size() {
+        #[derive(Clone)]
+        struct RecordResponse;
+        impl TrackCompletion<Handle, usize> for RecordResponse {
+            type Output = Handle;
+            fn track_completion(&self, mut handle: Handle, size: usize) -> Handle {
+                handle.record(size);
+                handle
+            }
+        }
+
+        let mut svc = PendingBytes::new(Svc, |req: &&str| req.len(), RecordResponse);
+
+        let rsp = svc.call("hello");
+        assert_eq!(svc.load(), Bytes(5));
+
+        let handle = tokio_test::block_on(rsp).unwrap();
+        assert_eq!(handle.bytes(), 15);
+        assert_eq!(svc.load(), Bytes(15));
+
+        drop(handle);
+        assert_eq!(svc.load(), Bytes(0));
+    }
+}
@@ -10,7 +10,7 @@ use pin_project::pin_project;
 use std::pin::Pin;
 
 use super::completion::{CompleteOnResponse, TrackCompletion, TrackCompletionFuture};
-use super::Load;
+use super::{InFlight, Load, Nested};
 use std::sync::Arc;
 use std::task::{Context, Poll};
 use tower_service::Service;
@@ -58,6 +58,21 @@ impl<S, C> PendingRequests<S, C> {
         }
     }
 
+    /// Get a reference to the inner service
+    pub fn get_ref(&self) -> &S {
+        &self.service
+    }
+
+    /// Get a mutable reference to the inner service
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.service
+    }
+
+    /// Consume `self`, returning the inner service
+    pub fn into_inner(self) -> S {
+        self.service
+    }
+
     fn handle(&self) -> Handle {
         Handle(self.ref_count.clone())
     }
@@ -72,6 +87,20 @@ impl<S, C> Load for PendingRequests<S, C> {
     }
 }
 
+impl<S, C> Nested for PendingRequests<S, C> {
+    type Inner = S;
+
+    fn inner(&self) -> &S {
+        &self.service
+    }
+}
+
+impl<S, C> InFlight for PendingRequests<S, C> {
+    fn in_flight(&self) -> usize {
+        self.ref_count.ref_count() - 1
+    }
+}
+
 impl<S, C, Request> Service<Request> for PendingRequests<S, C>
 where
     S: Service<Request>,
@@ -105,6 +134,12 @@ impl<D, C> PendingRequestsDiscover<D, C> {
         D::Service: Service<Request>,
         C: TrackCompletion<Handle, <D::Service as Service<Request>>::Response>,
     {
+        Self::wrap(discover, completion)
+    }
+
+    /// Like [`PendingRequestsDiscover::new`], for callers that cannot name
+    /// the request type.
+    pub(crate) fn wrap(discover: D, completion: C) -> Self {
         Self {
             discover,
             completion,
@@ -135,6 +170,20 @@ where
     }
 }
 
+// ===== impl Count =====
+
+impl From<Count> for usize {
+    fn from(Count(value): Count) -> usize {
+        value
+    }
+}
+
+impl From<Count> for f64 {
+    fn from(Count(value): Count) -> f64 {
+        value as f64
+    }
+}
+
 // ==== RefCount ====
 
 impl RefCount {
@@ -212,4 +261,43 @@ mod tests {
         drop(i0);
         assert_eq!(svc.load(), Count(0));
     }
+
+    #[test]
+    fn with_failure() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct Fail;
+        impl Service<()> for Fail {
+            type Response = ();
+            type Error = ();
+            type Future = future::Ready<Result<(), ()>>;
+
+            fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), ()>> {
+                Poll::Ready(Ok(()))
+            }
+
+            fn call(&mut self, (): ()) -> Self::Future {
+                future::err(())
+            }
+        }
+
+        #[derive(Clone, Default)]
+        struct CountFailures(Arc<AtomicUsize>);
+        impl TrackCompletion<Handle, ()> for CountFailures {
+            type Output = ();
+            fn track_completion(&self, _: Handle, (): ()) {}
+            fn track_failure(&self, _: Handle) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let failures = CountFailures::default();
+        let mut svc = PendingRequests::new(Fail, failures.clone());
+
+        let rsp = svc.call(());
+        assert_eq!(svc.load(), Count(1));
+        tokio_test::block_on(rsp).unwrap_err();
+        assert_eq!(svc.load(), Count(0));
+        assert_eq!(failures.0.load(Ordering::SeqCst), 1);
+    }
 }
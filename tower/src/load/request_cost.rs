@@ -0,0 +1,286 @@
+//! A [`Load`] implementation that adds the estimated costs of in-flight requests to another load
+//! measurement.
+
+#[cfg(feature = "discover")]
+use crate::discover::{Change, Discover};
+#[cfg(feature = "discover")]
+use futures_core::{ready, Stream};
+#[cfg(feature = "discover")]
+use pin_project::pin_project;
+#[cfg(feature = "discover")]
+use std::pin::Pin;
+
+use super::completion::{CompleteOnResponse, TrackCompletion, TrackCompletionFuture};
+use super::{InFlight, Inherit, Load, Nested};
+use std::fmt;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+use std::task::{Context, Poll};
+use tower_service::Service;
+
+/// Adds the estimated costs of a service's in-flight requests to the load of the service.
+///
+/// Each request's cost is estimated by a user-supplied function when it is dispatched, and is
+/// counted until its [`Handle`] is dropped. This allows a service serving known-heavy requests,
+/// such as large batch queries, to appear more loaded than its wrapped load measurement would
+/// indicate until those requests complete. Costs should be expressed in the same units as the
+/// wrapped load.
+///
+/// Unlike [`PendingBytes`], which measures load by the sizes of pending requests alone,
+/// [`RequestCost`] adds the costs to another load measurement.
+///
+/// [`PendingBytes`]: super::PendingBytes
+pub struct RequestCost<S, F, C = CompleteOnResponse> {
+    service: S,
+    cost: F,
+    pending: Arc<AtomicU64>,
+    completion: C,
+}
+
+/// Wraps a `D`-typed stream of discovered services with [`RequestCost`].
+#[pin_project]
+#[cfg(feature = "discover")]
+#[cfg_attr(docsrs, doc(cfg(feature = "discover")))]
+pub struct RequestCostDiscover<D, F, C = CompleteOnResponse> {
+    #[pin]
+    discover: D,
+    cost: F,
+    completion: C,
+}
+
+/// Tracks the cost of an in-flight request, releasing it when dropped.
+#[derive(Debug)]
+pub struct Handle {
+    cost: f64,
+    pending: Arc<AtomicU64>,
+}
+
+// ===== impl RequestCost =====
+
+impl<S, F, C> RequestCost<S, F, C> {
+    /// Wraps an `S`-typed load measurement so that the costs of its pending requests, as
+    /// estimated by `cost`, are added to its load.
+    pub fn new(service: S, cost: F, completion: C) -> Self {
+        Self {
+            service,
+            cost,
+            pending: Arc::new(AtomicU64::new(0f64.to_bits())),
+            completion,
+        }
+    }
+
+    /// Returns the total estimated cost of the service's pending requests.
+    pub fn pending_cost(&self) -> f64 {
+        f64::from_bits(self.pending.load(Ordering::Acquire))
+    }
+
+    /// Get a reference to the inner service
+    pub fn get_ref(&self) -> &S {
+        &self.service
+    }
+
+    /// Get a mutable reference to the inner service
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.service
+    }
+
+    /// Consume `self`, returning the inner service
+    pub fn into_inner(self) -> S {
+        self.service
+    }
+
+    fn handle(&self, cost: f64) -> Handle {
+        add(&self.pending, cost);
+        Handle {
+            cost,
+            pending: self.pending.clone(),
+        }
+    }
+}
+
+impl<S, F, C> Load for RequestCost<S, F, C>
+where
+    S: Load,
+    S::Metric: Into<f64>,
+{
+    type Metric = f64;
+
+    fn load(&self) -> f64 {
+        self.service.load().into() + self.pending_cost()
+    }
+}
+
+impl<S, F, C> Nested for RequestCost<S, F, C> {
+    type Inner = S;
+
+    fn inner(&self) -> &S {
+        &self.service
+    }
+}
+
+impl<S: InFlight, F, C> InFlight for RequestCost<S, F, C> {
+    fn in_flight(&self) -> usize {
+        self.service.in_flight()
+    }
+}
+
+/// Only the wrapped load measurements are inherited: requests in flight on the replaced service
+/// continue to count against it.
+impl<S: Inherit, F, C> Inherit for RequestCost<S, F, C> {
+    fn inherit(&mut self, prior: &Self) {
+        self.service.inherit(&prior.service);
+    }
+}
+
+impl<S, F, C, Request> Service<Request> for RequestCost<S, F, C>
+where
+    S: Service<Request>,
+    F: Fn(&Request) -> f64,
+    C: TrackCompletion<Handle, S::Response>,
+{
+    type Response = C::Output;
+    type Error = S::Error;
+    type Future = TrackCompletionFuture<S::Future, C, Handle>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let handle = self.handle((self.cost)(&req));
+        TrackCompletionFuture::new(self.completion.clone(), handle, self.service.call(req))
+    }
+}
+
+impl<S: fmt::Debug, F, C: fmt::Debug> fmt::Debug for RequestCost<S, F, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestCost")
+            .field("service", &self.service)
+            .field("pending", &self.pending_cost())
+            .field("completion", &self.completion)
+            .finish()
+    }
+}
+
+// ===== impl RequestCostDiscover =====
+
+#[cfg(feature = "discover")]
+impl<D, F, C> RequestCostDiscover<D, F, C> {
+    /// Wraps a [`Discover`], wrapping all of its services with [`RequestCost`].
+    pub fn new<Request>(discover: D, cost: F, completion: C) -> Self
+    where
+        D: Discover,
+        D::Service: Service<Request>,
+        F: Fn(&Request) -> f64,
+        C: TrackCompletion<Handle, <D::Service as Service<Request>>::Response>,
+    {
+        Self {
+            discover,
+            cost,
+            completion,
+        }
+    }
+}
+
+#[cfg(feature = "discover")]
+impl<D, F, C> Stream for RequestCostDiscover<D, F, C>
+where
+    D: Discover,
+    F: Clone,
+    C: Clone,
+{
+    type Item = Result<Change<D::Key, RequestCost<D::Service, F, C>>, D::Error>;
+
+    /// Yields the next discovery change set.
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        use self::Change::*;
+
+        let this = self.project();
+        let change = match ready!(this.discover.poll_discover(cx)).transpose()? {
+            None => return Poll::Ready(None),
+            Some(Insert(k, svc)) => Insert(
+                k,
+                RequestCost::new(svc, this.cost.clone(), this.completion.clone()),
+            ),
+            Some(Remove(k)) => Remove(k),
+        };
+
+        Poll::Ready(Some(Ok(change)))
+    }
+}
+
+#[cfg(feature = "discover")]
+impl<D: fmt::Debug, F, C: fmt::Debug> fmt::Debug for RequestCostDiscover<D, F, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestCostDiscover")
+            .field("discover", &self.discover)
+            .field("completion", &self.completion)
+            .finish()
+    }
+}
+
+// ===== impl Handle =====
+
+impl Handle {
+    /// Returns the estimated cost of the request tracked by this handle.
+    pub fn cost(&self) -> f64 {
+        self.cost
+    }
+}
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        add(&self.pending, -self.cost);
+    }
+}
+
+fn add(pending: &AtomicU64, delta: f64) {
+    let _ = pending.fetch_update(Ordering::AcqRel, Ordering::Acquire, |bits| {
+        // Guard against rounding errors leaving a small negative cost.
+        Some((f64::from_bits(bits) + delta).max(0.0).to_bits())
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load::Constant;
+    use futures_util::future;
+    use std::task::{Context, Poll};
+
+    struct Svc;
+    impl Service<u32> for Svc {
+        type Response = ();
+        type Error = ();
+        type Future = future::Ready<Result<(), ()>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), ()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _: u32) -> Self::Future {
+            future::ok(())
+        }
+    }
+
+    #[test]
+    fn default() {
+        let svc = Constant::new(Svc, 1.0);
+        let mut svc = RequestCost::new(svc, |req: &u32| *req as f64, CompleteOnResponse);
+        assert_eq!(svc.load(), 1.0);
+
+        let rsp0 = svc.call(10);
+        assert_eq!(svc.load(), 11.0);
+
+        let rsp1 = svc.call(2);
+        assert_eq!(svc.load(), 13.0);
+
+        tokio_test::block_on(rsp0).unwrap();
+        assert_eq!(svc.load(), 3.0);
+
+        tokio_test::block_on(rsp1).unwrap();
+        assert_eq!(svc.load(), 1.0);
+    }
+}
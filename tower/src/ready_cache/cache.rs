@@ -5,6 +5,7 @@ use futures_core::Stream;
 use futures_util::stream::FuturesUnordered;
 pub use indexmap::Equivalent;
 use indexmap::IndexMap;
+use slab::Slab;
 use std::future::Future;
 use std::hash::Hash;
 use std::pin::Pin;
@@ -53,6 +54,14 @@ use tracing::{debug, trace};
 /// service. In such a case, it should be noted that calls to
 /// [`ReadyCache::poll_pending`] and [`ReadyCache::evict`] may perturb the order of
 /// the ready set, so any cached indexes should be discarded after such a call.
+///
+/// Ready services are stored in slots that are stable for as long as the
+/// service remains ready, so removing a service never moves the others. By
+/// default, the ready set's indexes are updated in constant time, which moves
+/// the last ready service into the removed service's index. When a
+/// reproducible ordering is more important than the cost of removal (e.g., in
+/// tests and simulations), [`ReadyCache::preserve_order`] can be used so that
+/// removals preserve the relative order of the remaining ready services.
 #[derive(Debug)]
 pub struct ReadyCache<K, S, Req>
 where
@@ -60,16 +69,33 @@ where
 {
     /// A stream of services that are not yet ready.
     pending: FuturesUnordered<Pending<K, S, Req>>,
-    /// An index of cancelation handles for pending streams.
-    pending_cancel_txs: IndexMap<K, CancelTx>,
+    /// An index of cancelation handles for pending streams, along with the ID
+    /// of the pending service that each handle cancels.
+    ///
+    /// A key's prior service may remain in the pending set after it has been
+    /// replaced, until it observes its cancelation; the ID distinguishes the
+    /// replacement from the prior service.
+    pending_cancel_txs: IndexMap<K, (CancelTx, u64)>,
+
+    /// The ID of the next service added to the pending set.
+    next_pending_id: u64,
 
     /// Services that have previously become ready. Readiness can become stale,
     /// so a given service should be polled immediately before use.
     ///
-    /// The cancelation oneshot is preserved (though unused) while the service is
-    /// ready so that it need not be reallocated each time a request is
-    /// dispatched.
-    ready: IndexMap<K, (S, CancelPair)>,
+    /// Services are not moved while they are ready, so removals only update
+    /// the indexes below.
+    ready: Slab<Ready<K, S>>,
+
+    /// The slot of each ready service, by key.
+    ready_slots: IndexMap<K, usize>,
+
+    /// The slots of the ready services, in the order of their indexes.
+    ready_order: Vec<usize>,
+
+    /// Whether removals from the ready set should preserve the order of the
+    /// remaining services.
+    preserve_order: bool,
 }
 
 // Safety: This is safe because we do not use `Pin::new_unchecked`.
@@ -79,6 +105,20 @@ type CancelRx = oneshot::Receiver<()>;
 type CancelTx = oneshot::Sender<()>;
 type CancelPair = (CancelTx, CancelRx);
 
+/// A service in the ready set.
+///
+/// The cancelation oneshot is preserved (though unused) while the service is
+/// ready so that it need not be reallocated each time a request is
+/// dispatched.
+#[derive(Debug)]
+struct Ready<K, S> {
+    key: K,
+    svc: S,
+    cancel: CancelPair,
+    /// The service's position in `ReadyCache::ready_order`.
+    index: usize,
+}
+
 #[derive(Debug)]
 enum PendingError<K, E> {
     Canceled(K),
@@ -90,6 +130,7 @@ enum PendingError<K, E> {
 /// May fail due to cancelation, i.e. if the service is evicted from the balancer.
 #[derive(Debug)]
 struct Pending<K, S, Req> {
+    id: u64,
     key: Option<K>,
     cancel: Option<CancelRx>,
     ready: Option<S>,
@@ -105,9 +146,13 @@ where
 {
     fn default() -> Self {
         Self {
-            ready: IndexMap::default(),
+            ready: Slab::new(),
+            ready_slots: IndexMap::default(),
+            ready_order: Vec::new(),
             pending: FuturesUnordered::new(),
             pending_cancel_txs: IndexMap::default(),
+            next_pending_id: 0,
+            preserve_order: false,
         }
     }
 }
@@ -116,6 +161,14 @@ impl<K, S, Req> ReadyCache<K, S, Req>
 where
     K: Eq + Hash,
 {
+    /// Configures whether removals from the ready set preserve the relative
+    /// order of the remaining ready services.
+    ///
+    /// Preserving order makes removals linear in the size of the ready set.
+    pub fn preserve_order(&mut self, preserve: bool) {
+        self.preserve_order = preserve;
+    }
+
     /// Returns the total number of services in the cache.
     pub fn len(&self) -> usize {
         self.ready_len() + self.pending_len()
@@ -123,12 +176,12 @@ where
 
     /// Returns whether or not there are any services in the cache.
     pub fn is_empty(&self) -> bool {
-        self.ready.is_empty() && self.pending.is_empty()
+        self.ready_order.is_empty() && self.pending.is_empty()
     }
 
     /// Returns the number of services in the ready set.
     pub fn ready_len(&self) -> usize {
-        self.ready.len()
+        self.ready_order.len()
     }
 
     /// Returns the number of services in the unready set.
@@ -137,33 +190,64 @@ where
     }
 
     /// Returns true iff the given key is in the unready set.
-    pub fn pending_contains<Q: Hash + Equivalent<K>>(&self, key: &Q) -> bool {
+    pub fn pending_contains<Q: Hash + Equivalent<K> + ?Sized>(&self, key: &Q) -> bool {
         self.pending_cancel_txs.contains_key(key)
     }
 
     /// Obtains a reference to a service in the ready set by key.
-    pub fn get_ready<Q: Hash + Equivalent<K>>(&self, key: &Q) -> Option<(usize, &K, &S)> {
-        self.ready.get_full(key).map(|(i, k, v)| (i, k, &v.0))
+    pub fn get_ready<Q: Hash + Equivalent<K> + ?Sized>(&self, key: &Q) -> Option<(usize, &K, &S)> {
+        let slot = *self.ready_slots.get(key)?;
+        let ready = &self.ready[slot];
+        Some((ready.index, &ready.key, &ready.svc))
     }
 
     /// Obtains a mutable reference to a service in the ready set by key.
-    pub fn get_ready_mut<Q: Hash + Equivalent<K>>(
+    pub fn get_ready_mut<Q: Hash + Equivalent<K> + ?Sized>(
         &mut self,
         key: &Q,
     ) -> Option<(usize, &K, &mut S)> {
-        self.ready
-            .get_full_mut(key)
-            .map(|(i, k, v)| (i, k, &mut v.0))
+        let slot = *self.ready_slots.get(key)?;
+        let ready = &mut self.ready[slot];
+        Some((ready.index, &ready.key, &mut ready.svc))
     }
 
     /// Obtains a reference to a service in the ready set by index.
     pub fn get_ready_index(&self, idx: usize) -> Option<(&K, &S)> {
-        self.ready.get_index(idx).map(|(k, v)| (k, &v.0))
+        let ready = &self.ready[*self.ready_order.get(idx)?];
+        Some((&ready.key, &ready.svc))
     }
 
     /// Obtains a mutable reference to a service in the ready set by index.
     pub fn get_ready_index_mut(&mut self, idx: usize) -> Option<(&mut K, &mut S)> {
-        self.ready.get_index_mut(idx).map(|(k, v)| (k, &mut v.0))
+        let ready = &mut self.ready[*self.ready_order.get(idx)?];
+        Some((&mut ready.key, &mut ready.svc))
+    }
+
+    /// Iterates over the services in the ready set.
+    pub fn iter_ready(&self) -> impl Iterator<Item = (&K, &S)> + '_ {
+        self.ready_order.iter().map(move |&slot| {
+            let ready = &self.ready[slot];
+            (&ready.key, &ready.svc)
+        })
+    }
+
+    /// Iterates over the services in the pending set.
+    ///
+    /// Services that have been evicted are not yielded, even if they have not
+    /// yet been dropped by [`ReadyCache::poll_pending`]. However, if a pending
+    /// service has been replaced by [`ReadyCache::push`], both the prior
+    /// service and its replacement may be yielded until
+    /// [`ReadyCache::poll_pending`] is called.
+    pub fn iter_pending(&self) -> impl Iterator<Item = (&K, &S)> + '_ {
+        self.pending.iter().filter_map(move |p| {
+            let key = p.key.as_ref()?;
+            let svc = p.ready.as_ref()?;
+            if self.pending_cancel_txs.contains_key(key) {
+                Some((key, svc))
+            } else {
+                None
+            }
+        })
     }
 
     /// Evicts an item from the cache.
@@ -173,18 +257,80 @@ where
     /// Services are dropped from the ready set immediately. Services in the
     /// pending set are marked for cancellation, but [`ReadyCache::poll_pending`]
     /// must be called to cause the service to be dropped.
-    pub fn evict<Q: Hash + Equivalent<K>>(&mut self, key: &Q) -> bool {
-        let canceled = if let Some(c) = self.pending_cancel_txs.swap_remove(key) {
+    pub fn evict<Q: Hash + Equivalent<K> + ?Sized>(&mut self, key: &Q) -> bool {
+        let canceled = if let Some((c, _)) = self.pending_cancel_txs.swap_remove(key) {
             c.send(()).expect("cancel receiver lost");
             true
         } else {
             false
         };
 
-        self.ready
-            .swap_remove_full(key)
-            .map(|_| true)
-            .unwrap_or(canceled)
+        self.remove_ready(key).map(|_| true).unwrap_or(canceled)
+    }
+
+    /// Removes an item from the cache, returning the removed service.
+    ///
+    /// Unlike [`ReadyCache::evict`], this returns ownership of the service to
+    /// the caller, whether it was in the ready set or the pending set. This
+    /// may be used, for instance, to keep a service alive until its in-flight
+    /// requests complete.
+    ///
+    /// Note that removing a pending service requires a scan of the pending
+    /// set.
+    pub fn remove<Q: Hash + Equivalent<K> + ?Sized>(&mut self, key: &Q) -> Option<S> {
+        if let Some((c, id)) = self.pending_cancel_txs.swap_remove(key) {
+            c.send(()).expect("cancel receiver lost");
+            // The pending future will resolve as canceled when it is next
+            // polled, so the service may be taken from it. Prior services for
+            // the same key may still be pending, so the service is found by
+            // its ID rather than by its key.
+            return self
+                .pending
+                .iter_mut()
+                .find(|p| p.id == id)
+                .and_then(|p| p.ready.take());
+        }
+
+        self.remove_ready(key).map(|(_, (svc, _))| svc)
+    }
+
+    fn remove_ready<Q: Hash + Equivalent<K> + ?Sized>(
+        &mut self,
+        key: &Q,
+    ) -> Option<(K, (S, CancelPair))> {
+        let slot = self.ready_slots.swap_remove(key)?;
+        Some(self.remove_ready_slot(slot))
+    }
+
+    fn remove_ready_index(&mut self, index: usize) -> Option<(K, (S, CancelPair))> {
+        let slot = *self.ready_order.get(index)?;
+        self.ready_slots.swap_remove(&self.ready[slot].key);
+        Some(self.remove_ready_slot(slot))
+    }
+
+    /// Removes the service in `slot` from the ready set, updating the indexes
+    /// of the services that remain.
+    fn remove_ready_slot(&mut self, slot: usize) -> (K, (S, CancelPair)) {
+        let Ready {
+            key,
+            svc,
+            cancel,
+            index,
+        } = self.ready.remove(slot);
+
+        if self.preserve_order {
+            self.ready_order.remove(index);
+            for (i, &slot) in self.ready_order.iter().enumerate().skip(index) {
+                self.ready[slot].index = i;
+            }
+        } else {
+            self.ready_order.swap_remove(index);
+            if let Some(&moved) = self.ready_order.get(index) {
+                self.ready[moved].index = index;
+            }
+        }
+
+        (key, (svc, cancel))
     }
 }
 
@@ -211,11 +357,14 @@ where
     }
 
     fn push_pending(&mut self, key: K, svc: S, (cancel_tx, cancel_rx): CancelPair) {
-        if let Some(c) = self.pending_cancel_txs.insert(key.clone(), cancel_tx) {
+        let id = self.next_pending_id;
+        self.next_pending_id += 1;
+        if let Some((c, _)) = self.pending_cancel_txs.insert(key.clone(), (cancel_tx, id)) {
             // If there is already a service for this key, cancel it.
             c.send(()).expect("cancel receiver lost");
         }
         self.pending.push(Pending {
+            id,
             key: Some(key),
             cancel: Some(cancel_rx),
             ready: Some(svc),
@@ -223,6 +372,27 @@ where
         });
     }
 
+    /// Adds a service to the ready set, replacing the ready service with the
+    /// same key, if any, in place.
+    fn insert_ready(&mut self, key: K, svc: S, cancel: CancelPair) {
+        if let Some(&slot) = self.ready_slots.get(&key) {
+            let ready = &mut self.ready[slot];
+            ready.svc = svc;
+            ready.cancel = cancel;
+            return;
+        }
+
+        let index = self.ready_order.len();
+        let slot = self.ready.insert(Ready {
+            key: key.clone(),
+            svc,
+            cancel,
+            index,
+        });
+        self.ready_slots.insert(key, slot);
+        self.ready_order.push(slot);
+    }
+
     /// Polls services pending readiness, adding ready services to the ready set.
     ///
     /// Returns [`Poll::Ready`] when there are no remaining unready services.
@@ -245,10 +415,10 @@ where
                 Poll::Ready(Some(Ok((key, svc, cancel_rx)))) => {
                     trace!("endpoint ready");
                     let cancel_tx = self.pending_cancel_txs.swap_remove(&key);
-                    if let Some(cancel_tx) = cancel_tx {
+                    if let Some((cancel_tx, _)) = cancel_tx {
                         // Keep track of the cancelation so that it need not be
                         // recreated after the service is used.
-                        self.ready.insert(key, (svc, (cancel_tx, cancel_rx)));
+                        self.insert_ready(key, svc, (cancel_tx, cancel_rx));
                     } else {
                         // This should not technically be possible. We must have decided to cancel
                         // a Service (by sending on the CancelTx), yet that same service then
@@ -290,12 +460,12 @@ where
     ///
     /// Returns true if the endpoint is ready and false if it is not. An error is
     /// returned if the endpoint fails.
-    pub fn check_ready<Q: Hash + Equivalent<K>>(
+    pub fn check_ready<Q: Hash + Equivalent<K> + ?Sized>(
         &mut self,
         cx: &mut Context<'_>,
         key: &Q,
     ) -> Result<bool, error::Failed<K>> {
-        match self.ready.get_full_mut(key) {
+        match self.get_ready(key) {
             Some((index, _, _)) => self.check_ready_index(cx, index),
             None => Ok(false),
         }
@@ -312,18 +482,16 @@ where
         cx: &mut Context<'_>,
         index: usize,
     ) -> Result<bool, error::Failed<K>> {
-        let svc = match self.ready.get_index_mut(index) {
+        let svc = match self.get_ready_index_mut(index) {
             None => return Ok(false),
-            Some((_, (svc, _))) => svc,
+            Some((_, svc)) => svc,
         };
         match svc.poll_ready(cx) {
             Poll::Ready(Ok(())) => Ok(true),
             Poll::Pending => {
                 // became unready; so move it back there.
-                let (key, (svc, cancel)) = self
-                    .ready
-                    .swap_remove_index(index)
-                    .expect("invalid ready index");
+                let (key, (svc, cancel)) =
+                    self.remove_ready_index(index).expect("invalid ready index");
 
                 // If a new version of this service has been added to the
                 // unready set, don't overwrite it.
@@ -335,10 +503,7 @@ where
             }
             Poll::Ready(Err(e)) => {
                 // failed, so drop it.
-                let (key, _) = self
-                    .ready
-                    .swap_remove_index(index)
-                    .expect("invalid ready index");
+                let (key, _) = self.remove_ready_index(index).expect("invalid ready index");
                 Err(error::Failed(key, e.into()))
             }
         }
@@ -349,11 +514,8 @@ where
     /// # Panics
     ///
     /// If the specified key does not exist in the ready
-    pub fn call_ready<Q: Hash + Equivalent<K>>(&mut self, key: &Q, req: Req) -> S::Future {
-        let (index, _, _) = self
-            .ready
-            .get_full_mut(key)
-            .expect("check_ready was not called");
+    pub fn call_ready<Q: Hash + Equivalent<K> + ?Sized>(&mut self, key: &Q, req: Req) -> S::Future {
+        let (index, _, _) = self.get_ready(key).expect("check_ready was not called");
         self.call_ready_index(index, req)
     }
 
@@ -364,8 +526,7 @@ where
     /// If the specified index is out of range.
     pub fn call_ready_index(&mut self, index: usize, req: Req) -> S::Future {
         let (key, (mut svc, cancel)) = self
-            .ready
-            .swap_remove_index(index)
+            .remove_ready_index(index)
             .expect("check_ready_index was not called");
 
         let fut = svc.call(req);
@@ -399,12 +560,16 @@ where
             return Err(PendingError::Canceled(key)).into();
         }
 
-        match self
-            .ready
-            .as_mut()
-            .expect("polled after ready")
-            .poll_ready(cx)
-        {
+        let svc = match self.ready.as_mut() {
+            Some(svc) => svc,
+            None => {
+                // The service was taken by `ReadyCache::remove`.
+                let key = self.key.take().expect("polled after complete");
+                return Err(PendingError::Canceled(key)).into();
+            }
+        };
+
+        match svc.poll_ready(cx) {
             Poll::Pending => Poll::Pending,
             Poll::Ready(Ok(())) => {
                 let key = self.key.take().expect("polled after complete");
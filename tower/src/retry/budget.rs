@@ -1,11 +1,20 @@
 //! A retry "budget" for allowing only a certain amount of retries over time.
 
+use super::{
+    combinators::{WithBudget, WithBudgetFuture},
+    Policy,
+};
+use futures_core::ready;
+use pin_project::pin_project;
 use std::{
     fmt,
+    future::{self, Future, Ready},
+    pin::Pin,
     sync::{
         atomic::{AtomicIsize, Ordering},
-        Mutex,
+        Arc, Mutex,
     },
+    task::{Context, Poll},
     time::Duration,
 };
 use tokio::time::Instant;
@@ -27,6 +36,40 @@ pub struct Overdrawn {
     _inner: (),
 }
 
+/// A retry [`Policy`] that retries failed requests as long as a [`Budget`]
+/// permits it.
+///
+/// Each request that completes without being retried deposits into the
+/// budget, and each retry withdraws from it. This bounds the fraction of
+/// requests that are retried, so that a widespread failure cannot be amplified
+/// into a retry storm. Only errors for which the `is_retryable` function returns
+/// `true` are retried.
+///
+/// When used with a load balancer, such as [`p2c::Balance`], each retry
+/// selects an endpoint anew, so a request that fails on one endpoint may be
+/// transparently dispatched to another.
+///
+/// This is equivalent to applying [`PolicyExt::with_budget`] to a policy that
+/// retries the errors for which `is_retryable` returns `true`.
+///
+/// [`p2c::Balance`]: crate::balance::p2c::Balance
+/// [`PolicyExt::with_budget`]: super::PolicyExt::with_budget
+#[derive(Clone)]
+pub struct BudgetedRetry<F> {
+    policy: WithBudget<RetryIf<F>>,
+}
+
+/// The [`Future`] returned by [`BudgetedRetry`]'s [`Policy::retry`].
+#[pin_project]
+pub struct BudgetedRetryFuture<F> {
+    #[pin]
+    future: WithBudgetFuture<Ready<RetryIf<F>>>,
+}
+
+/// Retries the errors for which a function returns `true`.
+#[derive(Clone)]
+struct RetryIf<F>(F);
+
 #[derive(Debug)]
 struct Bucket {
     generation: Mutex<Generation>,
@@ -131,6 +174,11 @@ impl Budget {
             Err(Overdrawn { _inner: () })
         }
     }
+
+    /// Returns a withdrawal for a retry that was not made.
+    pub(super) fn refund(&self) {
+        self.bucket.put(self.withdraw_amount);
+    }
 }
 
 impl Default for Budget {
@@ -149,6 +197,89 @@ impl fmt::Debug for Budget {
     }
 }
 
+// ===== impl BudgetedRetry =====
+
+impl<F> BudgetedRetry<F> {
+    /// Creates a policy that retries errors for which `is_retryable` returns
+    /// `true`, as permitted by `budget`.
+    ///
+    /// The budget may be shared with other policies so that they draw from a
+    /// common allowance of retries.
+    pub fn new(budget: Arc<Budget>, is_retryable: F) -> Self {
+        BudgetedRetry {
+            policy: WithBudget::new(RetryIf(is_retryable), budget),
+        }
+    }
+
+    /// Returns the budget governing this policy's retries.
+    pub fn budget(&self) -> &Arc<Budget> {
+        self.policy.budget()
+    }
+}
+
+impl<F, Req, Res, E> Policy<Req, Res, E> for BudgetedRetry<F>
+where
+    F: Fn(&E) -> bool + Clone,
+    Req: Clone,
+{
+    type Future = BudgetedRetryFuture<F>;
+
+    fn retry(&self, req: &Req, result: Result<&Res, &E>) -> Option<Self::Future> {
+        let future = self.policy.retry(req, result)?;
+        Some(BudgetedRetryFuture { future })
+    }
+
+    fn clone_request(&self, req: &Req) -> Option<Req> {
+        Some(req.clone())
+    }
+}
+
+impl<F> fmt::Debug for BudgetedRetry<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BudgetedRetry")
+            .field("budget", self.budget())
+            .finish()
+    }
+}
+
+// ===== impl BudgetedRetryFuture =====
+
+impl<F> Future for BudgetedRetryFuture<F> {
+    type Output = BudgetedRetry<F>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let policy = ready!(self.project().future.poll(cx));
+        Poll::Ready(BudgetedRetry { policy })
+    }
+}
+
+impl<F> fmt::Debug for BudgetedRetryFuture<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BudgetedRetryFuture").finish()
+    }
+}
+
+// ===== impl RetryIf =====
+
+impl<F, Req, Res, E> Policy<Req, Res, E> for RetryIf<F>
+where
+    F: Fn(&E) -> bool + Clone,
+    Req: Clone,
+{
+    type Future = Ready<Self>;
+
+    fn retry(&self, _: &Req, result: Result<&Res, &E>) -> Option<Self::Future> {
+        match result {
+            Err(error) if (self.0)(error) => Some(future::ready(self.clone())),
+            _ => None,
+        }
+    }
+
+    fn clone_request(&self, req: &Req) -> Option<Req> {
+        Some(req.clone())
+    }
+}
+
 // ===== impl Bucket =====
 
 impl Bucket {
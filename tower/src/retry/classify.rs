@@ -0,0 +1,46 @@
+use super::Policy;
+use crate::classify::Classify;
+use std::future::{self, Ready};
+
+/// A [`Policy`] that retries errors that a [`Classify`] implementation
+/// classifies as [retryable].
+///
+/// Successful responses are never retried. Requests are cloned with
+/// [`Clone`]. Like any [`Policy`], this may be combined with attempt limits,
+/// budgets, and backoff using [`PolicyExt`].
+///
+/// [retryable]: crate::classify::ErrorClass::is_retryable
+/// [`PolicyExt`]: super::PolicyExt
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RetryableErrors<C> {
+    classify: C,
+}
+
+impl<C> RetryableErrors<C> {
+    /// Creates a policy that retries the errors that `classify` classifies as
+    /// retryable.
+    pub fn new(classify: C) -> Self {
+        RetryableErrors { classify }
+    }
+}
+
+impl<C, Req, Res, E> Policy<Req, Res, E> for RetryableErrors<C>
+where
+    C: Classify<E> + Clone,
+    Req: Clone,
+{
+    type Future = Ready<Self>;
+
+    fn retry(&self, _: &Req, result: Result<&Res, &E>) -> Option<Self::Future> {
+        let error = result.err()?;
+        if self.classify.classify(error).is_retryable() {
+            Some(future::ready(self.clone()))
+        } else {
+            None
+        }
+    }
+
+    fn clone_request(&self, req: &Req) -> Option<Req> {
+        Some(req.clone())
+    }
+}
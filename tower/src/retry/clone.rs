@@ -0,0 +1,81 @@
+use std::{
+    convert::Infallible,
+    fmt,
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Asynchronously clones requests that a [`Policy`] cannot clone.
+///
+/// [`Policy::clone_request`] must clone a request synchronously, before it is
+/// passed to the inner service. Some requests, such as HTTP requests with
+/// streaming bodies, can only be cloned by first consuming part of the
+/// request, for example by buffering the body up to a size limit.
+///
+/// A [`Retry`] configured with [`Retry::with_async_clone`] consults its
+/// [`AsyncClone`] whenever the policy returns [`None`] from
+/// [`Policy::clone_request`]. The request is dispatched once the returned
+/// future completes, so implementations should bound how long they wait (for
+/// example, by giving up on the clone once a deadline has passed) and resolve
+/// to `None` if the request cannot be cloned.
+///
+/// [`Policy`]: super::Policy
+/// [`Policy::clone_request`]: super::Policy::clone_request
+/// [`Retry`]: super::Retry
+/// [`Retry::with_async_clone`]: super::Retry::with_async_clone
+pub trait AsyncClone<Req> {
+    /// The [`Future`] type returned by [`AsyncClone::clone_request`].
+    ///
+    /// The future resolves to the request to dispatch, and optionally a
+    /// clone of it to retain for retries.
+    type Future: Future<Output = (Req, Option<Req>)>;
+
+    /// Starts cloning a request.
+    ///
+    /// If the request cannot be cloned, it is returned as an error, and it is
+    /// dispatched immediately without being retried.
+    fn clone_request(&self, req: Req) -> Result<Self::Future, Req>;
+}
+
+/// An [`AsyncClone`] that never clones requests.
+///
+/// This is the default for [`Retry`], which then only retries requests that
+/// its [`Policy`] can clone.
+///
+/// [`Retry`]: super::Retry
+/// [`Policy`]: super::Policy
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoAsyncClone;
+
+/// The [`Future`] returned by [`NoAsyncClone`]'s
+/// [`AsyncClone::clone_request`].
+///
+/// This type cannot be constructed.
+pub struct NoAsyncCloneFuture<Req> {
+    never: Infallible,
+    _req: PhantomData<fn() -> Req>,
+}
+
+impl<Req> AsyncClone<Req> for NoAsyncClone {
+    type Future = NoAsyncCloneFuture<Req>;
+
+    fn clone_request(&self, req: Req) -> Result<Self::Future, Req> {
+        Err(req)
+    }
+}
+
+impl<Req> Future for NoAsyncCloneFuture<Req> {
+    type Output = (Req, Option<Req>);
+
+    fn poll(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.never {}
+    }
+}
+
+impl<Req> fmt::Debug for NoAsyncCloneFuture<Req> {
+    fn fmt(&self, _: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.never {}
+    }
+}
@@ -0,0 +1,553 @@
+//! Combinators for assembling retry [`Policy`]s from smaller pieces.
+//!
+//! Rather than writing a single policy that tracks attempts, consults a
+//! [`Budget`], and waits between attempts, each of these concerns may be
+//! layered onto a simple policy that only classifies which results are
+//! retryable. The [`PolicyExt`] trait provides methods for doing so:
+//!
+//! ```
+//! use std::{sync::Arc, time::Duration};
+//! use tower::retry::{budget::Budget, Policy, PolicyExt};
+//! use futures_util::future;
+//!
+//! #[derive(Clone)]
+//! struct RetryErrors;
+//!
+//! type Req = String;
+//! type Res = String;
+//! type Error = std::io::Error;
+//!
+//! impl Policy<Req, Res, Error> for RetryErrors {
+//!     type Future = future::Ready<Self>;
+//!
+//!     fn retry(&self, _: &Req, result: Result<&Res, &Error>) -> Option<Self::Future> {
+//!         result.err().map(|_| future::ready(RetryErrors))
+//!     }
+//!
+//!     fn clone_request(&self, req: &Req) -> Option<Req> {
+//!         Some(req.clone())
+//!     }
+//! }
+//!
+//! let budget = Arc::new(Budget::new(Duration::from_secs(10), 10, 0.2));
+//! let policy = RetryErrors
+//!     .limit_attempts(3)
+//!     .with_budget(budget)
+//!     .with_backoff(|attempt| Duration::from_millis(10 * attempt as u64));
+//! ```
+//!
+//! [`Budget`]: super::budget::Budget
+
+use super::{budget::Budget, Policy};
+use futures_core::ready;
+use pin_project::pin_project;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::time::{sleep, Sleep};
+
+/// An extension trait for [`Policy`]s that provides a variety of convenient
+/// combinators.
+pub trait PolicyExt<Req, Res, E>: Policy<Req, Res, E> {
+    /// Retries a request only if both `self` and `other` would retry it.
+    ///
+    /// `other` is only consulted if `self` would retry the request. If `other`
+    /// would not retry it, the future returned by `self` is dropped without
+    /// being polled, so a [`WithBudget`] policy is not charged for the retry.
+    /// Requests are cloned by `self`, or by `other` if `self` cannot clone
+    /// them.
+    fn and<P>(self, other: P) -> And<Self, P>
+    where
+        P: Policy<Req, Res, E>,
+    {
+        And::new(self, other)
+    }
+
+    /// Retries a request if either `self` or `other` would retry it.
+    ///
+    /// `other` is only consulted if `self` would not retry the request. A
+    /// policy that does not retry the request, or is not consulted, keeps its
+    /// current state for the next attempt. Requests are cloned by `self`, or
+    /// by `other` if `self` cannot clone them.
+    fn or<P>(self, other: P) -> Or<Self, P>
+    where
+        P: Policy<Req, Res, E>,
+    {
+        Or::new(self, other)
+    }
+
+    /// Retries a request at most `max` times, as long as `self` would retry
+    /// it.
+    fn limit_attempts(self, max: usize) -> LimitAttempts<Self> {
+        LimitAttempts::new(self, max)
+    }
+
+    /// Retries a request only if `self` would retry it and `budget` permits
+    /// it.
+    ///
+    /// Each result that is not retried deposits into the budget, and each
+    /// retry withdraws from it. If the future returned by [`Policy::retry`] is
+    /// dropped before it completes, so that the retry is never made, the
+    /// withdrawal is refunded.
+    fn with_budget(self, budget: Arc<Budget>) -> WithBudget<Self> {
+        WithBudget::new(self, budget)
+    }
+
+    /// Waits before each retry of a request.
+    ///
+    /// The `backoff` function is called with the number of the retry being
+    /// made, starting at 1, and returns how long to wait before making it.
+    fn with_backoff<B>(self, backoff: B) -> WithBackoff<Self, B>
+    where
+        B: Fn(usize) -> Duration,
+    {
+        WithBackoff::new(self, backoff)
+    }
+}
+
+impl<P, Req, Res, E> PolicyExt<Req, Res, E> for P where P: Policy<Req, Res, E> {}
+
+/// A [`Policy`] that retries a request only if both of its policies would.
+///
+/// See [`PolicyExt::and`] for details.
+#[derive(Clone, Debug)]
+pub struct And<A, B> {
+    a: A,
+    b: B,
+}
+
+/// The [`Future`] returned by [`And`]'s [`Policy::retry`].
+#[pin_project]
+#[derive(Debug)]
+pub struct AndFuture<A: Future, B: Future> {
+    #[pin]
+    join: Join<A, B>,
+}
+
+/// A [`Policy`] that retries a request if either of its policies would.
+///
+/// See [`PolicyExt::or`] for details.
+#[derive(Clone, Debug)]
+pub struct Or<A, B> {
+    a: A,
+    b: B,
+}
+
+/// The [`Future`] returned by [`Or`]'s [`Policy::retry`].
+#[pin_project]
+#[derive(Debug)]
+pub struct OrFuture<A: Future, B: Future> {
+    #[pin]
+    join: Join<A, B>,
+}
+
+/// A [`Policy`] that limits the number of times a request is retried.
+///
+/// See [`PolicyExt::limit_attempts`] for details.
+#[derive(Clone, Debug)]
+pub struct LimitAttempts<P> {
+    policy: P,
+    remaining: usize,
+}
+
+/// The [`Future`] returned by [`LimitAttempts`]'s [`Policy::retry`].
+#[pin_project]
+#[derive(Debug)]
+pub struct LimitAttemptsFuture<F> {
+    #[pin]
+    future: F,
+    remaining: usize,
+}
+
+/// A [`Policy`] that only retries requests as permitted by a [`Budget`].
+///
+/// See [`PolicyExt::with_budget`] for details.
+#[derive(Clone, Debug)]
+pub struct WithBudget<P> {
+    policy: P,
+    budget: Arc<Budget>,
+}
+
+/// The [`Future`] returned by [`WithBudget`]'s [`Policy::retry`].
+///
+/// Dropping this future before it completes refunds the withdrawal made for
+/// the retry.
+#[pin_project(PinnedDrop)]
+#[derive(Debug)]
+pub struct WithBudgetFuture<F> {
+    #[pin]
+    future: F,
+    budget: Option<Arc<Budget>>,
+}
+
+/// A [`Policy`] that waits before each retry.
+///
+/// See [`PolicyExt::with_backoff`] for details.
+#[derive(Clone)]
+pub struct WithBackoff<P, B> {
+    policy: P,
+    backoff: B,
+    attempt: usize,
+}
+
+/// The [`Future`] returned by [`WithBackoff`]'s [`Policy::retry`].
+#[pin_project]
+pub struct WithBackoffFuture<F, B> {
+    #[pin]
+    sleep: Sleep,
+    #[pin]
+    future: F,
+    backoff: Option<B>,
+    attempt: usize,
+}
+
+/// Drives two futures to completion, holding the output of whichever
+/// completes first.
+#[pin_project]
+#[derive(Debug)]
+struct Join<A: Future, B: Future> {
+    #[pin]
+    a: MaybeDone<A>,
+    #[pin]
+    b: MaybeDone<B>,
+}
+
+#[pin_project(project = MaybeDoneProj)]
+enum MaybeDone<F: Future> {
+    Pending(#[pin] F),
+    Done(Option<F::Output>),
+}
+
+// ===== impl And =====
+
+impl<A, B> And<A, B> {
+    /// Creates a policy that retries a request only if both `a` and `b` would.
+    pub fn new(a: A, b: B) -> Self {
+        And { a, b }
+    }
+}
+
+impl<A, B, Req, Res, E> Policy<Req, Res, E> for And<A, B>
+where
+    A: Policy<Req, Res, E>,
+    B: Policy<Req, Res, E>,
+{
+    type Future = AndFuture<A::Future, B::Future>;
+
+    fn retry(&self, req: &Req, result: Result<&Res, &E>) -> Option<Self::Future> {
+        let a = self.a.retry(req, result)?;
+        let b = self.b.retry(req, result)?;
+        Some(AndFuture {
+            join: Join::new(MaybeDone::Pending(a), MaybeDone::Pending(b)),
+        })
+    }
+
+    fn clone_request(&self, req: &Req) -> Option<Req> {
+        self.a
+            .clone_request(req)
+            .or_else(|| self.b.clone_request(req))
+    }
+}
+
+impl<A, B> Future for AndFuture<A, B>
+where
+    A: Future,
+    B: Future,
+{
+    type Output = And<A::Output, B::Output>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let (a, b) = ready!(self.project().join.poll_join(cx));
+        Poll::Ready(And::new(a, b))
+    }
+}
+
+// ===== impl Or =====
+
+impl<A, B> Or<A, B> {
+    /// Creates a policy that retries a request if either `a` or `b` would.
+    pub fn new(a: A, b: B) -> Self {
+        Or { a, b }
+    }
+}
+
+impl<A, B, Req, Res, E> Policy<Req, Res, E> for Or<A, B>
+where
+    A: Policy<Req, Res, E> + Clone,
+    B: Policy<Req, Res, E> + Clone,
+{
+    type Future = OrFuture<A::Future, B::Future>;
+
+    fn retry(&self, req: &Req, result: Result<&Res, &E>) -> Option<Self::Future> {
+        let join = match self.a.retry(req, result) {
+            Some(a) => Join::new(MaybeDone::Pending(a), MaybeDone::Done(Some(self.b.clone()))),
+            None => {
+                let b = self.b.retry(req, result)?;
+                Join::new(MaybeDone::Done(Some(self.a.clone())), MaybeDone::Pending(b))
+            }
+        };
+        Some(OrFuture { join })
+    }
+
+    fn clone_request(&self, req: &Req) -> Option<Req> {
+        self.a
+            .clone_request(req)
+            .or_else(|| self.b.clone_request(req))
+    }
+}
+
+impl<A, B> Future for OrFuture<A, B>
+where
+    A: Future,
+    B: Future,
+{
+    type Output = Or<A::Output, B::Output>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let (a, b) = ready!(self.project().join.poll_join(cx));
+        Poll::Ready(Or::new(a, b))
+    }
+}
+
+// ===== impl LimitAttempts =====
+
+impl<P> LimitAttempts<P> {
+    /// Creates a policy that retries a request at most `max` times, as long as
+    /// `policy` would retry it.
+    pub fn new(policy: P, max: usize) -> Self {
+        LimitAttempts {
+            policy,
+            remaining: max,
+        }
+    }
+
+    /// Returns the number of retries that may still be made.
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+}
+
+impl<P, Req, Res, E> Policy<Req, Res, E> for LimitAttempts<P>
+where
+    P: Policy<Req, Res, E>,
+{
+    type Future = LimitAttemptsFuture<P::Future>;
+
+    fn retry(&self, req: &Req, result: Result<&Res, &E>) -> Option<Self::Future> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let future = self.policy.retry(req, result)?;
+        Some(LimitAttemptsFuture {
+            future,
+            remaining: self.remaining - 1,
+        })
+    }
+
+    fn clone_request(&self, req: &Req) -> Option<Req> {
+        self.policy.clone_request(req)
+    }
+}
+
+impl<F, P> Future for LimitAttemptsFuture<F>
+where
+    F: Future<Output = P>,
+{
+    type Output = LimitAttempts<P>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let policy = ready!(this.future.poll(cx));
+        Poll::Ready(LimitAttempts::new(policy, *this.remaining))
+    }
+}
+
+// ===== impl WithBudget =====
+
+impl<P> WithBudget<P> {
+    /// Creates a policy that retries a request only if `policy` would retry it
+    /// and `budget` permits it.
+    pub fn new(policy: P, budget: Arc<Budget>) -> Self {
+        WithBudget { policy, budget }
+    }
+
+    /// Returns the budget governing this policy's retries.
+    pub fn budget(&self) -> &Arc<Budget> {
+        &self.budget
+    }
+}
+
+impl<P, Req, Res, E> Policy<Req, Res, E> for WithBudget<P>
+where
+    P: Policy<Req, Res, E>,
+{
+    type Future = WithBudgetFuture<P::Future>;
+
+    fn retry(&self, req: &Req, result: Result<&Res, &E>) -> Option<Self::Future> {
+        let future = match self.policy.retry(req, result) {
+            Some(future) => future,
+            None => {
+                self.budget.deposit();
+                return None;
+            }
+        };
+
+        self.budget.withdraw().ok()?;
+        Some(WithBudgetFuture {
+            future,
+            budget: Some(self.budget.clone()),
+        })
+    }
+
+    fn clone_request(&self, req: &Req) -> Option<Req> {
+        self.policy.clone_request(req)
+    }
+}
+
+impl<F, P> Future for WithBudgetFuture<F>
+where
+    F: Future<Output = P>,
+{
+    type Output = WithBudget<P>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let policy = ready!(this.future.poll(cx));
+        let budget = this.budget.take().expect("polled after complete");
+        Poll::Ready(WithBudget::new(policy, budget))
+    }
+}
+
+#[pin_project::pinned_drop]
+impl<F> PinnedDrop for WithBudgetFuture<F> {
+    fn drop(self: Pin<&mut Self>) {
+        if let Some(budget) = self.project().budget.take() {
+            budget.refund();
+        }
+    }
+}
+
+// ===== impl WithBackoff =====
+
+impl<P, B> WithBackoff<P, B> {
+    /// Creates a policy that waits for the duration returned by `backoff`
+    /// before each retry that `policy` would make.
+    pub fn new(policy: P, backoff: B) -> Self {
+        WithBackoff {
+            policy,
+            backoff,
+            attempt: 0,
+        }
+    }
+}
+
+impl<P, B, Req, Res, E> Policy<Req, Res, E> for WithBackoff<P, B>
+where
+    P: Policy<Req, Res, E>,
+    B: Fn(usize) -> Duration + Clone,
+{
+    type Future = WithBackoffFuture<P::Future, B>;
+
+    fn retry(&self, req: &Req, result: Result<&Res, &E>) -> Option<Self::Future> {
+        let future = self.policy.retry(req, result)?;
+        let attempt = self.attempt + 1;
+        Some(WithBackoffFuture {
+            sleep: sleep((self.backoff)(attempt)),
+            future,
+            backoff: Some(self.backoff.clone()),
+            attempt,
+        })
+    }
+
+    fn clone_request(&self, req: &Req) -> Option<Req> {
+        self.policy.clone_request(req)
+    }
+}
+
+impl<P: fmt::Debug, B> fmt::Debug for WithBackoff<P, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WithBackoff")
+            .field("policy", &self.policy)
+            .field("attempt", &self.attempt)
+            .finish()
+    }
+}
+
+impl<F, P, B> Future for WithBackoffFuture<F, B>
+where
+    F: Future<Output = P>,
+{
+    type Output = WithBackoff<P, B>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        ready!(this.sleep.poll(cx));
+        let policy = ready!(this.future.poll(cx));
+        Poll::Ready(WithBackoff {
+            policy,
+            backoff: this.backoff.take().expect("polled after complete"),
+            attempt: *this.attempt,
+        })
+    }
+}
+
+impl<F: fmt::Debug, B> fmt::Debug for WithBackoffFuture<F, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WithBackoffFuture")
+            .field("sleep", &self.sleep)
+            .field("future", &self.future)
+            .field("attempt", &self.attempt)
+            .finish()
+    }
+}
+
+// ===== impl Join =====
+
+impl<A: Future, B: Future> Join<A, B> {
+    fn new(a: MaybeDone<A>, b: MaybeDone<B>) -> Self {
+        Join { a, b }
+    }
+
+    fn poll_join(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<(A::Output, B::Output)> {
+        let mut this = self.project();
+        let a = this.a.as_mut().poll_done(cx).is_ready();
+        let b = this.b.as_mut().poll_done(cx).is_ready();
+        if !(a && b) {
+            return Poll::Pending;
+        }
+        Poll::Ready((this.a.take_output(), this.b.take_output()))
+    }
+}
+
+impl<F: Future> MaybeDone<F> {
+    fn poll_done(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let output = match self.as_mut().project() {
+            MaybeDoneProj::Pending(future) => ready!(future.poll(cx)),
+            MaybeDoneProj::Done(_) => return Poll::Ready(()),
+        };
+        self.set(MaybeDone::Done(Some(output)));
+        Poll::Ready(())
+    }
+
+    fn take_output(self: Pin<&mut Self>) -> F::Output {
+        match self.project() {
+            MaybeDoneProj::Done(output) => output.take().expect("polled after complete"),
+            MaybeDoneProj::Pending(_) => unreachable!("future must be done"),
+        }
+    }
+}
+
+impl<F: Future + fmt::Debug> fmt::Debug for MaybeDone<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MaybeDone::Pending(future) => f.debug_tuple("Pending").field(future).finish(),
+            MaybeDone::Done(_) => f.debug_tuple("Done").finish(),
+        }
+    }
+}
@@ -1,61 +1,106 @@
 //! Future types
 
-use super::{Policy, Retry};
+use super::{AllRetryable, AsyncClone, NoAsyncClone, Policy, Retried, Retry};
 use futures_core::ready;
 use pin_project::pin_project;
 use std::future::Future;
 use std::pin::Pin;
 use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::Instant;
 use tower_service::Service;
 
 /// The [`Future`] returned by a [`Retry`] service.
 #[pin_project]
 #[derive(Debug)]
-pub struct ResponseFuture<P, S, Request>
+pub struct ResponseFuture<P, S, Request, C = NoAsyncClone, R = AllRetryable>
 where
     P: Policy<Request, S::Response, S::Error>,
     S: Service<Request>,
+    C: AsyncClone<Request>,
 {
+    /// The clone of the request retained for retries.
     request: Option<Request>,
+    /// The request to dispatch once the inner service is ready.
+    pending: Option<Request>,
     #[pin]
-    retry: Retry<P, S>,
+    retry: Retry<P, S, C, R>,
     #[pin]
-    state: State<S::Future, P::Future>,
+    state: State<S::Future, P::Future, C::Future>,
+    /// The number of times the request has been dispatched.
+    attempts: usize,
+    /// The total time spent between a failed attempt and the next.
+    delay: Duration,
+    /// When the policy decided to retry the current attempt.
+    failed_at: Option<Instant>,
 }
 
 #[pin_project(project = StateProj)]
 #[derive(Debug)]
-enum State<F, P> {
+enum State<F, P, C> {
     /// Polling the future from [`Service::call`]
     Called(#[pin] F),
     /// Polling the future from [`Policy::retry`]
     Checking(#[pin] P),
-    /// Polling [`Service::poll_ready`] after [`Checking`] was OK.
+    /// Polling the future from [`AsyncClone::clone_request`]
+    Cloning(#[pin] C),
+    /// Polling [`Service::poll_ready`] after the request was cloned.
     Retrying,
 }
 
-impl<P, S, Request> ResponseFuture<P, S, Request>
+impl<P, S, Request, C, R> ResponseFuture<P, S, Request, C, R>
 where
     P: Policy<Request, S::Response, S::Error>,
     S: Service<Request>,
+    C: AsyncClone<Request>,
 {
     pub(crate) fn new(
         request: Option<Request>,
-        retry: Retry<P, S>,
+        retry: Retry<P, S, C, R>,
         future: S::Future,
-    ) -> ResponseFuture<P, S, Request> {
+    ) -> ResponseFuture<P, S, Request, C, R> {
         ResponseFuture {
             request,
+            pending: None,
             retry,
             state: State::Called(future),
+            attempts: 1,
+            delay: Duration::ZERO,
+            failed_at: None,
         }
     }
+
+    pub(crate) fn cloning(
+        retry: Retry<P, S, C, R>,
+        cloning: C::Future,
+    ) -> ResponseFuture<P, S, Request, C, R> {
+        ResponseFuture {
+            request: None,
+            pending: None,
+            retry,
+            state: State::Cloning(cloning),
+            attempts: 0,
+            delay: Duration::ZERO,
+            failed_at: None,
+        }
+    }
+
+    /// Returns the number of times the request has been dispatched.
+    pub(crate) fn attempts(&self) -> usize {
+        self.attempts
+    }
+
+    /// Returns the total time spent waiting to retry the request.
+    pub(crate) fn delay(&self) -> Duration {
+        self.delay
+    }
 }
 
-impl<P, S, Request> Future for ResponseFuture<P, S, Request>
+impl<P, S, Request, C, R> Future for ResponseFuture<P, S, Request, C, R>
 where
     P: Policy<Request, S::Response, S::Error> + Clone,
     S: Service<Request> + Clone,
+    C: AsyncClone<Request>,
 {
     type Output = Result<S::Response, S::Error>;
 
@@ -69,6 +114,7 @@ where
                     if let Some(ref req) = this.request {
                         match this.retry.policy.retry(req, result.as_ref()) {
                             Some(checking) => {
+                                *this.failed_at = Some(Instant::now());
                                 this.state.set(State::Checking(checking));
                             }
                             None => return Poll::Ready(result),
@@ -84,6 +130,28 @@ where
                         .project()
                         .policy
                         .set(ready!(future.poll(cx)));
+                    let req = this
+                        .request
+                        .take()
+                        .expect("retrying requires cloned request");
+                    *this.request = this.retry.policy.clone_request(&req);
+                    if this.request.is_some() {
+                        *this.pending = Some(req);
+                        this.state.set(State::Retrying);
+                        continue;
+                    }
+                    match this.retry.clone.clone_request(req) {
+                        Ok(cloning) => this.state.set(State::Cloning(cloning)),
+                        Err(req) => {
+                            *this.pending = Some(req);
+                            this.state.set(State::Retrying);
+                        }
+                    }
+                }
+                StateProj::Cloning(future) => {
+                    let (req, cloned) = ready!(future.poll(cx));
+                    *this.request = cloned;
+                    *this.pending = Some(req);
                     this.state.set(State::Retrying);
                 }
                 StateProj::Retrying => {
@@ -99,11 +167,11 @@ where
                     // in Ready to make it Unpin so that we can get &mut Ready as needed to call
                     // poll_ready on it.
                     ready!(this.retry.as_mut().project().service.poll_ready(cx))?;
-                    let req = this
-                        .request
-                        .take()
-                        .expect("retrying requires cloned request");
-                    *this.request = this.retry.policy.clone_request(&req);
+                    let req = this.pending.take().expect("retrying requires a request");
+                    *this.attempts += 1;
+                    if let Some(failed_at) = this.failed_at.take() {
+                        *this.delay += failed_at.elapsed();
+                    }
                     this.state.set(State::Called(
                         this.retry.as_mut().project().service.call(req),
                     ));
@@ -112,3 +180,58 @@ where
         }
     }
 }
+
+/// The [`Future`] returned by a [`ReportRetries`] service.
+///
+/// [`ReportRetries`]: super::ReportRetries
+#[pin_project]
+pub struct RetriedFuture<P, S, Request, C = NoAsyncClone, R = AllRetryable>
+where
+    P: Policy<Request, S::Response, S::Error>,
+    S: Service<Request>,
+    C: AsyncClone<Request>,
+{
+    #[pin]
+    inner: ResponseFuture<P, S, Request, C, R>,
+}
+
+impl<P, S, Request, C, R> RetriedFuture<P, S, Request, C, R>
+where
+    P: Policy<Request, S::Response, S::Error>,
+    S: Service<Request>,
+    C: AsyncClone<Request>,
+{
+    pub(crate) fn new(inner: ResponseFuture<P, S, Request, C, R>) -> Self {
+        RetriedFuture { inner }
+    }
+}
+
+impl<P, S, Request, C, R> Future for RetriedFuture<P, S, Request, C, R>
+where
+    P: Policy<Request, S::Response, S::Error> + Clone,
+    S: Service<Request> + Clone,
+    C: AsyncClone<Request>,
+{
+    type Output = Result<Retried<S::Response>, S::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+        let response = ready!(this.inner.as_mut().poll(cx))?;
+        let inner = this.inner.as_ref().get_ref();
+        Poll::Ready(Ok(Retried::new(response, inner.attempts(), inner.delay())))
+    }
+}
+
+impl<P, S, Request, C, R> std::fmt::Debug for RetriedFuture<P, S, Request, C, R>
+where
+    P: Policy<Request, S::Response, S::Error>,
+    S: Service<Request>,
+    C: AsyncClone<Request>,
+    ResponseFuture<P, S, Request, C, R>: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetriedFuture")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
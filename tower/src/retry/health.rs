@@ -0,0 +1,116 @@
+//! Suppressing retries against unhealthy targets.
+//!
+//! Retrying requests against a target that is known to be failing (for
+//! instance, because a circuit breaker in front of it is open) only wastes
+//! attempts and delays failover. The [`Health`] trait allows such a component
+//! to report the state of its target, and [`SuppressUnhealthy`] wraps a
+//! [`Policy`] so that requests are not retried while the target is unhealthy.
+
+use super::Policy;
+use futures_core::ready;
+use pin_project::pin_project;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Reports whether a target is healthy enough for requests to be retried
+/// against it.
+///
+/// This is typically implemented by a circuit breaker, which should report that
+/// its target is unhealthy while the breaker is open.
+///
+/// This trait is implemented for closures returning `bool`.
+pub trait Health {
+    /// Returns `true` if requests may currently be retried against the target.
+    fn is_healthy(&self) -> bool;
+}
+
+/// A [`Policy`] that only retries requests while a [`Health`] probe reports
+/// that the target is healthy.
+///
+/// The probe is consulted before the inner policy, so unhealthy targets do not
+/// consume any state tracked by the inner policy (such as a retry [budget]).
+///
+/// [budget]: crate::retry::budget
+#[derive(Clone, Debug)]
+pub struct SuppressUnhealthy<P, H> {
+    policy: P,
+    health: H,
+}
+
+/// The [`Future`] returned by [`SuppressUnhealthy`]'s [`Policy::retry`].
+#[pin_project]
+#[derive(Debug)]
+pub struct SuppressUnhealthyFuture<F, H> {
+    #[pin]
+    future: F,
+    health: Option<H>,
+}
+
+// ===== impl Health =====
+
+impl<F> Health for F
+where
+    F: Fn() -> bool,
+{
+    fn is_healthy(&self) -> bool {
+        (self)()
+    }
+}
+
+// ===== impl SuppressUnhealthy =====
+
+impl<P, H> SuppressUnhealthy<P, H> {
+    /// Wraps `policy` so that requests are only retried while `health` reports
+    /// that the target is healthy.
+    pub fn new(policy: P, health: H) -> Self {
+        SuppressUnhealthy { policy, health }
+    }
+
+    /// Get a reference to the inner policy.
+    pub fn get_ref(&self) -> &P {
+        &self.policy
+    }
+}
+
+impl<P, H, Req, Res, E> Policy<Req, Res, E> for SuppressUnhealthy<P, H>
+where
+    P: Policy<Req, Res, E>,
+    H: Health + Clone,
+{
+    type Future = SuppressUnhealthyFuture<P::Future, H>;
+
+    fn retry(&self, req: &Req, result: Result<&Res, &E>) -> Option<Self::Future> {
+        if !self.health.is_healthy() {
+            return None;
+        }
+
+        let future = self.policy.retry(req, result)?;
+        Some(SuppressUnhealthyFuture {
+            future,
+            health: Some(self.health.clone()),
+        })
+    }
+
+    fn clone_request(&self, req: &Req) -> Option<Req> {
+        self.policy.clone_request(req)
+    }
+}
+
+// ===== impl SuppressUnhealthyFuture =====
+
+impl<F, P, H> Future for SuppressUnhealthyFuture<F, H>
+where
+    F: Future<Output = P>,
+{
+    type Output = SuppressUnhealthy<P, H>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let policy = ready!(this.future.poll(cx));
+        let health = this.health.take().expect("polled after complete");
+        Poll::Ready(SuppressUnhealthy::new(policy, health))
+    }
+}
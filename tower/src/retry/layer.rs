@@ -1,27 +1,43 @@
-use super::Retry;
+use super::{AllRetryable, Retry};
 use tower_layer::Layer;
 
 /// Retry requests based on a policy
 #[derive(Debug)]
-pub struct RetryLayer<P> {
+pub struct RetryLayer<P, R = AllRetryable> {
     policy: P,
+    retryable: R,
 }
 
 impl<P> RetryLayer<P> {
     /// Create a new [`RetryLayer`] from a retry policy
     pub fn new(policy: P) -> Self {
-        RetryLayer { policy }
+        RetryLayer {
+            policy,
+            retryable: AllRetryable,
+        }
     }
 }
 
-impl<P, S> Layer<S> for RetryLayer<P>
+impl<P, R> RetryLayer<P, R> {
+    /// Uses `retryable` to decide whether each request may be retried. See
+    /// [`Retry::with_retryable`].
+    pub fn with_retryable<R2>(self, retryable: R2) -> RetryLayer<P, R2> {
+        RetryLayer {
+            policy: self.policy,
+            retryable,
+        }
+    }
+}
+
+impl<P, R, S> Layer<S> for RetryLayer<P, R>
 where
     P: Clone,
+    R: Clone,
 {
-    type Service = Retry<P, S>;
+    type Service = Retry<P, S, super::NoAsyncClone, R>;
 
     fn layer(&self, service: S) -> Self::Service {
         let policy = self.policy.clone();
-        Retry::new(policy, service)
+        Retry::new(policy, service).with_retryable(self.retryable.clone())
     }
 }
@@ -1,12 +1,24 @@
 //! Middleware for retrying "failed" requests.
 
 pub mod budget;
+mod classify;
+mod clone;
+pub mod combinators;
 pub mod future;
+pub mod health;
 mod layer;
 mod policy;
+pub mod report;
+mod retryable;
 
+pub use self::classify::RetryableErrors;
+pub use self::clone::{AsyncClone, NoAsyncClone, NoAsyncCloneFuture};
+pub use self::combinators::PolicyExt;
+pub use self::health::{Health, SuppressUnhealthy};
 pub use self::layer::RetryLayer;
-pub use self::policy::Policy;
+pub use self::policy::{NeverRetry, NeverRetryFuture, Policy};
+pub use self::report::{ReportRetries, Retried};
+pub use self::retryable::{AllRetryable, Retryable};
 
 use self::future::ResponseFuture;
 use pin_project::pin_project;
@@ -15,13 +27,16 @@ use tower_service::Service;
 
 /// Configure retrying requests of "failed" responses.
 ///
-/// A [`Policy`] classifies what is a "failed" response.
+/// A [`Policy`] classifies what is a "failed" response. Individual requests
+/// may be exempted from retries with [`Retry::with_retryable`].
 #[pin_project]
 #[derive(Clone, Debug)]
-pub struct Retry<P, S> {
+pub struct Retry<P, S, C = NoAsyncClone, R = AllRetryable> {
     #[pin]
     policy: P,
     service: S,
+    clone: C,
+    retryable: R,
 }
 
 // ===== impl Retry =====
@@ -29,7 +44,47 @@ pub struct Retry<P, S> {
 impl<P, S> Retry<P, S> {
     /// Retry the inner service depending on this [`Policy`].
     pub fn new(policy: P, service: S) -> Self {
-        Retry { policy, service }
+        Retry {
+            policy,
+            service,
+            clone: NoAsyncClone,
+            retryable: AllRetryable,
+        }
+    }
+}
+
+impl<P, S, C, R> Retry<P, S, C, R> {
+    /// Uses `clone` to asynchronously clone requests that the [`Policy`]
+    /// cannot clone.
+    ///
+    /// A request that is cloned asynchronously is dispatched once its clone
+    /// completes, after driving a clone of the inner service to readiness.
+    pub fn with_async_clone<C2>(self, clone: C2) -> Retry<P, S, C2, R> {
+        Retry {
+            policy: self.policy,
+            service: self.service,
+            clone,
+            retryable: self.retryable,
+        }
+    }
+
+    /// Uses `retryable` to decide whether each request may be retried.
+    ///
+    /// Requests that `retryable` rejects are never retried, whatever the
+    /// [`Policy`] would decide. See [`Retryable`].
+    pub fn with_retryable<R2>(self, retryable: R2) -> Retry<P, S, C, R2> {
+        Retry {
+            policy: self.policy,
+            service: self.service,
+            clone: self.clone,
+            retryable,
+        }
+    }
+
+    /// Wraps `self` so that each response reports the retries made to
+    /// produce it. See [`ReportRetries`].
+    pub fn report_retries(self) -> ReportRetries<P, S, C, R> {
+        ReportRetries::new(self)
     }
 
     /// Get a reference to the inner service
@@ -48,14 +103,16 @@ impl<P, S> Retry<P, S> {
     }
 }
 
-impl<P, S, Request> Service<Request> for Retry<P, S>
+impl<P, S, C, R, Request> Service<Request> for Retry<P, S, C, R>
 where
     P: Policy<Request, S::Response, S::Error> + Clone,
     S: Service<Request> + Clone,
+    C: AsyncClone<Request> + Clone,
+    R: Retryable<Request> + Clone,
 {
     type Response = S::Response;
     type Error = S::Error;
-    type Future = ResponseFuture<P, S, Request>;
+    type Future = ResponseFuture<P, S, Request, C, R>;
 
     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
         // NOTE: the Future::poll impl for ResponseFuture assumes that Retry::poll_ready is
@@ -65,9 +122,23 @@ where
     }
 
     fn call(&mut self, request: Request) -> Self::Future {
+        if !self.retryable.is_retryable(&request) {
+            let future = self.service.call(request);
+            return ResponseFuture::new(None, self.clone(), future);
+        }
+
         let cloned = self.policy.clone_request(&request);
-        let future = self.service.call(request);
+        if cloned.is_some() {
+            let future = self.service.call(request);
+            return ResponseFuture::new(cloned, self.clone(), future);
+        }
 
-        ResponseFuture::new(cloned, self.clone(), future)
+        match self.clone.clone_request(request) {
+            Ok(cloning) => ResponseFuture::cloning(self.clone(), cloning),
+            Err(request) => {
+                let future = self.service.call(request);
+                ResponseFuture::new(None, self.clone(), future)
+            }
+        }
     }
 }
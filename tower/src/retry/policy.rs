@@ -1,4 +1,8 @@
-use std::future::Future;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
 
 /// A "retry policy" to classify if a request should be retried.
 ///
@@ -65,3 +69,42 @@ pub trait Policy<Req, Res, E>: Sized {
     /// If the request cannot be cloned, return [`None`].
     fn clone_request(&self, req: &Req) -> Option<Req>;
 }
+
+/// A [`Policy`] that never retries requests.
+///
+/// Since requests are never retried, they are never cloned either, so wrapping
+/// a service in a [`Retry`] with this policy adds no per-request cost. This is
+/// useful as a default when the retry policy is chosen at runtime, and as a
+/// starting point for the [combinators] in [`PolicyExt`].
+///
+/// [`Retry`]: super::Retry
+/// [combinators]: super::combinators
+/// [`PolicyExt`]: super::PolicyExt
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NeverRetry;
+
+/// The [`Future`] returned by [`NeverRetry`]'s [`Policy::retry`].
+///
+/// This type cannot be constructed.
+#[derive(Debug)]
+pub enum NeverRetryFuture {}
+
+impl<Req, Res, E> Policy<Req, Res, E> for NeverRetry {
+    type Future = NeverRetryFuture;
+
+    fn retry(&self, _: &Req, _: Result<&Res, &E>) -> Option<Self::Future> {
+        None
+    }
+
+    fn clone_request(&self, _: &Req) -> Option<Req> {
+        None
+    }
+}
+
+impl Future for NeverRetryFuture {
+    type Output = NeverRetry;
+
+    fn poll(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Self::Output> {
+        match *self {}
+    }
+}
@@ -0,0 +1,124 @@
+//! Reporting the retries made for each response.
+//!
+//! Retries add latency that is otherwise invisible to the caller: a slow
+//! response may have been slow to produce, or may have succeeded only after
+//! several failed attempts and backoffs. [`ReportRetries`] wraps a [`Retry`]
+//! service so that each response is returned in a [`Retried`], which records
+//! how many attempts were made and how long was spent waiting to retry them.
+
+use super::future::{ResponseFuture, RetriedFuture};
+use super::{AsyncClone, Policy, Retry, Retryable};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tower_service::Service;
+
+/// A response, along with the retries made to produce it.
+#[derive(Clone, Debug)]
+pub struct Retried<T> {
+    inner: T,
+    attempts: usize,
+    delay: Duration,
+}
+
+/// Wraps a [`Retry`] service so that its responses report the retries made to
+/// produce them.
+///
+/// Responses are returned as [`Retried`] values. Errors are returned as-is.
+#[derive(Clone, Debug)]
+pub struct ReportRetries<P, S, C, R> {
+    retry: Retry<P, S, C, R>,
+}
+
+// ===== impl Retried =====
+
+impl<T> Retried<T> {
+    pub(crate) fn new(inner: T, attempts: usize, delay: Duration) -> Self {
+        Retried {
+            inner,
+            attempts,
+            delay,
+        }
+    }
+
+    /// Returns the number of times the request was dispatched to the inner
+    /// service, including the attempt that produced this response.
+    pub fn attempts(&self) -> usize {
+        self.attempts
+    }
+
+    /// Returns the number of times the request was retried.
+    pub fn retries(&self) -> usize {
+        self.attempts.saturating_sub(1)
+    }
+
+    /// Returns the total time spent between each failed attempt and the next.
+    ///
+    /// This includes the time spent waiting for the [`Policy`] to decide to
+    /// retry (for instance, a backoff), cloning the request, and waiting for
+    /// the inner service to become ready again. It does not include the time
+    /// spent on the attempts themselves.
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    /// Get a reference to the response.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the response.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Consume `self`, returning the response.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+// ===== impl ReportRetries =====
+
+impl<P, S, C, R> ReportRetries<P, S, C, R> {
+    /// Wraps `retry` so that its responses report the retries made to produce
+    /// them.
+    pub fn new(retry: Retry<P, S, C, R>) -> Self {
+        ReportRetries { retry }
+    }
+
+    /// Get a reference to the inner [`Retry`] service.
+    pub fn get_ref(&self) -> &Retry<P, S, C, R> {
+        &self.retry
+    }
+
+    /// Get a mutable reference to the inner [`Retry`] service.
+    pub fn get_mut(&mut self) -> &mut Retry<P, S, C, R> {
+        &mut self.retry
+    }
+
+    /// Consume `self`, returning the inner [`Retry`] service.
+    pub fn into_inner(self) -> Retry<P, S, C, R> {
+        self.retry
+    }
+}
+
+impl<P, S, C, R, Request> Service<Request> for ReportRetries<P, S, C, R>
+where
+    P: Policy<Request, S::Response, S::Error> + Clone,
+    S: Service<Request> + Clone,
+    C: AsyncClone<Request> + Clone,
+    R: Retryable<Request> + Clone,
+{
+    type Response = Retried<S::Response>;
+    type Error = S::Error;
+    type Future = RetriedFuture<P, S, Request, C, R>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.retry.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let future: ResponseFuture<P, S, Request, C, R> = self.retry.call(request);
+        RetriedFuture::new(future)
+    }
+}
@@ -0,0 +1,41 @@
+/// Decides whether individual requests may be retried, regardless of a
+/// [`Retry`]'s [`Policy`].
+///
+/// This allows callers to opt individual requests out of retries, for
+/// example because they have side effects that must not be repeated. The
+/// decision is typically carried on the request itself, so this is
+/// implemented for closures taking a reference to the request, such as
+/// `|req: &http::Request<B>| req.extensions().get::<NoRetry>().is_none()`.
+///
+/// A request that is not retryable is dispatched once, and its response is
+/// returned without consulting the policy.
+///
+/// [`Retry`]: super::Retry
+/// [`Policy`]: super::Policy
+pub trait Retryable<Req> {
+    /// Returns `false` if `req` must not be retried.
+    fn is_retryable(&self, req: &Req) -> bool;
+}
+
+/// A [`Retryable`] that allows every request to be retried.
+///
+/// This is the default for [`Retry`].
+///
+/// [`Retry`]: super::Retry
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AllRetryable;
+
+impl<Req> Retryable<Req> for AllRetryable {
+    fn is_retryable(&self, _: &Req) -> bool {
+        true
+    }
+}
+
+impl<F, Req> Retryable<Req> for F
+where
+    F: Fn(&Req) -> bool,
+{
+    fn is_retryable(&self, req: &Req) -> bool {
+        self(req)
+    }
+}
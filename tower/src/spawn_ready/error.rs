@@ -0,0 +1,25 @@
+//! Error types
+
+use std::{error, fmt};
+
+/// The service did not become ready within the timeout configured with
+/// [`SpawnReady::with_timeout`].
+///
+/// [`SpawnReady::with_timeout`]: super::SpawnReady::with_timeout
+#[derive(Debug, Default)]
+pub struct ReadyTimeout(());
+
+impl ReadyTimeout {
+    /// Construct a new ready timeout error
+    pub fn new() -> Self {
+        ReadyTimeout(())
+    }
+}
+
+impl fmt::Display for ReadyTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("timed out waiting for service to become ready")
+    }
+}
+
+impl error::Error for ReadyTimeout {}
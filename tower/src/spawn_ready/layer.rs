@@ -1,14 +1,35 @@
 use super::MakeSpawnReady;
+use crate::util::TaskSet;
+use std::time::Duration;
 use tower_layer::Layer;
 
 /// Spawns tasks to drive its inner service to readiness.
 #[derive(Debug, Clone, Default)]
-pub struct SpawnReadyLayer;
+pub struct SpawnReadyLayer {
+    timeout: Option<Duration>,
+    tasks: Option<TaskSet>,
+}
 
 impl SpawnReadyLayer {
     /// Builds a [`SpawnReadyLayer`] with the default executor.
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Applies [`SpawnReady::with_timeout`] to each service that is built.
+    ///
+    /// [`SpawnReady::with_timeout`]: super::SpawnReady::with_timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Applies [`SpawnReady::with_task_set`] to each service that is built.
+    ///
+    /// [`SpawnReady::with_task_set`]: super::SpawnReady::with_task_set
+    pub fn with_task_set(mut self, tasks: TaskSet) -> Self {
+        self.tasks = Some(tasks);
+        self
     }
 }
 
@@ -16,6 +37,13 @@ impl<S> Layer<S> for SpawnReadyLayer {
     type Service = MakeSpawnReady<S>;
 
     fn layer(&self, service: S) -> Self::Service {
-        MakeSpawnReady::new(service)
+        let mut make = MakeSpawnReady::new(service);
+        if let Some(timeout) = self.timeout {
+            make = make.with_timeout(timeout);
+        }
+        if let Some(ref tasks) = self.tasks {
+            make = make.with_task_set(tasks.clone());
+        }
+        make
     }
 }
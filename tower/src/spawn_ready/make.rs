@@ -1,10 +1,12 @@
 use super::SpawnReady;
+use crate::util::TaskSet;
 use futures_core::ready;
 use pin_project::pin_project;
 use std::{
     future::Future,
     pin::Pin,
     task::{Context, Poll},
+    time::Duration,
 };
 use tower_service::Service;
 
@@ -12,12 +14,31 @@ use tower_service::Service;
 #[derive(Clone, Debug)]
 pub struct MakeSpawnReady<S> {
     inner: S,
+    timeout: Option<Duration>,
+    tasks: Option<TaskSet>,
 }
 
 impl<S> MakeSpawnReady<S> {
     /// Creates a new [`MakeSpawnReady`] wrapping `service`.
     pub fn new(service: S) -> Self {
-        Self { inner: service }
+        Self {
+            inner: service,
+            timeout: None,
+            tasks: None,
+        }
+    }
+
+    /// Applies [`SpawnReady::with_timeout`] to each service that is built.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Applies [`SpawnReady::with_task_set`] to each service that is built,
+    /// so that all of their background tasks share `tasks`.
+    pub fn with_task_set(mut self, tasks: TaskSet) -> Self {
+        self.tasks = Some(tasks);
+        self
     }
 }
 
@@ -27,6 +48,8 @@ impl<S> MakeSpawnReady<S> {
 pub struct MakeFuture<F> {
     #[pin]
     inner: F,
+    timeout: Option<Duration>,
+    tasks: Option<TaskSet>,
 }
 
 impl<S, Target> Service<Target> for MakeSpawnReady<S>
@@ -44,6 +67,8 @@ where
     fn call(&mut self, target: Target) -> Self::Future {
         MakeFuture {
             inner: self.inner.call(target),
+            timeout: self.timeout,
+            tasks: self.tasks.clone(),
         }
     }
 }
@@ -57,7 +82,13 @@ where
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.project();
         let inner = ready!(this.inner.poll(cx))?;
-        let svc = SpawnReady::new(inner);
+        let mut svc = SpawnReady::new(inner);
+        if let Some(timeout) = *this.timeout {
+            svc = svc.with_timeout(timeout);
+        }
+        if let Some(tasks) = this.tasks.take() {
+            svc = svc.with_task_set(tasks);
+        }
         Poll::Ready(Ok(svc))
     }
 }
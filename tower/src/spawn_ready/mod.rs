@@ -1,6 +1,7 @@
 //! When an underlying service is not ready, drive it to readiness on a
 //! background task.
 
+pub mod error;
 pub mod future;
 mod layer;
 mod make;
@@ -1,27 +1,45 @@
-use super::future::ResponseFuture;
-use crate::{util::ServiceExt, BoxError};
+use super::{error::ReadyTimeout, future::ResponseFuture};
+use crate::util::{ServiceExt, Spawn, Task, TaskSet};
+use crate::BoxError;
 use futures_core::ready;
 use futures_util::future::TryFutureExt;
 use std::{
     future::Future,
     pin::Pin,
     task::{Context, Poll},
+    time::Duration,
 };
 use tower_service::Service;
 use tracing::Instrument;
 
 /// Spawns tasks to drive an inner service to readiness.
 ///
+/// While the background task is running, [`poll_ready`] registers the current
+/// task to be woken when it completes. A load balancer polling a
+/// [`SpawnReady`] endpoint is therefore notified as soon as the endpoint
+/// becomes ready.
+///
 /// See crate level documentation for more details.
+///
+/// By default, the background task drives the service until it becomes ready
+/// or fails, however long that takes. [`SpawnReady::with_timeout`] bounds
+/// the wait.
+///
+/// The background task is aborted when the [`SpawnReady`] is dropped. It may
+/// also be tied to a shared [`TaskSet`] with [`SpawnReady::with_task_set`].
+///
+/// [`poll_ready`]: crate::Service::poll_ready
 #[derive(Debug)]
 pub struct SpawnReady<S> {
     inner: Inner<S>,
+    timeout: Option<Duration>,
+    tasks: TaskSet,
 }
 
 #[derive(Debug)]
 enum Inner<S> {
     Service(Option<S>),
-    Future(tokio::task::JoinHandle<Result<S, BoxError>>),
+    Future(Task<Result<S, BoxError>>),
 }
 
 impl<S> SpawnReady<S> {
@@ -29,8 +47,50 @@ impl<S> SpawnReady<S> {
     pub fn new(service: S) -> Self {
         Self {
             inner: Inner::Service(Some(service)),
+            timeout: None,
+            tasks: TaskSet::new(),
         }
     }
+
+    /// Spawns background tasks onto `tasks`, so that they are aborted when
+    /// every handle to the set is dropped, even if this [`SpawnReady`] is
+    /// leaked.
+    ///
+    /// By default, each [`SpawnReady`] spawns its tasks onto a set of its own.
+    pub fn with_task_set(mut self, tasks: TaskSet) -> Self {
+        self.tasks = tasks;
+        self
+    }
+
+    /// Spawns background tasks onto `executor` rather than onto the Tokio
+    /// runtime.
+    ///
+    /// This is equivalent to [`SpawnReady::with_task_set`] with a
+    /// [`TaskSet::with_executor`]. Note that [`SpawnReady::with_timeout`]
+    /// relies on Tokio's timer, so it may only be used if `executor` runs
+    /// tasks within a Tokio runtime.
+    pub fn with_executor<E>(self, executor: E) -> Self
+    where
+        E: Spawn + Send + Sync + 'static,
+    {
+        self.with_task_set(TaskSet::with_executor(executor))
+    }
+
+    /// Fails if the background task does not drive the service to readiness
+    /// within `timeout`.
+    ///
+    /// When the timeout elapses, the background task is cancelled, the inner
+    /// service is dropped, and [`poll_ready`] fails with a [`ReadyTimeout`]
+    /// error. As with any other readiness error, this [`SpawnReady`] should
+    /// then be discarded; a [`Reconnect`] or a balancer may be used to replace
+    /// it with a new service.
+    ///
+    /// [`poll_ready`]: crate::Service::poll_ready
+    /// [`Reconnect`]: crate::reconnect::Reconnect
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
 }
 
 impl<S> Drop for SpawnReady<S> {
@@ -60,8 +120,19 @@ where
                     }
 
                     let svc = svc.take().expect("illegal state");
-                    let rx =
-                        tokio::spawn(svc.ready_oneshot().map_err(Into::into).in_current_span());
+                    let ready = svc.ready_oneshot().map_err(Into::into);
+                    let rx = match self.timeout {
+                        None => self.tasks.spawn(ready.in_current_span()),
+                        Some(timeout) => self.tasks.spawn(
+                            async move {
+                                match tokio::time::timeout(timeout, ready).await {
+                                    Ok(ready) => ready,
+                                    Err(_) => Err(ReadyTimeout::new().into()),
+                                }
+                            }
+                            .in_current_span(),
+                        ),
+                    };
                     Inner::Future(rx)
                 }
                 Inner::Future(ref mut fut) => {
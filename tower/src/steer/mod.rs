@@ -68,6 +68,14 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! A [`Split`] routes a fraction of requests to a canary service, such as a new version of a
+//! service that is being rolled out.
+
+mod split;
+
+pub use self::split::{Split, SplitFuture};
+
 use std::task::{Context, Poll};
 use std::{collections::VecDeque, marker::PhantomData};
 use tower_service::Service;
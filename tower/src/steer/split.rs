@@ -0,0 +1,196 @@
+use pin_project::pin_project;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::sync::watch;
+use tower_service::Service;
+
+/// Routes a fraction of requests to a canary service and the rest to a
+/// primary service.
+///
+/// The fraction of requests sent to the canary is a value between 0.0 and
+/// 1.0. It may be fixed with [`Split::new`], or read from a [`watch`] channel
+/// with [`Split::from_watch`], so that a rollout can be ramped (for instance,
+/// from 1% to 50% to 100% of requests) without rebuilding the service.
+///
+/// Requests are split deterministically rather than at random: each request
+/// adds the current fraction to a running total, and a request is sent to the
+/// canary each time the total reaches one. At a fraction of 0.25, for
+/// instance, every fourth request is sent to the canary.
+///
+/// Unlike [`Steer`], [`Split`] decides which service will receive the next
+/// request before it is made, so [`poll_ready`] only waits for that service to
+/// become ready.
+///
+/// [`watch`]: tokio::sync::watch
+/// [`Steer`]: super::Steer
+/// [`poll_ready`]: crate::Service::poll_ready
+#[derive(Debug)]
+pub struct Split<A, B> {
+    primary: A,
+    canary: B,
+    fraction: Fraction,
+    /// The fraction of a request accumulated towards the next canary request.
+    credit: f64,
+    /// The service chosen for the next request, once it is ready.
+    ready: Option<Route>,
+}
+
+/// The [`Future`] returned by a [`Split`] service.
+#[pin_project(project = SplitFutureProj)]
+#[derive(Debug)]
+pub enum SplitFuture<A, B> {
+    /// The response from the primary service.
+    Primary(#[pin] A),
+    /// The response from the canary service.
+    Canary(#[pin] B),
+}
+
+#[derive(Clone, Debug)]
+enum Fraction {
+    Fixed(f64),
+    Watch(watch::Receiver<f64>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Route {
+    Primary,
+    Canary,
+}
+
+// ===== impl Split =====
+
+impl<A, B> Split<A, B> {
+    /// Creates a [`Split`] that sends `fraction` of requests to `canary` and
+    /// the rest to `primary`.
+    ///
+    /// `fraction` is clamped between 0.0 and 1.0.
+    pub fn new(primary: A, canary: B, fraction: f64) -> Self {
+        Self::with_fraction(primary, canary, Fraction::Fixed(fraction))
+    }
+
+    /// Creates a [`Split`] whose canary fraction is read from a [`watch`]
+    /// channel.
+    ///
+    /// Each request is routed according to the most recent value sent on the
+    /// channel. If the sender is dropped, the last value sent continues to be
+    /// used. Values are clamped between 0.0 and 1.0.
+    ///
+    /// [`watch`]: tokio::sync::watch
+    pub fn from_watch(primary: A, canary: B, fraction: watch::Receiver<f64>) -> Self {
+        Self::with_fraction(primary, canary, Fraction::Watch(fraction))
+    }
+
+    fn with_fraction(primary: A, canary: B, fraction: Fraction) -> Self {
+        Self {
+            primary,
+            canary,
+            fraction,
+            credit: 0.0,
+            ready: None,
+        }
+    }
+
+    /// Returns the fraction of requests currently sent to the canary.
+    pub fn fraction(&self) -> f64 {
+        let fraction = match self.fraction {
+            Fraction::Fixed(fraction) => fraction,
+            Fraction::Watch(ref rx) => *rx.borrow(),
+        };
+        if fraction.is_nan() {
+            return 0.0;
+        }
+        fraction.clamp(0.0, 1.0)
+    }
+
+    /// Get a reference to the primary service.
+    pub fn primary(&self) -> &A {
+        &self.primary
+    }
+
+    /// Get a reference to the canary service.
+    pub fn canary(&self) -> &B {
+        &self.canary
+    }
+
+    /// Consume `self`, returning the primary and canary services.
+    pub fn into_inner(self) -> (A, B) {
+        (self.primary, self.canary)
+    }
+
+    /// Chooses the service that will receive the next request.
+    fn next_route(&self) -> Route {
+        if self.credit + self.fraction() >= 1.0 {
+            Route::Canary
+        } else {
+            Route::Primary
+        }
+    }
+}
+
+impl<A, B, Req> Service<Req> for Split<A, B>
+where
+    A: Service<Req>,
+    B: Service<Req, Response = A::Response, Error = A::Error>,
+{
+    type Response = A::Response;
+    type Error = A::Error;
+    type Future = SplitFuture<A::Future, B::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // The route is chosen anew each time, so that an update to the
+        // fraction applies even while a service is not ready.
+        let route = self.next_route();
+        self.ready = None;
+        match route {
+            Route::Primary => futures_core::ready!(self.primary.poll_ready(cx))?,
+            Route::Canary => futures_core::ready!(self.canary.poll_ready(cx))?,
+        }
+        self.ready = Some(route);
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Req) -> Self::Future {
+        let route = self.ready.take().expect("called before ready");
+        self.credit += self.fraction();
+        match route {
+            Route::Primary => SplitFuture::Primary(self.primary.call(req)),
+            Route::Canary => {
+                self.credit -= 1.0;
+                SplitFuture::Canary(self.canary.call(req))
+            }
+        }
+    }
+}
+
+impl<A: Clone, B: Clone> Clone for Split<A, B> {
+    fn clone(&self) -> Self {
+        Self {
+            primary: self.primary.clone(),
+            canary: self.canary.clone(),
+            fraction: self.fraction.clone(),
+            credit: self.credit,
+            // The clone has not been polled ready.
+            ready: None,
+        }
+    }
+}
+
+// ===== impl SplitFuture =====
+
+impl<A, B, T, E> Future for SplitFuture<A, B>
+where
+    A: Future<Output = Result<T, E>>,
+    B: Future<Output = Result<T, E>>,
+{
+    type Output = Result<T, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project() {
+            SplitFutureProj::Primary(future) => future.poll(cx),
+            SplitFutureProj::Canary(future) => future.poll(cx),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use std::cell::Cell;
+use std::time::Duration;
+use tokio::time::Instant;
+
+thread_local! {
+    static CURRENT: Cell<Option<Instant>> = Cell::default();
+}
+
+/// The time remaining for the request that is currently being made.
+///
+/// Each [`Timeout`] configured [with a budget] publishes its deadline as the
+/// current budget while it calls its inner service and while it polls the
+/// resulting response future. Such a [`Timeout`] nested inside another never
+/// waits past its parent's deadline: its timeout is reduced to whatever
+/// remains of the current budget. This allows, for instance, an outer timeout
+/// of one second to bound the sum of the per-attempt timeouts of a retried
+/// service, without each layer's duration being adjusted by hand.
+///
+/// Budgets are propagated within a task. Requests that are handed off to
+/// another task, for example by a [`Buffer`], are not constrained by the
+/// budget of the task that enqueued them.
+///
+/// [`Timeout`]: super::Timeout
+/// [with a budget]: super::Timeout::with_budget
+/// [`Buffer`]: crate::buffer::Buffer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimeBudget {
+    deadline: Instant,
+}
+
+impl TimeBudget {
+    /// Returns the budget of the request currently being made on this task,
+    /// if it is constrained by a [`Timeout`] that shares a budget.
+    ///
+    /// Middleware other than [`Timeout`] may use this to avoid starting work
+    /// that cannot complete in time.
+    ///
+    /// [`Timeout`]: super::Timeout
+    pub fn current() -> Option<Self> {
+        CURRENT
+            .with(Cell::get)
+            .map(|deadline| TimeBudget { deadline })
+    }
+
+    /// Returns the time by which the request must complete.
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    /// Returns the time remaining until the deadline, or zero if it has
+    /// passed.
+    pub fn remaining(&self) -> Duration {
+        self.deadline.saturating_duration_since(Instant::now())
+    }
+
+    /// Returns the earlier of `deadline` and the current budget's deadline.
+    pub(crate) fn clamp(deadline: Instant) -> Instant {
+        match Self::current() {
+            Some(budget) if budget.deadline < deadline => budget.deadline,
+            _ => deadline,
+        }
+    }
+
+    /// Runs `f` with `deadline` as the current budget.
+    pub(crate) fn scope<R>(deadline: Instant, f: impl FnOnce() -> R) -> R {
+        struct Restore(Option<Instant>);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                let prior = self.0;
+                CURRENT.with(|current| current.set(prior));
+            }
+        }
+
+        let _restore = Restore(CURRENT.with(|current| current.replace(Some(deadline))));
+        f()
+    }
+}
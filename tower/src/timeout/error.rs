@@ -20,3 +20,45 @@ impl fmt::Display for Elapsed {
 }
 
 impl error::Error for Elapsed {}
+
+/// The phase of a [`oneshot_with_timeout`] that was in progress when its
+/// timeout elapsed.
+///
+/// [`oneshot_with_timeout`]: super::oneshot_with_timeout
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Phase {
+    /// The service did not become ready in time.
+    Ready,
+    /// The service was called, but its response did not complete in time.
+    Call,
+}
+
+/// The timeout of a [`oneshot_with_timeout`] elapsed.
+///
+/// [`oneshot_with_timeout`]: super::oneshot_with_timeout
+#[derive(Debug)]
+pub struct OneshotElapsed {
+    phase: Phase,
+}
+
+impl OneshotElapsed {
+    pub(super) fn new(phase: Phase) -> Self {
+        OneshotElapsed { phase }
+    }
+
+    /// Returns the phase that was in progress when the timeout elapsed.
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+}
+
+impl fmt::Display for OneshotElapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.phase {
+            Phase::Ready => f.pad("timed out waiting for service to become ready"),
+            Phase::Call => f.pad("request timed out"),
+        }
+    }
+}
+
+impl error::Error for OneshotElapsed {}
@@ -1,13 +1,17 @@
 //! Future types
 
-use super::error::Elapsed;
+use super::budget::TimeBudget;
+use super::error::{Elapsed, OneshotElapsed, Phase};
+use futures_core::ready;
 use pin_project::pin_project;
 use std::{
+    fmt,
     future::Future,
     pin::Pin,
     task::{Context, Poll},
 };
-use tokio::time::Sleep;
+use tokio::time::{Instant, Sleep};
+use tower_service::Service;
 
 /// [`Timeout`] response future
 ///
@@ -19,11 +23,18 @@ pub struct ResponseFuture<T> {
     response: T,
     #[pin]
     sleep: Sleep,
+    /// The deadline published as the current budget, if the timeout shares
+    /// one.
+    deadline: Option<Instant>,
 }
 
 impl<T> ResponseFuture<T> {
-    pub(crate) fn new(response: T, sleep: Sleep) -> Self {
-        ResponseFuture { response, sleep }
+    pub(crate) fn new(response: T, sleep: Sleep, deadline: Option<Instant>) -> Self {
+        ResponseFuture {
+            response,
+            sleep,
+            deadline,
+        }
     }
 }
 
@@ -37,8 +48,14 @@ where
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.project();
 
-        // First, try polling the future
-        match this.response.poll(cx) {
+        // First, try polling the future. If this timeout shares a budget, any
+        // budgeted timeouts the future applies are constrained by this one.
+        let response = this.response;
+        let poll = match *this.deadline {
+            Some(deadline) => TimeBudget::scope(deadline, || response.poll(cx)),
+            None => response.poll(cx),
+        };
+        match poll {
             Poll::Ready(v) => return Poll::Ready(v.map_err(Into::into)),
             Poll::Pending => {}
         }
@@ -50,3 +67,85 @@ where
         }
     }
 }
+
+/// Future returned by [`oneshot_with_timeout`].
+///
+/// [`oneshot_with_timeout`]: crate::timeout::oneshot_with_timeout
+#[pin_project]
+pub struct OneshotFuture<S: Service<Req>, Req> {
+    #[pin]
+    state: OneshotState<S, Req>,
+    #[pin]
+    sleep: Sleep,
+}
+
+#[pin_project(project = OneshotStateProj)]
+enum OneshotState<S: Service<Req>, Req> {
+    NotReady(S, Option<Req>),
+    Called(#[pin] S::Future),
+    Done,
+}
+
+impl<S, Req> OneshotFuture<S, Req>
+where
+    S: Service<Req>,
+{
+    pub(crate) fn new(svc: S, req: Req, sleep: Sleep) -> Self {
+        OneshotFuture {
+            state: OneshotState::NotReady(svc, Some(req)),
+            sleep,
+        }
+    }
+}
+
+impl<S, Req> Future for OneshotFuture<S, Req>
+where
+    S: Service<Req>,
+    S::Error: Into<crate::BoxError>,
+{
+    type Output = Result<S::Response, crate::BoxError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+        loop {
+            let phase = match this.state.as_mut().project() {
+                OneshotStateProj::NotReady(svc, req) => match svc.poll_ready(cx) {
+                    Poll::Ready(ready) => {
+                        ready.map_err(Into::into)?;
+                        let f = svc.call(req.take().expect("already called"));
+                        this.state.set(OneshotState::Called(f));
+                        continue;
+                    }
+                    Poll::Pending => Phase::Ready,
+                },
+                OneshotStateProj::Called(fut) => match fut.poll(cx) {
+                    Poll::Ready(res) => {
+                        this.state.set(OneshotState::Done);
+                        return Poll::Ready(res.map_err(Into::into));
+                    }
+                    Poll::Pending => Phase::Call,
+                },
+                OneshotStateProj::Done => panic!("polled after complete"),
+            };
+
+            ready!(this.sleep.as_mut().poll(cx));
+            this.state.set(OneshotState::Done);
+            return Poll::Ready(Err(OneshotElapsed::new(phase).into()));
+        }
+    }
+}
+
+impl<S, Req> fmt::Debug for OneshotFuture<S, Req>
+where
+    S: Service<Req> + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("OneshotFuture");
+        match self.state {
+            OneshotState::NotReady(ref svc, _) => d.field("service", svc),
+            OneshotState::Called(_) => d.field("state", &"Called"),
+            OneshotState::Done => d.field("state", &"Done"),
+        };
+        d.field("sleep", &self.sleep).finish()
+    }
+}
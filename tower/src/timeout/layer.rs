@@ -1,24 +1,97 @@
-use super::Timeout;
-use std::time::Duration;
+use super::{NoOverride, Source, Timeout};
+use std::{fmt, time::Duration};
+use tokio::sync::watch;
 use tower_layer::Layer;
 
 /// Applies a timeout to requests via the supplied inner service.
-#[derive(Debug, Clone)]
-pub struct TimeoutLayer {
-    timeout: Duration,
+#[derive(Clone)]
+pub struct TimeoutLayer<O = NoOverride> {
+    timeout: Source,
+    per_request: O,
+    budget: bool,
 }
 
 impl TimeoutLayer {
     /// Create a timeout from a duration
     pub fn new(timeout: Duration) -> Self {
-        TimeoutLayer { timeout }
+        TimeoutLayer {
+            timeout: Source::Fixed(timeout),
+            per_request: NoOverride,
+            budget: false,
+        }
+    }
+
+    /// Create a timeout whose duration is read from a [`watch`] channel.
+    ///
+    /// See [`Timeout::from_watch`] for details.
+    ///
+    /// [`watch`]: tokio::sync::watch
+    pub fn from_watch(timeout: watch::Receiver<Duration>) -> Self {
+        TimeoutLayer {
+            timeout: Source::Watch(timeout, None),
+            per_request: NoOverride,
+            budget: false,
+        }
+    }
+
+    /// Calls `hook` once the sender of the [`watch`] channel this timeout was
+    /// created [from] is dropped.
+    ///
+    /// See [`Timeout::on_watch_closed`] for details. The hook is shared by all
+    /// services produced by this layer.
+    ///
+    /// [`watch`]: tokio::sync::watch
+    /// [from]: TimeoutLayer::from_watch
+    pub fn on_watch_closed<F>(mut self, hook: F) -> Self
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.timeout.on_closed(hook);
+        self
+    }
+}
+
+impl<O> TimeoutLayer<O> {
+    /// Uses `per_request` to choose the timeout for each request.
+    ///
+    /// See [`Timeout::with_override`] for details.
+    pub fn with_override<O2>(self, per_request: O2) -> TimeoutLayer<O2> {
+        TimeoutLayer {
+            timeout: self.timeout,
+            per_request,
+            budget: self.budget,
+        }
+    }
+
+    /// Shares a [`TimeBudget`] with other timeouts that are configured with a
+    /// budget.
+    ///
+    /// See [`Timeout::with_budget`] for details.
+    ///
+    /// [`TimeBudget`]: super::TimeBudget
+    pub fn with_budget(mut self) -> Self {
+        self.budget = true;
+        self
     }
 }
 
-impl<S> Layer<S> for TimeoutLayer {
-    type Service = Timeout<S>;
+impl<S, O: Clone> Layer<S> for TimeoutLayer<O> {
+    type Service = Timeout<S, O>;
 
     fn layer(&self, service: S) -> Self::Service {
-        Timeout::new(service, self.timeout)
+        Timeout {
+            inner: service,
+            timeout: self.timeout.clone(),
+            per_request: self.per_request.clone(),
+            budget: self.budget,
+        }
+    }
+}
+
+impl<O> fmt::Debug for TimeoutLayer<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimeoutLayer")
+            .field("timeout", &self.timeout)
+            .finish()
     }
 }
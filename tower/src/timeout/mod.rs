@@ -1,24 +1,97 @@
 //! Middleware that applies a timeout to requests.
 //!
 //! If the response does not complete within the specified timeout, the response
-//! will be aborted.
+//! will be aborted. Nested timeouts configured [with a budget] share a
+//! [`TimeBudget`], so that an inner timeout never outlasts an outer one.
+//!
+//! [with a budget]: Timeout::with_budget
 
+mod budget;
 pub mod error;
 pub mod future;
 mod layer;
+mod request;
 
+pub use self::budget::TimeBudget;
 pub use self::layer::TimeoutLayer;
+pub use self::request::{NoOverride, TimeoutOverride};
 
-use self::future::ResponseFuture;
+use self::future::{OneshotFuture, ResponseFuture};
+use std::fmt;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
 use std::task::{Context, Poll};
 use std::time::Duration;
+use tokio::sync::watch;
 use tower_service::Service;
 
 /// Applies a timeout to requests.
-#[derive(Debug, Clone)]
-pub struct Timeout<T> {
+///
+/// The timeout may be chosen for individual requests with
+/// [`Timeout::with_override`], and nested timeouts may share a
+/// [`TimeBudget`] with [`Timeout::with_budget`].
+#[derive(Clone)]
+pub struct Timeout<T, O = NoOverride> {
     inner: T,
+    timeout: Source,
+    per_request: O,
+    budget: bool,
+}
+
+/// Where the timeout for each request is read from.
+#[derive(Debug, Clone)]
+enum Source {
+    Fixed(Duration),
+    Watch(watch::Receiver<Duration>, Option<ClosedHook>),
+}
+
+/// Invoked once when a [`Source::Watch`]'s sender is dropped.
+#[derive(Clone)]
+struct ClosedHook {
+    notified: Arc<AtomicBool>,
+    hook: Arc<dyn Fn() + Send + Sync>,
+}
+
+/// Waits for `svc` to become ready and calls it with `req`, failing if the
+/// whole exchange takes longer than `timeout`.
+///
+/// This is like [`ServiceExt::oneshot`], but bounds the time spent waiting in
+/// [`poll_ready`] as well as the time spent waiting for the response. This is
+/// useful when calling a service that was just returned by a [`MakeService`],
+/// which may never become ready if, for example, its peer is unreachable.
+///
+/// If the timeout elapses, the future fails with an [`OneshotElapsed`] error
+/// whose [`phase`] tells whether the service had been called.
+///
+/// [`ServiceExt::oneshot`]: crate::ServiceExt::oneshot
+/// [`poll_ready`]: crate::Service::poll_ready
+/// [`MakeService`]: crate::MakeService
+/// [`OneshotElapsed`]: error::OneshotElapsed
+/// [`phase`]: error::OneshotElapsed::phase
+pub fn oneshot_with_timeout<S, Request>(
+    svc: S,
+    req: Request,
     timeout: Duration,
+) -> OneshotFuture<S, Request>
+where
+    S: Service<Request>,
+    S::Error: Into<crate::BoxError>,
+{
+    OneshotFuture::new(svc, req, tokio::time::sleep(timeout))
+}
+
+/// Returns the instant `timeout` from now.
+///
+/// Like [`tokio::time::sleep`], a timeout too large to be represented as an
+/// [`Instant`] is treated as roughly 30 years, rather than panicking.
+///
+/// [`Instant`]: tokio::time::Instant
+fn deadline_after(timeout: Duration) -> tokio::time::Instant {
+    const FAR_FUTURE: Duration = Duration::from_secs(86400 * 365 * 30);
+    let now = tokio::time::Instant::now();
+    now.checked_add(timeout).unwrap_or_else(|| now + FAR_FUTURE)
 }
 
 // ===== impl Timeout =====
@@ -26,7 +99,89 @@ pub struct Timeout<T> {
 impl<T> Timeout<T> {
     /// Creates a new [`Timeout`]
     pub fn new(inner: T, timeout: Duration) -> Self {
-        Timeout { inner, timeout }
+        Timeout {
+            inner,
+            timeout: Source::Fixed(timeout),
+            per_request: NoOverride,
+            budget: false,
+        }
+    }
+
+    /// Creates a new [`Timeout`] whose duration is read from a [`watch`]
+    /// channel.
+    ///
+    /// Each request uses the most recent value sent on the channel when the
+    /// request is made, so the timeout may be changed at runtime without
+    /// rebuilding the service. Requests that are already in flight are not
+    /// affected by updates. If the sender is dropped, the last value sent
+    /// continues to be used.
+    ///
+    /// [`watch`]: tokio::sync::watch
+    pub fn from_watch(inner: T, timeout: watch::Receiver<Duration>) -> Self {
+        Timeout {
+            inner,
+            timeout: Source::Watch(timeout, None),
+            per_request: NoOverride,
+            budget: false,
+        }
+    }
+
+    /// Calls `hook` once the sender of the [`watch`] channel this timeout was
+    /// created [from] is dropped.
+    ///
+    /// Once the sender is dropped, the timeout can no longer be updated, so
+    /// this may be used to alert that the service is now running with a frozen
+    /// configuration. The hook is called at most once, the next time the
+    /// timeout is read after the sender is dropped, and is shared with clones
+    /// of this service. It has no effect if the timeout is fixed.
+    ///
+    /// [`watch`]: tokio::sync::watch
+    /// [from]: Timeout::from_watch
+    pub fn on_watch_closed<F>(mut self, hook: F) -> Self
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.timeout.on_closed(hook);
+        self
+    }
+}
+
+impl<T, O> Timeout<T, O> {
+    /// Uses `per_request` to choose the timeout for each request.
+    ///
+    /// Requests for which `per_request` returns `None` use this service's
+    /// default timeout. Requests that exceed their timeout fail with the same
+    /// [`Elapsed`] error either way.
+    ///
+    /// [`Elapsed`]: error::Elapsed
+    pub fn with_override<O2>(self, per_request: O2) -> Timeout<T, O2> {
+        Timeout {
+            inner: self.inner,
+            timeout: self.timeout,
+            per_request,
+            budget: self.budget,
+        }
+    }
+
+    /// Shares a [`TimeBudget`] with other timeouts that are configured with a
+    /// budget.
+    ///
+    /// While it calls its inner service and polls the response future, this
+    /// timeout publishes its deadline as the current [`TimeBudget`]. If the
+    /// request is already constrained by the budget of an outer timeout, this
+    /// timeout's deadline is reduced to whatever remains of that budget. This
+    /// allows, for instance, an outer timeout of one second to bound the sum
+    /// of the per-attempt timeouts of a retried service.
+    ///
+    /// Timeouts without a budget neither publish nor respect one.
+    pub fn with_budget(mut self) -> Self {
+        self.budget = true;
+        self
+    }
+
+    /// Returns the default timeout that will be applied to the next request.
+    pub fn timeout(&self) -> Duration {
+        self.timeout.get()
     }
 
     /// Get a reference to the inner service
@@ -45,10 +200,11 @@ impl<T> Timeout<T> {
     }
 }
 
-impl<S, Request> Service<Request> for Timeout<S>
+impl<S, O, Request> Service<Request> for Timeout<S, O>
 where
     S: Service<Request>,
     S::Error: Into<crate::BoxError>,
+    O: TimeoutOverride<Request>,
 {
     type Response = S::Response;
     type Error = crate::BoxError;
@@ -62,9 +218,78 @@ where
     }
 
     fn call(&mut self, request: Request) -> Self::Future {
-        let response = self.inner.call(request);
-        let sleep = tokio::time::sleep(self.timeout);
+        let timeout = self
+            .per_request
+            .timeout(&request)
+            .unwrap_or_else(|| self.timeout.get());
+        if !self.budget {
+            let response = self.inner.call(request);
+            let sleep = tokio::time::sleep(timeout);
+            return ResponseFuture::new(response, sleep, None);
+        }
+
+        let deadline = TimeBudget::clamp(deadline_after(timeout));
+        let inner = &mut self.inner;
+        let response = TimeBudget::scope(deadline, || inner.call(request));
+        let sleep = tokio::time::sleep_until(deadline);
+
+        ResponseFuture::new(response, sleep, Some(deadline))
+    }
+}
+
+impl<T: fmt::Debug, O> fmt::Debug for Timeout<T, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Timeout")
+            .field("inner", &self.inner)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+// ===== impl Source =====
+
+impl Source {
+    fn get(&self) -> Duration {
+        match self {
+            Source::Fixed(timeout) => *timeout,
+            Source::Watch(rx, hook) => {
+                if let Some(hook) = hook {
+                    if rx.has_changed().is_err() {
+                        hook.notify();
+                    }
+                }
+                *rx.borrow()
+            }
+        }
+    }
+
+    fn on_closed<F>(&mut self, hook: F)
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        if let Source::Watch(_, ref mut closed) = self {
+            *closed = Some(ClosedHook {
+                notified: Arc::new(AtomicBool::new(false)),
+                hook: Arc::new(hook),
+            });
+        }
+    }
+}
+
+// ===== impl ClosedHook =====
+
+impl ClosedHook {
+    fn notify(&self) {
+        if !self.notified.swap(true, Ordering::AcqRel) {
+            (self.hook)();
+        }
+    }
+}
 
-        ResponseFuture::new(response, sleep)
+impl fmt::Debug for ClosedHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClosedHook")
+            .field("notified", &self.notified)
+            .finish()
     }
 }
@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+/// Chooses the timeout for individual requests, overriding a [`Timeout`]'s
+/// default.
+///
+/// This is implemented for closures taking a reference to the request, so a
+/// request type that carries its own deadline may be supported with, for
+/// example, `|req: &MyRequest| req.timeout()`.
+///
+/// [`Timeout`]: super::Timeout
+pub trait TimeoutOverride<Request> {
+    /// Returns the timeout to apply to `request`, or `None` to use the
+    /// service's default timeout.
+    fn timeout(&self, request: &Request) -> Option<Duration>;
+}
+
+/// A [`TimeoutOverride`] that never overrides the default timeout.
+///
+/// This is the default for [`Timeout`].
+///
+/// [`Timeout`]: super::Timeout
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoOverride;
+
+impl<Request> TimeoutOverride<Request> for NoOverride {
+    fn timeout(&self, _: &Request) -> Option<Duration> {
+        None
+    }
+}
+
+impl<F, Request> TimeoutOverride<Request> for F
+where
+    F: Fn(&Request) -> Option<Duration>,
+{
+    fn timeout(&self, request: &Request) -> Option<Duration> {
+        self(request)
+    }
+}
@@ -16,6 +16,9 @@ mod oneshot;
 mod optional;
 mod ready;
 mod service_fn;
+mod sink;
+#[cfg(feature = "task-set")]
+mod task_set;
 mod then;
 
 #[allow(deprecated)]
@@ -33,10 +36,14 @@ pub use self::{
     optional::Optional,
     ready::{Ready, ReadyAnd, ReadyOneshot},
     service_fn::{service_fn, ServiceFn},
+    sink::{ServiceSink, SinkService},
     then::{Then, ThenLayer},
 };
 
 pub use self::call_all::{CallAll, CallAllUnordered};
+#[cfg(feature = "task-set")]
+#[cfg_attr(docsrs, doc(cfg(feature = "task-set")))]
+pub use self::task_set::{Spawn, Task, TaskSet, TokioExecutor};
 use std::future::Future;
 
 use crate::layer::util::Identity;
@@ -45,6 +52,9 @@ pub mod error {
     //! Error types
 
     pub use super::optional::error as optional;
+    #[cfg(feature = "task-set")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "task-set")))]
+    pub use super::task_set::error as task_set;
 }
 
 pub mod future {
@@ -98,6 +108,20 @@ pub trait ServiceExt<Request>: tower_service::Service<Request> {
         Oneshot::new(self, req)
     }
 
+    /// Convert this `Service` into a [`Sink`] of fire-and-forget requests.
+    ///
+    /// At most `max_in_flight` requests are pending at once, and failed responses are passed to
+    /// `on_error`. See the documentation for [`ServiceSink`] for details.
+    ///
+    /// [`Sink`]: https://docs.rs/futures/latest/futures/sink/trait.Sink.html
+    fn into_sink<F>(self, max_in_flight: usize, on_error: F) -> ServiceSink<Self, Request, F>
+    where
+        Self: Sized,
+        F: FnMut(Self::Error),
+    {
+        ServiceSink::new(self, max_in_flight, on_error)
+    }
+
     /// Process all requests from the given [`Stream`], and produce a [`Stream`] of their responses.
     ///
     /// This is essentially [`Stream<Item = Request>`][stream] + `Self` => [`Stream<Item =
@@ -0,0 +1,212 @@
+use futures_core::{ready, Stream};
+use futures_util::{future, sink::Sink, stream::FuturesUnordered};
+use pin_project::pin_project;
+use std::{
+    fmt,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tower_service::Service;
+
+/// A [`Sink`] that calls a [`Service`] with each item sent to it.
+///
+/// Requests are fire-and-forget: their responses are discarded, and failed
+/// responses are passed to an error callback rather than failing the sink. At
+/// most `max_in_flight` requests are pending at once; once this limit is
+/// reached, the sink applies backpressure until a request completes. Errors
+/// from the service's [`poll_ready`] fail the sink.
+///
+/// Flushing or closing the sink waits for all pending requests to complete.
+///
+/// This type is created by [`ServiceExt::into_sink`].
+///
+/// [`Sink`]: https://docs.rs/futures/latest/futures/sink/trait.Sink.html
+/// [`poll_ready`]: crate::Service::poll_ready
+/// [`ServiceExt::into_sink`]: crate::util::ServiceExt::into_sink
+#[pin_project]
+pub struct ServiceSink<S, Req, F>
+where
+    S: Service<Req>,
+{
+    service: S,
+    #[pin]
+    in_flight: FuturesUnordered<S::Future>,
+    max_in_flight: usize,
+    on_error: F,
+    _req: PhantomData<fn(Req)>,
+}
+
+/// A [`Service`] that sends each request to a [`Sink`].
+///
+/// Each call sends its request to the sink and resolves once the request has
+/// been accepted. Requests that have been accepted are flushed the next time
+/// the service is polled for readiness, or when [`SinkService::poll_flush`]
+/// is called.
+///
+/// [`Sink`]: https://docs.rs/futures/latest/futures/sink/trait.Sink.html
+#[derive(Clone, Debug)]
+pub struct SinkService<K> {
+    sink: K,
+    unflushed: bool,
+}
+
+// ===== impl ServiceSink =====
+
+impl<S, Req, F> ServiceSink<S, Req, F>
+where
+    S: Service<Req>,
+    F: FnMut(S::Error),
+{
+    /// Creates a [`Sink`] that calls `service` with each item, allowing at most
+    /// `max_in_flight` pending requests and passing failed responses to
+    /// `on_error`.
+    ///
+    /// # Panics
+    ///
+    /// If `max_in_flight` is 0.
+    ///
+    /// [`Sink`]: https://docs.rs/futures/latest/futures/sink/trait.Sink.html
+    pub fn new(service: S, max_in_flight: usize, on_error: F) -> Self {
+        assert!(max_in_flight > 0, "max_in_flight must be positive");
+        Self {
+            service,
+            in_flight: FuturesUnordered::new(),
+            max_in_flight,
+            on_error,
+            _req: PhantomData,
+        }
+    }
+
+    /// Get a reference to the inner service
+    pub fn get_ref(&self) -> &S {
+        &self.service
+    }
+
+    /// Get a mutable reference to the inner service
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.service
+    }
+
+    /// Consume `self`, returning the inner service
+    ///
+    /// Any pending requests are dropped.
+    pub fn into_inner(self) -> S {
+        self.service
+    }
+
+    /// Drives pending requests until at most `max` remain.
+    fn poll_in_flight(self: Pin<&mut Self>, cx: &mut Context<'_>, max: usize) -> Poll<()> {
+        let mut this = self.project();
+        while this.in_flight.len() > max {
+            match ready!(this.in_flight.as_mut().poll_next(cx)) {
+                Some(Ok(_)) => {}
+                Some(Err(error)) => (this.on_error)(error),
+                None => break,
+            }
+        }
+        Poll::Ready(())
+    }
+}
+
+impl<S, Req, F> Sink<Req> for ServiceSink<S, Req, F>
+where
+    S: Service<Req>,
+    F: FnMut(S::Error),
+{
+    type Error = S::Error;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+        let max = self.max_in_flight - 1;
+        ready!(self.as_mut().poll_in_flight(cx, max));
+        self.project().service.poll_ready(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Req) -> Result<(), S::Error> {
+        let this = self.project();
+        let fut = this.service.call(item);
+        this.in_flight.push(fut);
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+        ready!(self.poll_in_flight(cx, 0));
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+        self.poll_flush(cx)
+    }
+}
+
+impl<S, Req, F> fmt::Debug for ServiceSink<S, Req, F>
+where
+    S: Service<Req> + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServiceSink")
+            .field("service", &self.service)
+            .field("in_flight", &self.in_flight.len())
+            .field("max_in_flight", &self.max_in_flight)
+            .finish()
+    }
+}
+
+// ===== impl SinkService =====
+
+impl<K> SinkService<K> {
+    /// Creates a [`Service`] that sends each request to `sink`.
+    pub fn new(sink: K) -> Self {
+        Self {
+            sink,
+            unflushed: false,
+        }
+    }
+
+    /// Flushes the requests that have been sent to the sink.
+    pub fn poll_flush<Req>(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), K::Error>>
+    where
+        K: Sink<Req> + Unpin,
+    {
+        if self.unflushed {
+            ready!(Pin::new(&mut self.sink).poll_flush(cx))?;
+            self.unflushed = false;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    /// Get a reference to the inner sink
+    pub fn get_ref(&self) -> &K {
+        &self.sink
+    }
+
+    /// Get a mutable reference to the inner sink
+    pub fn get_mut(&mut self) -> &mut K {
+        &mut self.sink
+    }
+
+    /// Consume `self`, returning the inner sink
+    pub fn into_inner(self) -> K {
+        self.sink
+    }
+}
+
+impl<K, Req> Service<Req> for SinkService<K>
+where
+    K: Sink<Req> + Unpin,
+{
+    type Response = ();
+    type Error = K::Error;
+    type Future = future::Ready<Result<(), K::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        ready!(self.poll_flush(cx))?;
+        Pin::new(&mut self.sink).poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Req) -> Self::Future {
+        let res = Pin::new(&mut self.sink).start_send(req);
+        self.unflushed |= res.is_ok();
+        future::ready(res)
+    }
+}
@@ -0,0 +1,24 @@
+//! Error types
+
+use std::{error, fmt};
+
+/// Error returned by a [`Task`] that was aborted, or that panicked, before it
+/// completed.
+///
+/// [`Task`]: super::Task
+#[derive(Debug)]
+pub struct Cancelled(());
+
+impl Cancelled {
+    pub(crate) fn new() -> Cancelled {
+        Cancelled(())
+    }
+}
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("task was cancelled")
+    }
+}
+
+impl error::Error for Cancelled {}
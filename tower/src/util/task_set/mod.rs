@@ -0,0 +1,238 @@
+//! Ties spawned tasks to the lifetime of a handle.
+
+use self::error::Cancelled;
+use futures_util::future::{AbortHandle, Abortable};
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex, Weak},
+    task::{Context, Poll},
+};
+use tokio::sync::oneshot;
+
+pub mod error;
+
+/// A set of spawned tasks that are aborted when the set is dropped.
+///
+/// Middleware such as [`SpawnReady`] and [`Buffer`] spawn helper tasks onto
+/// the Tokio runtime. Detached tasks may outlive the component that spawned
+/// them, which can leak tasks in long-running processes. Spawning them onto a
+/// [`TaskSet`] instead ties them to its handles: clones of a [`TaskSet`]
+/// share the same tasks, and once every clone has been dropped, any tasks
+/// that are still running are aborted. Tasks may also be aborted explicitly
+/// with [`TaskSet::abort_all`].
+///
+/// Completed tasks are removed from the set as they finish.
+///
+/// Tasks are spawned onto the Tokio runtime by default. Any other executor
+/// that implements [`Spawn`] may be used instead with
+/// [`TaskSet::with_executor`].
+///
+/// [`SpawnReady`]: crate::spawn_ready::SpawnReady
+/// [`Buffer`]: crate::buffer::Buffer
+#[derive(Clone, Default)]
+pub struct TaskSet {
+    tasks: Arc<Tasks>,
+}
+
+/// An executor onto which a [`TaskSet`] spawns its tasks.
+///
+/// The executor must drive each task to completion in the background. Tasks
+/// are aborted by the [`TaskSet`] itself, so the executor need not support
+/// cancellation.
+///
+/// This trait is implemented for closures taking the task to spawn.
+pub trait Spawn {
+    /// Spawns `task` to run in the background.
+    fn spawn(&self, task: Pin<Box<dyn Future<Output = ()> + Send + 'static>>);
+}
+
+/// A [`Spawn`] implementation that spawns tasks onto the current Tokio
+/// runtime.
+///
+/// This is the executor used by default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TokioExecutor;
+
+/// A handle to a task spawned on a [`TaskSet`].
+///
+/// The handle resolves to the task's output, or fails with [`Cancelled`] if
+/// the task was aborted or panicked. Dropping the handle does not abort the
+/// task.
+pub struct Task<T> {
+    id: u64,
+    rx: oneshot::Receiver<T>,
+    tasks: Weak<Tasks>,
+}
+
+struct Tasks {
+    executor: Box<dyn Spawn + Send + Sync>,
+    handles: Mutex<Handles>,
+}
+
+#[derive(Default)]
+struct Handles {
+    next_id: u64,
+    running: HashMap<u64, AbortHandle>,
+}
+
+// ===== impl TaskSet =====
+
+impl TaskSet {
+    /// Creates an empty [`TaskSet`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty [`TaskSet`] that spawns its tasks onto `executor`.
+    pub fn with_executor<E>(executor: E) -> Self
+    where
+        E: Spawn + Send + Sync + 'static,
+    {
+        Self {
+            tasks: Arc::new(Tasks {
+                executor: Box::new(executor),
+                handles: Mutex::default(),
+            }),
+        }
+    }
+
+    /// Spawns `future` onto this set's executor as part of this set.
+    ///
+    /// With the default executor, this must be called while on the Tokio
+    /// runtime.
+    pub fn spawn<F>(&self, future: F) -> Task<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        let tasks = Arc::downgrade(&self.tasks);
+        let (abort, registration) = AbortHandle::new_pair();
+
+        // The task is inserted before it is spawned, so that a task that
+        // completes immediately cannot try to remove itself before it is
+        // inserted, and so that the lock is not held while spawning.
+        let id = {
+            let mut handles = self.tasks.handles.lock().unwrap();
+            let id = handles.next_id;
+            handles.next_id += 1;
+            handles.running.insert(id, abort);
+            id
+        };
+        let cleanup = tasks.clone();
+        let task = Abortable::new(
+            async move {
+                let _ = tx.send(future.await);
+            },
+            registration,
+        );
+        self.tasks.executor.spawn(Box::pin(async move {
+            let _ = task.await;
+            if let Some(tasks) = cleanup.upgrade() {
+                tasks.remove(id);
+            }
+        }));
+
+        Task { id, rx, tasks }
+    }
+
+    /// Returns the number of tasks in this set that have not yet completed.
+    pub fn len(&self) -> usize {
+        self.tasks.handles.lock().unwrap().running.len()
+    }
+
+    /// Returns whether every task in this set has completed.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Aborts every task in this set.
+    pub fn abort_all(&self) {
+        self.tasks.abort_all();
+    }
+}
+
+impl fmt::Debug for TaskSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TaskSet").field("len", &self.len()).finish()
+    }
+}
+
+// ===== impl Task =====
+
+impl<T> Task<T> {
+    /// Aborts the task.
+    pub fn abort(&self) {
+        if let Some(tasks) = self.tasks.upgrade() {
+            if let Some(handle) = tasks.remove(self.id) {
+                handle.abort();
+            }
+        }
+    }
+}
+
+impl<T> Future for Task<T> {
+    type Output = Result<T, Cancelled>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.rx)
+            .poll(cx)
+            .map_err(|_| Cancelled::new())
+    }
+}
+
+impl<T> fmt::Debug for Task<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Task").field("id", &self.id).finish()
+    }
+}
+
+// ===== impl Spawn =====
+
+impl<F> Spawn for F
+where
+    F: Fn(Pin<Box<dyn Future<Output = ()> + Send + 'static>>),
+{
+    fn spawn(&self, task: Pin<Box<dyn Future<Output = ()> + Send + 'static>>) {
+        (self)(task)
+    }
+}
+
+impl Spawn for TokioExecutor {
+    fn spawn(&self, task: Pin<Box<dyn Future<Output = ()> + Send + 'static>>) {
+        tokio::spawn(task);
+    }
+}
+
+// ===== impl Tasks =====
+
+impl Default for Tasks {
+    fn default() -> Self {
+        Self {
+            executor: Box::new(TokioExecutor),
+            handles: Mutex::default(),
+        }
+    }
+}
+
+impl Tasks {
+    fn remove(&self, id: u64) -> Option<AbortHandle> {
+        self.handles.lock().unwrap().running.remove(&id)
+    }
+
+    fn abort_all(&self) {
+        let running = std::mem::take(&mut self.handles.lock().unwrap().running);
+        for (_, handle) in running {
+            handle.abort();
+        }
+    }
+}
+
+impl Drop for Tasks {
+    fn drop(&mut self) {
+        self.abort_all();
+    }
+}
@@ -168,3 +168,274 @@ fn stress() {
         }
     }
 }
+
+#[cfg(all(feature = "buffer", feature = "retry"))]
+#[tokio::test(flavor = "current_thread")]
+async fn retries_failed_request_on_another_endpoint() {
+    use std::sync::Arc;
+    use tower::buffer::Buffer;
+    use tower::retry::budget::{Budget, BudgetedRetry};
+    use tower::retry::Retry;
+
+    let _t = support::trace_init();
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel::<Result<_, &'static str>>();
+    let (svc_a, mut handle_a) = mock::pair::<Req, Req>();
+    let (svc_b, mut handle_b) = mock::pair::<Req, Req>();
+    handle_a.allow(1);
+    handle_b.allow(1);
+    tx.send(Ok(Change::Insert("a", Mock(svc_a)))).unwrap();
+    tx.send(Ok(Change::Insert("b", Mock(svc_b)))).unwrap();
+
+    let policy = BudgetedRetry::new(Arc::new(Budget::default()), |_: &tower::BoxError| true);
+    let balance = Balance::new(support::IntoStream(rx));
+    let mut svc = Retry::new(policy, Buffer::new(balance, 10));
+
+    futures_util::future::poll_fn(|cx| svc.poll_ready(cx))
+        .await
+        .unwrap();
+    let rsp = tokio::spawn(svc.call("hello"));
+
+    // Whichever endpoint is selected first fails the request. Because it has
+    // no more capacity, the retry must be dispatched to the other endpoint.
+    let mut retry_handle = tokio::select! {
+        req = handle_a.next_request() => {
+            req.unwrap().1.send_error("doom");
+            handle_b
+        }
+        req = handle_b.next_request() => {
+            req.unwrap().1.send_error("doom");
+            handle_a
+        }
+    };
+    let (req, send_response) = retry_handle.next_request().await.unwrap();
+    assert_eq!(req, "hello");
+    send_response.send_response("world");
+
+    assert_eq!(rsp.await.unwrap().unwrap(), "world");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn health_checked_endpoint_becomes_unready() {
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+    use std::time::Duration;
+    use tokio_test::assert_ready_ok;
+    use tower::balance::health::HealthChecked;
+
+    let _t = support::trace_init();
+    tokio::time::pause();
+
+    let healthy = Arc::new(AtomicBool::new(true));
+    let (mut svc, mut handle) = mock::spawn_with::<Req, Req, _, _>(|s| {
+        let healthy = healthy.clone();
+        let check = move || futures_util::future::ready(healthy.load(Ordering::SeqCst));
+        HealthChecked::new(s, check, Duration::from_secs(1), 2)
+    });
+    handle.allow(10);
+    assert_ready_ok!(svc.poll_ready());
+
+    // A single failed probe does not make the endpoint unready.
+    healthy.store(false, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(1001)).await;
+    assert_ready_ok!(svc.poll_ready());
+
+    tokio::time::sleep(Duration::from_millis(1001)).await;
+    assert_pending!(svc.poll_ready());
+    assert!(!svc.get_ref().is_healthy());
+
+    // The endpoint recovers once a probe succeeds.
+    healthy.store(true, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(1001)).await;
+    assert!(svc.is_woken());
+    assert_ready_ok!(svc.poll_ready());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn idle_health_checked_endpoint_recovers() {
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+    use std::time::Duration;
+    use tokio_test::assert_ready_ok;
+    use tower::balance::health::HealthChecked;
+
+    let _t = support::trace_init();
+    tokio::time::pause();
+
+    let healthy = Arc::new(AtomicBool::new(false));
+    let (mut svc, mut handle) = mock::spawn_with::<Req, Req, _, _>(|s| {
+        let healthy = healthy.clone();
+        let check = move || futures_util::future::ready(healthy.load(Ordering::SeqCst));
+        HealthChecked::new(s, check, Duration::from_secs(1), 1)
+    });
+    handle.allow(10);
+
+    // Probes run while the endpoint is not polled.
+    tokio::time::sleep(Duration::from_millis(1001)).await;
+    assert!(!svc.get_ref().is_healthy());
+    assert_pending!(svc.poll_ready());
+
+    healthy.store(true, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(1001)).await;
+    assert!(svc.get_ref().is_healthy());
+    assert!(svc.is_woken());
+    assert_ready_ok!(svc.poll_ready());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn evict_on_error_fails_endpoint() {
+    use std::io;
+    use tokio_test::{assert_ready_err, assert_ready_ok};
+    use tower::balance::health::EvictOnError;
+    use tower::classify::DefaultClassify;
+
+    let _t = support::trace_init();
+
+    let (mut svc, mut handle) =
+        mock::spawn_with::<Req, Req, _, _>(|s| EvictOnError::new(s, DefaultClassify));
+    handle.allow(10);
+
+    // Errors that do not reflect on the endpoint do not evict it.
+    assert_ready_ok!(svc.poll_ready());
+    let mut rsp = task::spawn(svc.call("hello"));
+    let (_, send) = assert_ready!(handle.poll_request()).unwrap();
+    send.send_error(io::Error::new(io::ErrorKind::InvalidInput, "invalid"));
+    assert_ready_err!(rsp.poll());
+    assert!(!svc.get_ref().is_failed());
+
+    assert_ready_ok!(svc.poll_ready());
+    let mut rsp = task::spawn(svc.call("hello"));
+    let (_, send) = assert_ready!(handle.poll_request()).unwrap();
+    send.send_error(io::Error::new(io::ErrorKind::ConnectionReset, "reset"));
+    assert_eq!(assert_ready_err!(rsp.poll()).to_string(), "reset");
+    assert!(svc.get_ref().is_failed());
+
+    let error = assert_ready_err!(svc.poll_ready());
+    assert!(error.is::<tower::balance::error::Evicted>());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn discovers_from_infallible_stream() {
+    use tokio_test::assert_ready_ok;
+    use tower::discover::StreamDiscover;
+
+    let _t = support::trace_init();
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let disco = StreamDiscover::new(tokio_stream::wrappers::UnboundedReceiverStream::new(rx));
+    let mut svc = mock::Spawn::new(Balance::<_, Req>::new(disco));
+    assert_pending!(svc.poll_ready());
+
+    let (svc_a, mut handle_a) = mock::pair::<Req, Req>();
+    handle_a.allow(1);
+    tx.send(Change::Insert("a", Mock(svc_a))).unwrap();
+    assert!(svc.is_woken());
+    assert_ready_ok!(svc.poll_ready());
+
+    let mut rsp = task::spawn(svc.call("hello"));
+    let (req, send_response) = assert_ready!(handle_a.poll_request()).unwrap();
+    assert_eq!(req, "hello");
+    send_response.send_response("world");
+    assert_eq!(assert_ready_ok!(rsp.poll()), "world");
+
+    tx.send(Change::Remove("a")).unwrap();
+    assert_pending!(svc.poll_ready());
+    assert_eq!(svc.get_ref().len(), 0);
+}
+
+#[cfg(feature = "spawn-ready")]
+#[tokio::test(flavor = "current_thread")]
+async fn woken_when_spawn_ready_endpoint_becomes_ready() {
+    use tokio_test::assert_ready_ok;
+    use tower::discover::StreamDiscover;
+    use tower::spawn_ready::SpawnReady;
+
+    let _t = support::trace_init();
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let disco = StreamDiscover::new(tokio_stream::wrappers::UnboundedReceiverStream::new(rx));
+    let mut svc = mock::Spawn::new(Balance::<_, Req>::new(disco));
+
+    let (svc_a, mut handle_a) = mock::pair::<Req, Req>();
+    handle_a.allow(0);
+    let endpoint = tower::load::Constant::new(SpawnReady::new(svc_a), 0);
+    tx.send(Change::Insert("a", endpoint)).unwrap();
+
+    // The endpoint's readiness is driven on a background task.
+    assert_pending!(svc.poll_ready());
+    tokio::task::yield_now().await;
+    assert_pending!(svc.poll_ready());
+    tokio::task::yield_now().await;
+    assert!(!svc.is_woken());
+
+    // The balancer is woken once the background task completes.
+    handle_a.allow(1);
+    for _ in 0..10 {
+        if svc.is_woken() {
+            break;
+        }
+        tokio::task::yield_now().await;
+    }
+    assert!(svc.is_woken());
+    assert_ready_ok!(svc.poll_ready());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn discovers_from_list_handle() {
+    use tokio_test::assert_ready_ok;
+    use tower::discover::ChannelList;
+
+    let _t = support::trace_init();
+
+    let (list, handle) = ChannelList::pair();
+    let mut svc = mock::Spawn::new(Balance::<_, Req>::new(list));
+    assert_pending!(svc.poll_ready());
+
+    let (svc_a, mut handle_a) = mock::pair::<Req, Req>();
+    handle_a.allow(1);
+    assert!(handle.insert("a", Mock(svc_a)));
+    assert!(svc.is_woken());
+    assert_ready_ok!(svc.poll_ready());
+    assert_eq!(svc.get_ref().len(), 1);
+
+    assert!(handle.remove("a"));
+    assert_pending!(svc.poll_ready());
+    assert_eq!(svc.get_ref().len(), 0);
+
+    drop(svc);
+    assert!(handle.is_closed());
+}
+
+#[tokio::test]
+async fn make_balance_instruments_endpoints() {
+    use tokio_test::assert_ready_ok;
+    use tower::balance::p2c::MakeBalance;
+    use tower::discover::ServiceList;
+
+    let _t = support::trace_init();
+
+    // The endpoints do not implement `Load` themselves.
+    type Endpoints = ServiceList<Vec<mock::Mock<Req, Req>>>;
+    let (make, mut make_handle) = mock::pair::<(), Endpoints>();
+    let mut make = mock::Spawn::new(MakeBalance::new(make).with_pending_requests());
+
+    let (endpoint, mut handle) = mock::pair::<Req, Req>();
+    make_handle.allow(1);
+    assert_ready_ok!(make.poll_ready());
+    let balance = make.call(());
+    let (_, rsp) = make_handle.next_request().await.unwrap();
+    rsp.send_response(ServiceList::new(vec![endpoint]));
+    let mut balance = mock::Spawn::new(balance.await.unwrap());
+
+    handle.allow(1);
+    assert_ready_ok!(balance.poll_ready());
+    let rsp = balance.call("hello");
+    let (req, send) = handle.next_request().await.unwrap();
+    assert_eq!(req, "hello");
+    send.send_response("world");
+    assert_eq!(rsp.await.unwrap(), "world");
+}
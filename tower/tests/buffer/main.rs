@@ -3,7 +3,7 @@
 mod support;
 use std::thread;
 use tokio_test::{assert_pending, assert_ready, assert_ready_err, assert_ready_ok, task};
-use tower::buffer::{error, Buffer};
+use tower::buffer::{error, Buffer, Builder, Policy, PriorityBuffer};
 use tower::{util::ServiceExt, Service};
 use tower_test::{assert_request_eq, mock};
 
@@ -408,6 +408,424 @@ async fn doesnt_leak_permits() {
     assert_ready_ok!(ready3.poll());
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn dispatch_hook_observes_envelopes() {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    let _t = support::trace_init();
+
+    let dispatched = Arc::new(Mutex::new(Vec::new()));
+    let (mut service, mut handle) = mock::spawn_with(|s| {
+        let dispatched = dispatched.clone();
+        let (svc, worker) = tower::buffer::Builder::new(10)
+            .on_dispatch(move |envelope| {
+                dispatched
+                    .lock()
+                    .unwrap()
+                    .push((envelope.caller(), envelope.deadline().is_some()))
+            })
+            .pair(s);
+
+        // Queue timeouts are tracked with the runtime's timer.
+        let rt = tokio::runtime::Handle::current();
+        thread::spawn(move || {
+            let _enter = rt.enter();
+            let mut fut = tokio_test::task::spawn(worker);
+            while fut.poll().is_pending() {}
+        });
+
+        svc
+    });
+
+    let mut service2 = mock::Spawn::new(service.get_ref().clone());
+    service2
+        .get_mut()
+        .set_queue_timeout(Some(Duration::from_secs(1)));
+    assert_ne!(
+        service.get_ref().caller_id(),
+        service2.get_ref().caller_id()
+    );
+
+    assert_ready_ok!(service.poll_ready());
+    let mut response1 = task::spawn(service.call("hello"));
+    assert_request_eq!(handle, "hello").send_response("world");
+
+    assert_ready_ok!(service2.poll_ready());
+    let mut response2 = task::spawn(service2.call("hello2"));
+    assert_request_eq!(handle, "hello2").send_response("world2");
+
+    let_worker_work();
+    assert_eq!(assert_ready_ok!(response1.poll()), "world");
+    assert_eq!(assert_ready_ok!(response2.poll()), "world2");
+
+    assert_eq!(
+        *dispatched.lock().unwrap(),
+        vec![
+            (service.get_ref().caller_id(), false),
+            (service2.get_ref().caller_id(), true),
+        ]
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn scoped_accepts_borrowed_requests() {
+    use std::{cell::Cell, rc::Rc};
+
+    let _t = support::trace_init();
+
+    // Neither the service nor its requests are `Send` or `'static`.
+    let calls = Rc::new(Cell::new(0));
+    let svc = tower::service_fn({
+        let calls = calls.clone();
+        move |req: &[u8]| {
+            calls.set(calls.get() + 1);
+            futures_util::future::ok::<_, tower::BoxError>(req.len())
+        }
+    });
+    let data = [1u8, 2, 3, 4];
+
+    let (buffer, worker) = Buffer::scoped(svc, 1);
+    let requests = async {
+        let rsp1 = buffer.clone().oneshot(&data[..2]);
+        let rsp2 = buffer.oneshot(&data[..]);
+        futures_util::future::join(rsp1, rsp2).await
+    };
+    let ((), (rsp1, rsp2)) = futures_util::future::join(worker, requests).await;
+
+    assert_eq!(rsp1.unwrap(), 2);
+    assert_eq!(rsp2.unwrap(), 4);
+    assert_eq!(calls.get(), 2);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn reports_worker_panics() {
+    let _t = support::trace_init();
+
+    let svc = tower::service_fn(|req: &'static str| {
+        if req == "panic" {
+            panic!("service panicked on {}", req);
+        }
+        futures_util::future::ok::<_, tower::BoxError>(req)
+    });
+    let (mut service, worker) = Buffer::pair(svc, 2);
+    let mut worker = task::spawn(worker);
+
+    let rsp1 = service.ready().await.unwrap().call("hello");
+    let rsp2 = service.ready().await.unwrap().call("panic");
+    assert_ready!(worker.poll());
+
+    assert_eq!(rsp1.await.unwrap(), "hello");
+    let err = rsp2.await.unwrap_err();
+    let err = err
+        .downcast_ref::<error::WorkerError>()
+        .unwrap_or_else(|| panic!("should be a WorkerError: {:?}", err));
+    assert_eq!(err.message(), Some("service panicked on panic"));
+
+    // Later callers see the same error.
+    let err = service.ready().await.unwrap_err();
+    assert!(
+        err.is::<error::WorkerError>(),
+        "should be a WorkerError: {:?}",
+        err
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn drop_newest_on_overflow() {
+    let _t = support::trace_init();
+
+    let (svc, mut handle) = mock::pair::<&'static str, &'static str>();
+    let (mut service, worker) = Builder::new(1).policy(Policy::DropNewest).pair(svc);
+    let mut worker = task::spawn(worker);
+
+    handle.allow(0);
+    let rsp1 = service.ready().await.unwrap().call("hello");
+    assert_pending!(worker.poll());
+
+    // The queue is full, so the new request fails immediately.
+    let rsp2 = service.ready().await.unwrap().call("world");
+    let err = rsp2.await.unwrap_err();
+    assert!(
+        err.is::<error::Overloaded>(),
+        "should be Overloaded: {:?}",
+        err
+    );
+
+    handle.allow(1);
+    assert_pending!(worker.poll());
+    assert_request_eq!(handle, "hello").send_response("goodbye");
+    assert_eq!(rsp1.await.unwrap(), "goodbye");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn drop_oldest_on_overflow() {
+    let _t = support::trace_init();
+
+    let (svc, mut handle) = mock::pair::<&'static str, &'static str>();
+    let (mut service, worker) = Builder::new(1).policy(Policy::DropOldest).pair(svc);
+    let mut worker = task::spawn(worker);
+
+    handle.allow(0);
+    let rsp1 = service.ready().await.unwrap().call("hello");
+    assert_pending!(worker.poll());
+
+    // The oldest request is dropped to make room for the new one.
+    let rsp2 = service.ready().await.unwrap().call("world");
+    assert_pending!(worker.poll());
+    let err = rsp1.await.unwrap_err();
+    assert!(
+        err.is::<error::Overloaded>(),
+        "should be Overloaded: {:?}",
+        err
+    );
+
+    handle.allow(1);
+    assert_pending!(worker.poll());
+    assert_request_eq!(handle, "world").send_response("goodbye");
+    assert_eq!(rsp2.await.unwrap(), "goodbye");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn priority_buffer_dispatches_by_priority() {
+    let _t = support::trace_init();
+
+    let (svc, mut handle) = mock::pair::<&'static str, &'static str>();
+    let priority = |req: &&'static str| if req.starts_with("high") { 1 } else { 0 };
+    let (service, worker) = PriorityBuffer::pair(svc, 10, priority);
+    let mut service = service.with_limit(0, 2);
+    let mut worker = task::spawn(worker);
+
+    handle.allow(0);
+    let rsp1 = service.ready().await.unwrap().call("low 1");
+    assert_pending!(worker.poll());
+    let rsp2 = service.ready().await.unwrap().call("low 2");
+    let rsp3 = service.ready().await.unwrap().call("high");
+
+    // The low priority queue is full.
+    let err = service
+        .ready()
+        .await
+        .unwrap()
+        .call("low 3")
+        .await
+        .unwrap_err();
+    assert!(
+        err.is::<error::Overloaded>(),
+        "should be Overloaded: {:?}",
+        err
+    );
+
+    // The high priority request preempts the waiting request.
+    handle.allow(3);
+    assert_pending!(worker.poll());
+    assert_request_eq!(handle, "high").send_response("high");
+    assert_request_eq!(handle, "low 1").send_response("low 1");
+    assert_request_eq!(handle, "low 2").send_response("low 2");
+    assert_eq!(rsp1.await.unwrap(), "low 1");
+    assert_eq!(rsp2.await.unwrap(), "low 2");
+    assert_eq!(rsp3.await.unwrap(), "high");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn fails_requests_that_expire_while_queued() {
+    use std::time::Duration;
+
+    let _t = support::trace_init();
+    tokio::time::pause();
+
+    let (svc, mut handle) = mock::pair::<&'static str, &'static str>();
+    let (mut service, worker) = Builder::new(10)
+        .queue_timeout(Duration::from_secs(1))
+        .pair(svc);
+    let mut worker = task::spawn(worker);
+
+    handle.allow(0);
+    let rsp1 = service.ready().await.unwrap().call("hello");
+    let rsp2 = service.ready().await.unwrap().call("world");
+    assert_pending!(worker.poll());
+
+    // The requests expire while the inner service is not ready.
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert!(worker.is_woken());
+    assert_pending!(worker.poll());
+    for rsp in [rsp1, rsp2] {
+        let err = rsp.await.unwrap_err();
+        assert!(err.is::<error::Expired>(), "should be Expired: {:?}", err);
+    }
+
+    // Requests that are dispatched in time are unaffected.
+    handle.allow(1);
+    let rsp3 = service.ready().await.unwrap().call("hello");
+    assert_pending!(worker.poll());
+    assert_request_eq!(handle, "hello").send_response("goodbye");
+    assert_eq!(rsp3.await.unwrap(), "goodbye");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn fails_requests_behind_the_head_of_the_queue_when_they_expire() {
+    use std::time::Duration;
+
+    let _t = support::trace_init();
+    tokio::time::pause();
+
+    let (svc, mut handle) = mock::pair::<&'static str, &'static str>();
+    let (mut service, worker) = Builder::new(10).pair(svc);
+    let mut service2 = service.clone();
+    service2.set_queue_timeout(Some(Duration::from_secs(1)));
+    let mut worker = task::spawn(worker);
+
+    // The request at the head of the queue has no queue timeout.
+    handle.allow(0);
+    let mut rsp1 = task::spawn(service.ready().await.unwrap().call("hello"));
+    let rsp2 = service2.ready().await.unwrap().call("world");
+    assert_pending!(worker.poll());
+
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert!(worker.is_woken());
+    assert_pending!(worker.poll());
+    let err = rsp2.await.unwrap_err();
+    assert!(err.is::<error::Expired>(), "should be Expired: {:?}", err);
+    assert_pending!(rsp1.poll());
+
+    handle.allow(1);
+    assert_pending!(worker.poll());
+    assert_request_eq!(handle, "hello").send_response("goodbye");
+    assert_eq!(assert_ready_ok!(rsp1.poll()), "goodbye");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn admission_hook_rejects_tags_and_transforms() {
+    use std::sync::{Arc, Mutex};
+
+    let _t = support::trace_init();
+
+    let tags = Arc::new(Mutex::new(Vec::new()));
+    let (svc, mut handle) = mock::pair::<&'static str, &'static str>();
+    let (service, worker) = {
+        let tags = tags.clone();
+        Builder::new(10)
+            .on_dispatch(move |envelope| tags.lock().unwrap().push(envelope.tag()))
+            .pair(svc)
+    };
+    let mut service = service.with_admission(|req, admission: &mut tower::buffer::Admission| {
+        if admission.queue_depth() >= 2 {
+            return Err("queue too deep".into());
+        }
+        admission.set_tag(admission.queue_depth() as u64);
+        Ok(if req == "ping" { "pong" } else { req })
+    });
+    let mut worker = task::spawn(worker);
+
+    handle.allow(0);
+    let rsp1 = service.ready().await.unwrap().call("ping");
+    let rsp2 = service.ready().await.unwrap().call("hello");
+    let rsp3 = service.ready().await.unwrap().call("rejected");
+    let err = rsp3.await.unwrap_err();
+    assert!(err.is::<error::Rejected>(), "should be Rejected: {:?}", err);
+    let reason = std::error::Error::source(&*err).expect("rejection must have a reason");
+    assert_eq!(reason.to_string(), "queue too deep");
+
+    handle.allow(2);
+    assert_pending!(worker.poll());
+    assert_request_eq!(handle, "pong").send_response("rsp1");
+    assert_request_eq!(handle, "hello").send_response("rsp2");
+    assert_eq!(rsp1.await.unwrap(), "rsp1");
+    assert_eq!(rsp2.await.unwrap(), "rsp2");
+    assert_eq!(*tags.lock().unwrap(), vec![Some(0), Some(1)]);
+
+    // Dispatched requests no longer count towards the queue's depth.
+    handle.allow(1);
+    let rsp4 = service.ready().await.unwrap().call("again");
+    assert_pending!(worker.poll());
+    assert_request_eq!(handle, "again").send_response("rsp4");
+    assert_eq!(rsp4.await.unwrap(), "rsp4");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn journal_replays_undispatched_requests() {
+    use std::collections::BTreeMap;
+    use std::sync::{Arc, Mutex};
+    use tower::buffer::Journal;
+
+    #[derive(Clone, Default)]
+    struct MemoryJournal(Arc<Mutex<(u64, BTreeMap<u64, &'static str>)>>);
+
+    impl Journal<&'static str> for MemoryJournal {
+        fn append(&self, request: &&'static str) -> Result<u64, tower::BoxError> {
+            if *request == "unrecordable" {
+                return Err("journal full".into());
+            }
+            let mut journal = self.0.lock().unwrap();
+            journal.0 += 1;
+            let id = journal.0;
+            journal.1.insert(id, *request);
+            Ok(id)
+        }
+
+        fn remove(&self, id: u64) {
+            self.0.lock().unwrap().1.remove(&id);
+        }
+
+        fn replay(&self) -> Vec<(u64, &'static str)> {
+            let journal = self.0.lock().unwrap();
+            journal.1.iter().map(|(id, req)| (*id, *req)).collect()
+        }
+    }
+
+    impl MemoryJournal {
+        fn recorded(&self) -> Vec<&'static str> {
+            self.0.lock().unwrap().1.values().copied().collect()
+        }
+    }
+
+    let _t = support::trace_init();
+    let journal = MemoryJournal::default();
+
+    // Requests that are queued when the worker is dropped remain recorded.
+    let (svc, mut handle) = mock::pair::<&'static str, &'static str>();
+    let (service, worker) = Buffer::pair(svc, 10);
+    let mut service = service.with_journal(journal.clone());
+    let mut worker = task::spawn(worker);
+
+    handle.allow(1);
+    let rsp1 = service.ready().await.unwrap().call("hello");
+    let rsp2 = service.ready().await.unwrap().call("world");
+    let err = service
+        .ready()
+        .await
+        .unwrap()
+        .call("unrecordable")
+        .await
+        .unwrap_err();
+    assert!(
+        err.is::<error::Unrecorded>(),
+        "should be Unrecorded: {:?}",
+        err
+    );
+    assert_eq!(journal.recorded(), vec!["hello", "world"]);
+
+    assert_pending!(worker.poll());
+    assert_request_eq!(handle, "hello").send_response("rsp1");
+    assert_eq!(rsp1.await.unwrap(), "rsp1");
+    assert_eq!(journal.recorded(), vec!["world"]);
+    drop((worker, rsp2, service));
+
+    // A new buffer replays the remaining requests.
+    let (svc, mut handle) = mock::pair::<&'static str, &'static str>();
+    let (service, worker) = Buffer::pair(svc, 10);
+    let mut service = service.with_journal(journal.clone());
+    let mut worker = task::spawn(worker);
+    let mut replayed = service.replay();
+    assert_eq!(replayed.len(), 1);
+
+    handle.allow(1);
+    assert_pending!(worker.poll());
+    assert_request_eq!(handle, "world").send_response("rsp2");
+    assert_eq!(replayed.pop().unwrap().await.unwrap(), "rsp2");
+    assert!(journal.recorded().is_empty());
+}
+
 type Mock = mock::Mock<&'static str, &'static str>;
 type Handle = mock::Handle<&'static str, &'static str>;
 
@@ -0,0 +1,71 @@
+use futures_util::future::poll_fn;
+use std::pin::Pin;
+use tokio_test::assert_pending;
+use tower::discover::{Change, ChannelList, Dedup, Discover};
+
+type Disco = Dedup<ChannelList<u32, &'static str>>;
+
+async fn next_change(disco: &mut Disco) -> Change<u32, &'static str> {
+    poll_fn(|cx| Pin::new(&mut *disco).poll_discover(cx))
+        .await
+        .expect("discovery must not end")
+        .expect("discovery must not fail")
+}
+
+fn assert_no_change(disco: &mut Disco) {
+    let mut next = tokio_test::task::spawn(poll_fn(|cx| Pin::new(&mut *disco).poll_discover(cx)));
+    assert_pending!(next.poll());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn suppresses_redundant_changes() {
+    let (list, handle) = ChannelList::pair();
+    let mut disco = Dedup::new(list);
+
+    handle.insert(1, "a");
+    handle.insert(1, "a");
+    handle.remove(5);
+    handle.insert(2, "b");
+    handle.remove(2);
+    handle.insert(3, "c");
+    handle.insert(3, "d");
+    assert!(matches!(
+        next_change(&mut disco).await,
+        Change::Insert(1, "a")
+    ));
+    assert!(matches!(
+        next_change(&mut disco).await,
+        Change::Insert(3, "d")
+    ));
+    assert_no_change(&mut disco);
+
+    // Unchanged services are suppressed across batches, too.
+    handle.insert(1, "a");
+    handle.insert(3, "d");
+    assert_no_change(&mut disco);
+
+    handle.insert(1, "b");
+    assert!(matches!(
+        next_change(&mut disco).await,
+        Change::Insert(1, "b")
+    ));
+
+    // Removing a key that was yielded earlier supersedes pending changes.
+    handle.insert(3, "e");
+    handle.remove(3);
+    handle.remove(3);
+    assert!(matches!(next_change(&mut disco).await, Change::Remove(3)));
+    assert_no_change(&mut disco);
+
+    // Removed keys may be inserted again.
+    handle.insert(3, "d");
+    assert!(matches!(
+        next_change(&mut disco).await,
+        Change::Insert(3, "d")
+    ));
+
+    drop(handle);
+    assert!(poll_fn(|cx| Pin::new(&mut disco).poll_discover(cx))
+        .await
+        .is_none());
+}
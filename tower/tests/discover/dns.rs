@@ -0,0 +1,38 @@
+use super::support;
+use futures_util::future::poll_fn;
+use std::{net::SocketAddr, pin::Pin, time::Duration};
+use tokio_test::assert_pending;
+use tower::discover::{Change, Discover, DnsDiscover};
+use trust_dns_resolver::{
+    config::{ResolverConfig, ResolverOpts},
+    TokioAsyncResolver,
+};
+
+#[tokio::test(flavor = "current_thread")]
+async fn dns_discovers_resolved_addresses() {
+    let _t = support::trace_init();
+    tokio::time::pause();
+
+    let resolver = TokioAsyncResolver::tokio(ResolverConfig::default(), ResolverOpts::default())
+        .expect("resolver");
+    // IP literals are resolved without issuing a query.
+    let mut disco = DnsDiscover::new(resolver, "127.0.0.1", 8080, |addr: SocketAddr| addr)
+        .with_interval(Duration::from_secs(1));
+
+    let addr = "127.0.0.1:8080".parse::<SocketAddr>().unwrap();
+    match poll_fn(|cx| Pin::new(&mut disco).poll_discover(cx)).await {
+        Some(Ok(Change::Insert(key, svc))) => {
+            assert_eq!(key, addr);
+            assert_eq!(svc, addr);
+        }
+        change => panic!("unexpected change: {:?}", change),
+    }
+
+    // Re-resolving the same address yields no further changes.
+    let mut next = tokio_test::task::spawn(poll_fn(|cx| Pin::new(&mut disco).poll_discover(cx)));
+    assert_pending!(next.poll());
+    tokio::time::advance(Duration::from_millis(1001)).await;
+    assert_pending!(next.poll());
+    drop(next);
+    assert_eq!(disco.addrs().len(), 1);
+}
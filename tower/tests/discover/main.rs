@@ -0,0 +1,14 @@
+#![cfg(feature = "discover")]
+#[cfg(feature = "discover-channel")]
+mod dedup;
+#[cfg(feature = "dns")]
+mod dns;
+#[cfg(feature = "discover-channel")]
+mod map;
+#[cfg(all(feature = "resolve", feature = "util"))]
+mod resolve;
+#[cfg(feature = "discover-channel")]
+mod subset;
+#[cfg(any(feature = "dns", feature = "resolve"))]
+#[path = "../support.rs"]
+pub(crate) mod support;
@@ -0,0 +1,32 @@
+use futures_util::future::poll_fn;
+use std::pin::Pin;
+use tower::discover::{Change, ChannelList, Discover, DiscoverMap};
+
+#[tokio::test(flavor = "current_thread")]
+async fn maps_discovered_services() {
+    let (list, handle) = ChannelList::pair();
+    let mut disco = DiscoverMap::new(list, |key: &u32, svc: &'static str| {
+        format!("{}-{}", svc, key)
+    });
+
+    handle.insert(1, "a");
+    handle.insert(1, "b");
+    handle.remove(1);
+    drop(handle);
+
+    let mut changes = Vec::new();
+    while let Some(change) = poll_fn(|cx| Pin::new(&mut disco).poll_discover(cx)).await {
+        changes.push(match change.expect("discovery must not fail") {
+            Change::Insert(key, svc) => (key, Some(svc)),
+            Change::Remove(key) => (key, None),
+        });
+    }
+    assert_eq!(
+        changes,
+        vec![
+            (1, Some("a-1".to_string())),
+            (1, Some("b-1".to_string())),
+            (1, None),
+        ]
+    );
+}
@@ -0,0 +1,70 @@
+use super::support;
+use futures_util::future::{self, poll_fn};
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio_test::assert_pending;
+use tower::discover::{Change, Discover, ResolveDiscover};
+use tower::{service_fn, BoxError};
+
+#[tokio::test(flavor = "current_thread")]
+async fn resolve_discovers_made_services() {
+    let _t = support::trace_init();
+    tokio::time::pause();
+
+    let addrs = Arc::new(Mutex::new(vec![1u16, 2, 13]));
+    let resolver = service_fn({
+        let addrs = addrs.clone();
+        move |name: &'static str| {
+            assert_eq!(name, "svc");
+            future::ok::<_, BoxError>(addrs.lock().unwrap().clone())
+        }
+    });
+    // Services can't be made for unlucky addresses.
+    let make = service_fn(|addr: u16| {
+        if addr == 13 {
+            future::err::<String, BoxError>("unlucky".into())
+        } else {
+            future::ok(format!("svc-{}", addr))
+        }
+    });
+    let mut disco =
+        ResolveDiscover::new(resolver, "svc", make).with_interval(Duration::from_secs(1));
+
+    let mut changes = vec![next_change(&mut disco).await, next_change(&mut disco).await];
+    changes.sort();
+    assert_eq!(
+        changes,
+        vec![
+            (1, Some("svc-1".to_string())),
+            (2, Some("svc-2".to_string())),
+        ]
+    );
+    assert_eq!(disco.addrs().len(), 3);
+
+    // Removed addresses are removed and new addresses are made after the
+    // target is re-resolved.
+    *addrs.lock().unwrap() = vec![2, 3];
+    let mut changes = vec![next_change(&mut disco).await, next_change(&mut disco).await];
+    changes.sort();
+    assert_eq!(changes, vec![(1, None), (3, Some("svc-3".to_string()))]);
+
+    // Re-resolving the same addresses yields no further changes.
+    let mut next = tokio_test::task::spawn(poll_fn(|cx| Pin::new(&mut disco).poll_discover(cx)));
+    assert_pending!(next.poll());
+    tokio::time::advance(Duration::from_millis(1001)).await;
+    assert_pending!(next.poll());
+}
+
+async fn next_change<D>(disco: &mut D) -> (u16, Option<String>)
+where
+    D: Discover<Key = u16, Service = String, Error = BoxError> + Unpin,
+{
+    match poll_fn(|cx| Pin::new(&mut *disco).poll_discover(cx)).await {
+        Some(Ok(Change::Insert(key, svc))) => (key, Some(svc)),
+        Some(Ok(Change::Remove(key))) => (key, None),
+        change => panic!("unexpected change: {:?}", change.map(|c| c.map(|_| ()))),
+    }
+}
@@ -0,0 +1,90 @@
+use futures_util::future::poll_fn;
+use std::{collections::HashSet, pin::Pin};
+use tokio_test::assert_pending;
+use tower::discover::{Change, ChannelList, Discover, ListHandle, Subset};
+
+type Disco = Subset<ChannelList<u32, &'static str>>;
+
+fn subset(size: usize, client: u64) -> (Disco, ListHandle<u32, &'static str>) {
+    let (list, handle) = ChannelList::pair();
+    (Subset::new(list, size, client), handle)
+}
+
+async fn next_change(disco: &mut Disco) -> Change<u32, &'static str> {
+    poll_fn(|cx| Pin::new(&mut *disco).poll_discover(cx))
+        .await
+        .expect("discovery must not end")
+        .expect("discovery must not fail")
+}
+
+fn assert_no_change(disco: &mut Disco) {
+    let mut next = tokio_test::task::spawn(poll_fn(|cx| Pin::new(&mut *disco).poll_discover(cx)));
+    assert_pending!(next.poll());
+}
+
+async fn initial(size: usize, client: u64) -> (Disco, ListHandle<u32, &'static str>) {
+    let (mut disco, handle) = subset(size, client);
+    for key in 0..10 {
+        handle.insert(key, "svc");
+    }
+    for _ in 0..size {
+        match next_change(&mut disco).await {
+            Change::Insert(key, _) => assert!(key < 10),
+            change => panic!("unexpected change: {:?}", change),
+        }
+    }
+    assert_no_change(&mut disco);
+    (disco, handle)
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn subset_is_deterministic_per_client() {
+    let (a, _ha) = initial(3, 1).await;
+    let (b, _hb) = initial(3, 1).await;
+    assert_eq!(a.selected().len(), 3);
+    assert_eq!(a.selected(), b.selected());
+
+    // Different clients spread their subsets across the endpoints.
+    let mut covered = HashSet::new();
+    for client in 0..8 {
+        let (disco, _h) = initial(3, client).await;
+        covered.extend(disco.selected().iter().copied());
+    }
+    assert!(covered.len() > 3, "subsets must vary by client");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn subset_is_stable() {
+    let (mut disco, handle) = initial(3, 7).await;
+    let selected = disco.selected().clone();
+
+    // New endpoints do not displace selected ones.
+    handle.insert(100, "svc");
+    assert_no_change(&mut disco);
+    assert_eq!(disco.selected(), &selected);
+
+    // Unselected endpoints may be removed without affecting the subset.
+    let unselected = (0..10).find(|k| !selected.contains(k)).unwrap();
+    handle.remove(unselected);
+    assert_no_change(&mut disco);
+
+    // Selected endpoints are updated in place.
+    let key = *selected.iter().next().unwrap();
+    handle.insert(key, "svc-2");
+    assert!(matches!(
+        next_change(&mut disco).await,
+        Change::Insert(k, "svc-2") if k == key
+    ));
+
+    // Removing a selected endpoint fills its vacancy.
+    handle.remove(key);
+    assert!(matches!(next_change(&mut disco).await, Change::Remove(k) if k == key));
+    let added = match next_change(&mut disco).await {
+        Change::Insert(added, _) => added,
+        change => panic!("unexpected change: {:?}", change),
+    };
+    assert!(!selected.contains(&added));
+    assert_ne!(added, unselected);
+    assert_eq!(disco.selected().len(), 3);
+    assert_no_change(&mut disco);
+}
@@ -0,0 +1,48 @@
+#![cfg(feature = "drain")]
+#[path = "../support.rs"]
+mod support;
+
+use tokio_test::{assert_pending, assert_ready, assert_ready_err, assert_ready_ok, task};
+use tower::drain::{error::Draining, Drain, DrainLayer};
+use tower_test::{assert_request_eq, mock};
+
+#[tokio::test(flavor = "current_thread")]
+async fn stops_accepting_and_waits_for_outstanding() {
+    let _t = support::trace_init();
+
+    let drain = Drain::new();
+    let layer = DrainLayer::new(drain.watch());
+    let (mut service, mut handle) = mock::spawn_layer::<&'static str, &'static str, _>(layer);
+
+    assert_ready_ok!(service.poll_ready());
+    let mut response = task::spawn(service.call("hello"));
+    assert_eq!(drain.outstanding(), 1);
+
+    let mut drained = task::spawn(drain.signal());
+    assert_pending!(drained.poll());
+    assert_pending!(service.poll_ready(), "no requests accepted once draining");
+
+    assert_request_eq!(handle, "hello").send_response("world");
+    assert_eq!(assert_ready_ok!(response.poll()), "world");
+    assert!(drained.is_woken());
+    assert_ready!(drained.poll());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn rejects_when_draining() {
+    let _t = support::trace_init();
+
+    let drain = Drain::new();
+    let layer = DrainLayer::new(drain.watch()).reject_when_draining();
+    let (mut service, _handle) = mock::spawn_layer::<&'static str, &'static str, _>(layer);
+
+    assert_ready_ok!(service.poll_ready());
+
+    // Dropped responses are no longer outstanding.
+    drop(service.call("hello"));
+
+    let mut drained = task::spawn(drain.signal());
+    assert_ready!(drained.poll());
+    let err = assert_ready_err!(service.poll_ready());
+    assert!(err.is::<Draining>(), "should be a Draining error");
+}
@@ -1,6 +1,6 @@
 #[path = "../support.rs"]
 mod support;
-use tokio_test::{assert_pending, assert_ready, assert_ready_ok};
+use tokio_test::{assert_pending, assert_ready, assert_ready_ok, task};
 use tower::limit::concurrency::ConcurrencyLimitLayer;
 use tower_test::{assert_request_eq, mock};
 
@@ -215,3 +215,254 @@ async fn multi_waiters() {
 
     assert!(s3.is_woken());
 }
+
+#[tokio::test(flavor = "current_thread")]
+async fn check_does_not_acquire() {
+    let _t = support::trace_init();
+    let limit = ConcurrencyLimitLayer::new(1);
+    let (mut service, mut handle) = mock::spawn_layer(limit);
+
+    assert!(service.get_ref().check());
+    assert!(service.get_ref().check());
+
+    assert_ready_ok!(service.poll_ready());
+    assert!(service.get_ref().check(), "an acquired permit may be used");
+
+    let r1 = service.call("hello 1");
+    assert!(!service.get_ref().check());
+
+    assert_request_eq!(handle, "hello 1").send_response("world 1");
+    assert_eq!(r1.await.unwrap(), "world 1");
+
+    assert!(service.get_ref().check());
+    assert_ready_ok!(service.poll_ready());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn make_limit_released_when_service_dropped() {
+    use tower::limit::concurrency::MakeConcurrencyLimitLayer;
+
+    let _t = support::trace_init();
+    let limit = MakeConcurrencyLimitLayer::new(1);
+    let (mut make, mut handle) = mock::spawn_layer::<(), mock::Mock<(), ()>, _>(limit);
+
+    assert_ready_ok!(make.poll_ready());
+    let mut made = task::spawn(make.call(()));
+
+    // The permit is held while the service is being made...
+    assert_pending!(make.poll_ready());
+
+    let (svc, _svc_handle) = mock::pair::<(), ()>();
+    assert_request_eq!(handle, ()).send_response(svc);
+    let svc = assert_ready_ok!(made.poll());
+
+    // ...and for as long as the made service exists.
+    assert_pending!(make.poll_ready());
+    assert!(!make.is_woken());
+
+    drop(svc);
+    assert!(make.is_woken());
+    assert_ready_ok!(make.poll_ready());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn make_limit_released_when_make_fails() {
+    use tower::limit::concurrency::MakeConcurrencyLimitLayer;
+
+    let _t = support::trace_init();
+    let limit = MakeConcurrencyLimitLayer::new(1);
+    let (mut make, mut handle) = mock::spawn_layer::<(), mock::Mock<(), ()>, _>(limit);
+
+    assert_ready_ok!(make.poll_ready());
+    let mut made = task::spawn(make.call(()));
+    assert_pending!(make.poll_ready());
+
+    assert_request_eq!(handle, ()).send_error("connection refused");
+    assert!(made.poll().is_ready());
+    drop(made);
+
+    assert!(make.is_woken());
+    assert_ready_ok!(make.poll_ready());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn permit_forwarded_to_response() {
+    use std::time::Duration;
+    use tower::limit::concurrency::{ConcurrencyLimit, Permit, TrackRelease};
+
+    #[derive(Clone)]
+    struct Forward;
+    impl TrackRelease<&'static str> for Forward {
+        type Output = (&'static str, Permit);
+        fn track_release(&self, permit: Permit, rsp: &'static str) -> Self::Output {
+            (rsp, permit)
+        }
+    }
+
+    let _t = support::trace_init();
+    tokio::time::pause();
+    let (mut service, mut handle) = mock::spawn_with(|s| {
+        ConcurrencyLimit::new(s, 1).with_release(Forward, Duration::from_secs(10))
+    });
+
+    // The permit is held until the forwarded permit is dropped...
+    assert_ready_ok!(service.poll_ready());
+    let rsp = service.call("hello 1");
+    assert_request_eq!(handle, "hello 1").send_response("world 1");
+    let (rsp, permit) = rsp.await.unwrap();
+    assert_eq!(rsp, "world 1");
+    assert_pending!(service.poll_ready());
+
+    drop(permit);
+    tokio::task::yield_now().await;
+    assert!(service.is_woken());
+    assert_ready_ok!(service.poll_ready());
+
+    // ...or until the release timeout elapses.
+    let rsp = service.call("hello 2");
+    assert_request_eq!(handle, "hello 2").send_response("world 2");
+    let (_, _permit) = rsp.await.unwrap();
+    assert_pending!(service.poll_ready());
+
+    tokio::time::sleep(Duration::from_secs(10)).await;
+    tokio::task::yield_now().await;
+    assert!(service.is_woken());
+    assert_ready_ok!(service.poll_ready());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn observer_reports_waits() {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tower::limit::{ConcurrencyLimit, Observe};
+
+    #[derive(Clone, Default)]
+    struct Events(Arc<Mutex<Vec<String>>>);
+    impl Observe for Events {
+        fn acquired(&self) {
+            self.0.lock().unwrap().push("acquired".into());
+        }
+        fn wait_started(&self) {
+            self.0.lock().unwrap().push("wait_started".into());
+        }
+        fn wait_ended(&self, waited: Duration) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("wait_ended {}s", waited.as_secs()));
+        }
+        fn rejected(&self) {
+            self.0.lock().unwrap().push("rejected".into());
+        }
+    }
+
+    let _t = support::trace_init();
+    tokio::time::pause();
+    let events = Events::default();
+    let obs = events.clone();
+    let (mut service, mut handle) =
+        mock::spawn_with(move |s| ConcurrencyLimit::new(s, 1).with_observer(obs.clone()));
+
+    assert_ready_ok!(service.poll_ready());
+    let r1 = service.call("hello 1");
+
+    let mut service2 = service.clone();
+    assert!(!service2.get_mut().check());
+    assert_pending!(service2.poll_ready());
+    assert_pending!(service2.poll_ready());
+
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_request_eq!(handle, "hello 1").send_response("world 1");
+    assert_eq!(r1.await.unwrap(), "world 1");
+    assert_ready_ok!(service2.poll_ready());
+    let _r2 = service2.call("hello 2");
+
+    assert_eq!(
+        *events.0.lock().unwrap(),
+        [
+            "acquired",
+            "rejected",
+            "wait_started",
+            "wait_ended 1s",
+            "acquired"
+        ]
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn reclaims_permits_held_too_long() {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tower::limit::{ConcurrencyLimit, Observe};
+
+    #[derive(Clone, Default)]
+    struct Reclaimed(Arc<Mutex<Vec<Duration>>>);
+    impl Observe for Reclaimed {
+        fn reclaimed(&self, held: Duration) {
+            self.0.lock().unwrap().push(held);
+        }
+    }
+
+    let _t = support::trace_init();
+    tokio::time::pause();
+    let reclaimed = Reclaimed::default();
+    let obs = reclaimed.clone();
+    let (mut service, mut handle) = mock::spawn_with(move |s| {
+        ConcurrencyLimit::new(s, 1)
+            .with_max_hold(Duration::from_secs(1))
+            .with_observer(obs.clone())
+    });
+
+    assert_ready_ok!(service.poll_ready());
+    let mut r1 = task::spawn(service.call("hello 1"));
+    assert_pending!(r1.poll());
+    assert_pending!(service.poll_ready());
+
+    // The permit is reclaimed once the response has been pending too long.
+    tokio::time::advance(Duration::from_millis(1001)).await;
+    assert!(r1.is_woken());
+    assert_pending!(r1.poll());
+    assert_eq!(
+        *reclaimed.0.lock().unwrap(),
+        vec![Duration::from_millis(1001)]
+    );
+    assert!(service.is_woken());
+    assert_ready_ok!(service.poll_ready());
+    let mut r2 = task::spawn(service.call("hello 2"));
+
+    // The request that held it still completes.
+    assert_request_eq!(handle, "hello 1").send_response("world 1");
+    assert_eq!(assert_ready_ok!(r1.poll()), "world 1");
+    assert_request_eq!(handle, "hello 2").send_response("world 2");
+    assert_eq!(assert_ready_ok!(r2.poll()), "world 2");
+    assert_eq!(reclaimed.0.lock().unwrap().len(), 1);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn utilization_counts_permits_in_use() {
+    use tower::limit::UtilizationLoad;
+    use tower::load::Load;
+
+    let _t = support::trace_init();
+    let limit = ConcurrencyLimitLayer::new(4);
+    let (mut service, mut handle) = mock::spawn_layer(limit);
+    assert_eq!(service.get_ref().utilization(), 0.0);
+
+    assert_ready_ok!(service.poll_ready());
+    assert_eq!(service.get_ref().utilization(), 0.25);
+    let r1 = service.call("hello 1");
+    assert_ready_ok!(service.poll_ready());
+    let r2 = service.call("hello 2");
+    assert_eq!(service.get_ref().utilization(), 0.5);
+
+    assert_request_eq!(handle, "hello 1").send_response("world 1");
+    assert_eq!(r1.await.unwrap(), "world 1");
+    assert_eq!(service.get_ref().utilization(), 0.25);
+
+    let svc = UtilizationLoad::new(service.into_inner());
+    assert_eq!(svc.load(), 0.25);
+
+    assert_request_eq!(handle, "hello 2").send_response("world 2");
+    assert_eq!(r2.await.unwrap(), "world 2");
+    assert_eq!(svc.load(), 0.0);
+}
@@ -1,5 +1,6 @@
 #![cfg(feature = "limit")]
 mod concurrency;
 mod rate;
+mod route;
 #[path = "../support.rs"]
 pub(crate) mod support;
@@ -69,3 +69,111 @@ async fn remaining_gets_reset() {
 
     assert_ready_ok!(service.poll_ready());
 }
+
+#[tokio::test(flavor = "current_thread")]
+async fn check_does_not_consume() {
+    let _t = support::trace_init();
+    time::pause();
+
+    let rate_limit = RateLimitLayer::new(1, Duration::from_millis(100));
+    let (mut service, mut handle) = mock::spawn_layer(rate_limit);
+
+    assert!(service.get_ref().check());
+    assert!(service.get_ref().check());
+
+    assert_ready_ok!(service.poll_ready());
+    let response = service.call("hello");
+    assert_request_eq!(handle, "hello").send_response("world");
+    assert_eq!(response.await.unwrap(), "world");
+
+    assert!(!service.get_ref().check());
+    assert_pending!(service.poll_ready());
+
+    time::advance(Duration::from_millis(101)).await;
+    assert!(service.get_ref().check());
+    assert_ready_ok!(service.poll_ready());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn burst_then_steady_rate() {
+    let _t = support::trace_init();
+    time::pause();
+
+    // One request every 100ms, with bursts of up to 3 requests.
+    let rate_limit = RateLimitLayer::new(10, Duration::from_secs(1)).with_burst(3);
+    let (mut service, mut handle) = mock::spawn_layer(rate_limit);
+
+    // The bucket starts full.
+    for _ in 0..3 {
+        assert_ready_ok!(service.poll_ready());
+        let response = service.call("hello");
+        assert_request_eq!(handle, "hello").send_response("world");
+        assert_eq!(response.await.unwrap(), "world");
+    }
+    assert_pending!(service.poll_ready());
+
+    // Tokens are added at the steady rate.
+    time::advance(Duration::from_millis(101)).await;
+    assert!(service.is_woken());
+    assert_ready_ok!(service.poll_ready());
+    let response = service.call("two");
+    assert_request_eq!(handle, "two").send_response("done");
+    assert_eq!(response.await.unwrap(), "done");
+    assert_pending!(service.poll_ready());
+
+    // The bucket refills after a period of inactivity.
+    time::advance(Duration::from_secs(1)).await;
+    for _ in 0..3 {
+        assert_ready_ok!(service.poll_ready());
+        let response = service.call("three");
+        assert_request_eq!(handle, "three").send_response("done");
+        assert_eq!(response.await.unwrap(), "done");
+    }
+    assert_pending!(service.poll_ready());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn utilization_counts_remaining_requests() {
+    let _t = support::trace_init();
+    time::pause();
+
+    let rate_limit = RateLimitLayer::new(4, Duration::from_millis(100));
+    let (mut service, mut handle) = mock::spawn_layer(rate_limit);
+    assert_eq!(service.get_ref().remaining(), 4);
+    assert_eq!(service.get_ref().utilization(), 0.0);
+
+    for _ in 0..3 {
+        assert_ready_ok!(service.poll_ready());
+        let response = service.call("hello");
+        assert_request_eq!(handle, "hello").send_response("world");
+        assert_eq!(response.await.unwrap(), "world");
+    }
+    assert_eq!(service.get_ref().remaining(), 1);
+    assert_eq!(service.get_ref().utilization(), 0.75);
+
+    time::advance(Duration::from_millis(100)).await;
+    assert_eq!(service.get_ref().remaining(), 4);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn utilization_counts_bucket_tokens() {
+    let _t = support::trace_init();
+    time::pause();
+
+    let rate_limit = RateLimitLayer::new(10, Duration::from_secs(1)).with_burst(4);
+    let (mut service, mut handle) = mock::spawn_layer(rate_limit);
+    assert_eq!(service.get_ref().remaining(), 4);
+
+    for _ in 0..4 {
+        assert_ready_ok!(service.poll_ready());
+        let response = service.call("hello");
+        assert_request_eq!(handle, "hello").send_response("world");
+        assert_eq!(response.await.unwrap(), "world");
+    }
+    assert_eq!(service.get_ref().remaining(), 0);
+    assert_eq!(service.get_ref().utilization(), 1.0);
+
+    time::advance(Duration::from_millis(200)).await;
+    assert_eq!(service.get_ref().remaining(), 2);
+    assert_eq!(service.get_ref().utilization(), 0.5);
+}
@@ -0,0 +1,68 @@
+#[path = "../support.rs"]
+mod support;
+use tokio_test::{assert_pending, assert_ready_ok};
+use tower::limit::route::{error::RouteLimited, RouteLimitLayer, RouteLimits};
+use tower_test::{assert_request_eq, mock};
+
+fn route(req: &&'static str) -> &'static str {
+    req.split('/').next().unwrap()
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn limits_routes_and_globally() {
+    let _t = support::trace_init();
+    let limits = RouteLimits::new().global(3).route("a", 1);
+    let layer = RouteLimitLayer::new(limits, route as fn(&&'static str) -> &'static str);
+    let (mut service, mut handle) = mock::spawn_layer(layer);
+
+    assert_ready_ok!(service.poll_ready());
+    let a1 = service.call("a/1");
+
+    // The route is at its limit, so the request fails without being sent.
+    assert_ready_ok!(service.poll_ready());
+    let err = service.call("a/2").await.unwrap_err();
+    assert!(err.is::<RouteLimited>(), "unexpected error: {:?}", err);
+
+    // Other routes are only subject to the global limit.
+    assert_ready_ok!(service.poll_ready());
+    let b1 = service.call("b/1");
+    assert_ready_ok!(service.poll_ready());
+    let b2 = service.call("b/2");
+    assert_pending!(service.poll_ready());
+
+    assert_request_eq!(handle, "a/1").send_response("ok a/1");
+    assert_request_eq!(handle, "b/1").send_response("ok b/1");
+    assert_request_eq!(handle, "b/2").send_response("ok b/2");
+    assert_pending!(handle.poll_request());
+
+    assert_eq!(a1.await.unwrap(), "ok a/1");
+    assert!(service.is_woken());
+
+    // Completing the first request frees capacity on its route.
+    assert_ready_ok!(service.poll_ready());
+    let a3 = service.call("a/3");
+    assert_request_eq!(handle, "a/3").send_response("ok a/3");
+    assert_eq!(a3.await.unwrap(), "ok a/3");
+
+    assert_eq!(b1.await.unwrap(), "ok b/1");
+    assert_eq!(b2.await.unwrap(), "ok b/2");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn clones_share_limits() {
+    let _t = support::trace_init();
+    let limits = RouteLimits::new().route("a", 1);
+    let layer = RouteLimitLayer::new(limits, route as fn(&&'static str) -> &'static str);
+    let (mut service, mut handle) = mock::spawn_layer(layer);
+    let mut clone = service.clone();
+
+    assert_ready_ok!(service.poll_ready());
+    let a1 = service.call("a/1");
+
+    assert_ready_ok!(clone.poll_ready());
+    let err = clone.call("a/2").await.unwrap_err();
+    assert!(err.is::<RouteLimited>(), "unexpected error: {:?}", err);
+
+    assert_request_eq!(handle, "a/1").send_response("ok");
+    assert_eq!(a1.await.unwrap(), "ok");
+}
@@ -4,6 +4,7 @@ mod support;
 
 use tokio_test::{assert_pending, assert_ready, task};
 use tower::ready_cache::ReadyCache;
+use tower_service::Service;
 use tower_test::mock;
 
 type Req = &'static str;
@@ -191,3 +192,161 @@ fn duplicate_key_by_index() {
     // _and_ service 0 should now be callable
     assert!(task.enter(|cx, _| cache.check_ready(cx, &0)).unwrap());
 }
+
+/// Pushes `n` ready services, keyed by their index, and promotes them all.
+fn ready_cache(
+    task: &mut task::Spawn<()>,
+    n: usize,
+) -> (ReadyCache<usize, Mock, Req>, Vec<mock::Handle<Req, Req>>) {
+    let mut cache = ReadyCache::<usize, Mock, Req>::default();
+    let mut handles = Vec::new();
+    for i in 0..n {
+        let (service, mut handle) = mock::pair::<Req, Req>();
+        handle.allow(1);
+        cache.push(i, service);
+        handles.push(handle);
+    }
+    assert_ready!(task.enter(|cx, _| cache.poll_pending(cx))).unwrap();
+    assert_eq!(cache.ready_len(), n);
+    (cache, handles)
+}
+
+/// Asserts that every ready service is reachable both by key and by index.
+fn assert_indices_consistent(cache: &ReadyCache<usize, Mock, Req>, keys: &[usize]) {
+    assert_eq!(cache.ready_len(), keys.len());
+    for key in keys {
+        let (index, k, _) = cache.get_ready(key).expect("ready by key");
+        assert_eq!(k, key);
+        let (k, _) = cache.get_ready_index(index).expect("ready by index");
+        assert_eq!(k, key);
+    }
+    assert!(cache.get_ready_index(keys.len()).is_none());
+}
+
+#[test]
+fn evict_ready_keeps_indices_consistent() {
+    let _t = support::trace_init();
+
+    let mut task = task::spawn(());
+    let (mut cache, _handles) = ready_cache(&mut task, 4);
+
+    assert!(cache.evict(&1));
+    assert_indices_consistent(&cache, &[0, 2, 3]);
+    assert!(cache.evict(&3));
+    assert_indices_consistent(&cache, &[0, 2]);
+    assert!(cache.evict(&0));
+    assert_indices_consistent(&cache, &[2]);
+
+    // Evicting an already-evicted key is a no-op.
+    assert!(!cache.evict(&1));
+    assert_indices_consistent(&cache, &[2]);
+}
+
+#[test]
+fn evict_last_ready_service() {
+    let _t = support::trace_init();
+
+    let mut task = task::spawn(());
+    let (mut cache, _handles) = ready_cache(&mut task, 1);
+
+    assert!(cache.evict(&0));
+    assert!(cache.is_empty());
+    assert!(cache.get_ready_index(0).is_none());
+    // A stale index is reported as not ready rather than panicking.
+    assert!(!task.enter(|cx, _| cache.check_ready_index(cx, 0)).unwrap());
+}
+
+#[test]
+fn call_ready_index_after_eviction() {
+    let _t = support::trace_init();
+
+    let mut task = task::spawn(());
+    let (mut cache, mut handles) = ready_cache(&mut task, 3);
+
+    assert!(cache.evict(&0));
+    assert_indices_consistent(&cache, &[1, 2]);
+
+    // Every remaining index still refers to a ready, callable service.
+    for index in (0..cache.ready_len()).rev() {
+        assert!(task
+            .enter(|cx, _| cache.check_ready_index(cx, index))
+            .unwrap());
+        let (key, _) = cache.get_ready_index(index).unwrap();
+        let key = *key;
+        let _fut = cache.call_ready_index(index, "hello");
+        let (req, _) = assert_ready!(handles[key].poll_request()).expect("request");
+        assert_eq!(req, "hello");
+    }
+    assert_eq!(cache.ready_len(), 0);
+    assert_eq!(cache.pending_len(), 2);
+}
+
+#[test]
+fn replace_ready_service_keeps_index() {
+    let _t = support::trace_init();
+
+    let mut task = task::spawn(());
+    let (mut cache, mut handles) = ready_cache(&mut task, 3);
+    let (index, _, _) = cache.get_ready(&1).expect("ready by key");
+
+    let (service, mut handle) = mock::pair::<Req, Req>();
+    handle.allow(1);
+    cache.push(1, service);
+    assert_ready!(task.enter(|cx, _| cache.poll_pending(cx))).unwrap();
+
+    // The replacement takes the prior service's place in the ready set.
+    assert_indices_consistent(&cache, &[0, 1, 2]);
+    assert_eq!(cache.get_ready(&1).expect("ready by key").0, index);
+    assert!(task.enter(|cx, _| cache.check_ready(cx, &1)).unwrap());
+    let _fut = cache.call_ready(&1, "hello");
+    let (req, _) = assert_ready!(handle.poll_request()).expect("request");
+    assert_eq!(req, "hello");
+    // The prior service has been dropped.
+    assert!(assert_ready!(handles[1].poll_request()).is_none());
+}
+
+#[test]
+fn evict_preserving_order() {
+    let _t = support::trace_init();
+
+    let mut task = task::spawn(());
+    let (mut cache, _handles) = ready_cache(&mut task, 4);
+    cache.preserve_order(true);
+
+    let order = |cache: &ReadyCache<usize, Mock, Req>| {
+        cache.iter_ready().map(|(k, _)| *k).collect::<Vec<_>>()
+    };
+    let mut expected = order(&cache);
+    let removed = expected.remove(1);
+    assert!(cache.evict(&removed));
+    assert_eq!(order(&cache), expected);
+    assert_indices_consistent(&cache, &expected);
+}
+
+#[test]
+fn remove_replaced_pending_service() {
+    let _t = support::trace_init();
+
+    let mut task = task::spawn(());
+    let mut cache = ReadyCache::<usize, Mock, Req>::default();
+
+    let (prior, mut prior_handle) = mock::pair::<Req, Req>();
+    prior_handle.allow(0);
+    cache.push(0, prior);
+    let (replacement, mut handle) = mock::pair::<Req, Req>();
+    handle.allow(0);
+    cache.push(0, replacement);
+
+    // The replacement, rather than the prior service, is removed.
+    let mut removed = cache.remove(&0).expect("pending service must be removed");
+    handle.allow(1);
+    assert!(assert_ready!(task.enter(|cx, _| removed.poll_ready(cx))).is_ok());
+    let _fut = removed.call("hello");
+    let (req, _) = assert_ready!(handle.poll_request()).expect("request");
+    assert_eq!(req, "hello");
+
+    // Neither service becomes ready under the removed key.
+    prior_handle.allow(1);
+    assert_ready!(task.enter(|cx, _| cache.poll_pending(cx))).unwrap();
+    assert!(cache.is_empty());
+}
@@ -3,8 +3,9 @@
 mod support;
 
 use futures_util::future;
+use tokio::time;
 use tokio_test::{assert_pending, assert_ready_err, assert_ready_ok, task};
-use tower::retry::Policy;
+use tower::retry::{AsyncClone, Policy};
 use tower_test::{assert_request_eq, mock};
 
 #[tokio::test(flavor = "current_thread")]
@@ -26,6 +27,27 @@ async fn retry_errors() {
     assert_eq!(fut.into_inner().await.unwrap(), "world");
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn retry_skips_requests_that_opt_out() {
+    let _t = support::trace_init();
+
+    let retry = tower::retry::RetryLayer::new(RetryErrors)
+        .with_retryable(|req: &Req| !req.starts_with("no-retry"));
+    let (mut service, mut handle) = mock::spawn_layer(retry);
+
+    assert_ready_ok!(service.poll_ready());
+    let mut fut = task::spawn(service.call("no-retry hello"));
+    assert_request_eq!(handle, "no-retry hello").send_error("retry me");
+    assert_eq!(assert_ready_err!(fut.poll()).to_string(), "retry me");
+
+    assert_ready_ok!(service.poll_ready());
+    let mut fut = task::spawn(service.call("hello"));
+    assert_request_eq!(handle, "hello").send_error("retry me");
+    assert_pending!(fut.poll());
+    assert_request_eq!(handle, "hello").send_response("world");
+    assert_eq!(fut.into_inner().await.unwrap(), "world");
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn retry_limit() {
     let _t = support::trace_init();
@@ -90,6 +112,262 @@ async fn success_with_cannot_clone() {
     assert_ready_ok!(fut.poll(), "world");
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn retry_async_clone() {
+    let _t = support::trace_init();
+
+    let (mock, mut handle) = mock::pair();
+    let retry = tower::retry::Retry::new(RetryUncloned, mock).with_async_clone(CloneShort(5));
+    let mut service = mock::Spawn::new(retry);
+
+    // Requests that can be cloned are retried.
+    assert_ready_ok!(service.poll_ready());
+    let mut fut = task::spawn(service.call("hello"));
+    assert_pending!(fut.poll());
+    assert_request_eq!(handle, "hello").send_error("retry 1");
+    assert_pending!(fut.poll());
+    assert_request_eq!(handle, "hello").send_response("world");
+    assert_ready_ok!(fut.poll(), "world");
+
+    // Requests that are too long to clone are dispatched, but not retried.
+    assert_ready_ok!(service.poll_ready());
+    let mut fut = task::spawn(service.call("hello world"));
+    assert_pending!(fut.poll());
+    assert_request_eq!(handle, "hello world").send_error("retry 1");
+    assert_eq!(assert_ready_err!(fut.poll()).to_string(), "retry 1");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn retry_suppressed_while_unhealthy() {
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+    use tower::retry::SuppressUnhealthy;
+
+    let _t = support::trace_init();
+
+    let healthy = Arc::new(AtomicBool::new(true));
+    let policy = SuppressUnhealthy::new(RetryErrors, {
+        let healthy = healthy.clone();
+        move || healthy.load(Ordering::SeqCst)
+    });
+    let (mut service, mut handle) = new_service(policy);
+
+    assert_ready_ok!(service.poll_ready());
+    let mut fut = task::spawn(service.call("hello"));
+
+    assert_request_eq!(handle, "hello").send_error("retry 1");
+    assert_pending!(fut.poll());
+
+    healthy.store(false, Ordering::SeqCst);
+    assert_request_eq!(handle, "hello").send_error("retry 2");
+    assert_eq!(assert_ready_err!(fut.poll()).to_string(), "retry 2");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn budgeted_retry_stops_when_overdrawn() {
+    use std::{sync::Arc, time::Duration};
+    use tower::retry::budget::{Budget, BudgetedRetry};
+
+    let _t = support::trace_init();
+
+    // A budget with no reserve only allows retries after deposits.
+    let budget = Arc::new(Budget::new(Duration::from_secs(1), 0, 1.0));
+    let policy = BudgetedRetry::new(budget, |_: &Error| true);
+    let (mut service, mut handle) = new_service(policy);
+
+    assert_ready_ok!(service.poll_ready());
+    let mut fut = task::spawn(service.call("hello"));
+    assert_request_eq!(handle, "hello").send_error("retry 1");
+    assert_eq!(assert_ready_err!(fut.poll()).to_string(), "retry 1");
+
+    // A successful request deposits enough for a single retry.
+    assert_ready_ok!(service.poll_ready());
+    let mut fut = task::spawn(service.call("hello"));
+    assert_request_eq!(handle, "hello").send_response("world");
+    assert_eq!(assert_ready_ok!(fut.poll()), "world");
+
+    assert_ready_ok!(service.poll_ready());
+    let mut fut = task::spawn(service.call("hello"));
+    assert_request_eq!(handle, "hello").send_error("retry 1");
+    assert_pending!(fut.poll());
+    assert_request_eq!(handle, "hello").send_error("retry 2");
+    assert_eq!(assert_ready_err!(fut.poll()).to_string(), "retry 2");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn combined_policy_limits_attempts_with_backoff() {
+    use std::time::Duration;
+    use tower::retry::PolicyExt;
+
+    let _t = support::trace_init();
+    time::pause();
+
+    let policy = RetryErrors
+        .limit_attempts(2)
+        .with_backoff(|attempt| Duration::from_millis(100 * attempt as u64));
+    let (mut service, mut handle) = new_service(policy);
+
+    assert_ready_ok!(service.poll_ready());
+    let mut fut = task::spawn(service.call("hello"));
+    assert_request_eq!(handle, "hello").send_error("retry 1");
+
+    // The retry is delayed by the backoff.
+    assert_pending!(fut.poll());
+    assert_pending!(handle.poll_request());
+    time::advance(Duration::from_millis(101)).await;
+    assert_pending!(fut.poll());
+    assert_request_eq!(handle, "hello").send_error("retry 2");
+
+    assert_pending!(fut.poll());
+    time::advance(Duration::from_millis(201)).await;
+    assert_pending!(fut.poll());
+    assert_request_eq!(handle, "hello").send_error("retry 3");
+
+    // The attempt limit has been reached.
+    assert_eq!(assert_ready_err!(fut.poll()).to_string(), "retry 3");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn combined_policy_and_or() {
+    use tower::retry::{NeverRetry, PolicyExt};
+
+    let _t = support::trace_init();
+
+    let policy = NeverRetry.or(RetryErrors.and(UnlessErr("fatal")));
+    let (mut service, mut handle) = new_service(policy);
+
+    assert_ready_ok!(service.poll_ready());
+    let mut fut = task::spawn(service.call("hello"));
+    assert_request_eq!(handle, "hello").send_error("retry 1");
+    assert_pending!(fut.poll());
+    assert_request_eq!(handle, "hello").send_error("fatal");
+    assert_eq!(assert_ready_err!(fut.poll()).to_string(), "fatal");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn combined_policy_only_charges_budget_for_retries() {
+    use std::{sync::Arc, time::Duration};
+    use tower::retry::{budget::Budget, PolicyExt};
+
+    let _t = support::trace_init();
+
+    // A budget with no reserve only allows retries after deposits.
+    let budget = Arc::new(Budget::new(Duration::from_secs(1), 0, 1.0));
+    let policy = RetryErrors.with_budget(budget).and(UnlessErr("fatal"));
+    let (mut service, mut handle) = new_service(policy);
+
+    // A successful request deposits enough for a single retry.
+    assert_ready_ok!(service.poll_ready());
+    let mut fut = task::spawn(service.call("hello"));
+    assert_request_eq!(handle, "hello").send_response("world");
+    assert_eq!(assert_ready_ok!(fut.poll()), "world");
+
+    // The budget would permit a retry, but the other policy declines it, so
+    // the budget is not charged.
+    assert_ready_ok!(service.poll_ready());
+    let mut fut = task::spawn(service.call("hello"));
+    assert_request_eq!(handle, "hello").send_error("fatal");
+    assert_eq!(assert_ready_err!(fut.poll()).to_string(), "fatal");
+
+    assert_ready_ok!(service.poll_ready());
+    let mut fut = task::spawn(service.call("hello"));
+    assert_request_eq!(handle, "hello").send_error("retry 1");
+    assert_pending!(fut.poll());
+    assert_request_eq!(handle, "hello").send_error("retry 2");
+    assert_eq!(assert_ready_err!(fut.poll()).to_string(), "retry 2");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn report_retries_counts_attempts_and_delay() {
+    use std::time::Duration;
+    use tower::retry::PolicyExt;
+
+    let _t = support::trace_init();
+    time::pause();
+
+    let policy = RetryErrors.with_backoff(|_| Duration::from_millis(100));
+    let (mut service, mut handle) =
+        mock::spawn_with(|mock| tower::retry::Retry::new(policy.clone(), mock).report_retries());
+
+    assert_ready_ok!(service.poll_ready());
+    let mut fut = task::spawn(service.call("hello"));
+    assert_request_eq!(handle, "hello").send_error("retry 1");
+    assert_pending!(fut.poll());
+    time::advance(Duration::from_millis(101)).await;
+    assert_pending!(fut.poll());
+    assert_request_eq!(handle, "hello").send_response("world");
+
+    let retried = assert_ready_ok!(fut.poll());
+    assert_eq!(retried.attempts(), 2);
+    assert_eq!(retried.retries(), 1);
+    assert!(retried.delay() >= Duration::from_millis(100));
+    assert_eq!(retried.into_inner(), "world");
+
+    // A request that succeeds immediately reports a single attempt.
+    assert_ready_ok!(service.poll_ready());
+    let mut fut = task::spawn(service.call("hello"));
+    assert_request_eq!(handle, "hello").send_response("world");
+    let retried = assert_ready_ok!(fut.poll());
+    assert_eq!(retried.attempts(), 1);
+    assert_eq!(retried.delay(), Duration::ZERO);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn retryable_errors_uses_classifier() {
+    use std::io;
+    use tower::classify::DefaultClassify;
+    use tower::retry::RetryableErrors;
+
+    let _t = support::trace_init();
+
+    let (mut service, mut handle) = new_service(RetryableErrors::new(DefaultClassify));
+
+    assert_ready_ok!(service.poll_ready());
+    let mut fut = task::spawn(service.call("hello"));
+    assert_request_eq!(handle, "hello")
+        .send_error(io::Error::new(io::ErrorKind::ConnectionReset, "reset"));
+    assert_pending!(fut.poll());
+
+    // Errors caused by the request itself are not retried.
+    assert_request_eq!(handle, "hello")
+        .send_error(io::Error::new(io::ErrorKind::InvalidInput, "invalid"));
+    assert_eq!(assert_ready_err!(fut.poll()).to_string(), "invalid");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn scripted_failures_are_retried_with_backoff() {
+    use std::time::Duration;
+    use tower::retry::PolicyExt;
+    use tower::ServiceExt;
+    use tower_test::retry::Script;
+
+    let _t = support::trace_init();
+    time::pause();
+
+    let (scripted, attempts) = Script::<Res, Error>::new()
+        .fail("retry 1".into())
+        .fail("retry 2".into())
+        .respond("world")
+        .build();
+    let policy = RetryErrors
+        .limit_attempts(3)
+        .with_backoff(|attempt| Duration::from_millis(100 * attempt as u64));
+    let service = tower::retry::Retry::new(policy, scripted.clone());
+
+    assert_eq!(service.oneshot("hello").await.unwrap(), "world");
+    assert_eq!(attempts.count(), 3);
+    // Tokio's timer rounds sleeps up to the next millisecond.
+    let delays = attempts.delays();
+    assert_eq!(delays.len(), 2);
+    for (delay, backoff) in delays.into_iter().zip([100, 200]) {
+        let backoff = Duration::from_millis(backoff);
+        assert!(delay >= backoff && delay <= backoff + Duration::from_millis(1));
+    }
+    assert_eq!(scripted.remaining(), 0);
+}
+
 type Req = &'static str;
 type Res = &'static str;
 type InnerError = &'static str;
@@ -167,6 +445,33 @@ impl Policy<Req, Res, Error> for CannotClone {
     }
 }
 
+#[derive(Clone)]
+struct RetryUncloned;
+
+impl Policy<Req, Res, Error> for RetryUncloned {
+    type Future = future::Ready<Self>;
+    fn retry(&self, _: &Req, result: Result<&Res, &Error>) -> Option<Self::Future> {
+        result.err().map(|_| future::ready(RetryUncloned))
+    }
+
+    fn clone_request(&self, _req: &Req) -> Option<Req> {
+        None
+    }
+}
+
+/// Clones requests up to a maximum length.
+#[derive(Clone)]
+struct CloneShort(usize);
+
+impl AsyncClone<Req> for CloneShort {
+    type Future = future::Ready<(Req, Option<Req>)>;
+
+    fn clone_request(&self, req: Req) -> Result<Self::Future, Req> {
+        let cloned = Some(req).filter(|req| req.len() <= self.0);
+        Ok(future::ready((req, cloned)))
+    }
+}
+
 fn new_service<P: Policy<Req, Res, Error> + Clone>(
     policy: P,
 ) -> (mock::Spawn<tower::retry::Retry<P, Mock>>, Handle) {
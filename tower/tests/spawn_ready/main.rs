@@ -4,7 +4,7 @@ mod support;
 
 use tokio::time;
 use tokio_test::{assert_pending, assert_ready, assert_ready_err, assert_ready_ok};
-use tower::spawn_ready::{SpawnReady, SpawnReadyLayer};
+use tower::spawn_ready::{error::ReadyTimeout, SpawnReady, SpawnReadyLayer};
 use tower::util::ServiceExt;
 use tower_test::mock;
 
@@ -28,6 +28,54 @@ async fn when_inner_is_not_ready() {
     assert_ready_ok!(service.poll_ready());
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn when_inner_is_not_ready_in_time() {
+    time::pause();
+
+    let _t = support::trace_init();
+
+    let (service, mut handle) = mock::pair::<(), ()>();
+    let service = SpawnReady::new(service).with_timeout(time::Duration::from_secs(1));
+    let mut service = mock::Spawn::new(service);
+
+    // Make the service NotReady
+    handle.allow(0);
+
+    assert_pending!(service.poll_ready());
+    time::sleep(time::Duration::from_millis(1001)).await;
+    let err = assert_ready_err!(service.poll_ready());
+    assert!(err.is::<ReadyTimeout>(), "unexpected error: {:?}", err);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn spawns_onto_executor() {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    let _t = support::trace_init();
+
+    let spawned = Arc::new(AtomicUsize::new(0));
+    let executor = {
+        let spawned = spawned.clone();
+        move |task| {
+            spawned.fetch_add(1, Ordering::SeqCst);
+            tokio::spawn(task);
+        }
+    };
+    let (service, mut handle) = mock::pair::<(), ()>();
+    let mut service = mock::Spawn::new(SpawnReady::new(service).with_executor(executor));
+
+    handle.allow(0);
+    assert_pending!(service.poll_ready());
+    assert_eq!(spawned.load(Ordering::SeqCst), 1);
+
+    handle.allow(1);
+    tokio::task::yield_now().await;
+    assert_ready_ok!(service.poll_ready());
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn when_inner_fails() {
     let _t = support::trace_init();
@@ -57,3 +57,44 @@ async fn pending_all_ready() {
         ),
     }
 }
+
+#[tokio::test(flavor = "current_thread")]
+async fn split_routes_fraction_to_canary() {
+    use tower::steer::Split;
+    use tower::ServiceExt;
+
+    let _t = support::trace_init();
+
+    let (tx, rx) = tokio::sync::watch::channel(0.25);
+    let mut split = Split::from_watch(MyService(1, true), MyService(2, true), rx);
+
+    let mut routed = Vec::new();
+    for _ in 0..8 {
+        let rsp = split.ready().await.unwrap().call(String::new()).await;
+        routed.push(rsp.unwrap());
+    }
+    assert_eq!(routed, vec![1, 1, 1, 2, 1, 1, 1, 2]);
+
+    // The fraction may be ramped up at runtime.
+    tx.send(1.0).unwrap();
+    for _ in 0..4 {
+        let rsp = split.ready().await.unwrap().call(String::new()).await;
+        assert_eq!(rsp.unwrap(), 2);
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn split_waits_for_selected_service() {
+    use tower::steer::Split;
+
+    let _t = support::trace_init();
+
+    // The canary is not ready, but no requests are routed to it.
+    let mut split = Split::new(MyService(1, true), MyService(2, false), 0.0);
+    let p = futures_util::poll!(futures_util::future::poll_fn(|cx| split.poll_ready(cx)));
+    assert!(p.is_ready());
+
+    let mut split = Split::new(MyService(1, true), MyService(2, false), 1.0);
+    let p = futures_util::poll!(futures_util::future::poll_fn(|cx| split.poll_ready(cx)));
+    assert!(p.is_pending());
+}
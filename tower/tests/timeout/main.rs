@@ -0,0 +1,210 @@
+#![cfg(feature = "timeout")]
+#[path = "../support.rs"]
+mod support;
+
+use std::time::Duration;
+use tokio::{sync::watch, time};
+use tokio_test::{assert_pending, assert_ready_err, assert_ready_ok, task};
+use tower::timeout::{
+    error::{Elapsed, OneshotElapsed, Phase},
+    oneshot_with_timeout, TimeoutLayer,
+};
+use tower_test::{assert_request_eq, mock};
+
+#[tokio::test(flavor = "current_thread")]
+async fn timeout_updated_from_watch() {
+    let _t = support::trace_init();
+    time::pause();
+
+    let (tx, rx) = watch::channel(Duration::from_secs(1));
+    let (mut service, mut handle) = mock::spawn_layer::<_, (), _>(TimeoutLayer::from_watch(rx));
+    assert_eq!(service.get_ref().timeout(), Duration::from_secs(1));
+
+    assert_ready_ok!(service.poll_ready());
+    let mut fut = task::spawn(service.call("hello"));
+    let _rsp = assert_request_eq!(handle, "hello");
+
+    // Updates do not affect requests that are already in flight.
+    tx.send(Duration::from_secs(10)).unwrap();
+    assert_eq!(service.get_ref().timeout(), Duration::from_secs(10));
+
+    time::advance(Duration::from_millis(1001)).await;
+    let err = assert_ready_err!(fut.poll());
+    assert!(err.is::<Elapsed>());
+
+    // Subsequent requests use the updated timeout.
+    assert_ready_ok!(service.poll_ready());
+    let mut fut = task::spawn(service.call("world"));
+    let (_, rsp) = handle.next_request().await.expect("request");
+
+    time::advance(Duration::from_millis(1001)).await;
+    assert_pending!(fut.poll());
+
+    rsp.send_response(());
+    assert_ready_ok!(fut.poll());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn notifies_when_watch_closed() {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    let _t = support::trace_init();
+
+    let closed = Arc::new(AtomicUsize::new(0));
+    let (tx, rx) = watch::channel(Duration::from_secs(1));
+    let layer = TimeoutLayer::from_watch(rx).on_watch_closed({
+        let closed = closed.clone();
+        move || {
+            closed.fetch_add(1, Ordering::SeqCst);
+        }
+    });
+    let (mut service, mut handle) = mock::spawn_layer::<_, (), _>(layer);
+
+    assert_ready_ok!(service.poll_ready());
+    let _fut = service.call("hello");
+    let _rsp = assert_request_eq!(handle, "hello");
+    assert_eq!(closed.load(Ordering::SeqCst), 0);
+
+    tx.send(Duration::from_secs(2)).unwrap();
+    drop(tx);
+
+    // The last value continues to be used, and the hook is only called once.
+    assert_ready_ok!(service.poll_ready());
+    let _fut = service.call("world");
+    let _rsp = assert_request_eq!(handle, "world");
+    assert_eq!(service.get_ref().timeout(), Duration::from_secs(2));
+    assert_eq!(closed.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn per_request_override() {
+    let _t = support::trace_init();
+    time::pause();
+
+    let layer = TimeoutLayer::new(Duration::from_secs(1)).with_override(|req: &&'static str| {
+        if *req == "slow" {
+            Some(Duration::from_secs(10))
+        } else {
+            None
+        }
+    });
+    let (mut service, mut handle) = mock::spawn_layer::<_, (), _>(layer);
+
+    assert_ready_ok!(service.poll_ready());
+    let mut fast = task::spawn(service.call("fast"));
+    let _fast = assert_request_eq!(handle, "fast");
+    assert_ready_ok!(service.poll_ready());
+    let mut slow = task::spawn(service.call("slow"));
+    let slow_rsp = assert_request_eq!(handle, "slow");
+
+    // The default timeout applies to requests that aren't overridden.
+    time::advance(Duration::from_millis(1001)).await;
+    let err = assert_ready_err!(fast.poll());
+    assert!(err.is::<Elapsed>());
+    assert_pending!(slow.poll());
+
+    slow_rsp.send_response(());
+    assert_ready_ok!(slow.poll());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn huge_timeout_does_not_overflow() {
+    let _t = support::trace_init();
+
+    let layer = TimeoutLayer::new(Duration::from_secs(u64::MAX));
+    for layer in &[layer.clone(), layer.with_budget()] {
+        let (mut service, mut handle) = mock::spawn_layer::<_, (), _>(layer.clone());
+
+        assert_ready_ok!(service.poll_ready());
+        let mut fut = task::spawn(service.call("hello"));
+        let rsp = assert_request_eq!(handle, "hello");
+        assert_pending!(fut.poll());
+
+        rsp.send_response(());
+        assert_ready_ok!(fut.poll());
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn oneshot_timeout_reports_phase() {
+    let _t = support::trace_init();
+    time::pause();
+
+    let (service, mut handle) = mock::pair::<&'static str, &'static str>();
+
+    // The service never becomes ready.
+    handle.allow(0);
+    let mut fut = task::spawn(oneshot_with_timeout(
+        service.clone(),
+        "hello",
+        Duration::from_secs(1),
+    ));
+    assert_pending!(fut.poll());
+    time::advance(Duration::from_millis(1001)).await;
+    let err = assert_ready_err!(fut.poll());
+    let err = err.downcast::<OneshotElapsed>().unwrap();
+    assert_eq!(err.phase(), Phase::Ready);
+
+    // The service is called, but never responds.
+    handle.allow(1);
+    let mut fut = task::spawn(oneshot_with_timeout(
+        service,
+        "hello",
+        Duration::from_secs(1),
+    ));
+    assert_pending!(fut.poll());
+    let _rsp = assert_request_eq!(handle, "hello");
+    time::advance(Duration::from_millis(1001)).await;
+    let err = assert_ready_err!(fut.poll());
+    let err = err.downcast::<OneshotElapsed>().unwrap();
+    assert_eq!(err.phase(), Phase::Call);
+}
+
+#[cfg(feature = "util")]
+#[tokio::test(flavor = "current_thread")]
+async fn nested_timeouts_share_budget() {
+    use tower::{service_fn, timeout::TimeBudget, Service, ServiceExt};
+
+    let _t = support::trace_init();
+    time::pause();
+
+    // Reports the budget remaining when the request is made and after it has
+    // waited for half a second.
+    let probe = service_fn(|()| {
+        let called = TimeBudget::current().map(|b| b.remaining());
+        async move {
+            time::sleep(Duration::from_millis(500)).await;
+            let polled = TimeBudget::current().map(|b| b.remaining());
+            Ok::<_, tower::BoxError>((called, polled))
+        }
+    });
+    let inner = tower::timeout::Timeout::new(probe, Duration::from_secs(10)).with_budget();
+    let mut svc = tower::timeout::Timeout::new(inner, Duration::from_secs(1)).with_budget();
+
+    let mut fut = task::spawn(svc.ready().await.unwrap().call(()));
+    assert_pending!(fut.poll());
+    time::advance(Duration::from_millis(501)).await;
+    let (called, polled) = assert_ready_ok!(fut.poll());
+    assert_eq!(called, Some(Duration::from_secs(1)));
+    assert_eq!(polled, Some(Duration::from_millis(499)));
+    assert_eq!(TimeBudget::current(), None);
+}
+
+#[cfg(feature = "util")]
+#[tokio::test(flavor = "current_thread")]
+async fn timeouts_without_budget_do_not_share_one() {
+    use tower::{service_fn, timeout::TimeBudget, Service, ServiceExt};
+
+    let _t = support::trace_init();
+    time::pause();
+
+    let probe = service_fn(|()| async { Ok::<_, tower::BoxError>(TimeBudget::current()) });
+    let inner = tower::timeout::Timeout::new(probe, Duration::from_secs(10));
+    let mut svc = tower::timeout::Timeout::new(inner, Duration::from_secs(1));
+
+    let budget = svc.ready().await.unwrap().call(()).await.unwrap();
+    assert_eq!(budget, None);
+}
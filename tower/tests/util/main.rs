@@ -4,5 +4,8 @@
 mod call_all;
 mod oneshot;
 mod service_fn;
+mod sink;
 #[path = "../support.rs"]
 pub(crate) mod support;
+#[cfg(feature = "task-set")]
+mod task_set;
@@ -0,0 +1,53 @@
+use futures::{channel::mpsc, SinkExt, StreamExt};
+use std::sync::{Arc, Mutex};
+use tokio_test::{assert_pending, assert_ready_ok, task};
+use tower::util::{ServiceExt, SinkService};
+use tower::Service;
+use tower_test::{assert_request_eq, mock};
+
+#[tokio::test(flavor = "current_thread")]
+async fn service_as_sink() {
+    let _t = super::support::trace_init();
+
+    let (service, mut handle) = mock::pair::<&'static str, &'static str>();
+    let errors = Arc::new(Mutex::new(Vec::new()));
+    let mut sink = {
+        let errors = errors.clone();
+        service.into_sink(2, move |e: tower::BoxError| {
+            errors.lock().unwrap().push(e.to_string())
+        })
+    };
+
+    // Two requests may be in flight at once.
+    sink.feed("one").await.unwrap();
+    sink.feed("two").await.unwrap();
+    let mut send = task::spawn(sink.feed("three"));
+    assert_pending!(send.poll());
+
+    assert_request_eq!(handle, "one").send_error("failed");
+    assert!(send.is_woken());
+    assert_ready_ok!(send.poll());
+    drop(send);
+    assert_eq!(*errors.lock().unwrap(), vec!["failed".to_string()]);
+
+    // Flushing waits for the remaining requests.
+    let mut flush = task::spawn(sink.flush());
+    assert_pending!(flush.poll());
+    assert_request_eq!(handle, "two").send_response("ok");
+    assert_request_eq!(handle, "three").send_response("ok");
+    assert_ready_ok!(flush.poll());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn sink_as_service() {
+    let _t = super::support::trace_init();
+
+    let (tx, mut rx) = mpsc::channel::<&'static str>(0);
+    let mut service = SinkService::new(tx);
+
+    service.ready().await.unwrap().call("one").await.unwrap();
+    assert_eq!(rx.next().await, Some("one"));
+
+    service.ready().await.unwrap().call("two").await.unwrap();
+    assert_eq!(rx.next().await, Some("two"));
+}
@@ -0,0 +1,86 @@
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tower::util::{error::task_set::Cancelled, TaskSet};
+
+#[tokio::test(flavor = "current_thread")]
+async fn task_set_returns_outputs() {
+    let tasks = TaskSet::new();
+    let task = tasks.spawn(async { 42 });
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(task.await.unwrap(), 42);
+    tokio::task::yield_now().await;
+    assert!(tasks.is_empty(), "completed tasks must be removed");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn task_set_aborts_tasks_when_dropped() {
+    let tasks = TaskSet::new();
+    let (tx, rx) = oneshot::channel::<()>();
+    let task = tasks.spawn(async move {
+        // Dropping the sender notifies the receiver when the task is aborted.
+        let _tx = tx;
+        tokio::time::sleep(Duration::from_secs(60)).await;
+    });
+
+    // Clones share the set's tasks.
+    let clone = tasks.clone();
+    drop(tasks);
+    assert_eq!(clone.len(), 1);
+
+    drop(clone);
+    assert!(rx.await.is_err(), "task must be aborted");
+    let _: Cancelled = task.await.unwrap_err();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn task_set_aborts_individual_tasks() {
+    let tasks = TaskSet::new();
+    let slow = tasks.spawn(std::future::pending::<()>());
+    let fast = tasks.spawn(async { "done" });
+
+    slow.abort();
+    assert_eq!(tasks.len(), 1);
+    assert!(slow.await.is_err());
+    assert_eq!(fast.await.unwrap(), "done");
+
+    let slow = tasks.spawn(std::future::pending::<()>());
+    tasks.abort_all();
+    assert!(tasks.is_empty());
+    assert!(slow.await.is_err());
+}
+
+#[test]
+fn task_set_spawns_onto_custom_executor() {
+    use std::{
+        future::Future,
+        pin::Pin,
+        sync::{Arc, Mutex},
+    };
+
+    type Queue = Arc<Mutex<Vec<Pin<Box<dyn Future<Output = ()> + Send>>>>>;
+
+    // An executor that only queues its tasks, to be driven by hand outside of
+    // any runtime.
+    let queue = Queue::default();
+    let tasks = TaskSet::with_executor({
+        let queue = queue.clone();
+        move |task| queue.lock().unwrap().push(task)
+    });
+
+    let mut task = tokio_test::task::spawn(tasks.spawn(async { 42 }));
+    assert_eq!(tasks.len(), 1);
+    assert!(task.poll().is_pending());
+
+    let spawned = queue.lock().unwrap().pop().expect("task must be spawned");
+    assert!(tokio_test::task::spawn(spawned).poll().is_ready());
+    assert!(tasks.is_empty(), "completed tasks must be removed");
+    assert!(task.is_woken());
+    assert_eq!(tokio_test::assert_ready!(task.poll()).unwrap(), 42);
+
+    // Aborted tasks complete when they are next polled.
+    let mut task = tokio_test::task::spawn(tasks.spawn(std::future::pending::<()>()));
+    let spawned = queue.lock().unwrap().pop().expect("task must be spawned");
+    tasks.abort_all();
+    assert!(tokio_test::task::spawn(spawned).poll().is_ready());
+    let _: Cancelled = tokio_test::assert_ready!(task.poll()).unwrap_err();
+}